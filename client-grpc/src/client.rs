@@ -27,6 +27,18 @@ cfg_if::cfg_if! {
 impl crate::service::Client<RpcServiceClient<Channel>> for CargoClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type QueryItineraryRequest = QueryItineraryRequest;
+    type QueryItineraryResponse = QueryItineraryResponse;
+    type CreateItineraryRequest = CreateItineraryRequest;
+    type CreateItineraryResponse = CreateItineraryResponse;
+    type CancelItineraryRequest = CancelItineraryRequest;
+    type CancelItineraryResponse = CancelItineraryResponse;
+    type RetryCompensationRequest = RetryCompensationRequest;
+    type RetryCompensationResponse = RetryCompensationResponse;
+    type CargoScan = CargoScan;
+    type StreamScansResponse = StreamScansResponse;
+    type WatchParcelRequest = WatchParcelRequest;
+    type WatchParcelStream = tonic::codec::Streaming<CargoScan>;
 
     async fn is_ready(
         &self,
@@ -37,6 +49,62 @@ impl crate::service::Client<RpcServiceClient<Channel>> for CargoClient {
         grpc_debug!("request: {:?}", request);
         self.get_client().await?.is_ready(request).await
     }
+
+    async fn query_itinerary(
+        &self,
+        request: Self::QueryItineraryRequest,
+    ) -> Result<tonic::Response<Self::QueryItineraryResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.query_itinerary(request).await
+    }
+
+    async fn create_itinerary(
+        &self,
+        request: Self::CreateItineraryRequest,
+    ) -> Result<tonic::Response<Self::CreateItineraryResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.create_itinerary(request).await
+    }
+
+    async fn cancel_itinerary(
+        &self,
+        request: Self::CancelItineraryRequest,
+    ) -> Result<tonic::Response<Self::CancelItineraryResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.cancel_itinerary(request).await
+    }
+
+    async fn retry_compensation(
+        &self,
+        request: Self::RetryCompensationRequest,
+    ) -> Result<tonic::Response<Self::RetryCompensationResponse>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.retry_compensation(request).await
+    }
+
+    async fn stream_scans<R>(
+        &self,
+        request: R,
+    ) -> Result<tonic::Response<Self::StreamScansResponse>, tonic::Status>
+    where
+        R: tonic::IntoStreamingRequest<Message = Self::CargoScan> + Send + 'static,
+    {
+        grpc_info!("{} client.", self.get_name());
+        self.get_client().await?.stream_scans(request).await
+    }
+
+    async fn watch_parcel(
+        &self,
+        request: Self::WatchParcelRequest,
+    ) -> Result<tonic::Response<Self::WatchParcelStream>, tonic::Status> {
+        grpc_info!("{} client.", self.get_name());
+        grpc_debug!("request: {:?}", request);
+        self.get_client().await?.watch_parcel(request).await
+    }
 }
 
 #[cfg(feature = "stub_client")]
@@ -44,6 +112,18 @@ impl crate::service::Client<RpcServiceClient<Channel>> for CargoClient {
 impl crate::service::Client<RpcServiceClient<Channel>> for CargoClient {
     type ReadyRequest = ReadyRequest;
     type ReadyResponse = ReadyResponse;
+    type QueryItineraryRequest = QueryItineraryRequest;
+    type QueryItineraryResponse = QueryItineraryResponse;
+    type CreateItineraryRequest = CreateItineraryRequest;
+    type CreateItineraryResponse = CreateItineraryResponse;
+    type CancelItineraryRequest = CancelItineraryRequest;
+    type CancelItineraryResponse = CancelItineraryResponse;
+    type RetryCompensationRequest = RetryCompensationRequest;
+    type RetryCompensationResponse = RetryCompensationResponse;
+    type CargoScan = CargoScan;
+    type StreamScansResponse = StreamScansResponse;
+    type WatchParcelRequest = WatchParcelRequest;
+    type WatchParcelStream = futures::stream::BoxStream<'static, Result<CargoScan, tonic::Status>>;
 
     async fn is_ready(
         &self,
@@ -53,6 +133,123 @@ impl crate::service::Client<RpcServiceClient<Channel>> for CargoClient {
         grpc_debug!("(MOCK) request: {:?}", request);
         Ok(tonic::Response::new(ReadyResponse { ready: true }))
     }
+
+    async fn query_itinerary(
+        &self,
+        request: Self::QueryItineraryRequest,
+    ) -> Result<tonic::Response<Self::QueryItineraryResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let itinerary = Itinerary {
+            cargo_weight_g: request.parcels.iter().map(|parcel| parcel.weight_g).sum(),
+            parcels: request.parcels,
+            user_id: request.user_id,
+            acquisition_vertiport_id: request.origin_vertiport_id,
+            delivery_vertiport_id: request.target_vertiport_id,
+            ..Default::default()
+        };
+
+        Ok(tonic::Response::new(QueryItineraryResponse {
+            itineraries: vec![DraftItinerary {
+                id: lib_common::uuid::Uuid::new_v4().to_string(),
+                itinerary: Some(itinerary),
+            }],
+        }))
+    }
+
+    async fn create_itinerary(
+        &self,
+        request: Self::CreateItineraryRequest,
+    ) -> Result<tonic::Response<Self::CreateItineraryResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CreateItineraryResponse {
+            itinerary_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            parcels: vec![CargoInfo {
+                parcel_id: lib_common::uuid::Uuid::new_v4().to_string(),
+                parcel: Some(Parcel::default()),
+            }],
+        }))
+    }
+
+    async fn cancel_itinerary(
+        &self,
+        request: Self::CancelItineraryRequest,
+    ) -> Result<tonic::Response<Self::CancelItineraryResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(CancelItineraryResponse {
+            itinerary_id: request.id,
+            refund: false,
+            refund_amount: 0.0,
+            currency_unit: CurrencyUnit::Usd as i32,
+        }))
+    }
+
+    async fn retry_compensation(
+        &self,
+        request: Self::RetryCompensationRequest,
+    ) -> Result<tonic::Response<Self::RetryCompensationResponse>, tonic::Status> {
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        Ok(tonic::Response::new(RetryCompensationResponse {
+            itinerary_id: request.itinerary_id,
+            complete: true,
+            pending_steps: vec![],
+        }))
+    }
+
+    async fn stream_scans<R>(
+        &self,
+        request: R,
+    ) -> Result<tonic::Response<Self::StreamScansResponse>, tonic::Status>
+    where
+        R: tonic::IntoStreamingRequest<Message = Self::CargoScan> + Send + 'static,
+    {
+        use futures::StreamExt;
+
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        let scans: Vec<CargoScan> = tonic::IntoStreamingRequest::into_streaming_request(request)
+            .into_inner()
+            .collect()
+            .await;
+        grpc_debug!("(MOCK) request: {:?}", scans);
+        let results: Vec<ScanResult> = scans
+            .into_iter()
+            .enumerate()
+            .map(|(index, scan)| ScanResult {
+                index: index as u32,
+                parcel_id: scan.parcel_id,
+                accepted: true,
+                reason: String::new(),
+            })
+            .collect();
+
+        Ok(tonic::Response::new(StreamScansResponse {
+            accepted: results.len() as u32,
+            rejected: 0,
+            results,
+        }))
+    }
+
+    async fn watch_parcel(
+        &self,
+        request: Self::WatchParcelRequest,
+    ) -> Result<tonic::Response<Self::WatchParcelStream>, tonic::Status> {
+        use futures::StreamExt;
+
+        grpc_warn!("(MOCK) {} client.", self.get_name());
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let scan = CargoScan {
+            parcel_id: request.parcel_id,
+            scanner_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            timestamp: Some(lib_common::time::Utc::now().into()),
+            ..Default::default()
+        };
+        Ok(tonic::Response::new(
+            futures::stream::iter(vec![Ok(scan)]).boxed(),
+        ))
+    }
 }
 
 #[cfg(test)]
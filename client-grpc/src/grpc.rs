@@ -15,6 +15,410 @@ pub struct ReadyResponse {
     #[prost(bool, tag = "1")]
     pub ready: bool,
 }
+/// Time window (min and max)
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TimeWindow {
+    /// The start of the window
+    #[prost(message, optional, tag = "1")]
+    pub timestamp_min: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The end of the window
+    #[prost(message, optional, tag = "2")]
+    pub timestamp_max: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Geographic point with altitude
+#[derive(Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GeoPointZ {
+    /// Longitude
+    #[prost(double, tag = "1")]
+    pub x: f64,
+    /// Latitude
+    #[prost(double, tag = "2")]
+    pub y: f64,
+    /// Altitude
+    #[prost(double, tag = "3")]
+    pub z: f64,
+}
+/// Non-privileged flight plan information
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct FlightPlan {
+    /// The ID of the vertiport to leave from
+    #[prost(string, tag = "1")]
+    pub origin_vertiport_id: ::prost::alloc::string::String,
+    /// The ID of the vertipad to leave from
+    #[prost(string, tag = "2")]
+    pub origin_vertipad_id: ::prost::alloc::string::String,
+    /// The ID of the destination vertiport
+    #[prost(string, tag = "3")]
+    pub target_vertiport_id: ::prost::alloc::string::String,
+    /// The ID of the destination vertipad
+    #[prost(string, tag = "4")]
+    pub target_vertipad_id: ::prost::alloc::string::String,
+    /// The path of the flight plan
+    #[prost(message, repeated, tag = "5")]
+    pub path: ::prost::alloc::vec::Vec<GeoPointZ>,
+    /// The start of the window of departure
+    #[prost(message, optional, tag = "6")]
+    pub origin_timeslot_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The end of the window of departure
+    #[prost(message, optional, tag = "7")]
+    pub origin_timeslot_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The start of the window of arrival
+    #[prost(message, optional, tag = "8")]
+    pub target_timeslot_start: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The end of the window of arrival
+    #[prost(message, optional, tag = "9")]
+    pub target_timeslot_end: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The unique ID of the aircraft
+    #[prost(string, tag = "10")]
+    pub vehicle_id: ::prost::alloc::string::String,
+    /// The priority of the flight plan
+    #[prost(int32, tag = "11")]
+    pub flight_priority: i32,
+}
+/// Invoice line
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct InvoiceItem {
+    /// The item name
+    #[prost(string, tag = "1")]
+    pub item: ::prost::alloc::string::String,
+    /// The item cost
+    #[prost(float, tag = "2")]
+    pub cost: f32,
+}
+/// A parcel to carry, as described by the customer
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Parcel {
+    /// The weight of the parcel in grams
+    #[prost(uint32, tag = "1")]
+    pub weight_g: u32,
+    /// The length of the parcel in millimeters
+    #[prost(uint32, tag = "2")]
+    pub length_mm: u32,
+    /// The width of the parcel in millimeters
+    #[prost(uint32, tag = "3")]
+    pub width_mm: u32,
+    /// The height of the parcel in millimeters
+    #[prost(uint32, tag = "4")]
+    pub height_mm: u32,
+    /// A reference or nickname chosen by the customer, empty if none
+    #[prost(string, tag = "5")]
+    pub reference: ::prost::alloc::string::String,
+    /// The special handling the parcel requires
+    #[prost(enumeration = "HandlingClass", repeated, tag = "6")]
+    pub handling: ::prost::alloc::vec::Vec<i32>,
+    /// The person receiving the parcel, notified of its progress
+    #[prost(message, optional, tag = "7")]
+    pub recipient: ::core::option::Option<Recipient>,
+    /// Issue a delivery PIN at booking, sent to the customer and required
+    ///  from the recipient at the handover
+    #[prost(bool, tag = "8")]
+    pub delivery_pin: bool,
+}
+/// The person receiving a parcel
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Recipient {
+    /// The recipient's name
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The recipient's email address, empty if none
+    #[prost(string, tag = "2")]
+    pub email: ::prost::alloc::string::String,
+    /// The recipient's phone number, empty if none
+    #[prost(string, tag = "3")]
+    pub phone: ::prost::alloc::string::String,
+}
+/// A parcel registered for a booked itinerary
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CargoInfo {
+    /// The ID of the registered parcel
+    #[prost(string, tag = "1")]
+    pub parcel_id: ::prost::alloc::string::String,
+    /// The parcel as described by the customer
+    #[prost(message, optional, tag = "2")]
+    pub parcel: ::core::option::Option<Parcel>,
+}
+/// Itinerary
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Itinerary {
+    /// Each leg of the itinerary
+    #[prost(message, repeated, tag = "1")]
+    pub flight_plans: ::prost::alloc::vec::Vec<FlightPlan>,
+    /// The currency type
+    #[prost(enumeration = "CurrencyUnit", tag = "2")]
+    pub currency_unit: i32,
+    /// The cost of the trip for the customer
+    #[prost(message, repeated, tag = "3")]
+    pub invoice: ::prost::alloc::vec::Vec<InvoiceItem>,
+    /// Cargo weight in grams, the combined weight of the parcels
+    #[prost(uint32, tag = "4")]
+    pub cargo_weight_g: u32,
+    /// User ID
+    #[prost(string, tag = "5")]
+    pub user_id: ::prost::alloc::string::String,
+    /// Acquisition vertiport ID
+    #[prost(string, tag = "6")]
+    pub acquisition_vertiport_id: ::prost::alloc::string::String,
+    /// Delivery vertiport ID
+    #[prost(string, tag = "7")]
+    pub delivery_vertiport_id: ::prost::alloc::string::String,
+    /// The parcels to carry
+    #[prost(message, repeated, tag = "8")]
+    pub parcels: ::prost::alloc::vec::Vec<Parcel>,
+}
+/// Itinerary stored in the cache until it is booked or expires
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DraftItinerary {
+    /// The draft ID
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// The itinerary information
+    #[prost(message, optional, tag = "2")]
+    pub itinerary: ::core::option::Option<Itinerary>,
+}
+/// Itinerary search request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryItineraryRequest {
+    /// The ID of the vertiport to leave from
+    #[prost(string, tag = "1")]
+    pub origin_vertiport_id: ::prost::alloc::string::String,
+    /// The ID of the destination vertiport
+    #[prost(string, tag = "2")]
+    pub target_vertiport_id: ::prost::alloc::string::String,
+    /// The window of departure
+    #[prost(message, optional, tag = "3")]
+    pub time_depart_window: ::core::option::Option<TimeWindow>,
+    /// Deprecated, use parcels
+    /// The weight of a single parcel of unknown dimensions, only used without parcels
+    #[prost(uint32, optional, tag = "4")]
+    pub cargo_weight_g: ::core::option::Option<u32>,
+    /// The user ID
+    #[prost(string, tag = "5")]
+    pub user_id: ::prost::alloc::string::String,
+    /// The parcels to carry
+    #[prost(message, repeated, tag = "6")]
+    pub parcels: ::prost::alloc::vec::Vec<Parcel>,
+}
+/// Itinerary search response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct QueryItineraryResponse {
+    /// Priced draft itineraries
+    #[prost(message, repeated, tag = "1")]
+    pub itineraries: ::prost::alloc::vec::Vec<DraftItinerary>,
+}
+/// Itinerary booking request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateItineraryRequest {
+    /// The draft itinerary ID to book
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// The user ID
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+}
+/// Itinerary booking response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateItineraryResponse {
+    /// The ID of the booked itinerary
+    #[prost(string, tag = "1")]
+    pub itinerary_id: ::prost::alloc::string::String,
+    /// The registered parcels
+    #[prost(message, repeated, tag = "3")]
+    pub parcels: ::prost::alloc::vec::Vec<CargoInfo>,
+}
+/// Itinerary cancellation request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelItineraryRequest {
+    /// The itinerary ID to cancel
+    #[prost(string, tag = "1")]
+    pub id: ::prost::alloc::string::String,
+    /// The user ID
+    #[prost(string, tag = "2")]
+    pub user_id: ::prost::alloc::string::String,
+}
+/// Itinerary cancellation response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelItineraryResponse {
+    /// The ID of the cancelled itinerary
+    #[prost(string, tag = "1")]
+    pub itinerary_id: ::prost::alloc::string::String,
+    /// Whether the itinerary was cancelled early enough to be refunded
+    #[prost(bool, tag = "2")]
+    pub refund: bool,
+    /// The refunded amount, zero without a refund
+    #[prost(float, tag = "3")]
+    pub refund_amount: f32,
+    /// The currency of the refund
+    #[prost(enumeration = "CurrencyUnit", tag = "4")]
+    pub currency_unit: i32,
+}
+/// Compensation retry request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryCompensationRequest {
+    /// The ID of the itinerary whose booking failed
+    #[prost(string, tag = "1")]
+    pub itinerary_id: ::prost::alloc::string::String,
+}
+/// Compensation retry response object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RetryCompensationResponse {
+    /// The ID of the itinerary whose booking failed
+    #[prost(string, tag = "1")]
+    pub itinerary_id: ::prost::alloc::string::String,
+    /// Whether all changes of the booking are undone
+    #[prost(bool, tag = "2")]
+    pub complete: bool,
+    /// The steps still to be undone
+    #[prost(string, repeated, tag = "3")]
+    pub pending_steps: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// Parcel scan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CargoScan {
+    /// The unique ID (UUID) of the scanner device
+    #[prost(string, tag = "1")]
+    pub scanner_id: ::prost::alloc::string::String,
+    /// The unique ID (UUID) of the parcel, may be empty if code is given
+    #[prost(string, tag = "2")]
+    pub parcel_id: ::prost::alloc::string::String,
+    /// The latitude of the scan location
+    #[prost(double, tag = "3")]
+    pub latitude: f64,
+    /// The longitude of the scan location
+    #[prost(double, tag = "4")]
+    pub longitude: f64,
+    /// The altitude of the scan location
+    #[prost(double, tag = "5")]
+    pub altitude: f64,
+    /// When the scan was taken, by the scanner's clock
+    #[prost(message, optional, tag = "6")]
+    pub timestamp: ::core::option::Option<::lib_common::time::Timestamp>,
+    /// The signed code printed on the parcel label, empty if none
+    #[prost(string, tag = "7")]
+    pub code: ::prost::alloc::string::String,
+    /// When the server received the scan, set by the server
+    #[prost(message, optional, tag = "8")]
+    pub received_at: ::core::option::Option<::lib_common::time::Timestamp>,
+}
+/// Outcome of a single streamed scan
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ScanResult {
+    /// The position of the scan in the stream
+    #[prost(uint32, tag = "1")]
+    pub index: u32,
+    /// The unique ID (UUID) of the scanned parcel
+    #[prost(string, tag = "2")]
+    pub parcel_id: ::prost::alloc::string::String,
+    /// True if the scan was recorded
+    #[prost(bool, tag = "3")]
+    pub accepted: bool,
+    /// The reason the scan was rejected, empty if accepted
+    #[prost(string, tag = "4")]
+    pub reason: ::prost::alloc::string::String,
+}
+/// Scan stream summary
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StreamScansResponse {
+    /// The number of recorded scans
+    #[prost(uint32, tag = "1")]
+    pub accepted: u32,
+    /// The number of rejected scans
+    #[prost(uint32, tag = "2")]
+    pub rejected: u32,
+    /// The outcome of each scan, in stream order
+    #[prost(message, repeated, tag = "3")]
+    pub results: ::prost::alloc::vec::Vec<ScanResult>,
+}
+/// Parcel watch request object
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchParcelRequest {
+    /// The unique ID (UUID) of the parcel
+    #[prost(string, tag = "1")]
+    pub parcel_id: ::prost::alloc::string::String,
+}
+/// Supported Currencies
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CurrencyUnit {
+    /// One U.S. Dollar
+    Usd = 0,
+    /// One E.U. Euro
+    Euro = 1,
+}
+impl CurrencyUnit {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            CurrencyUnit::Usd => "USD",
+            CurrencyUnit::Euro => "EURO",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "USD" => Some(Self::Usd),
+            "EURO" => Some(Self::Euro),
+            _ => None,
+        }
+    }
+}
+/// Special handling required by a parcel
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum HandlingClass {
+    /// Breakable, handle with care
+    Fragile = 0,
+    /// Kept refrigerated in transit
+    Refrigerated = 1,
+    /// Dangerous goods
+    Hazmat = 2,
+}
+impl HandlingClass {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HandlingClass::Fragile => "FRAGILE",
+            HandlingClass::Refrigerated => "REFRIGERATED",
+            HandlingClass::Hazmat => "HAZMAT",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "FRAGILE" => Some(Self::Fragile),
+            "REFRIGERATED" => Some(Self::Refrigerated),
+            "HAZMAT" => Some(Self::Hazmat),
+            _ => None,
+        }
+    }
+}
 /// Generated client implementations.
 #[cfg(not(tarpaulin_include))]
 pub mod rpc_service_client {
@@ -122,5 +526,161 @@ pub mod rpc_service_client {
             req.extensions_mut().insert(GrpcMethod::new("grpc.RpcService", "isReady"));
             self.inner.unary(req, path, codec).await
         }
+        /// Search for available itineraries and store them as drafts
+        pub async fn query_itinerary(
+            &mut self,
+            request: impl tonic::IntoRequest<super::QueryItineraryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::QueryItineraryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/queryItinerary",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "queryItinerary"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Book a draft itinerary
+        pub async fn create_itinerary(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateItineraryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CreateItineraryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/createItinerary",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "createItinerary"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Cancel a booked itinerary
+        pub async fn cancel_itinerary(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelItineraryRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::CancelItineraryResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/cancelItinerary",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "cancelItinerary"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Retry the failed steps undoing a failed booking
+        pub async fn retry_compensation(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RetryCompensationRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::RetryCompensationResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/retryCompensation",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "retryCompensation"));
+            self.inner.unary(req, path, codec).await
+        }
+        /// Record a stream of parcel scans
+        pub async fn stream_scans(
+            &mut self,
+            request: impl tonic::IntoStreamingRequest<Message = super::CargoScan>,
+        ) -> std::result::Result<
+            tonic::Response<super::StreamScansResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/streamScans",
+            );
+            let mut req = request.into_streaming_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "streamScans"));
+            self.inner.client_streaming(req, path, codec).await
+        }
+        /// Send the scan history of a parcel, then each new scan as it is accepted
+        pub async fn watch_parcel(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchParcelRequest>,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::CargoScan>>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/grpc.RpcService/watchParcel",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("grpc.RpcService", "watchParcel"));
+            self.inner.server_streaming(req, path, codec).await
+        }
     }
 }
@@ -11,6 +11,31 @@ where
     type ReadyRequest;
     /// The type expected for ReadyResponse structs.
     type ReadyResponse;
+    /// The type expected for QueryItineraryRequest structs.
+    type QueryItineraryRequest;
+    /// The type expected for QueryItineraryResponse structs.
+    type QueryItineraryResponse;
+    /// The type expected for CreateItineraryRequest structs.
+    type CreateItineraryRequest;
+    /// The type expected for CreateItineraryResponse structs.
+    type CreateItineraryResponse;
+    /// The type expected for CancelItineraryRequest structs.
+    type CancelItineraryRequest;
+    /// The type expected for CancelItineraryResponse structs.
+    type CancelItineraryResponse;
+    /// The type expected for RetryCompensationRequest structs.
+    type RetryCompensationRequest;
+    /// The type expected for RetryCompensationResponse structs.
+    type RetryCompensationResponse;
+    /// The type expected for CargoScan structs.
+    type CargoScan;
+    /// The type expected for StreamScansResponse structs.
+    type StreamScansResponse;
+    /// The type expected for WatchParcelRequest structs.
+    type WatchParcelRequest;
+    /// The stream of [`CargoScan`](Self::CargoScan) results returned by
+    /// [`watch_parcel`](Self::watch_parcel).
+    type WatchParcelStream;
 
     /// Returns a [`tonic::Response`] containing a [`ReadyResponse`](Self::ReadyResponse)
     /// Takes an [`ReadyRequest`](Self::ReadyRequest).
@@ -38,4 +63,235 @@ where
         &self,
         request: Self::ReadyRequest,
     ) -> Result<tonic::Response<Self::ReadyResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`QueryItineraryResponse`](Self::QueryItineraryResponse)
+    /// Takes a [`QueryItineraryRequest`](Self::QueryItineraryRequest).
+    ///
+    /// The returned draft itineraries are priced and can be booked by their ID
+    ///  until they expire.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the request is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Internal`] if a dependency returned an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::{Duration, Utc};
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let timestamp_min = Utc::now() + Duration::try_minutes(10).ok_or("duration")?;
+    ///     let timestamp_max = timestamp_min + Duration::try_hours(1).ok_or("duration")?;
+    ///     let response = client
+    ///         .query_itinerary(cargo::QueryItineraryRequest {
+    ///             origin_vertiport_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///             target_vertiport_id: "59e51ad1-d57d-4d2c-bc2d-e2387367d17f".to_string(),
+    ///             time_depart_window: Some(cargo::TimeWindow {
+    ///                 timestamp_min: Some(timestamp_min.into()),
+    ///                 timestamp_max: Some(timestamp_max.into()),
+    ///             }),
+    ///             parcels: vec![cargo::Parcel {
+    ///                 weight_g: 200,
+    ///                 length_mm: 300,
+    ///                 width_mm: 200,
+    ///                 height_mm: 100,
+    ///                 reference: "blood samples".to_string(),
+    ///                 ..Default::default()
+    ///             }],
+    ///             cargo_weight_g: None,
+    ///             user_id: "a3f5b1a6-3c8e-4c9d-9f2a-6f0b0c3d1e2f".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn query_itinerary(
+        &self,
+        request: Self::QueryItineraryRequest,
+    ) -> Result<tonic::Response<Self::QueryItineraryResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CreateItineraryResponse`](Self::CreateItineraryResponse)
+    /// Takes a [`CreateItineraryRequest`](Self::CreateItineraryRequest).
+    ///
+    /// Books a draft itinerary returned by [`query_itinerary`](Self::query_itinerary).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the request is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::FailedPrecondition`] if svc-scheduler rejected the booking.
+    /// Returns [`tonic::Status`] with [`tonic::Code::DeadlineExceeded`] if svc-scheduler did not respond in time.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Internal`] if a dependency returned an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let response = client
+    ///         .create_itinerary(cargo::CreateItineraryRequest {
+    ///             id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///             user_id: "a3f5b1a6-3c8e-4c9d-9f2a-6f0b0c3d1e2f".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn create_itinerary(
+        &self,
+        request: Self::CreateItineraryRequest,
+    ) -> Result<tonic::Response<Self::CreateItineraryResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`CancelItineraryResponse`](Self::CancelItineraryResponse)
+    /// Takes a [`CancelItineraryRequest`](Self::CancelItineraryRequest).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the request is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::Internal`] if a dependency returned an error.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let response = client
+    ///         .cancel_itinerary(cargo::CancelItineraryRequest {
+    ///             id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///             user_id: "a3f5b1a6-3c8e-4c9d-9f2a-6f0b0c3d1e2f".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn cancel_itinerary(
+        &self,
+        request: Self::CancelItineraryRequest,
+    ) -> Result<tonic::Response<Self::CancelItineraryResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`RetryCompensationResponse`](Self::RetryCompensationResponse)
+    /// Takes a [`RetryCompensationRequest`](Self::RetryCompensationRequest).
+    ///
+    /// Retries the steps undoing a failed booking that have not succeeded
+    /// yet, for example after the retries in the background gave up.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the itinerary ID is invalid.
+    /// Returns [`tonic::Status`] with [`tonic::Code::NotFound`] if no booking of the itinerary failed.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let response = client
+    ///         .retry_compensation(cargo::RetryCompensationRequest {
+    ///             itinerary_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///         })
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn retry_compensation(
+        &self,
+        request: Self::RetryCompensationRequest,
+    ) -> Result<tonic::Response<Self::RetryCompensationResponse>, tonic::Status>;
+
+    /// Returns a [`tonic::Response`] containing a [`StreamScansResponse`](Self::StreamScansResponse)
+    /// Takes a stream of [`CargoScan`](Self::CargoScan) messages.
+    ///
+    /// Each scan is validated and recorded as it arrives. Rejected scans do not
+    /// end the stream, the response lists the outcome of every scan once the
+    /// stream is closed.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] if the stream could not be read.
+    ///
+    /// # Examples
+    /// ```
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use lib_common::time::Utc;
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let scans = vec![cargo::CargoScan {
+    ///         scanner_id: "0bb5f9a1-d3ac-4a64-8e2b-44bd3ee4c4b0".to_string(),
+    ///         parcel_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///         latitude: 52.3745905,
+    ///         longitude: 4.9160036,
+    ///         altitude: 0.0,
+    ///         timestamp: Some(Utc::now().into()),
+    ///         ..Default::default()
+    ///     }];
+    ///     let response = client
+    ///         .stream_scans(futures::stream::iter(scans))
+    ///         .await?;
+    ///     println!("RESPONSE={:?}", response.into_inner());
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn stream_scans<R>(
+        &self,
+        request: R,
+    ) -> Result<tonic::Response<Self::StreamScansResponse>, tonic::Status>
+    where
+        R: tonic::IntoStreamingRequest<Message = Self::CargoScan> + Send + 'static;
+
+    /// Returns a [`tonic::Response`] containing a [`WatchParcelStream`](Self::WatchParcelStream)
+    /// Takes a [`WatchParcelRequest`](Self::WatchParcelRequest).
+    ///
+    /// The stream starts with the recorded scans of the parcel, followed by
+    /// each new scan as it is accepted. It stays open until either side
+    /// closes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`tonic::Status`] with [`tonic::Code::InvalidArgument`] if the parcel ID is not a UUID.
+    /// Returns [`tonic::Status`] with [`tonic::Code::NotFound`] if the scan history could not be retrieved.
+    ///
+    /// # Examples
+    /// ```
+    /// use futures::StreamExt;
+    /// use lib_common::grpc::get_endpoint_from_env;
+    /// use svc_cargo_client_grpc::prelude::*;
+    ///
+    /// async fn example () -> Result<(), Box<dyn std::error::Error>> {
+    ///     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_GRPC");
+    ///     let client = CargoClient::new_client(&host, port, "cargo");
+    ///     let mut scans = client
+    ///         .watch_parcel(cargo::WatchParcelRequest {
+    ///             parcel_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+    ///         })
+    ///         .await?
+    ///         .into_inner();
+    ///     while let Some(scan) = scans.next().await {
+    ///         println!("SCAN={:?}", scan?);
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    async fn watch_parcel(
+        &self,
+        request: Self::WatchParcelRequest,
+    ) -> Result<tonic::Response<Self::WatchParcelStream>, tonic::Status>;
 }
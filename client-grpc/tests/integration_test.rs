@@ -18,6 +18,7 @@ fn get_log_string(function: &str, name: &str) -> String {
 async fn test_client_requests_and_logs() {
     use logtest::Logger;
 
+    use lib_common::time::{Duration, Utc};
     use svc_cargo_client_grpc::prelude::*;
 
     let name = "cargo";
@@ -25,6 +26,7 @@ async fn test_client_requests_and_logs() {
         lib_common::grpc::get_endpoint_from_env("GRPC_HOST", "GRPC_PORT");
 
     let client = CargoClient::new_client(&server_host, server_port, name);
+    let user_id = "a3f5b1a6-3c8e-4c9d-9f2a-6f0b0c3d1e2f".to_string();
 
     // Start the logger.
     let mut logger = Logger::start();
@@ -49,4 +51,154 @@ async fn test_client_requests_and_logs() {
             }
         }));
     }
+
+    //test_query_itinerary_request_logs
+    {
+        let timestamp_min = Utc::now() + Duration::try_minutes(10).unwrap();
+        let timestamp_max = timestamp_min + Duration::try_hours(1).unwrap();
+        let result = client
+            .query_itinerary(cargo::QueryItineraryRequest {
+                origin_vertiport_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+                target_vertiport_id: "59e51ad1-d57d-4d2c-bc2d-e2387367d17f".to_string(),
+                time_depart_window: Some(cargo::TimeWindow {
+                    timestamp_min: Some(timestamp_min.into()),
+                    timestamp_max: Some(timestamp_max.into()),
+                }),
+                parcels: vec![cargo::Parcel {
+                    weight_g: 200,
+                    length_mm: 300,
+                    width_mm: 200,
+                    height_mm: 100,
+                    reference: "blood samples".to_string(),
+                    ..Default::default()
+                }],
+                cargo_weight_g: None,
+                user_id: user_id.clone(),
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        // Search for the expected log message
+        let expected = get_log_string("query_itinerary", name);
+        println!("expected message: {}", expected);
+        assert!(logger.any(|log| {
+            if log.target().contains("app::") {
+                println!("{}", log.target());
+                let message = log.args();
+                println!("{:?}", message);
+                log.args() == expected
+            } else {
+                false
+            }
+        }));
+    }
+
+    //test_create_itinerary_request_logs
+    {
+        let result = client
+            .create_itinerary(cargo::CreateItineraryRequest {
+                id: "2b6c3e0a-8f4d-4b7e-9a1c-5d3e7f9b1a2c".to_string(),
+                user_id: user_id.clone(),
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        // Search for the expected log message
+        let expected = get_log_string("create_itinerary", name);
+        println!("expected message: {}", expected);
+        assert!(logger.any(|log| {
+            if log.target().contains("app::") {
+                println!("{}", log.target());
+                let message = log.args();
+                println!("{:?}", message);
+                log.args() == expected
+            } else {
+                false
+            }
+        }));
+    }
+
+    //test_cancel_itinerary_request_logs
+    {
+        let result = client
+            .cancel_itinerary(cargo::CancelItineraryRequest {
+                id: "7e1d2c3b-4a5f-4e6d-8c9b-0a1b2c3d4e5f".to_string(),
+                user_id: user_id.clone(),
+            })
+            .await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+
+        // Search for the expected log message
+        let expected = get_log_string("cancel_itinerary", name);
+        println!("expected message: {}", expected);
+        assert!(logger.any(|log| {
+            if log.target().contains("app::") {
+                println!("{}", log.target());
+                let message = log.args();
+                println!("{:?}", message);
+                log.args() == expected
+            } else {
+                false
+            }
+        }));
+    }
+
+    //test_stream_scans_request_logs
+    {
+        let scans = vec![cargo::CargoScan {
+            scanner_id: "0bb5f9a1-d3ac-4a64-8e2b-44bd3ee4c4b0".to_string(),
+            parcel_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+            latitude: 52.3745905,
+            longitude: 4.9160036,
+            altitude: 0.0,
+            timestamp: Some(Utc::now().into()),
+            ..Default::default()
+        }];
+        let result = client.stream_scans(futures::stream::iter(scans)).await;
+        println!("{:?}", result);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap().into_inner().results.len(), 1);
+
+        // Search for the expected log message
+        let expected = get_log_string("stream_scans", name);
+        println!("expected message: {}", expected);
+        assert!(logger.any(|log| {
+            if log.target().contains("app::") {
+                println!("{}", log.target());
+                let message = log.args();
+                println!("{:?}", message);
+                log.args() == expected
+            } else {
+                false
+            }
+        }));
+    }
+
+    //test_watch_parcel_request_logs
+    {
+        let result = client
+            .watch_parcel(cargo::WatchParcelRequest {
+                parcel_id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
+            })
+            .await;
+        println!("{:?}", result.is_ok());
+        assert!(result.is_ok());
+
+        // Search for the expected log message
+        let expected = get_log_string("watch_parcel", name);
+        println!("expected message: {}", expected);
+        assert!(logger.any(|log| {
+            if log.target().contains("app::") {
+                println!("{}", log.target());
+                let message = log.args();
+                println!("{:?}", message);
+                log.args() == expected
+            } else {
+                false
+            }
+        }));
+    }
 }
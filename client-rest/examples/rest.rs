@@ -30,10 +30,11 @@ fn evaluate(resp: Result<Response<Body>, Error>, expected_code: StatusCode) -> (
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("NOTE: Ensure the server is running, or this example will fail.");
 
-    let rate_limit = std::env::var("REQUEST_LIMIT_PER_SECOND")
-        .unwrap_or_else(|_| "2".to_string())
+    // Burst allowed per client for query routes such as /cargo/vertiports
+    let rate_limit = std::env::var("REST_QUERY_LIMIT_BURST")
+        .unwrap_or_else(|_| "10".to_string())
         .parse::<u64>()
-        .unwrap_or(2);
+        .unwrap_or(10);
 
     let (host, port) = get_endpoint_from_env("SERVER_HOSTNAME", "SERVER_PORT_REST");
     let url = format!("http://{host}:{port}");
@@ -119,7 +120,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 timestamp_min: depart_timestamp_min,
                 timestamp_max: depart_timestamp_min + Duration::seconds(360),
             },
-            cargo_weight_g: 200,
+            parcels: vec![Parcel {
+                weight_g: 200,
+                length_mm: 300,
+                width_mm: 200,
+                height_mm: 100,
+                reference: Some("samples".to_string()),
+                handling: vec![],
+                recipient: None,
+                delivery_pin: false,
+            }],
+            cargo_weight_g: None,
             user_id: Uuid::new_v4().to_string(),
         };
 
@@ -170,7 +181,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
 
         let resp = client.request(req).await;
-        let (success, result_str) = evaluate(resp, StatusCode::OK);
+        let (success, result_str) = evaluate(resp, StatusCode::ACCEPTED);
         ok &= success;
 
         println!("{}: {}", uri, result_str);
@@ -182,7 +193,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // DELETE /cargo/cancel
     {
         let data = ItineraryCancelRequest {
-            // arbitrary UUIDs
+            // arbitrary UUIDs, not booked
             id: "cabcdd14-03ab-4ac0-b58c-dd4175bc587e".to_string(),
             user_id: Uuid::new_v4().to_string(),
         };
@@ -202,7 +213,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         };
 
         let resp = client.request(req).await;
-        let (success, result_str) = evaluate(resp, StatusCode::OK);
+        let (success, result_str) = evaluate(resp, StatusCode::NOT_FOUND);
         ok &= success;
 
         println!("{}: {}", uri, result_str);
@@ -216,10 +227,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let data = CargoScan {
             scanner_id: Uuid::new_v4().to_string(),
             parcel_id: Uuid::new_v4().to_string(),
+            code: None,
             latitude: 52.37474373455002,
             longitude: 4.9167298573581295,
             altitude: 0.0,
             timestamp: Utc::now(),
+            received_at: None,
         };
 
         let Ok(data) = serde_json::to_string(&data) else {
@@ -0,0 +1,905 @@
+//! Command-line arguments and request inputs
+
+use clap::{Args, Parser, Subcommand};
+use lib_common::time::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use svc_cargo_client_rest::client::{DEFAULT_RETRIES, DEFAULT_TIMEOUT_MS};
+use svc_cargo_client_rest::types::*;
+
+/// Errors reading the request inputs
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputError {
+    /// The input file could not be read
+    File(String),
+
+    /// The input file is not valid JSON for the request
+    Json(String),
+
+    /// A required flag was not provided
+    Missing(&'static str),
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            InputError::File(e) => write!(f, "could not read input file: {e}"),
+            InputError::Json(e) => write!(f, "invalid input file: {e}"),
+            InputError::Missing(flag) => write!(f, "missing required flag --{flag}"),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// svc-cargo operator tool
+#[derive(Parser, Debug)]
+#[command(name = "cargo-cli", version, about)]
+pub struct Cli {
+    /// svc-cargo REST server URL
+    #[arg(long, env = "CARGO_REST_URL", default_value = "http://localhost:8000")]
+    pub url: String,
+
+    /// Time allowed for each request attempt, in milliseconds
+    #[arg(long, default_value_t = DEFAULT_TIMEOUT_MS)]
+    pub timeout_ms: u64,
+
+    /// Number of retries when the server could not process a request
+    #[arg(long, default_value_t = DEFAULT_RETRIES)]
+    pub retries: u32,
+
+    /// Bearer token identifying the user, required to quote, book and cancel
+    #[arg(long, env = "CARGO_REST_TOKEN", hide_env_values = true)]
+    pub token: Option<String>,
+
+    /// Print raw JSON instead of tables
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// The operation to run
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+/// Operations
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Find vertiports near a location
+    Vertiports(VertiportsArgs),
+
+    /// Get priced draft itineraries
+    Quote(QuoteArgs),
+
+    /// Show a draft itinerary and the time left to book it
+    Draft(DraftArgs),
+
+    /// Book a draft itinerary
+    Book(BookArgs),
+
+    /// Show the status of a booking job
+    Booking(BookingArgs),
+
+    /// Cancel a booked itinerary
+    Cancel(CancelArgs),
+
+    /// List booked itineraries
+    Itineraries(ItinerariesArgs),
+
+    /// Show a booked itinerary with its legs and parcels
+    Itinerary(ItineraryArgs),
+
+    /// Get replacements for a booked itinerary, or confirm one
+    Modify(ModifyArgs),
+
+    /// Record a parcel scan
+    Scan(ScanArgs),
+
+    /// Deliver a parcel with a proof of delivery
+    Deliver(DeliverArgs),
+
+    /// Show the status and scans of a parcel
+    Track(TrackArgs),
+
+    /// Get the shipping label of a parcel
+    Label(LabelArgs),
+
+    /// Issue a new delivery PIN for a parcel
+    Pin(PinArgs),
+
+    /// Show the upcoming landings at a vertiport
+    Occupations(OccupationsArgs),
+
+    /// Show or change who is notified of parcel events
+    Notifications(NotificationsArgs),
+}
+
+/// Reads a request from a JSON file
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, InputError> {
+    let data = std::fs::read_to_string(path).map_err(|e| InputError::File(e.to_string()))?;
+    serde_json::from_str(&data).map_err(|e| InputError::Json(e.to_string()))
+}
+
+fn require<T>(value: Option<T>, flag: &'static str) -> Result<T, InputError> {
+    value.ok_or(InputError::Missing(flag))
+}
+
+/// `POST /cargo/vertiports` inputs
+#[derive(Args, Debug)]
+pub struct VertiportsArgs {
+    /// JSON file with a QueryVertiportsRequest, instead of flags
+    #[arg(long, conflicts_with_all = ["latitude", "longitude"])]
+    pub file: Option<PathBuf>,
+
+    /// Latitude of the search location
+    #[arg(long, allow_negative_numbers = true)]
+    pub latitude: Option<f32>,
+
+    /// Longitude of the search location
+    #[arg(long, allow_negative_numbers = true)]
+    pub longitude: Option<f32>,
+}
+
+impl VertiportsArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<QueryVertiportsRequest, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        Ok(QueryVertiportsRequest {
+            latitude: require(self.latitude, "latitude")?,
+            longitude: require(self.longitude, "longitude")?,
+        })
+    }
+}
+
+/// `POST /cargo/request` inputs
+#[derive(Args, Debug)]
+pub struct QuoteArgs {
+    /// JSON file with a QueryItineraryRequest, instead of flags
+    #[arg(long, conflicts_with_all = ["origin", "target", "depart_after", "depart_before", "parcel"])]
+    pub file: Option<PathBuf>,
+
+    /// Origin vertiport ID
+    #[arg(long)]
+    pub origin: Option<String>,
+
+    /// Destination vertiport ID
+    #[arg(long)]
+    pub target: Option<String>,
+
+    /// Earliest departure (RFC 3339)
+    #[arg(long)]
+    pub depart_after: Option<DateTime<Utc>>,
+
+    /// Latest departure (RFC 3339)
+    #[arg(long)]
+    pub depart_before: Option<DateTime<Utc>>,
+
+    /// A parcel as WEIGHT_G:LENGTHxWIDTHxHEIGHT_MM[:REFERENCE], repeated for
+    ///  each parcel. Parcels needing special handling, a recipient or a
+    ///  delivery PIN are given with --file.
+    #[arg(long, value_parser = parse_parcel)]
+    pub parcel: Vec<Parcel>,
+}
+
+/// Parse a parcel given as WEIGHT_G:LENGTHxWIDTHxHEIGHT_MM[:REFERENCE]
+fn parse_parcel(value: &str) -> Result<Parcel, String> {
+    let mut fields = value.splitn(3, ':');
+    let number = |field: &str| {
+        field
+            .trim()
+            .parse::<u32>()
+            .map_err(|e| format!("invalid number {field:?}: {e}"))
+    };
+
+    let weight_g = number(fields.next().unwrap_or_default())?;
+    let dimensions = fields
+        .next()
+        .ok_or_else(|| "expected WEIGHT_G:LENGTHxWIDTHxHEIGHT_MM[:REFERENCE]".to_string())?
+        .split('x')
+        .map(number)
+        .collect::<Result<Vec<u32>, String>>()?;
+
+    let [length_mm, width_mm, height_mm] = dimensions[..] else {
+        return Err("expected dimensions as LENGTHxWIDTHxHEIGHT_MM".to_string());
+    };
+
+    Ok(Parcel {
+        weight_g,
+        length_mm,
+        width_mm,
+        height_mm,
+        reference: fields.next().map(str::to_string),
+        handling: vec![],
+        recipient: None,
+        delivery_pin: false,
+    })
+}
+
+impl QuoteArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<QueryItineraryRequest, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        Ok(QueryItineraryRequest {
+            origin_vertiport_id: require(self.origin, "origin")?,
+            target_vertiport_id: require(self.target, "target")?,
+            time_depart_window: TimeWindow {
+                timestamp_min: require(self.depart_after, "depart-after")?,
+                timestamp_max: require(self.depart_before, "depart-before")?,
+            },
+            parcels: require(Some(self.parcel).filter(|p| !p.is_empty()), "parcel")?,
+            cargo_weight_g: None,
+            user_id: String::new(),
+        })
+    }
+}
+
+/// `GET /cargo/create/{job_id}` inputs
+#[derive(Args, Debug)]
+pub struct BookingArgs {
+    /// Booking job ID
+    pub job_id: String,
+
+    /// Check again every few seconds until the booking completes or fails
+    #[arg(long)]
+    pub wait: bool,
+}
+
+/// `GET /cargo/draft/{id}` inputs
+#[derive(Args, Debug)]
+pub struct DraftArgs {
+    /// Draft itinerary ID
+    pub draft_id: String,
+
+    /// Check availability, price again and extend the draft expiry
+    #[arg(long)]
+    pub refresh: bool,
+}
+
+/// `PUT /cargo/create` inputs
+#[derive(Args, Debug)]
+pub struct BookArgs {
+    /// JSON file with an ItineraryCreateRequest, instead of flags
+    #[arg(long, conflicts_with = "id")]
+    pub file: Option<PathBuf>,
+
+    /// Draft itinerary ID
+    #[arg(long)]
+    pub id: Option<String>,
+}
+
+impl BookArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<ItineraryCreateRequest, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        Ok(ItineraryCreateRequest {
+            id: require(self.id, "id")?,
+            user_id: String::new(),
+        })
+    }
+}
+
+/// `DELETE /cargo/cancel` inputs
+#[derive(Args, Debug)]
+pub struct CancelArgs {
+    /// JSON file with an ItineraryCancelRequest, instead of flags
+    #[arg(long, conflicts_with = "id")]
+    pub file: Option<PathBuf>,
+
+    /// Itinerary ID
+    #[arg(long)]
+    pub id: Option<String>,
+}
+
+impl CancelArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<ItineraryCancelRequest, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        Ok(ItineraryCancelRequest {
+            id: require(self.id, "id")?,
+            user_id: String::new(),
+        })
+    }
+}
+
+/// `GET /cargo/itineraries` inputs
+#[derive(Args, Debug)]
+pub struct ItinerariesArgs {
+    /// Only itineraries with this status (active, cancelled)
+    #[arg(long, value_parser = parse_itinerary_status)]
+    pub status: Option<ItineraryStatus>,
+
+    /// Only itineraries booked at or after this time (RFC 3339)
+    #[arg(long)]
+    pub booked_after: Option<DateTime<Utc>>,
+
+    /// Only itineraries booked before this time (RFC 3339)
+    #[arg(long)]
+    pub booked_before: Option<DateTime<Utc>>,
+
+    /// Page to show, starting at 1
+    #[arg(long)]
+    pub page: Option<u32>,
+
+    /// Itineraries per page
+    #[arg(long)]
+    pub per_page: Option<u32>,
+}
+
+impl ItinerariesArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> QueryItinerariesRequest {
+        QueryItinerariesRequest {
+            status: self.status,
+            booked_after: self.booked_after,
+            booked_before: self.booked_before,
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+}
+
+/// `GET /cargo/itinerary/{id}` inputs
+#[derive(Args, Debug)]
+pub struct ItineraryArgs {
+    /// Itinerary ID
+    pub itinerary_id: String,
+}
+
+/// `POST` and `PUT /cargo/itinerary/{id}/modify` inputs
+#[derive(Args, Debug)]
+pub struct ModifyArgs {
+    /// Itinerary ID
+    pub itinerary_id: String,
+
+    /// Replace the itinerary with this draft, returned by a previous call
+    #[arg(long, conflicts_with_all = ["depart_after", "depart_before", "weight_g"])]
+    pub confirm: Option<String>,
+
+    /// New earliest departure (RFC 3339)
+    #[arg(long, requires = "depart_before")]
+    pub depart_after: Option<DateTime<Utc>>,
+
+    /// New latest departure (RFC 3339)
+    #[arg(long, requires = "depart_after")]
+    pub depart_before: Option<DateTime<Utc>>,
+
+    /// New cargo weight in grams
+    #[arg(long)]
+    pub weight_g: Option<u32>,
+}
+
+impl ModifyArgs {
+    /// The replacement query described by the arguments
+    pub fn request(&self) -> ItineraryModifyRequest {
+        let time_depart_window = match (self.depart_after, self.depart_before) {
+            (Some(timestamp_min), Some(timestamp_max)) => Some(TimeWindow {
+                timestamp_min,
+                timestamp_max,
+            }),
+            _ => None,
+        };
+
+        ItineraryModifyRequest {
+            time_depart_window,
+            cargo_weight_g: self.weight_g,
+        }
+    }
+}
+
+fn parse_itinerary_status(value: &str) -> Result<ItineraryStatus, String> {
+    match value.to_lowercase().as_str() {
+        "active" => Ok(ItineraryStatus::Active),
+        "cancelled" => Ok(ItineraryStatus::Cancelled),
+        _ => Err(format!(
+            "unknown status {value}, expected active or cancelled"
+        )),
+    }
+}
+
+/// `PUT /cargo/scan` inputs
+#[derive(Args, Debug)]
+pub struct ScanArgs {
+    /// JSON file with a CargoScan, instead of flags
+    #[arg(long, conflicts_with_all = ["parcel_id", "code", "scanner_id", "latitude", "longitude", "altitude", "timestamp"])]
+    pub file: Option<PathBuf>,
+
+    /// Parcel ID
+    #[arg(long)]
+    pub parcel_id: Option<String>,
+
+    /// Signed code from the parcel label, instead of the parcel ID
+    #[arg(long)]
+    pub code: Option<String>,
+
+    /// Scanner ID
+    #[arg(long)]
+    pub scanner_id: Option<String>,
+
+    /// Latitude of the scan
+    #[arg(long, allow_negative_numbers = true)]
+    pub latitude: Option<f64>,
+
+    /// Longitude of the scan
+    #[arg(long, allow_negative_numbers = true)]
+    pub longitude: Option<f64>,
+
+    /// Altitude of the scan
+    #[arg(long, allow_negative_numbers = true, default_value_t = 0.0)]
+    pub altitude: f64,
+
+    /// Time of the scan (RFC 3339), defaults to now
+    #[arg(long)]
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+impl ScanArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<CargoScan, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        let parcel_id = match self.code {
+            Some(_) => self.parcel_id.unwrap_or_default(),
+            None => require(self.parcel_id, "parcel-id")?,
+        };
+
+        Ok(CargoScan {
+            parcel_id,
+            code: self.code,
+            scanner_id: require(self.scanner_id, "scanner-id")?,
+            latitude: require(self.latitude, "latitude")?,
+            longitude: require(self.longitude, "longitude")?,
+            altitude: self.altitude,
+            timestamp: self.timestamp.unwrap_or_else(Utc::now),
+            received_at: None,
+        })
+    }
+}
+
+/// `GET /cargo/notifications` and `PUT /cargo/notifications` inputs
+#[derive(Args, Debug)]
+pub struct NotificationsArgs {
+    /// Notify you of the events of your parcels
+    #[arg(long)]
+    pub sender: Option<bool>,
+
+    /// Notify the recipients of your parcels
+    #[arg(long)]
+    pub recipient: Option<bool>,
+}
+
+impl NotificationsArgs {
+    /// The settings to keep, or None to only show them
+    pub fn request(&self, current: NotificationSettings) -> Option<NotificationSettings> {
+        if self.sender.is_none() && self.recipient.is_none() {
+            return None;
+        }
+
+        Some(NotificationSettings {
+            sender: self.sender.unwrap_or(current.sender),
+            recipient: self.recipient.unwrap_or(current.recipient),
+        })
+    }
+}
+
+/// `PUT /cargo/deliver` inputs
+#[derive(Args, Debug)]
+pub struct DeliverArgs {
+    /// The final scan of the parcel
+    #[command(flatten)]
+    pub scan: ScanArgs,
+
+    /// Name of the person receiving the parcel
+    #[arg(long)]
+    pub recipient: Option<String>,
+
+    /// SHA-256 hash (hex) of the recipient's signature or of a photo of the
+    ///  handover
+    #[arg(long)]
+    pub content_hash: Option<String>,
+
+    /// The delivery PIN given by the recipient
+    #[arg(long)]
+    pub pin: Option<String>,
+}
+
+impl DeliverArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<DeliveryConfirmation, InputError> {
+        Ok(DeliveryConfirmation {
+            scan: self.scan.request()?,
+            recipient_name: require(self.recipient, "recipient")?,
+            content_hash: require(self.content_hash, "content-hash")?,
+            pin: self.pin,
+        })
+    }
+}
+
+/// `GET /cargo/track/{id}` inputs
+#[derive(Args, Debug)]
+pub struct TrackArgs {
+    /// Parcel ID
+    pub parcel_id: String,
+
+    /// Keep printing new scans as they are accepted
+    #[arg(long)]
+    pub watch: bool,
+}
+
+/// `GET /cargo/parcel/{id}/label` inputs
+#[derive(Args, Debug)]
+pub struct LabelArgs {
+    /// Parcel ID
+    pub parcel_id: String,
+
+    /// Print the label for Zebra printers instead
+    #[arg(long)]
+    pub zpl: bool,
+}
+
+/// `POST /cargo/parcel/{id}/pin` inputs
+#[derive(Args, Debug)]
+pub struct PinArgs {
+    /// Parcel ID
+    pub parcel_id: String,
+}
+
+/// `POST /cargo/occupations` inputs
+#[derive(Args, Debug)]
+pub struct OccupationsArgs {
+    /// JSON file with a QueryScheduleRequest, instead of flags
+    #[arg(long, conflicts_with_all = ["vertiport_id", "after", "before", "limit"])]
+    pub file: Option<PathBuf>,
+
+    /// Vertiport ID
+    #[arg(long)]
+    pub vertiport_id: Option<String>,
+
+    /// Earliest arrival (RFC 3339)
+    #[arg(long, requires = "before")]
+    pub after: Option<DateTime<Utc>>,
+
+    /// Latest arrival (RFC 3339)
+    #[arg(long, requires = "after")]
+    pub before: Option<DateTime<Utc>>,
+
+    /// Maximum number of landings to return
+    #[arg(long, default_value_t = MAX_LANDINGS_TO_RETURN)]
+    pub limit: u32,
+}
+
+impl OccupationsArgs {
+    /// The request described by the arguments
+    pub fn request(self) -> Result<QueryScheduleRequest, InputError> {
+        if let Some(file) = self.file {
+            return load(&file);
+        }
+
+        let arrival_window = match (self.after, self.before) {
+            (Some(timestamp_min), Some(timestamp_max)) => Some(TimeWindow {
+                timestamp_min,
+                timestamp_max,
+            }),
+            _ => None,
+        };
+
+        Ok(QueryScheduleRequest {
+            vertiport_id: require(self.vertiport_id, "vertiport-id")?,
+            arrival_window,
+            limit: self.limit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cli_definition() {
+        use clap::CommandFactory;
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_quote_from_flags() {
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "quote",
+            "--origin",
+            "a",
+            "--target",
+            "b",
+            "--depart-after",
+            "2026-01-01T10:00:00Z",
+            "--depart-before",
+            "2026-01-01T11:00:00Z",
+            "--parcel",
+            "500:300x200x100:samples",
+            "--parcel",
+            "250:100x100x100",
+        ])
+        .unwrap();
+
+        let Command::Quote(args) = cli.command else {
+            panic!("expected quote command");
+        };
+
+        let request = args.request().unwrap();
+        assert_eq!(request.origin_vertiport_id, "a");
+        assert_eq!(request.target_vertiport_id, "b");
+        assert_eq!(request.parcels.len(), 2);
+        assert_eq!(request.parcels[0].reference, Some("samples".to_string()));
+        assert_eq!(request.parcels[1].reference, None);
+        assert_eq!(request.cargo_weight_g(), 750);
+        assert!(request.user_id.is_empty());
+    }
+
+    #[test]
+    fn test_parse_parcel() {
+        let parcel = parse_parcel("500:300x200x100").unwrap();
+        assert_eq!(parcel.weight_g, 500);
+        assert_eq!(parcel.length_mm, 300);
+        assert_eq!(parcel.width_mm, 200);
+        assert_eq!(parcel.height_mm, 100);
+        assert_eq!(parcel.reference, None);
+
+        let parcel = parse_parcel("500:300x200x100:box: fragile").unwrap();
+        assert_eq!(parcel.reference, Some("box: fragile".to_string()));
+
+        assert!(parse_parcel("500").is_err());
+        assert!(parse_parcel("500:300x200").is_err());
+        assert!(parse_parcel("heavy:300x200x100").is_err());
+    }
+
+    #[test]
+    fn test_token() {
+        let cli =
+            Cli::try_parse_from(["cargo-cli", "--token", "abc", "book", "--id", "a"]).unwrap();
+        assert_eq!(cli.token, Some("abc".to_string()));
+    }
+
+    #[test]
+    fn test_itineraries_from_flags() {
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "itineraries",
+            "--status",
+            "Cancelled",
+            "--page",
+            "2",
+        ])
+        .unwrap();
+
+        let Command::Itineraries(args) = cli.command else {
+            panic!("expected itineraries command");
+        };
+
+        let request = args.request();
+        assert_eq!(request.status, Some(ItineraryStatus::Cancelled));
+        assert_eq!(request.page, Some(2));
+        assert!(request.per_page.is_none());
+
+        let result = Cli::try_parse_from(["cargo-cli", "itineraries", "--status", "lost"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_modify_from_flags() {
+        let cli = Cli::try_parse_from(["cargo-cli", "modify", "a", "--weight-g", "500"]).unwrap();
+        let Command::Modify(args) = cli.command else {
+            panic!("expected modify command");
+        };
+
+        let request = args.request();
+        assert_eq!(request.cargo_weight_g, Some(500));
+        assert!(request.time_depart_window.is_none());
+        assert!(args.confirm.is_none());
+
+        // both ends of the window are needed
+        let result = Cli::try_parse_from([
+            "cargo-cli",
+            "modify",
+            "a",
+            "--depart-after",
+            "2026-01-01T10:00:00Z",
+        ]);
+        assert!(result.is_err());
+
+        // confirming doesn't take new values
+        let result = Cli::try_parse_from([
+            "cargo-cli",
+            "modify",
+            "a",
+            "--confirm",
+            "b",
+            "--weight-g",
+            "500",
+        ]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_deliver_from_flags() {
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "deliver",
+            "--parcel-id",
+            "a",
+            "--scanner-id",
+            "b",
+            "--latitude",
+            "52.1",
+            "--longitude",
+            "-4.9",
+            "--recipient",
+            "J. Doe",
+            "--content-hash",
+            "abc",
+            "--pin",
+            "123456",
+        ])
+        .unwrap();
+
+        let Command::Deliver(args) = cli.command else {
+            panic!("expected deliver command");
+        };
+
+        let request = args.request().unwrap();
+        assert_eq!(request.scan.parcel_id, "a");
+        assert_eq!(request.scan.longitude, -4.9);
+        assert_eq!(request.recipient_name, "J. Doe");
+        assert_eq!(request.content_hash, "abc");
+        assert_eq!(request.pin, Some("123456".to_string()));
+
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "deliver",
+            "--parcel-id",
+            "a",
+            "--scanner-id",
+            "b",
+            "--latitude",
+            "0",
+            "--longitude",
+            "0",
+        ])
+        .unwrap();
+        let Command::Deliver(args) = cli.command else {
+            panic!("expected deliver command");
+        };
+
+        assert_eq!(
+            args.request().unwrap_err(),
+            InputError::Missing("recipient")
+        );
+    }
+
+    #[test]
+    fn test_scan_with_code() {
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "scan",
+            "--code",
+            "header.payload.signature",
+            "--scanner-id",
+            "b",
+            "--latitude",
+            "52.1",
+            "--longitude",
+            "4.9",
+        ])
+        .unwrap();
+
+        let Command::Scan(args) = cli.command else {
+            panic!("expected scan command");
+        };
+
+        let request = args.request().unwrap();
+        assert!(request.parcel_id.is_empty());
+        assert_eq!(request.code, Some("header.payload.signature".to_string()));
+
+        let cli = Cli::try_parse_from([
+            "cargo-cli",
+            "scan",
+            "--scanner-id",
+            "b",
+            "--latitude",
+            "52.1",
+            "--longitude",
+            "4.9",
+        ])
+        .unwrap();
+        let Command::Scan(args) = cli.command else {
+            panic!("expected scan command");
+        };
+
+        assert_eq!(
+            args.request().unwrap_err(),
+            InputError::Missing("parcel-id")
+        );
+    }
+
+    #[test]
+    fn test_notifications_settings() {
+        let current = NotificationSettings::default();
+
+        let cli = Cli::try_parse_from(["cargo-cli", "notifications"]).unwrap();
+        let Command::Notifications(args) = cli.command else {
+            panic!("expected notifications command");
+        };
+        assert_eq!(args.request(current), None);
+
+        let cli =
+            Cli::try_parse_from(["cargo-cli", "notifications", "--recipient", "false"]).unwrap();
+        let Command::Notifications(args) = cli.command else {
+            panic!("expected notifications command");
+        };
+        assert_eq!(
+            args.request(current),
+            Some(NotificationSettings {
+                sender: true,
+                recipient: false,
+            })
+        );
+    }
+
+    #[test]
+    fn test_missing_flag() {
+        let cli = Cli::try_parse_from(["cargo-cli", "book"]).unwrap();
+        let Command::Book(args) = cli.command else {
+            panic!("expected book command");
+        };
+
+        assert_eq!(args.request().unwrap_err(), InputError::Missing("id"));
+    }
+
+    #[test]
+    fn test_file_conflicts_with_flags() {
+        let result =
+            Cli::try_parse_from(["cargo-cli", "cancel", "--file", "cancel.json", "--id", "a"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let path = std::env::temp_dir().join("cargo-cli-test-cancel.json");
+        std::fs::write(&path, r#"{"id": "a"}"#).unwrap();
+        let request: ItineraryCancelRequest = load(&path).unwrap();
+        assert_eq!(request.id, "a");
+        assert!(request.user_id.is_empty());
+
+        std::fs::write(&path, "{").unwrap();
+        let error = load::<ItineraryCancelRequest>(&path).unwrap_err();
+        assert!(matches!(error, InputError::Json(_)));
+        std::fs::remove_file(&path).unwrap();
+
+        let error = load::<ItineraryCancelRequest>(&path).unwrap_err();
+        assert!(matches!(error, InputError::File(_)));
+    }
+
+    #[test]
+    fn test_input_error_display() {
+        assert_eq!(
+            InputError::Missing("id").to_string(),
+            "missing required flag --id"
+        );
+        assert_eq!(
+            InputError::File("denied".to_string()).to_string(),
+            "could not read input file: denied"
+        );
+    }
+}
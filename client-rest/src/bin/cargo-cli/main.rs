@@ -0,0 +1,171 @@
+//! Command-line tool for svc-cargo operators
+//!
+//! Each subcommand calls one REST route. Requests are built from flags or
+//!  read from a JSON file with `--file`. Results are printed as tables, or
+//!  as the raw JSON response with `--json`.
+
+mod args;
+mod output;
+
+use args::{Cli, Command};
+use clap::Parser;
+use futures::StreamExt;
+use serde::Serialize;
+use std::time::Duration;
+use svc_cargo_client_rest::types::{BookingJobStatus, ItineraryModifyConfirmRequest};
+use svc_cargo_client_rest::CargoRestClient;
+
+/// How often `booking --wait` checks the job status
+const BOOKING_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Print the response as JSON, or as the table produced by `render`
+fn print<T: Serialize>(
+    value: &T,
+    json: bool,
+    render: impl Fn(&T) -> String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(value)?);
+    } else {
+        println!("{}", render(value));
+    }
+
+    Ok(())
+}
+
+#[tokio::main]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) needs running server
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let json = cli.json;
+    let mut client = CargoRestClient::new(&cli.url)
+        .with_timeout(Duration::from_millis(cli.timeout_ms))
+        .with_retries(cli.retries);
+
+    if let Some(token) = &cli.token {
+        client = client.with_token(token);
+    }
+
+    match cli.command {
+        Command::Vertiports(args) => {
+            let vertiports = client.query_vertiports(&args.request()?).await?;
+            print(&vertiports, json, |v| output::vertiports(v))?;
+        }
+        Command::Quote(args) => {
+            let drafts = client.request_flight(&args.request()?).await?;
+            print(&drafts, json, |d| output::quotes(d))?;
+        }
+        Command::Draft(args) => {
+            let details = match args.refresh {
+                true => client.refresh_draft(&args.draft_id).await?,
+                false => client.draft(&args.draft_id).await?,
+            };
+            print(&details, json, |d| output::draft(d))?;
+        }
+        Command::Book(args) => {
+            let request = args.request()?;
+            let job = client.create_itinerary(&request).await?;
+            print(&job, json, |j| output::booking(j))?;
+        }
+        Command::Booking(args) => {
+            let mut job = client.booking(&args.job_id).await?;
+            while args.wait && job.status == BookingJobStatus::Pending {
+                tokio::time::sleep(BOOKING_POLL_INTERVAL).await;
+                job = client.booking(&args.job_id).await?;
+            }
+
+            print(&job, json, |j| output::booking(j))?;
+        }
+        Command::Cancel(args) => {
+            let response = client.cancel_itinerary(&args.request()?).await?;
+            print(&response, json, |r| output::cancellation(r))?;
+        }
+        Command::Itineraries(args) => {
+            let response = client.itineraries(&args.request()).await?;
+            print(&response, json, |r| output::itineraries(r))?;
+        }
+        Command::Itinerary(args) => {
+            let details = client.itinerary(&args.itinerary_id).await?;
+            print(&details, json, |d| output::itinerary(d))?;
+        }
+        Command::Modify(args) => match &args.confirm {
+            Some(draft_id) => {
+                let request = ItineraryModifyConfirmRequest {
+                    draft_id: draft_id.clone(),
+                };
+                let response = client
+                    .modify_itinerary(&args.itinerary_id, &request)
+                    .await?;
+                println!(
+                    "itinerary {} replaced by {}.",
+                    args.itinerary_id, response.itinerary_id
+                );
+            }
+            None => {
+                let drafts = client
+                    .query_modifications(&args.itinerary_id, &args.request())
+                    .await?;
+                print(&drafts, json, |d| output::quotes(d))?;
+            }
+        },
+        Command::Scan(args) => {
+            let request = args.request()?;
+            client.scan_parcel(&request).await?;
+            match request.parcel_id.is_empty() {
+                true => println!("recorded scan of labelled parcel."),
+                false => println!("recorded scan of parcel {}.", request.parcel_id),
+            }
+        }
+        Command::Notifications(args) => {
+            let mut settings = client.notification_settings().await?;
+            if let Some(request) = args.request(settings) {
+                settings = client.update_notification_settings(&request).await?;
+            }
+
+            print(&settings, json, output::notifications)?;
+        }
+        Command::Deliver(args) => {
+            let proof = client.confirm_delivery(&args.request()?).await?;
+            print(&proof, json, output::delivery)?;
+        }
+        Command::Track(args) if args.watch => {
+            let scans = client.watch(&args.parcel_id).await?;
+            futures::pin_mut!(scans);
+
+            if !json {
+                println!("{}", output::scan_header());
+            }
+
+            while let Some(scan) = scans.next().await {
+                let scan = scan?;
+                if json {
+                    println!("{}", serde_json::to_string(&scan)?);
+                } else {
+                    println!("{}", output::scan_line(&scan));
+                }
+            }
+        }
+        Command::Track(args) => {
+            let response = client.track(&args.parcel_id).await?;
+            print(&response, json, output::tracking)?;
+        }
+        Command::Label(args) if args.zpl => {
+            println!("{}", client.parcel_label_zpl(&args.parcel_id).await?);
+        }
+        Command::Label(args) => {
+            let label = client.parcel_label(&args.parcel_id).await?;
+            print(&label, json, output::label)?;
+        }
+        Command::Pin(args) => {
+            let pin = client.delivery_pin(&args.parcel_id).await?;
+            print(&pin, json, output::delivery_pin)?;
+        }
+        Command::Occupations(args) => {
+            let response = client.occupations(&args.request()?).await?;
+            print(&response, json, |r| output::occupations(&r.occupations))?;
+        }
+    }
+
+    Ok(())
+}
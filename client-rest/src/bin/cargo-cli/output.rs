@@ -0,0 +1,608 @@
+//! Human-readable tables of the REST responses
+
+use svc_cargo_client_rest::types::*;
+
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Renders rows as a table with left-aligned, space-padded columns
+pub fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.len()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let separator: Vec<String> = widths.iter().map(|w| "-".repeat(*w)).collect();
+    let mut lines = vec![
+        line(headers.to_vec()),
+        line(separator.iter().map(String::as_str).collect()),
+    ];
+    for row in rows {
+        lines.push(line(row.iter().map(String::as_str).collect()));
+    }
+
+    lines.join("\n")
+}
+
+/// Vertiport search results
+pub fn vertiports(vertiports: &[Vertiport]) -> String {
+    let rows: Vec<Vec<String>> = vertiports
+        .iter()
+        .map(|v| {
+            vec![
+                v.id.clone(),
+                v.label.clone(),
+                v.latitude.to_string(),
+                v.longitude.to_string(),
+            ]
+        })
+        .collect();
+
+    table(&["ID", "LABEL", "LATITUDE", "LONGITUDE"], &rows)
+}
+
+/// Draft itineraries with their departure, arrival and price
+pub fn quotes(drafts: &[DraftItinerary]) -> String {
+    let rows: Vec<Vec<String>> = drafts
+        .iter()
+        .map(|draft| {
+            let itinerary = &draft.itinerary;
+            let departure = itinerary
+                .flight_plans
+                .first()
+                .map(|fp| fp.origin_timeslot_start.format(TIME_FORMAT).to_string())
+                .unwrap_or_default();
+            let arrival = itinerary
+                .flight_plans
+                .last()
+                .map(|fp| fp.target_timeslot_end.format(TIME_FORMAT).to_string())
+                .unwrap_or_default();
+            let total: f32 = itinerary.invoice.iter().map(|item| item.cost).sum();
+
+            vec![
+                draft.id.clone(),
+                itinerary.flight_plans.len().to_string(),
+                departure,
+                arrival,
+                format!("{total:.2} {:?}", itinerary.currency_unit),
+            ]
+        })
+        .collect();
+
+    table(
+        &["DRAFT ID", "LEGS", "DEPARTURE", "ARRIVAL", "PRICE"],
+        &rows,
+    )
+}
+
+/// A draft itinerary and the time left to book it
+pub fn draft(details: &DraftItineraryDetails) -> String {
+    let draft = DraftItinerary {
+        id: details.id.clone(),
+        itinerary: details.itinerary.clone(),
+    };
+
+    format!(
+        "{}\nexpires in {} seconds",
+        quotes(&[draft]),
+        details.ttl_seconds
+    )
+}
+
+/// A booking job with the itinerary and parcels it booked, or why it failed
+pub fn booking(job: &BookingJob) -> String {
+    let parcels = job
+        .parcels
+        .iter()
+        .map(|parcel| parcel.parcel_id.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let rows = vec![vec![
+        job.id.clone(),
+        job.draft_id.clone(),
+        format!("{:?}", job.status),
+        job.itinerary_id.clone().unwrap_or_default(),
+        parcels,
+        job.error.clone().unwrap_or_default(),
+    ]];
+
+    table(
+        &["JOB", "DRAFT", "STATUS", "ITINERARY", "PARCELS", "ERROR"],
+        &rows,
+    )
+}
+
+/// A cancelled itinerary and the amount refunded, if any
+pub fn cancellation(response: &ItineraryCancelResponse) -> String {
+    let refund = match response.refund {
+        true => format!(
+            "refund of {:.2} {:?}",
+            response.refund_amount, response.currency_unit
+        ),
+        false => "no refund".to_string(),
+    };
+
+    format!("cancelled itinerary {}, {refund}.", response.itinerary_id)
+}
+
+/// Who is notified of parcel events
+pub fn notifications(settings: &NotificationSettings) -> String {
+    let state = |on: bool| if on { "on" } else { "off" };
+    format!(
+        "sender notifications {}, recipient notifications {}.",
+        state(settings.sender),
+        state(settings.recipient)
+    )
+}
+
+/// Booked itineraries with their departure and parcels, and the page shown
+pub fn itineraries(response: &QueryItinerariesResponse) -> String {
+    let rows: Vec<Vec<String>> = response
+        .itineraries
+        .iter()
+        .map(|booked| {
+            let departure = booked
+                .itinerary
+                .flight_plans
+                .first()
+                .map(|fp| fp.origin_timeslot_start.format(TIME_FORMAT).to_string())
+                .unwrap_or_default();
+
+            vec![
+                booked.id.clone(),
+                format!("{:?}", booked.status),
+                booked.itinerary.flight_plans.len().to_string(),
+                departure,
+                booked.parcels.len().to_string(),
+            ]
+        })
+        .collect();
+
+    let pages = response.total.div_ceil(response.per_page.max(1)).max(1);
+    format!(
+        "{}\npage {} of {pages} ({} itineraries)",
+        table(&["ID", "STATUS", "LEGS", "DEPARTURE", "PARCELS"], &rows),
+        response.page,
+        response.total
+    )
+}
+
+/// A booked itinerary: its vertiports, legs, and parcels with their latest scan
+pub fn itinerary(details: &ItineraryDetails) -> String {
+    let vertiport = |vertiport: &Option<Vertiport>, id: &str| {
+        vertiport
+            .as_ref()
+            .map(|v| v.label.clone())
+            .unwrap_or_else(|| id.to_string())
+    };
+
+    let legs: Vec<Vec<String>> = details
+        .itinerary
+        .flight_plans
+        .iter()
+        .map(|fp| {
+            vec![
+                fp.origin_timeslot_start.format(TIME_FORMAT).to_string(),
+                fp.target_timeslot_end.format(TIME_FORMAT).to_string(),
+                fp.origin_vertiport_id.clone(),
+                fp.target_vertiport_id.clone(),
+                fp.vehicle_id.clone(),
+            ]
+        })
+        .collect();
+
+    let parcels: Vec<Vec<String>> = details
+        .parcels
+        .iter()
+        .map(|p| {
+            vec![
+                p.parcel.id.clone(),
+                p.parcel.weight_g.to_string(),
+                format!("{:?}", p.parcel.status),
+                p.latest_scan
+                    .as_ref()
+                    .map(|scan| scan.timestamp.format(TIME_FORMAT).to_string())
+                    .unwrap_or_default(),
+            ]
+        })
+        .collect();
+
+    [
+        format!("itinerary {} ({:?})", details.id, details.status),
+        format!(
+            "from {} to {}",
+            vertiport(
+                &details.acquisition_vertiport,
+                &details.itinerary.acquisition_vertiport_id
+            ),
+            vertiport(
+                &details.delivery_vertiport,
+                &details.itinerary.delivery_vertiport_id
+            )
+        ),
+        String::new(),
+        table(
+            &["DEPARTURE", "ARRIVAL", "ORIGIN", "TARGET", "AIRCRAFT"],
+            &legs,
+        ),
+        String::new(),
+        table(
+            &["PARCEL ID", "WEIGHT (G)", "STATUS", "LAST SCAN"],
+            &parcels,
+        ),
+    ]
+    .join("\n")
+}
+
+/// Parcel status and scans, oldest first, with the proof of delivery
+pub fn tracking(response: &QueryParcelResponse) -> String {
+    let mut lines = vec![format!("status {:?}", response.status)];
+    if let Some(proof) = &response.delivery {
+        lines.push(delivery(proof));
+    }
+
+    lines.push(String::new());
+    lines.push(scans(&response.scans));
+    lines.join("\n")
+}
+
+/// Proof of delivery of a parcel
+pub fn delivery(proof: &DeliveryProof) -> String {
+    format!(
+        "delivered {} to {} (PIN {}, proof {})",
+        proof.delivered_at.format(TIME_FORMAT),
+        proof.recipient_name,
+        if proof.pin_verified {
+            "verified"
+        } else {
+            "not issued"
+        },
+        proof.content_hash
+    )
+}
+
+/// Shipping label of a parcel
+pub fn label(label: &ParcelLabel) -> String {
+    [
+        format!("parcel {}", label.parcel_id),
+        format!("itinerary {}", label.itinerary_id),
+        format!(
+            "from {} to {}",
+            label.origin_vertiport, label.target_vertiport
+        ),
+        format!("code {}", label.code),
+    ]
+    .join("\n")
+}
+
+/// Delivery PIN of a parcel
+pub fn delivery_pin(pin: &DeliveryPin) -> String {
+    [
+        format!("parcel {}", pin.parcel_id),
+        format!("pin {}", pin.pin),
+    ]
+    .join("\n")
+}
+
+/// Parcel scans, oldest first
+pub fn scans(scans: &[CargoScan]) -> String {
+    let rows: Vec<Vec<String>> = scans.iter().map(scan_row).collect();
+    table(&SCAN_HEADERS, &rows)
+}
+
+/// Headers of the [`scans`] table
+const SCAN_HEADERS: [&str; 5] = ["TIME", "SCANNER ID", "LATITUDE", "LONGITUDE", "ALTITUDE"];
+
+/// Column widths of scans printed as they arrive, wide enough for the
+///  timestamp and a UUID
+const SCAN_WIDTHS: [usize; 5] = [19, 36, 12, 12, 10];
+
+/// Header line for scans printed one at a time with [`scan_line`]
+pub fn scan_header() -> String {
+    fixed(SCAN_HEADERS.iter().map(|h| h.to_string()).collect())
+}
+
+/// A single scan, aligned with [`scan_header`]
+pub fn scan_line(scan: &CargoScan) -> String {
+    fixed(scan_row(scan))
+}
+
+fn fixed(cells: Vec<String>) -> String {
+    cells
+        .iter()
+        .zip(SCAN_WIDTHS)
+        .map(|(cell, width)| format!("{cell:<width$}"))
+        .collect::<Vec<String>>()
+        .join("  ")
+        .trim_end()
+        .to_string()
+}
+
+fn scan_row(scan: &CargoScan) -> Vec<String> {
+    vec![
+        scan.timestamp.format(TIME_FORMAT).to_string(),
+        scan.scanner_id.clone(),
+        scan.latitude.to_string(),
+        scan.longitude.to_string(),
+        scan.altitude.to_string(),
+    ]
+}
+
+/// Upcoming landings at a vertiport
+pub fn occupations(occupations: &[Occupation]) -> String {
+    let rows: Vec<Vec<String>> = occupations
+        .iter()
+        .map(|o| {
+            vec![
+                o.time_window.timestamp_min.format(TIME_FORMAT).to_string(),
+                o.vertipad_display_name
+                    .clone()
+                    .unwrap_or_else(|| o.vertipad_id.clone()),
+                o.aircraft_nickname
+                    .clone()
+                    .unwrap_or_else(|| o.aircraft_id.clone()),
+                o.cargo_acquire.len().to_string(),
+                o.cargo_deliver.len().to_string(),
+            ]
+        })
+        .collect();
+
+    table(
+        &["ARRIVAL", "VERTIPAD", "AIRCRAFT", "PICKUPS", "DROPOFFS"],
+        &rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::time::Utc;
+
+    #[test]
+    fn test_table() {
+        let rows = vec![
+            vec!["1".to_string(), "first".to_string()],
+            vec!["22".to_string(), "second".to_string()],
+        ];
+
+        let expected = "ID  LABEL\n--  ------\n1   first\n22  second";
+        assert_eq!(table(&["ID", "LABEL"], &rows), expected);
+    }
+
+    #[test]
+    fn test_draft() {
+        let details = DraftItineraryDetails {
+            id: "draft".to_string(),
+            itinerary: Itinerary::default(),
+            ttl_seconds: 42,
+        };
+
+        let output = draft(&details);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].starts_with("draft"));
+        assert_eq!(lines[3], "expires in 42 seconds");
+    }
+
+    #[test]
+    fn test_booking() {
+        let job = BookingJob {
+            id: "job".to_string(),
+            draft_id: "draft".to_string(),
+            user_id: "user".to_string(),
+            status: BookingJobStatus::Failed,
+            itinerary_id: None,
+            parcels: vec![],
+            error: Some("rejected".to_string()),
+        };
+
+        let output = booking(&job);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].starts_with("job"));
+        assert!(lines[2].contains("Failed"));
+        assert!(lines[2].ends_with("rejected"));
+
+        let job = BookingJob {
+            status: BookingJobStatus::Complete,
+            itinerary_id: Some("itinerary".to_string()),
+            parcels: vec![
+                CargoInfo::new("first".to_string(), &Parcel::default()),
+                CargoInfo::new("second".to_string(), &Parcel::default()),
+            ],
+            error: None,
+            ..job
+        };
+
+        let output = booking(&job);
+        assert!(output.lines().nth(2).unwrap().contains("first,second"));
+    }
+
+    #[test]
+    fn test_notifications() {
+        let settings = NotificationSettings {
+            sender: true,
+            recipient: false,
+        };
+
+        assert_eq!(
+            notifications(&settings),
+            "sender notifications on, recipient notifications off."
+        );
+    }
+
+    #[test]
+    fn test_label() {
+        let data = ParcelLabel {
+            parcel_id: "p".to_string(),
+            itinerary_id: "i".to_string(),
+            origin_vertiport: "Amsterdam".to_string(),
+            target_vertiport: "Utrecht".to_string(),
+            code: "header.payload.signature".to_string(),
+        };
+
+        let output = label(&data);
+        assert_eq!(output.lines().nth(2).unwrap(), "from Amsterdam to Utrecht");
+        assert_eq!(
+            output.lines().last().unwrap(),
+            "code header.payload.signature"
+        );
+    }
+
+    #[test]
+    fn test_delivery_pin() {
+        let data = DeliveryPin {
+            parcel_id: "p".to_string(),
+            pin: "012345".to_string(),
+        };
+
+        assert_eq!(delivery_pin(&data), "parcel p\npin 012345");
+    }
+
+    #[test]
+    fn test_cancellation() {
+        let mut response = ItineraryCancelResponse {
+            itinerary_id: "itinerary".to_string(),
+            refund: true,
+            refund_amount: 12.5,
+            currency_unit: CurrencyUnit::Usd,
+        };
+
+        assert_eq!(
+            cancellation(&response),
+            "cancelled itinerary itinerary, refund of 12.50 Usd."
+        );
+
+        response.refund = false;
+        response.refund_amount = 0.0;
+        assert_eq!(
+            cancellation(&response),
+            "cancelled itinerary itinerary, no refund."
+        );
+    }
+
+    #[test]
+    fn test_itineraries() {
+        let response = QueryItinerariesResponse {
+            itineraries: vec![BookedItinerary {
+                id: "itinerary".to_string(),
+                status: ItineraryStatus::Active,
+                itinerary: Itinerary::default(),
+                parcels: vec![],
+            }],
+            page: 1,
+            per_page: 2,
+            total: 3,
+        };
+
+        let output = itineraries(&response);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].starts_with("itinerary  Active"));
+        assert_eq!(lines[3], "page 1 of 2 (3 itineraries)");
+    }
+
+    #[test]
+    fn test_itinerary() {
+        let details = ItineraryDetails {
+            id: "itinerary".to_string(),
+            status: ItineraryStatus::Cancelled,
+            itinerary: Itinerary {
+                acquisition_vertiport_id: "origin-id".to_string(),
+                delivery_vertiport_id: "target-id".to_string(),
+                ..Default::default()
+            },
+            acquisition_vertiport: Some(Vertiport {
+                id: "origin-id".to_string(),
+                label: "Origin".to_string(),
+                latitude: 0.0,
+                longitude: 0.0,
+                boundary: vec![],
+            }),
+            delivery_vertiport: None,
+            parcels: vec![ParcelDetails {
+                parcel: ParcelInfo {
+                    id: "parcel".to_string(),
+                    weight_g: 500,
+                    status: ParcelStatus::InFlight,
+                },
+                latest_scan: None,
+            }],
+        };
+
+        let output = itinerary(&details);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "itinerary itinerary (Cancelled)");
+        assert_eq!(lines[1], "from Origin to target-id");
+        assert!(lines.last().unwrap().starts_with("parcel"));
+        assert!(lines.last().unwrap().contains("InFlight"));
+    }
+
+    #[test]
+    fn test_scans() {
+        let scan = CargoScan {
+            scanner_id: "scanner".to_string(),
+            parcel_id: "parcel".to_string(),
+            code: None,
+            latitude: 1.5,
+            longitude: -2.5,
+            altitude: 0.0,
+            timestamp: Utc::now(),
+            received_at: None,
+        };
+
+        let output = scans(&[scan.clone()]);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("TIME"));
+        assert!(lines[2].contains("scanner"));
+        assert!(lines[2].contains("-2.5"));
+
+        let line = scan_line(&scan);
+        assert_eq!(line.find("scanner"), scan_header().find("SCANNER ID"));
+    }
+
+    #[test]
+    fn test_tracking() {
+        let mut response = QueryParcelResponse {
+            status: ParcelStatus::Arrived,
+            scans: vec![],
+            delivery: None,
+        };
+
+        let output = tracking(&response);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "status Arrived");
+        assert!(lines[2].starts_with("TIME"));
+
+        response.status = ParcelStatus::Delivered;
+        response.delivery = Some(DeliveryProof {
+            parcel_id: "parcel".to_string(),
+            scanner_id: "scanner".to_string(),
+            recipient_name: "J. Doe".to_string(),
+            content_hash: "abc".to_string(),
+            pin_verified: true,
+            delivered_at: Utc::now(),
+            received_at: Some(Utc::now()),
+        });
+
+        let output = tracking(&response);
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "status Delivered");
+        assert!(lines[1].contains("to J. Doe (PIN verified, proof abc)"));
+        assert!(lines[3].starts_with("TIME"));
+    }
+}
@@ -0,0 +1,717 @@
+//! Typed HTTP client for the svc-cargo REST API
+
+use crate::types::*;
+use futures::stream::{self, Stream};
+use hyper::body::{Bytes, HttpBody};
+use hyper::client::HttpConnector;
+use hyper::{Body, Client, Method, Request, Response, StatusCode};
+use lib_common::uuid::Uuid;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Default time allowed for a request to complete
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Default number of retries after a failed attempt
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Delay before the first retry, doubled for each further retry
+pub const RETRY_DELAY_MS: u64 = 250;
+
+/// Header identifying retries of the same operation
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Errors returned by the [`CargoRestClient`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RestClientError {
+    /// 400: the request was rejected as invalid
+    BadRequest,
+
+    /// 304: svc-scheduler did not accept the change
+    NotModified,
+
+    /// 401: missing or invalid bearer token
+    Unauthorized,
+
+    /// 404: the requested record does not exist
+    NotFound,
+
+    /// 408: a server dependency did not respond in time
+    RequestTimeout,
+
+    /// 429: the rate limit was exceeded
+    TooManyRequests,
+
+    /// 500: a server dependency returned an error
+    Internal,
+
+    /// 503: the server could not reach its dependencies
+    Unavailable,
+
+    /// Any other unsuccessful status code
+    Status(u16),
+
+    /// No response was received before the client timeout
+    Timeout,
+
+    /// The server could not be reached
+    Connection(String),
+
+    /// The request could not be built
+    Request,
+
+    /// The response body could not be read or parsed
+    Response,
+}
+
+impl Display for RestClientError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            RestClientError::BadRequest => write!(f, "bad request"),
+            RestClientError::NotModified => write!(f, "not modified"),
+            RestClientError::Unauthorized => write!(f, "unauthorized"),
+            RestClientError::NotFound => write!(f, "not found"),
+            RestClientError::RequestTimeout => write!(f, "server request timeout"),
+            RestClientError::TooManyRequests => write!(f, "too many requests"),
+            RestClientError::Internal => write!(f, "internal server error"),
+            RestClientError::Unavailable => write!(f, "service unavailable"),
+            RestClientError::Status(code) => write!(f, "unexpected status code {code}"),
+            RestClientError::Timeout => write!(f, "client timeout"),
+            RestClientError::Connection(e) => write!(f, "connection error: {e}"),
+            RestClientError::Request => write!(f, "could not build request"),
+            RestClientError::Response => write!(f, "could not read response"),
+        }
+    }
+}
+
+impl std::error::Error for RestClientError {}
+
+impl From<StatusCode> for RestClientError {
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::BAD_REQUEST => RestClientError::BadRequest,
+            StatusCode::NOT_MODIFIED => RestClientError::NotModified,
+            StatusCode::UNAUTHORIZED => RestClientError::Unauthorized,
+            StatusCode::NOT_FOUND => RestClientError::NotFound,
+            StatusCode::REQUEST_TIMEOUT => RestClientError::RequestTimeout,
+            StatusCode::TOO_MANY_REQUESTS => RestClientError::TooManyRequests,
+            StatusCode::INTERNAL_SERVER_ERROR => RestClientError::Internal,
+            StatusCode::SERVICE_UNAVAILABLE => RestClientError::Unavailable,
+            _ => RestClientError::Status(code.as_u16()),
+        }
+    }
+}
+
+impl RestClientError {
+    /// True if the request was not processed by the server and can be sent
+    ///  again safely
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            RestClientError::Connection(_)
+                | RestClientError::TooManyRequests
+                | RestClientError::Unavailable
+        )
+    }
+}
+
+/// Client for the svc-cargo REST API
+#[derive(Debug, Clone)]
+pub struct CargoRestClient {
+    base_url: String,
+    timeout: Duration,
+    retries: u32,
+    token: Option<String>,
+    client: Client<HttpConnector>,
+}
+
+impl CargoRestClient {
+    /// Create a client for the server at `base_url` (e.g. `http://localhost:8000`)
+    pub fn new(base_url: &str) -> Self {
+        CargoRestClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            retries: DEFAULT_RETRIES,
+            token: None,
+            client: Client::builder()
+                .pool_idle_timeout(Duration::from_secs(10))
+                .build_http(),
+        }
+    }
+
+    /// Set the time allowed for each attempt of a request
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the number of retries for requests that were not processed
+    ///  by the server, see [`RestClientError::is_retryable`]
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Authenticate requests with a bearer token, required for routes
+    ///  acting on behalf of a user
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// The server base URL
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// `GET /health`
+    pub async fn health(&self) -> Result<(), RestClientError> {
+        self.send(Method::GET, "/health", None).await.map(|_| ())
+    }
+
+    /// `GET /cargo/itineraries`
+    pub async fn itineraries(
+        &self,
+        request: &QueryItinerariesRequest,
+    ) -> Result<QueryItinerariesResponse, RestClientError> {
+        let path = format!("/cargo/itineraries{}", itineraries_query(request));
+        let bytes = self.send(Method::GET, &path, None).await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/itinerary/{id}`
+    pub async fn itinerary(&self, itinerary_id: &str) -> Result<ItineraryDetails, RestClientError> {
+        let bytes = self
+            .send(
+                Method::GET,
+                &format!("/cargo/itinerary/{itinerary_id}"),
+                None,
+            )
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `POST /cargo/itinerary/{id}/modify`
+    pub async fn query_modifications(
+        &self,
+        itinerary_id: &str,
+        request: &ItineraryModifyRequest,
+    ) -> Result<Vec<DraftItinerary>, RestClientError> {
+        let path = format!("/cargo/itinerary/{itinerary_id}/modify");
+        self.send_json(Method::POST, &path, request).await
+    }
+
+    /// `PUT /cargo/itinerary/{id}/modify`
+    pub async fn modify_itinerary(
+        &self,
+        itinerary_id: &str,
+        request: &ItineraryModifyConfirmRequest,
+    ) -> Result<ItineraryModifyResponse, RestClientError> {
+        let path = format!("/cargo/itinerary/{itinerary_id}/modify");
+        self.send_json(Method::PUT, &path, request).await
+    }
+
+    /// `GET /cargo/notifications`
+    pub async fn notification_settings(&self) -> Result<NotificationSettings, RestClientError> {
+        let bytes = self.send(Method::GET, "/cargo/notifications", None).await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `PUT /cargo/notifications`
+    pub async fn update_notification_settings(
+        &self,
+        request: &NotificationSettings,
+    ) -> Result<NotificationSettings, RestClientError> {
+        self.send_json(Method::PUT, "/cargo/notifications", request)
+            .await
+    }
+
+    /// `GET /cargo/parcel/{id}/label`
+    pub async fn parcel_label(&self, parcel_id: &str) -> Result<ParcelLabel, RestClientError> {
+        let bytes = self
+            .send(
+                Method::GET,
+                &format!("/cargo/parcel/{parcel_id}/label"),
+                None,
+            )
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/parcel/{id}/label?format=zpl`
+    pub async fn parcel_label_zpl(&self, parcel_id: &str) -> Result<String, RestClientError> {
+        let bytes = self
+            .send(
+                Method::GET,
+                &format!("/cargo/parcel/{parcel_id}/label?format=zpl"),
+                None,
+            )
+            .await?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| RestClientError::Response)
+    }
+
+    /// `POST /cargo/parcel/{id}/pin`
+    /// Each call issues a new PIN, replacing the previous one.
+    pub async fn delivery_pin(&self, parcel_id: &str) -> Result<DeliveryPin, RestClientError> {
+        let bytes = self
+            .send(
+                Method::POST,
+                &format!("/cargo/parcel/{parcel_id}/pin"),
+                None,
+            )
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `POST /cargo/vertiports`
+    pub async fn query_vertiports(
+        &self,
+        request: &QueryVertiportsRequest,
+    ) -> Result<Vec<Vertiport>, RestClientError> {
+        self.send_json(Method::POST, "/cargo/vertiports", request)
+            .await
+    }
+
+    /// `POST /cargo/request`
+    pub async fn request_flight(
+        &self,
+        request: &QueryItineraryRequest,
+    ) -> Result<Vec<DraftItinerary>, RestClientError> {
+        self.send_json(Method::POST, "/cargo/request", request)
+            .await
+    }
+
+    /// `PUT /cargo/create`
+    /// The draft is booked in the background, see [`Self::booking`].
+    pub async fn create_itinerary(
+        &self,
+        request: &ItineraryCreateRequest,
+    ) -> Result<BookingJob, RestClientError> {
+        let bytes = self
+            .send_idempotent(Method::PUT, "/cargo/create", request)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/create/{job_id}`
+    pub async fn booking(&self, job_id: &str) -> Result<BookingJob, RestClientError> {
+        let bytes = self
+            .send(Method::GET, &format!("/cargo/create/{job_id}"), None)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/draft/{id}`
+    pub async fn draft(&self, draft_id: &str) -> Result<DraftItineraryDetails, RestClientError> {
+        let bytes = self
+            .send(Method::GET, &format!("/cargo/draft/{draft_id}"), None)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `POST /cargo/draft/{id}/refresh`
+    pub async fn refresh_draft(
+        &self,
+        draft_id: &str,
+    ) -> Result<DraftItineraryDetails, RestClientError> {
+        let bytes = self
+            .send(
+                Method::POST,
+                &format!("/cargo/draft/{draft_id}/refresh"),
+                None,
+            )
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `DELETE /cargo/cancel`
+    pub async fn cancel_itinerary(
+        &self,
+        request: &ItineraryCancelRequest,
+    ) -> Result<ItineraryCancelResponse, RestClientError> {
+        let bytes = self
+            .send_idempotent(Method::DELETE, "/cargo/cancel", request)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `PUT /cargo/scan`
+    pub async fn scan_parcel(&self, request: &CargoScan) -> Result<(), RestClientError> {
+        self.send_idempotent(Method::PUT, "/cargo/scan", request)
+            .await
+            .map(|_| ())
+    }
+
+    /// `PUT /cargo/deliver`
+    pub async fn confirm_delivery(
+        &self,
+        request: &DeliveryConfirmation,
+    ) -> Result<DeliveryProof, RestClientError> {
+        let bytes = self
+            .send_idempotent(Method::PUT, "/cargo/deliver", request)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/track/{id}`
+    pub async fn track(&self, parcel_id: &str) -> Result<QueryParcelResponse, RestClientError> {
+        let bytes = self
+            .send(Method::GET, &format!("/cargo/track/{parcel_id}"), None)
+            .await?;
+
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// `GET /cargo/track/{id}/watch`
+    /// Returns the recorded scans of the parcel, followed by each new scan
+    ///  as it is accepted. The client timeout only applies until the stream
+    ///  is opened.
+    pub async fn watch(
+        &self,
+        parcel_id: &str,
+    ) -> Result<impl Stream<Item = Result<CargoScan, RestClientError>>, RestClientError> {
+        let path = format!("/cargo/track/{parcel_id}/watch");
+        let response = self
+            .attempts(|| self.execute(Method::GET, &path, None, None))
+            .await?;
+        let state = (response.into_body(), String::new());
+
+        Ok(stream::unfold(state, |(mut body, mut buffer)| async move {
+            loop {
+                if let Some(data) = next_event(&mut buffer) {
+                    let scan = serde_json::from_str(&data).map_err(|_| RestClientError::Response);
+                    return Some((scan, (body, buffer)));
+                }
+
+                match body.data().await? {
+                    Ok(chunk) => buffer.push_str(&String::from_utf8_lossy(&chunk)),
+                    Err(e) => {
+                        let error = RestClientError::Connection(e.to_string());
+                        return Some((Err(error), (body, buffer)));
+                    }
+                }
+            }
+        }))
+    }
+
+    /// `POST /cargo/occupations`
+    pub async fn occupations(
+        &self,
+        request: &QueryScheduleRequest,
+    ) -> Result<QueryScheduleResponse, RestClientError> {
+        self.send_json(Method::POST, "/cargo/occupations", request)
+            .await
+    }
+
+    /// Send a JSON body and parse the JSON response
+    async fn send_json<T: Serialize, R: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &T,
+    ) -> Result<R, RestClientError> {
+        let bytes = self.send_body(method, path, body).await?;
+        serde_json::from_slice(&bytes).map_err(|_| RestClientError::Response)
+    }
+
+    /// Send a JSON body
+    async fn send_body<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &T,
+    ) -> Result<Bytes, RestClientError> {
+        let body = serde_json::to_string(body).map_err(|_| RestClientError::Request)?;
+        self.send(method, path, Some(body)).await
+    }
+
+    /// Send a JSON body with a new idempotency key
+    /// Retries carry the same key, so the server processes the request once.
+    async fn send_idempotent<T: Serialize>(
+        &self,
+        method: Method,
+        path: &str,
+        body: &T,
+    ) -> Result<Bytes, RestClientError> {
+        let body = serde_json::to_string(body).map_err(|_| RestClientError::Request)?;
+        let key = Uuid::new_v4().to_string();
+        self.send_with_key(method, path, Some(body), Some(&key))
+            .await
+    }
+
+    /// Send a request and read the whole response body
+    async fn send(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<Bytes, RestClientError> {
+        self.send_with_key(method, path, body, None).await
+    }
+
+    /// Send a request with an optional idempotency key and read the whole
+    ///  response body
+    async fn send_with_key(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Bytes, RestClientError> {
+        self.attempts(|| {
+            let (method, body) = (method.clone(), body.clone());
+            async move {
+                let response = self.execute(method, path, body, idempotency_key).await?;
+                tokio::time::timeout(self.timeout, hyper::body::to_bytes(response.into_body()))
+                    .await
+                    .map_err(|_| RestClientError::Timeout)?
+                    .map_err(|_| RestClientError::Response)
+            }
+        })
+        .await
+    }
+
+    /// Run `attempt` until it succeeds, fails with an error that can't be
+    ///  retried, or runs out of retries
+    async fn attempts<T, F, Fut>(&self, attempt: F) -> Result<T, RestClientError>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Result<T, RestClientError>>,
+    {
+        let mut delay = Duration::from_millis(RETRY_DELAY_MS);
+        let mut retries = 0;
+        loop {
+            match attempt().await {
+                Err(e) if e.is_retryable() && retries < self.retries => {
+                    retries += 1;
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Send a single request, returning the response if successful
+    async fn execute(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<String>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Response<Body>, RestClientError> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.base_url, path));
+
+        if let Some(token) = &self.token {
+            builder = builder.header("authorization", format!("Bearer {token}"));
+        }
+
+        if let Some(key) = idempotency_key {
+            builder = builder.header(IDEMPOTENCY_KEY_HEADER, key);
+        }
+
+        let request = match body {
+            Some(body) => builder
+                .header("content-type", "application/json")
+                .body(Body::from(body)),
+            None => builder.body(Body::empty()),
+        }
+        .map_err(|_| RestClientError::Request)?;
+
+        let response = tokio::time::timeout(self.timeout, self.client.request(request))
+            .await
+            .map_err(|_| RestClientError::Timeout)?
+            .map_err(|e| RestClientError::Connection(e.to_string()))?;
+
+        match response.status() {
+            status if status.is_success() => Ok(response),
+            status => Err(status.into()),
+        }
+    }
+}
+
+/// Query string of a [`QueryItinerariesRequest`], empty without filters
+fn itineraries_query(request: &QueryItinerariesRequest) -> String {
+    // UTC with a 'Z' suffix, no characters need escaping
+    const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.fZ";
+
+    let mut params = vec![];
+    if let Some(status) = request.status {
+        params.push(format!("status={status:?}"));
+    }
+
+    if let Some(after) = request.booked_after {
+        params.push(format!("booked_after={}", after.format(TIME_FORMAT)));
+    }
+
+    if let Some(before) = request.booked_before {
+        params.push(format!("booked_before={}", before.format(TIME_FORMAT)));
+    }
+
+    if let Some(page) = request.page {
+        params.push(format!("page={page}"));
+    }
+
+    if let Some(per_page) = request.per_page {
+        params.push(format!("per_page={per_page}"));
+    }
+
+    if params.is_empty() {
+        return String::new();
+    }
+
+    format!("?{}", params.join("&"))
+}
+
+/// Take the data of the next complete Server-Sent Event from `buffer`.
+/// Events without data, such as keep-alive comments, are skipped.
+fn next_event(buffer: &mut String) -> Option<String> {
+    loop {
+        let end = buffer.find("\n\n")?;
+        let event: String = buffer.drain(..end + 2).collect();
+        let data = event
+            .lines()
+            .filter_map(|line| line.strip_prefix("data:"))
+            .map(|data| data.strip_prefix(' ').unwrap_or(data))
+            .collect::<Vec<&str>>()
+            .join("\n");
+
+        if !data.is_empty() {
+            return Some(data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rest_client_error_from_status_code() {
+        assert_eq!(
+            RestClientError::from(StatusCode::BAD_REQUEST),
+            RestClientError::BadRequest
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::NOT_MODIFIED),
+            RestClientError::NotModified
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::NOT_FOUND),
+            RestClientError::NotFound
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::REQUEST_TIMEOUT),
+            RestClientError::RequestTimeout
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::TOO_MANY_REQUESTS),
+            RestClientError::TooManyRequests
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::INTERNAL_SERVER_ERROR),
+            RestClientError::Internal
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::SERVICE_UNAVAILABLE),
+            RestClientError::Unavailable
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::UNAUTHORIZED),
+            RestClientError::Unauthorized
+        );
+        assert_eq!(
+            RestClientError::from(StatusCode::IM_A_TEAPOT),
+            RestClientError::Status(418)
+        );
+    }
+
+    #[test]
+    fn test_rest_client_error_display() {
+        assert_eq!(RestClientError::BadRequest.to_string(), "bad request");
+        assert_eq!(
+            RestClientError::Status(418).to_string(),
+            "unexpected status code 418"
+        );
+        assert_eq!(
+            RestClientError::Connection("refused".to_string()).to_string(),
+            "connection error: refused"
+        );
+        assert_eq!(RestClientError::Timeout.to_string(), "client timeout");
+    }
+
+    #[test]
+    fn test_rest_client_error_is_retryable() {
+        assert!(RestClientError::Connection(String::new()).is_retryable());
+        assert!(RestClientError::TooManyRequests.is_retryable());
+        assert!(RestClientError::Unavailable.is_retryable());
+        assert!(!RestClientError::BadRequest.is_retryable());
+        assert!(!RestClientError::Internal.is_retryable());
+        assert!(!RestClientError::Timeout.is_retryable());
+    }
+
+    #[test]
+    fn test_new_client() {
+        let client = CargoRestClient::new("http://localhost:8000/")
+            .with_timeout(Duration::from_secs(1))
+            .with_retries(0)
+            .with_token("token");
+
+        assert_eq!(client.base_url(), "http://localhost:8000");
+        assert_eq!(client.timeout, Duration::from_secs(1));
+        assert_eq!(client.retries, 0);
+        assert_eq!(client.token, Some("token".to_string()));
+    }
+
+    #[test]
+    fn test_itineraries_query() {
+        let mut request = QueryItinerariesRequest::default();
+        assert_eq!(itineraries_query(&request), "");
+
+        request.status = Some(ItineraryStatus::Active);
+        request.booked_after = Some("2026-01-01T10:00:00Z".parse().unwrap());
+        request.page = Some(2);
+        request.per_page = Some(10);
+        assert_eq!(
+            itineraries_query(&request),
+            "?status=Active&booked_after=2026-01-01T10:00:00Z&page=2&per_page=10"
+        );
+    }
+
+    #[test]
+    fn test_next_event() {
+        let mut buffer = ":\n\nevent: scan\ndata: {\"a\":1}\n\ndata: {\"b\"".to_string();
+        assert_eq!(next_event(&mut buffer).unwrap(), "{\"a\":1}");
+        assert!(next_event(&mut buffer).is_none());
+
+        buffer.push_str(":2}\n\n");
+        assert_eq!(next_event(&mut buffer).unwrap(), "{\"b\":2}");
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connection_error_retries() {
+        // nothing listens on the discard port
+        let client = CargoRestClient::new("http://127.0.0.1:9").with_retries(1);
+        let start = std::time::Instant::now();
+        let error = client.health().await.unwrap_err();
+        assert!(matches!(error, RestClientError::Connection(_)));
+        assert!(start.elapsed() >= Duration::from_millis(RETRY_DELAY_MS));
+    }
+}
@@ -1,4 +1,8 @@
 //! Imports the types used in REST communication with the svc-cargo server
+//!  and provides a typed client for its routes
+
+pub mod client;
+pub use client::{CargoRestClient, RestClientError};
 
 /// Types for messages to svc-cargo server
 pub mod types {
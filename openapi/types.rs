@@ -4,6 +4,7 @@ use num_traits::FromPrimitive;
 /// Types used for REST communication with the svc-cargo server
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::str::FromStr;
 use utoipa::{IntoParams, ToSchema};
 
 /// TODO(R5): Import payment type enums from svc-payment
@@ -15,6 +16,12 @@ pub use svc_scheduler_client_grpc::prelude::scheduler_storage::{
 /// Don't allow overly large numbers of occupations to be returned
 pub const MAX_LANDINGS_TO_RETURN: u32 = 50;
 
+/// Don't allow overly large pages of itineraries to be returned
+pub const MAX_ITINERARIES_PER_PAGE: u32 = 50;
+
+/// Number of itineraries per page if not specified
+pub const DEFAULT_ITINERARIES_PER_PAGE: u32 = 20;
+
 /// Non-privileged flight plan information
 #[derive(Debug, Clone, IntoParams, ToSchema, Deserialize, Serialize)]
 pub struct FlightPlan {
@@ -189,6 +196,82 @@ impl TryFrom<SchedulerFlightPlan> for FlightPlan {
     }
 }
 
+/// Special handling required by a parcel
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+pub enum HandlingClass {
+    /// Breakable, handle with care
+    Fragile,
+
+    /// Kept refrigerated in transit
+    Refrigerated,
+
+    /// Dangerous goods
+    Hazmat,
+}
+
+impl FromStr for HandlingClass {
+    type Err = String;
+
+    /// Parse a handling class name, ignoring case
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "fragile" => Ok(HandlingClass::Fragile),
+            "refrigerated" => Ok(HandlingClass::Refrigerated),
+            "hazmat" => Ok(HandlingClass::Hazmat),
+            _ => Err(format!("unknown handling class {name}")),
+        }
+    }
+}
+
+/// The person receiving a parcel
+#[derive(Debug, Clone, Default, PartialEq, ToSchema, Deserialize, Serialize)]
+pub struct Recipient {
+    /// The recipient's name
+    pub name: String,
+
+    /// The recipient's email address
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// The recipient's phone number
+    #[serde(default)]
+    pub phone: Option<String>,
+}
+
+/// A parcel to carry, as described by the customer
+#[derive(Debug, Clone, Default, PartialEq, ToSchema, Deserialize, Serialize)]
+pub struct Parcel {
+    /// The weight of the parcel in grams
+    pub weight_g: u32,
+
+    /// The length of the parcel in millimeters
+    /// Length, width and height are all zero if the dimensions are unknown.
+    pub length_mm: u32,
+
+    /// The width of the parcel in millimeters
+    pub width_mm: u32,
+
+    /// The height of the parcel in millimeters
+    pub height_mm: u32,
+
+    /// A reference or nickname chosen by the customer
+    #[serde(default)]
+    pub reference: Option<String>,
+
+    /// The special handling the parcel requires
+    #[serde(default)]
+    pub handling: Vec<HandlingClass>,
+
+    /// The person receiving the parcel, notified of its progress
+    #[serde(default)]
+    pub recipient: Option<Recipient>,
+
+    /// Issue a delivery PIN at booking, sent to the customer and required
+    ///  from the recipient at the handover
+    #[serde(default)]
+    pub delivery_pin: bool,
+}
+
 /// Request Body Information for Flight Query
 #[derive(Debug, Clone, IntoParams, ToSchema, Deserialize, Serialize)]
 pub struct QueryItineraryRequest {
@@ -201,14 +284,42 @@ pub struct QueryItineraryRequest {
     /// The window of departure
     pub time_depart_window: TimeWindow,
 
-    /// The estimated weight of cargo
-    pub cargo_weight_g: u32,
+    /// The parcels to carry
+    #[serde(default)]
+    pub parcels: Vec<Parcel>,
+
+    /// Deprecated, use `parcels`
+    /// The weight of a single parcel of unknown dimensions, only used
+    ///  without `parcels`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cargo_weight_g: Option<u32>,
 
     /// The User ID
-    /// TODO(R5): Get his from ACL module
+    /// Ignored by the server, the user is taken from the bearer token
+    #[serde(default)]
     pub user_id: String,
 }
 
+impl QueryItineraryRequest {
+    /// The combined weight of the parcels
+    pub fn cargo_weight_g(&self) -> u32 {
+        self.parcels.iter().fold(0, |total: u32, parcel| {
+            total.saturating_add(parcel.weight_g)
+        })
+    }
+
+    /// List the deprecated `cargo_weight_g` as a parcel of unknown
+    ///  dimensions, if no parcels are listed
+    pub fn normalize(&mut self) {
+        if let (true, Some(weight_g)) = (self.parcels.is_empty(), self.cargo_weight_g.take()) {
+            self.parcels.push(Parcel {
+                weight_g,
+                ..Default::default()
+            });
+        }
+    }
+}
+
 /// Request Body Information for Flight Query
 #[derive(Debug, Clone, IntoParams, ToSchema, Deserialize, Serialize)]
 pub struct DraftItinerary {
@@ -219,6 +330,19 @@ pub struct DraftItinerary {
     pub itinerary: Itinerary,
 }
 
+/// A draft itinerary with its remaining lifetime
+#[derive(Debug, Clone, ToSchema, Deserialize, Serialize)]
+pub struct DraftItineraryDetails {
+    /// The draft ID
+    pub id: String,
+
+    /// The itinerary information
+    pub itinerary: Itinerary,
+
+    /// Seconds until the draft expires and can no longer be booked
+    pub ttl_seconds: u64,
+}
+
 /// Time window (min and max)
 #[derive(Debug, Copy, Clone, IntoParams, ToSchema, Deserialize, Serialize)]
 pub struct TimeWindow {
@@ -236,10 +360,27 @@ pub struct ItineraryCancelRequest {
     pub id: String,
 
     /// User ID
-    /// TODO(R5): Get this from ACL module
+    /// Ignored by the server, the user is taken from the bearer token
+    #[serde(default)]
     pub user_id: String,
 }
 
+/// Outcome of an itinerary cancellation
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct ItineraryCancelResponse {
+    /// The ID of the cancelled itinerary
+    pub itinerary_id: String,
+
+    /// Whether the itinerary was cancelled early enough to be refunded
+    pub refund: bool,
+
+    /// The refunded amount, zero without a refund
+    pub refund_amount: f32,
+
+    /// The currency of the refund
+    pub currency_unit: CurrencyUnit,
+}
+
 /// Request Body Information for Region Query
 #[derive(Debug, Copy, Clone, Deserialize, Serialize, ToSchema)]
 pub struct QueryVertiportsRequest {
@@ -251,7 +392,7 @@ pub struct QueryVertiportsRequest {
 }
 
 /// Supported Currencies
-#[derive(Debug, Serialize, Deserialize, Copy, Clone, ToSchema)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, ToSchema)]
 pub enum CurrencyUnit {
     /// One U.S. Dollar
     Usd,
@@ -285,9 +426,13 @@ pub struct Itinerary {
     /// List of "item": "cost"
     pub invoice: Vec<InvoiceItem>,
 
-    /// Cargo Weight
+    /// Cargo Weight, the combined weight of the parcels
     pub cargo_weight_g: u32,
 
+    /// The parcels to carry
+    #[serde(default)]
+    pub parcels: Vec<Parcel>,
+
     /// User ID
     pub user_id: String,
 
@@ -308,6 +453,7 @@ impl Default for Itinerary {
             acquisition_vertiport_id: String::new(),
             delivery_vertiport_id: String::new(),
             cargo_weight_g: 0,
+            parcels: Vec::new(),
         }
     }
 }
@@ -319,10 +465,97 @@ pub struct ItineraryCreateRequest {
     pub id: String,
 
     /// User ID
-    /// TODO(R5): Get this from ACL module
+    /// Ignored by the server, the user is taken from the bearer token
+    #[serde(default)]
     pub user_id: String,
 }
 
+/// Parcel notifications settings of a user
+/// Notifications are sent when parcels are dropped off, depart, arrive, are
+///  delivered or are delayed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NotificationSettings {
+    /// Notify the user of the events of their parcels
+    pub sender: bool,
+
+    /// Notify the recipients of the user's parcels
+    pub recipient: bool,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        NotificationSettings {
+            sender: true,
+            recipient: true,
+        }
+    }
+}
+
+/// Status of a booking job
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum BookingJobStatus {
+    /// The itinerary is being booked
+    Pending,
+
+    /// The itinerary is booked and the parcel registered
+    Complete,
+
+    /// The booking failed
+    Failed,
+}
+
+/// A draft itinerary being booked in the background
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BookingJob {
+    /// The booking job ID
+    pub id: String,
+
+    /// The draft itinerary being booked
+    pub draft_id: String,
+
+    /// The user booking the draft
+    pub user_id: String,
+
+    /// The job status
+    pub status: BookingJobStatus,
+
+    /// The booked itinerary ID, once complete
+    pub itinerary_id: Option<String>,
+
+    /// The registered parcels, once complete
+    #[serde(default)]
+    pub parcels: Vec<CargoInfo>,
+
+    /// The reason the booking failed
+    pub error: Option<String>,
+}
+
+/// Request body information to modify a booked itinerary
+#[derive(Debug, Copy, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ItineraryModifyRequest {
+    /// The new window of departure
+    /// The current departure slot is kept if not provided
+    pub time_depart_window: Option<TimeWindow>,
+
+    /// The new weight of cargo
+    /// The current weight is kept if not provided
+    pub cargo_weight_g: Option<u32>,
+}
+
+/// Request body information to confirm the modification of a booked itinerary
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ItineraryModifyConfirmRequest {
+    /// The ID of the replacement draft itinerary
+    pub draft_id: String,
+}
+
+/// Confirmed modification of a booked itinerary
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ItineraryModifyResponse {
+    /// The ID of the itinerary replacing the modified itinerary
+    pub itinerary_id: String,
+}
+
 /// Vertiport Information
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Vertiport {
@@ -338,6 +571,10 @@ pub struct Vertiport {
 
     /// The longitude (float value) of the vertiport (centroid)
     pub longitude: f32,
+
+    /// The exterior ring of the vertiport's area
+    #[serde(default)]
+    pub boundary: Vec<GeoPointZ>,
 }
 
 /// Successful Payment Record
@@ -380,10 +617,50 @@ pub struct QueryScheduleResponse {
 pub struct CargoInfo {
     /// the unique UUID of the parcel or passenger
     pub parcel_id: String,
-    // /// the nickname of the parcel or passenger
-    // pub cargo_nickname: Option<String>
 
-    // TODO(R5): weight, etc.
+    /// The weight of the parcel in grams
+    #[serde(default)]
+    pub weight_g: u32,
+
+    /// The length of the parcel in millimeters
+    #[serde(default)]
+    pub length_mm: u32,
+
+    /// The width of the parcel in millimeters
+    #[serde(default)]
+    pub width_mm: u32,
+
+    /// The height of the parcel in millimeters
+    #[serde(default)]
+    pub height_mm: u32,
+
+    /// the reference or nickname of the parcel
+    #[serde(default)]
+    pub reference: Option<String>,
+
+    /// The special handling the parcel requires, null if unknown
+    #[serde(default)]
+    pub handling: Option<Vec<HandlingClass>>,
+
+    /// The itinerary the parcel is booked on
+    #[serde(default)]
+    pub itinerary_id: Option<String>,
+}
+
+impl CargoInfo {
+    /// A registered parcel as described by the customer
+    pub fn new(parcel_id: String, parcel: &Parcel) -> Self {
+        CargoInfo {
+            parcel_id,
+            weight_g: parcel.weight_g,
+            length_mm: parcel.length_mm,
+            width_mm: parcel.width_mm,
+            height_mm: parcel.height_mm,
+            reference: parcel.reference.clone(),
+            handling: Some(parcel.handling.clone()),
+            itinerary_id: None,
+        }
+    }
 }
 
 /// Vertipad Occupation
@@ -421,8 +698,14 @@ pub struct CargoScan {
     pub scanner_id: String,
 
     /// The unique ID (UUID) of the parcel or passenger
+    /// May be left empty when the signed label code is given.
+    #[serde(default)]
     pub parcel_id: String,
 
+    /// The signed code printed on the parcel label, instead of the parcel ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+
     /// The latitude (float value) of the scan location
     pub latitude: f64,
 
@@ -432,15 +715,255 @@ pub struct CargoScan {
     /// The altitude (float value) of the scan location
     pub altitude: f64,
 
-    /// The timestamp of the scan
+    /// When the scan was taken, by the scanner's clock
+    /// Scanners may keep scans while offline and send them later.
     pub timestamp: DateTime<Utc>,
+
+    /// When the server received the scan, set by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// Shipping label of a parcel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ParcelLabel {
+    /// The unique ID (UUID) of the parcel
+    pub parcel_id: String,
+
+    /// The itinerary the parcel is booked on
+    pub itinerary_id: String,
+
+    /// The name of the vertiport where the parcel is dropped off
+    pub origin_vertiport: String,
+
+    /// The name of the vertiport where the parcel is delivered
+    pub target_vertiport: String,
+
+    /// Signed code of the fields above, printed on the label for scanners
+    pub code: String,
+}
+
+/// Format of a shipping label
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelFormat {
+    /// The label fields and code as JSON
+    Json,
+
+    /// A label for Zebra printers, with the code as a QR code
+    Zpl,
+}
+
+/// Format of the shipping label of a parcel
+#[derive(Debug, Copy, Clone, Default, IntoParams, ToSchema, Deserialize, Serialize)]
+#[into_params(parameter_in = Query)]
+pub struct LabelQuery {
+    /// The format of the label, JSON if not given
+    pub format: Option<LabelFormat>,
 }
 
 /// Tracking Information Response
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct QueryParcelResponse {
+    /// The current parcel status
+    pub status: ParcelStatus,
+
     /// list of scans
     pub scans: Vec<CargoScan>,
+
+    /// The proof of delivery, once delivered
+    #[serde(default)]
+    pub delivery: Option<DeliveryProof>,
+}
+
+/// Delivery Confirmation Request Body
+/// The final scan of a parcel, taken when it is handed over at the
+///  delivery vertiport
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryConfirmation {
+    /// The final scan of the parcel
+    pub scan: CargoScan,
+
+    /// The name of the person receiving the parcel
+    pub recipient_name: String,
+
+    /// The SHA-256 hash of the recipient's signature or of a photo of the
+    ///  handover, in hexadecimal
+    pub content_hash: String,
+
+    /// The delivery PIN the customer passed on to the recipient
+    #[serde(default)]
+    pub pin: Option<String>,
+}
+
+/// Delivery PIN of a parcel
+/// Only its hash is kept, the PIN is not shown again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryPin {
+    /// The unique ID (UUID) of the parcel
+    pub parcel_id: String,
+
+    /// The PIN to give to the recipient
+    pub pin: String,
+}
+
+/// Proof of Delivery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DeliveryProof {
+    /// The unique ID (UUID) of the parcel
+    pub parcel_id: String,
+
+    /// The unique ID (UUID) of the scanner device used for the handover
+    pub scanner_id: String,
+
+    /// The name of the person who received the parcel
+    pub recipient_name: String,
+
+    /// The SHA-256 hash of the recipient's signature or of a photo of the
+    ///  handover, in hexadecimal
+    pub content_hash: String,
+
+    /// True if the recipient gave the delivery PIN, false for parcels
+    ///  delivered without one
+    pub pin_verified: bool,
+
+    /// The time of the handover, by the scanner's clock
+    pub delivered_at: DateTime<Utc>,
+
+    /// When the server received the handover, set by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub received_at: Option<DateTime<Utc>>,
+}
+
+/// Status of a booked itinerary
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ItineraryStatus {
+    /// The itinerary is booked
+    Active,
+
+    /// The itinerary was cancelled
+    Cancelled,
+}
+
+/// Status of a parcel
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum ParcelStatus {
+    /// Not yet handed over at the acquisition vertiport
+    NotDroppedOff,
+
+    /// Handed over at the acquisition vertiport
+    DroppedOff,
+
+    /// Loaded on board an aircraft at the acquisition vertiport
+    Loaded,
+
+    /// In flight, or at a transfer between flights
+    InFlight,
+
+    /// Arrived at the delivery vertiport
+    Arrived,
+
+    /// Handed over to the recipient at the delivery vertiport
+    Delivered,
+}
+
+/// A parcel registered for a booked itinerary
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ParcelInfo {
+    /// The unique ID (UUID) of the parcel
+    pub id: String,
+
+    /// The weight of the parcel
+    pub weight_g: u32,
+
+    /// The parcel status
+    pub status: ParcelStatus,
+}
+
+/// A booked itinerary with its parcels
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct BookedItinerary {
+    /// The itinerary ID
+    pub id: String,
+
+    /// The itinerary status
+    pub status: ItineraryStatus,
+
+    /// The confirmed flight plans and vertiports.
+    /// Pricing is not kept after booking, so the invoice is empty.
+    pub itinerary: Itinerary,
+
+    /// The parcels carried on the itinerary
+    pub parcels: Vec<ParcelInfo>,
+}
+
+/// A parcel of a booked itinerary with its latest scan
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ParcelDetails {
+    /// The parcel record
+    pub parcel: ParcelInfo,
+
+    /// The most recent scan of the parcel, if it was scanned
+    pub latest_scan: Option<CargoScan>,
+}
+
+/// Itinerary Details Response
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ItineraryDetails {
+    /// The itinerary ID
+    pub id: String,
+
+    /// The itinerary status
+    pub status: ItineraryStatus,
+
+    /// The confirmed flight plans and vertiports.
+    /// Pricing is not kept after booking, so the invoice is empty.
+    pub itinerary: Itinerary,
+
+    /// The vertiport where the parcels are dropped off
+    pub acquisition_vertiport: Option<Vertiport>,
+
+    /// The vertiport where the parcels are picked up
+    pub delivery_vertiport: Option<Vertiport>,
+
+    /// The parcels carried on the itinerary
+    pub parcels: Vec<ParcelDetails>,
+}
+
+/// Filters and paging for the itineraries of the user
+#[derive(Debug, Clone, Default, IntoParams, ToSchema, Deserialize, Serialize)]
+#[into_params(parameter_in = Query)]
+pub struct QueryItinerariesRequest {
+    /// Only return itineraries with this status
+    pub status: Option<ItineraryStatus>,
+
+    /// Only return itineraries booked at or after this time
+    pub booked_after: Option<DateTime<Utc>>,
+
+    /// Only return itineraries booked before this time
+    pub booked_before: Option<DateTime<Utc>>,
+
+    /// The page to return, starting at 1
+    pub page: Option<u32>,
+
+    /// The number of itineraries per page (max: [`MAX_ITINERARIES_PER_PAGE`])
+    pub per_page: Option<u32>,
+}
+
+/// Itineraries Response
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct QueryItinerariesResponse {
+    /// The itineraries on the requested page, latest booking first
+    pub itineraries: Vec<BookedItinerary>,
+
+    /// The returned page
+    pub page: u32,
+
+    /// The number of itineraries per page
+    pub per_page: u32,
+
+    /// The number of itineraries matching the filters, on all pages
+    pub total: u32,
 }
 
 #[cfg(test)]
@@ -620,4 +1143,21 @@ mod tests {
             "Invalid Flight Priority"
         );
     }
+
+    #[test]
+    fn test_handling_class_from_str() {
+        assert_eq!(
+            "Fragile".parse::<HandlingClass>(),
+            Ok(HandlingClass::Fragile)
+        );
+        assert_eq!(
+            "refrigerated".parse::<HandlingClass>(),
+            Ok(HandlingClass::Refrigerated)
+        );
+        assert_eq!("HAZMAT".parse::<HandlingClass>(), Ok(HandlingClass::Hazmat));
+        assert_eq!(
+            "radioactive".parse::<HandlingClass>().unwrap_err(),
+            "unknown handling class radioactive"
+        );
+    }
 }
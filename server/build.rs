@@ -3,8 +3,13 @@
 ///generates .rs files in src directory
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let server_config = tonic_build::configure()
+        .extern_path(
+            ".google.protobuf.Timestamp",
+            "::lib_common::time::Timestamp",
+        )
         .type_attribute("ReadyRequest", "#[derive(Eq, Copy)]")
-        .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]");
+        .type_attribute("ReadyResponse", "#[derive(Eq, Copy)]")
+        .type_attribute("GeoPointZ", "#[derive(Copy)]");
     let client_config = server_config.clone();
 
     client_config
@@ -5,39 +5,57 @@
 pub mod macros;
 pub mod pool;
 
-use crate::rest::api::rest_types::Itinerary;
+use crate::rest::api::rest_types::{BookingJob, CargoInfo, Itinerary, NotificationSettings};
+use crate::rest::idempotency::IdempotencyRecord;
+use crate::service::compensation::Compensation;
+use crate::service::delivery::DeliveryRecord;
+use crate::service::notify::ParcelContacts;
 use deadpool_redis::redis::{
     ErrorKind, FromRedisValue, RedisError, RedisWrite, ToRedisArgs, Value,
 };
 
-impl FromRedisValue for Itinerary {
-    fn from_redis_value(v: &Value) -> Result<Self, RedisError> {
-        let Value::Data(data) = v else {
-            return Err(RedisError::from((
-                ErrorKind::TypeError,
-                "Unexpected Redis value",
-            )));
-        };
-
-        let itinerary = serde_json::from_slice(data).map_err(|e| {
-            cache_warn!("error deserializing task: {}", e);
-            RedisError::from((ErrorKind::TypeError, "Invalid JSON"))
-        })?;
-
-        Ok(itinerary)
-    }
-}
+/// Cached values are stored as JSON
+macro_rules! impl_redis_json {
+    ($type:ty) => {
+        impl FromRedisValue for $type {
+            fn from_redis_value(v: &Value) -> Result<Self, RedisError> {
+                let Value::Data(data) = v else {
+                    return Err(RedisError::from((
+                        ErrorKind::TypeError,
+                        "Unexpected Redis value",
+                    )));
+                };
+
+                let value = serde_json::from_slice(data).map_err(|e| {
+                    cache_warn!("error deserializing task: {}", e);
+                    RedisError::from((ErrorKind::TypeError, "Invalid JSON"))
+                })?;
+
+                Ok(value)
+            }
+        }
 
-impl ToRedisArgs for Itinerary {
-    fn write_redis_args<W: ?Sized>(&self, out: &mut W)
-    where
-        W: RedisWrite,
-    {
-        let Ok(data) = serde_json::to_string(self) else {
-            cache_warn!("error serializing task");
-            return;
-        };
-
-        out.write_arg(data.as_bytes());
-    }
+        impl ToRedisArgs for $type {
+            fn write_redis_args<W: ?Sized>(&self, out: &mut W)
+            where
+                W: RedisWrite,
+            {
+                let Ok(data) = serde_json::to_string(self) else {
+                    cache_warn!("error serializing task");
+                    return;
+                };
+
+                out.write_arg(data.as_bytes());
+            }
+        }
+    };
 }
+
+impl_redis_json!(Itinerary);
+impl_redis_json!(BookingJob);
+impl_redis_json!(IdempotencyRecord);
+impl_redis_json!(Compensation);
+impl_redis_json!(CargoInfo);
+impl_redis_json!(DeliveryRecord);
+impl_redis_json!(ParcelContacts);
+impl_redis_json!(NotificationSettings);
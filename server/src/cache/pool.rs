@@ -1,6 +1,12 @@
 //! Redis connection pool implementation
-use super::Itinerary;
+use super::{
+    BookingJob, CargoInfo, Compensation, DeliveryRecord, IdempotencyRecord, Itinerary,
+    NotificationSettings, ParcelContacts,
+};
+use crate::service::scan::ScanReceipts;
 use deadpool_redis::redis::{FromRedisValue, Value};
+use lib_common::time::{DateTime, Utc};
+use std::collections::HashMap;
 use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
 use std::sync::Arc;
 use tokio::sync::{Mutex, OnceCell};
@@ -15,6 +21,19 @@ use crate::test_util::test_pool::Pool;
 /// How long to keep a task in memory after it's been processed
 const ITINERARY_KEEPALIVE_DURATION_SECONDS: usize = 120;
 
+/// How long to keep a booking job after it was last updated
+const BOOKING_JOB_KEEPALIVE_DURATION_SECONDS: usize = 3600;
+
+/// How long to keep the compensation of a failed booking after it was
+///  last updated
+const COMPENSATION_KEEPALIVE_DURATION_SECONDS: usize = 7 * 24 * 3600;
+
+/// How long to keep the contacts notified of a parcel's events
+const CONTACTS_KEEPALIVE_DURATION_SECONDS: usize = 30 * 24 * 3600;
+
+/// The sorted set of the parcels to check for delays, by due time
+const DELAY_CHECKS_KEY: &str = "cargo:delay_checks";
+
 /// A global static Redis pool.
 static REDIS_POOL: OnceCell<Arc<Mutex<CargoPool>>> = OnceCell::const_new();
 
@@ -237,6 +256,121 @@ pub trait ItineraryPool {
         Ok(())
     }
 
+    /// Replaces a draft itinerary that is still cached and resets its expiry
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn refresh_itinerary(
+        &mut self,
+        itinerary_id: String,
+        draft_itinerary: &Itinerary,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool refresh) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:draft:{itinerary_id}");
+        let value = connection
+            .hset(&key, "data", draft_itinerary)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool refresh) unexpected redis response to hset command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            // The field was overwritten
+            Value::Int(0) => {}
+            // The draft expired after it was read, don't bring it back
+            Value::Int(1) => {
+                cache_warn!("(ItineraryPool refresh) draft itinerary #{itinerary_id} expired.");
+                let _: Result<Value, _> = connection.del(&key).await;
+                return Err(CacheError::NotFound);
+            }
+            value => {
+                cache_error!(
+                    "(ItineraryPool refresh) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, ITINERARY_KEEPALIVE_DURATION_SECONDS)
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool refresh) could not set itinerary #{itinerary_id} expiry.",
+                );
+
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool refresh) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool refresh) refreshed draft itinerary #{itinerary_id}.");
+        Ok(())
+    }
+
+    /// Gets the number of seconds until a draft itinerary expires
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_itinerary_ttl(&mut self, itinerary_id: String) -> Result<u64, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:draft:{itinerary_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_ttl) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .ttl(&key)
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_ttl) could not get itinerary TTL from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            Value::Int(seconds) if seconds >= 0 => Ok(seconds as u64),
+            // -2: the key does not exist
+            Value::Int(-2) => {
+                cache_error!("(ItineraryPool get_ttl) key expired or does not exist.");
+                Err(CacheError::NotFound)
+            }
+            value => {
+                cache_error!(
+                    "(ItineraryPool get_ttl) unexpected redis response to ttl command: {:?}",
+                    value
+                );
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
     /// Gets task information
     #[cfg(not(tarpaulin_include))]
     // no_coverage: (R5) need redis connection, run in integration tests
@@ -274,152 +408,2129 @@ pub trait ItineraryPool {
             CacheError::InvalidValue
         })
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::rest::api::rest_types::CurrencyUnit;
-    use crate::rest::api::rest_types::Itinerary;
-    use lib_common::uuid::Uuid;
 
-    #[test]
-    fn test_cargo_pool_debug() {
-        let str = format!(
-            "{:?}",
-            CargoPool {
-                pool: Pool::default()
-            }
-        );
-        assert_eq!(str, "CargoPool");
-    }
+    /// Creates or replaces a booking job and resets its expiry
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_booking_job(&mut self, job: &BookingJob) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_job) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
 
-    #[test]
-    fn test_cache_error_display() {
-        assert_eq!(
-            format!("{}", CacheError::PoolUnavailable),
-            "Pool is unavailable."
-        );
-        assert_eq!(
-            format!("{}", CacheError::CouldNotCreatePool),
-            "Could not create pool."
-        );
-        assert_eq!(
-            format!("{}", CacheError::CouldNotConfigure),
-            "Could not configure cache."
-        );
-        assert_eq!(
-            format!("{}", CacheError::CouldNotConnect),
-            "Could not connect to cache."
-        );
-        assert_eq!(
-            format!("{}", CacheError::OperationFailed),
-            "Cache operation failed."
-        );
-        assert_eq!(format!("{}", CacheError::NotFound), "Key was not found.");
-        assert_eq!(
-            format!("{}", CacheError::InvalidValue),
-            "Invalid value retrieved."
-        );
-        assert_eq!(
-            format!("{}", CacheError::Unexpected),
-            "Unexpected response."
-        );
-        assert_eq!(format!("{}", CacheError::KeyCollision), "Key collision.");
-    }
+        let job_id = &job.id;
+        let key = format!("cargo:job:{job_id}");
+        let value = connection.hset(&key, "data", job).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_job) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
 
-    #[tokio::test]
-    async fn test_store_itinerary() {
-        lib_common::logger::get_log_handle().await;
-        ut_info!("start");
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_job) unexpected redis response to hset command: {:?}",
+                    value
+                );
 
-        let config = crate::config::Config::default();
-        let mut pool = CargoPool::new(config).unwrap();
-        let itinerary = Itinerary {
-            flight_plans: vec![],
-            invoice: vec![],
-            currency_unit: CurrencyUnit::Usd,
-            cargo_weight_g: 10,
-            user_id: Uuid::new_v4().to_string(),
-            acquisition_vertiport_id: Uuid::new_v4().to_string(),
-            delivery_vertiport_id: Uuid::new_v4().to_string(),
-        };
+                return Err(CacheError::Unexpected);
+            }
+        }
 
-        // trigger get pool failure
-        pool.pool.fail = true;
-        let itinerary_id = Uuid::new_v4().to_string();
-        let result = pool
-            .store_itinerary(itinerary_id, &itinerary)
+        let result = connection
+            .expire(&key, BOOKING_JOB_KEEPALIVE_DURATION_SECONDS)
             .await
-            .unwrap_err();
-        assert_eq!(result, CacheError::PoolUnavailable);
+            .map_err(|_| {
+                cache_error!("(ItineraryPool store_job) could not set job #{job_id} expiry.");
+                CacheError::OperationFailed
+            })?;
 
-        // trigger Err(())
-        pool.pool.fail = false;
-        let itinerary_id = "".to_string();
-        let result = pool
-            .store_itinerary(itinerary_id, &itinerary)
-            .await
-            .unwrap_err();
-        assert_eq!(result, CacheError::OperationFailed);
+        match result {
+            Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_job) unexpected redis response to expire command: {:?}",
+                    value
+                );
 
-        // trigger key collision
-        let itinerary_id = Uuid::new_v4().to_string();
-        pool.store_itinerary(itinerary_id.clone(), &itinerary)
-            .await
-            .unwrap();
+                return Err(CacheError::Unexpected);
+            }
+        }
 
-        let result = pool
-            .store_itinerary(itinerary_id, &itinerary)
-            .await
-            .unwrap_err();
-        assert_eq!(result, CacheError::KeyCollision);
+        cache_info!(
+            "(ItineraryPool store_job) booking job #{job_id} is {:?}.",
+            job.status
+        );
 
-        ut_info!("success");
+        Ok(())
     }
 
-    #[tokio::test]
-    async fn test_get_itinerary() {
-        lib_common::logger::get_log_handle().await;
-        ut_info!("start");
+    /// Gets a booking job
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_booking_job(&mut self, job_id: String) -> Result<BookingJob, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:job:{job_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_job) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_job) could not get booking job from Redis.");
+                CacheError::OperationFailed
+            })?;
 
-        let config = crate::config::Config::default();
-        let mut pool = CargoPool::new(config).unwrap();
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_job) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
 
-        // trigger failing pool get
-        let itinerary_id = Uuid::new_v4().to_string();
-        pool.pool.fail = true;
-        let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
-        assert_eq!(result, CacheError::PoolUnavailable);
+        BookingJob::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_job) could not deserialize booking job {:#?}; {e}",
+                value
+            );
 
-        // path to get Err(()) route
-        pool.pool.fail = false;
-        let itinerary_id = "".to_string();
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Keeps a booked itinerary with its invoice for `ttl_seconds`
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_booked_itinerary(
+        &mut self,
+        itinerary_id: String,
+        itinerary: &Itinerary,
+        ttl_seconds: usize,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_booked) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:booked:{itinerary_id}");
+        let value = connection
+            .hset(&key, "data", itinerary)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool store_booked) unexpected redis response to hset command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_booked) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, ttl_seconds.max(1))
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool store_booked) could not set itinerary #{itinerary_id} expiry."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_booked) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool store_booked) kept booked itinerary #{itinerary_id}.");
+        Ok(())
+    }
+
+    /// Gets a booked itinerary with its invoice
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_booked_itinerary(&mut self, itinerary_id: String) -> Result<Itinerary, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:booked:{itinerary_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_booked) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_booked) could not get itinerary from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_booked) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        Itinerary::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_booked) could not deserialize itinerary {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Removes a booked itinerary with its invoice
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn remove_booked_itinerary(&mut self, itinerary_id: &str) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:booked:{itinerary_id}");
+        let _: Value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool remove_booked) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .del(&key)
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool remove_booked) could not remove {key} from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        Ok(())
+    }
+
+    /// Creates or replaces the compensation of a failed booking and resets
+    ///  its expiry
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_compensation(&mut self, compensation: &Compensation) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_compensation) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let itinerary_id = &compensation.itinerary_id;
+        let key = format!("cargo:compensation:{itinerary_id}");
+        let value = connection
+            .hset(&key, "data", compensation)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool store_compensation) unexpected redis response to hset command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_compensation) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, COMPENSATION_KEEPALIVE_DURATION_SECONDS)
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool store_compensation) could not set itinerary #{itinerary_id} expiry."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_compensation) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!(
+            "(ItineraryPool store_compensation) kept compensation of itinerary #{itinerary_id}."
+        );
+        Ok(())
+    }
+
+    /// Gets the compensation of a failed booking
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_compensation(&mut self, itinerary_id: String) -> Result<Compensation, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:compensation:{itinerary_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_compensation) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_compensation) could not get compensation from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_compensation) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        Compensation::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_compensation) could not deserialize compensation {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Creates or replaces the description of a registered parcel
+    /// Descriptions are kept without expiry, for as long as the parcel.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_parcel(&mut self, parcel: &CargoInfo) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_parcel) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let parcel_id = &parcel.parcel_id;
+        let key = format!("cargo:parcel:{parcel_id}");
+        let value = connection.hset(&key, "data", parcel).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_parcel) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_parcel) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool store_parcel) kept parcel #{parcel_id}.");
+        Ok(())
+    }
+
+    /// Gets the description of a registered parcel
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_parcel(&mut self, parcel_id: String) -> Result<CargoInfo, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:parcel:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_parcel) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_parcel) could not get parcel from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_parcel) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        CargoInfo::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_parcel) could not deserialize parcel {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Creates or replaces the delivery record of a parcel
+    /// Delivery records are kept without expiry, as the proof of delivery.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_delivery(
+        &mut self,
+        parcel_id: &str,
+        record: &DeliveryRecord,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_delivery) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:delivery:{parcel_id}");
+        let value = connection.hset(&key, "data", record).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_delivery) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_delivery) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool store_delivery) kept delivery record #{parcel_id}.");
+        Ok(())
+    }
+
+    /// Gets the delivery record of a parcel
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_delivery(&mut self, parcel_id: String) -> Result<DeliveryRecord, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:delivery:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_delivery) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_delivery) could not get delivery record from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_delivery) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        DeliveryRecord::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_delivery) could not deserialize delivery record {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Counts a delivery PIN given for a parcel, before it is checked
+    /// Returns the number of PINs given since the PIN was issued or last
+    ///  cleared. The count is incremented atomically, so concurrent
+    ///  deliveries each see their own count.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn count_pin_failure(&mut self, parcel_id: &str) -> Result<u32, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:delivery:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool count_pin_failure) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hincr(&key, "failures", 1)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool count_pin_failure) unexpected redis response to hincrby command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            Value::Int(count) => Ok(count.max(0) as u32),
+            value => {
+                cache_error!(
+                    "(ItineraryPool count_pin_failure) unexpected redis response to hincrby command: {:?}",
+                    value
+                );
+
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
+    /// Clears the incorrect delivery PINs given for a parcel
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn reset_pin_failures(&mut self, parcel_id: &str) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:delivery:{parcel_id}");
+        let _: Value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool reset_pin_failures) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hset(&key, "failures", 0)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool reset_pin_failures) unexpected redis response to hset command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        Ok(())
+    }
+
+    /// Creates or replaces the contacts notified of a parcel's events and resets
+    ///  its expiry
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_parcel_contacts(
+        &mut self,
+        parcel_id: &str,
+        contacts: &ParcelContacts,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!(
+                "(ItineraryPool store_parcel_contacts) could not get connection from pool."
+            );
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:contacts:{parcel_id}");
+        let value = connection.hset(&key, "data", contacts).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_parcel_contacts) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_parcel_contacts) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, CONTACTS_KEEPALIVE_DURATION_SECONDS)
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool store_parcel_contacts) could not set parcel contacts #{parcel_id} expiry.");
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_parcel_contacts) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool store_parcel_contacts) kept parcel contacts #{parcel_id}.");
+        Ok(())
+    }
+
+    /// Gets the contacts notified of a parcel's events
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_parcel_contacts(&mut self, parcel_id: String) -> Result<ParcelContacts, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:contacts:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_parcel_contacts) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_parcel_contacts) could not get parcel contacts from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_parcel_contacts) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        ParcelContacts::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_parcel_contacts) could not deserialize parcel contacts {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Keeps the receipt time of a recorded scan of a parcel
+    /// Each scan is set as its own field, so concurrent scans don't
+    ///  overwrite each other. Receipts are kept as long as the scans.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_scan_receipt(
+        &mut self,
+        parcel_id: &str,
+        scan_id: &str,
+        received_at: DateTime<Utc>,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let key = format!("cargo:receipts:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool store_scan_receipt) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hset(&key, scan_id, received_at.to_rfc3339())
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool store_scan_receipt) unexpected redis response to hset command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_scan_receipt) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!("(ItineraryPool store_scan_receipt) kept receipt of scan #{scan_id}.");
+        Ok(())
+    }
+
+    /// Gets the receipt times of a parcel's recorded scans
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_scan_receipts(&mut self, parcel_id: String) -> Result<ScanReceipts, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:receipts:{parcel_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_scan_receipts) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hgetall(&key)
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_scan_receipts) could not get scan receipts from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        let fields = HashMap::<String, String>::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_scan_receipts) could not deserialize scan receipts {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })?;
+
+        if fields.is_empty() {
+            cache_error!("(ItineraryPool get_scan_receipts) key does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        let mut receipts = ScanReceipts::default();
+        for (scan_id, received_at) in fields {
+            let received_at = DateTime::parse_from_rfc3339(&received_at).map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool get_scan_receipts) invalid receipt time of scan #{scan_id}: {e}"
+                );
+                CacheError::InvalidValue
+            })?;
+
+            receipts
+                .received
+                .insert(scan_id, received_at.with_timezone(&Utc));
+        }
+
+        Ok(receipts)
+    }
+
+    /// Schedules the delay check of a parcel at `due`, in seconds since the
+    ///  epoch, replacing its previous schedule
+    /// Scheduled checks are kept without expiry until they are removed.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn schedule_delay_check(&mut self, parcel_id: &str, due: i64) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool schedule_delay_check) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .zadd(DELAY_CHECKS_KEY, parcel_id, due)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool schedule_delay_check) unexpected redis response to zadd command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            // New or rescheduled check
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool schedule_delay_check) unexpected redis response to zadd command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!(
+            "(ItineraryPool schedule_delay_check) scheduled delay check #{parcel_id} at {due}."
+        );
+        Ok(())
+    }
+
+    /// Gets the parcels whose delay check is due at `now`, in seconds since
+    ///  the epoch, earliest first
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_due_delay_checks(&mut self, now: i64) -> Result<Vec<String>, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_due_delay_checks) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .zrangebyscore(DELAY_CHECKS_KEY, "-inf", now)
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_due_delay_checks) could not get delay checks from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        Vec::<String>::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_due_delay_checks) could not deserialize delay checks {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Removes the delay check of a parcel
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn remove_delay_check(&mut self, parcel_id: &str) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool remove_delay_check) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .zrem(DELAY_CHECKS_KEY, parcel_id)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool remove_delay_check) unexpected redis response to zrem command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            // Removed, or already removed
+            Value::Int(0) | Value::Int(1) => Ok(()),
+            value => {
+                cache_error!(
+                    "(ItineraryPool remove_delay_check) unexpected redis response to zrem command: {:?}",
+                    value
+                );
+
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
+    /// Holds the status of a parcel for `lease_seconds`, until it is released
+    /// Returns false if the status is already held.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn claim_parcel_lock(
+        &mut self,
+        parcel_id: &str,
+        lease_seconds: usize,
+    ) -> Result<bool, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool claim_parcel_lock) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:lock:parcel:{parcel_id}");
+        let value = connection
+            .hset_nx(&key, "data", "locked")
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool claim_parcel_lock) unexpected redis response to hsetnx command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            Value::Int(1) => {}
+            Value::Int(0) => {
+                cache_debug!("(ItineraryPool claim_parcel_lock) parcel already locked: {key}");
+                return Ok(false);
+            }
+            value => {
+                cache_error!(
+                    "(ItineraryPool claim_parcel_lock) unexpected redis response to hsetnx command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, lease_seconds.max(1))
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool claim_parcel_lock) could not set {key} expiry.");
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => Ok(true),
+            value => {
+                cache_error!(
+                    "(ItineraryPool claim_parcel_lock) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
+    /// Releases the status of a parcel
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn release_parcel_lock(&mut self, parcel_id: &str) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:lock:parcel:{parcel_id}");
+        let _: Value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool release_parcel_lock) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .del(&key)
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool release_parcel_lock) could not remove {key} from Redis."
+                );
+                CacheError::OperationFailed
+            })?;
+
+        Ok(())
+    }
+
+    /// Creates or replaces the notification settings of a user
+    /// Settings are kept until replaced.
+    /// TODO(R5): keep notification settings in svc-storage
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_notification_settings(
+        &mut self,
+        user_id: &str,
+        settings: &NotificationSettings,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!(
+                "(ItineraryPool store_notification_settings) could not get connection from pool."
+            );
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:notifications:{user_id}");
+        let value = connection.hset(&key, "data", settings).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_notification_settings) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
+
+        match value {
+            // New or overwritten field
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_notification_settings) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        cache_info!(
+            "(ItineraryPool store_notification_settings) kept notification settings of user #{user_id}."
+        );
+        Ok(())
+    }
+
+    /// Gets the notification settings of a user
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_notification_settings(
+        &mut self,
+        user_id: String,
+    ) -> Result<NotificationSettings, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:notifications:{user_id}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!(
+                    "(ItineraryPool get_notification_settings) could not get connection from pool."
+                );
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_notification_settings) could not get notification settings from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_notification_settings) key does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        NotificationSettings::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_notification_settings) could not deserialize notification settings {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Records the first request made with an idempotency key, holding the
+    ///  key for `lease_seconds` until its response is stored
+    /// Returns false if the key is held or was already used within the window.
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn claim_idempotency_key(
+        &mut self,
+        key: String,
+        record: &IdempotencyRecord,
+        lease_seconds: usize,
+    ) -> Result<bool, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool claim_key) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:idempotency:{key}");
+        let value = connection
+            .hset_nx(&key, "data", record)
+            .await
+            .map_err(|e| {
+                cache_error!(
+                    "(ItineraryPool claim_key) unexpected redis response to hsetnx command: {:?}",
+                    e
+                );
+                CacheError::OperationFailed
+            })?;
+
+        match value {
+            Value::Int(1) => {}
+            Value::Int(0) => {
+                cache_debug!("(ItineraryPool claim_key) key already used: {key}");
+                return Ok(false);
+            }
+            value => {
+                cache_error!(
+                    "(ItineraryPool claim_key) unexpected redis response to hsetnx command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, lease_seconds.max(1))
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool claim_key) could not set {key} expiry.");
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => Ok(true),
+            value => {
+                cache_error!(
+                    "(ItineraryPool claim_key) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
+    /// Replaces the record of an idempotency key and resets its expiry
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn store_idempotency_record(
+        &mut self,
+        key: String,
+        record: &IdempotencyRecord,
+        window_seconds: usize,
+    ) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        cache_debug!("entry.");
+        let mut connection = self.pool().get().await.map_err(|_| {
+            cache_error!("(ItineraryPool store_record) could not get connection from pool.");
+            CacheError::PoolUnavailable
+        })?;
+
+        let key = format!("cargo:idempotency:{key}");
+        let value = connection.hset(&key, "data", record).await.map_err(|e| {
+            cache_error!(
+                "(ItineraryPool store_record) unexpected redis response to hset command: {:?}",
+                e
+            );
+            CacheError::OperationFailed
+        })?;
+
+        match value {
+            Value::Int(0) | Value::Int(1) => {}
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_record) unexpected redis response to hset command: {:?}",
+                    value
+                );
+
+                return Err(CacheError::Unexpected);
+            }
+        }
+
+        let result = connection
+            .expire(&key, window_seconds.max(1))
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool store_record) could not set {key} expiry.");
+                CacheError::OperationFailed
+            })?;
+
+        match result {
+            Value::Int(1) => Ok(()),
+            value => {
+                cache_error!(
+                    "(ItineraryPool store_record) unexpected redis response to expire command: {:?}",
+                    value
+                );
+
+                Err(CacheError::Unexpected)
+            }
+        }
+    }
+
+    /// Gets the record of an idempotency key
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn get_idempotency_record(&mut self, key: String) -> Result<IdempotencyRecord, CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:idempotency:{key}");
+        let value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_record) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .hget(&key, "data")
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool get_record) could not get record from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        if value == Value::Nil {
+            cache_error!("(ItineraryPool get_record) key expired or does not exist.");
+            return Err(CacheError::NotFound);
+        }
+
+        IdempotencyRecord::from_redis_value(&value).map_err(|e| {
+            cache_error!(
+                "(ItineraryPool get_record) could not deserialize record {:#?}; {e}",
+                value
+            );
+
+            CacheError::InvalidValue
+        })
+    }
+
+    /// Releases an idempotency key so that the request can be made again
+    #[cfg(not(tarpaulin_include))]
+    // no_coverage: (R5) need redis connection, run in integration tests
+    async fn remove_idempotency_key(&mut self, key: String) -> Result<(), CacheError>
+    where
+        Self: Send + Sync + 'async_trait,
+    {
+        let key = format!("cargo:idempotency:{key}");
+        let _: Value = self
+            .pool()
+            .get()
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool remove_key) could not get connection from pool.");
+                CacheError::PoolUnavailable
+            })?
+            .del(&key)
+            .await
+            .map_err(|_| {
+                cache_error!("(ItineraryPool remove_key) could not remove {key} from Redis.");
+                CacheError::OperationFailed
+            })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::Itinerary;
+    use crate::rest::api::rest_types::{
+        BookingJobStatus, CurrencyUnit, HandlingClass, Parcel, Recipient,
+    };
+    use lib_common::uuid::Uuid;
+
+    #[test]
+    fn test_cargo_pool_debug() {
+        let str = format!(
+            "{:?}",
+            CargoPool {
+                pool: Pool::default()
+            }
+        );
+        assert_eq!(str, "CargoPool");
+    }
+
+    #[test]
+    fn test_cache_error_display() {
+        assert_eq!(
+            format!("{}", CacheError::PoolUnavailable),
+            "Pool is unavailable."
+        );
+        assert_eq!(
+            format!("{}", CacheError::CouldNotCreatePool),
+            "Could not create pool."
+        );
+        assert_eq!(
+            format!("{}", CacheError::CouldNotConfigure),
+            "Could not configure cache."
+        );
+        assert_eq!(
+            format!("{}", CacheError::CouldNotConnect),
+            "Could not connect to cache."
+        );
+        assert_eq!(
+            format!("{}", CacheError::OperationFailed),
+            "Cache operation failed."
+        );
+        assert_eq!(format!("{}", CacheError::NotFound), "Key was not found.");
+        assert_eq!(
+            format!("{}", CacheError::InvalidValue),
+            "Invalid value retrieved."
+        );
+        assert_eq!(
+            format!("{}", CacheError::Unexpected),
+            "Unexpected response."
+        );
+        assert_eq!(format!("{}", CacheError::KeyCollision), "Key collision.");
+    }
+
+    #[tokio::test]
+    async fn test_store_itinerary() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let itinerary = Itinerary {
+            flight_plans: vec![],
+            invoice: vec![],
+            currency_unit: CurrencyUnit::Usd,
+            cargo_weight_g: 10,
+            parcels: vec![],
+            user_id: Uuid::new_v4().to_string(),
+            acquisition_vertiport_id: Uuid::new_v4().to_string(),
+            delivery_vertiport_id: Uuid::new_v4().to_string(),
+        };
+
+        // trigger get pool failure
+        pool.pool.fail = true;
+        let itinerary_id = Uuid::new_v4().to_string();
+        let result = pool
+            .store_itinerary(itinerary_id, &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // trigger Err(())
+        pool.pool.fail = false;
+        let itinerary_id = "".to_string();
+        let result = pool
+            .store_itinerary(itinerary_id, &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger key collision
+        let itinerary_id = Uuid::new_v4().to_string();
+        pool.store_itinerary(itinerary_id.clone(), &itinerary)
+            .await
+            .unwrap();
+
+        let result = pool
+            .store_itinerary(itinerary_id, &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::KeyCollision);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_get_itinerary() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+
+        // trigger failing pool get
+        let itinerary_id = Uuid::new_v4().to_string();
+        pool.pool.fail = true;
+        let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let itinerary_id = "".to_string();
+        let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let itinerary_id = Uuid::new_v4().to_string();
+        let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        // successful get
+        let itinerary = Itinerary {
+            flight_plans: vec![],
+            invoice: vec![],
+            currency_unit: CurrencyUnit::Usd,
+            cargo_weight_g: 10,
+            parcels: vec![],
+            user_id: Uuid::new_v4().to_string(),
+            acquisition_vertiport_id: Uuid::new_v4().to_string(),
+            delivery_vertiport_id: Uuid::new_v4().to_string(),
+        };
+
+        let itinerary_id = Uuid::new_v4().to_string();
+        pool.store_itinerary(itinerary_id.clone(), &itinerary)
+            .await
+            .unwrap();
+
+        assert!(pool.get_itinerary(itinerary_id).await.is_ok());
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_itinerary() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let mut itinerary = Itinerary {
+            cargo_weight_g: 10,
+            user_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        // trigger get pool failure
+        pool.pool.fail = true;
+        let itinerary_id = Uuid::new_v4().to_string();
+        let result = pool
+            .refresh_itinerary(itinerary_id, &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // trigger Err(())
+        pool.pool.fail = false;
+        let result = pool
+            .refresh_itinerary("".to_string(), &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // expired drafts are not brought back
+        let itinerary_id = Uuid::new_v4().to_string();
+        let result = pool
+            .refresh_itinerary(itinerary_id.clone(), &itinerary)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
         let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        // successful refresh
+        let itinerary_id = Uuid::new_v4().to_string();
+        pool.store_itinerary(itinerary_id.clone(), &itinerary)
+            .await
+            .unwrap();
+
+        itinerary.cargo_weight_g = 20;
+        pool.refresh_itinerary(itinerary_id.clone(), &itinerary)
+            .await
+            .unwrap();
+
+        let result = pool.get_itinerary(itinerary_id.clone()).await.unwrap();
+        assert_eq!(result.cargo_weight_g, 20);
+        assert_eq!(
+            pool.get_itinerary_ttl(itinerary_id).await.unwrap(),
+            ITINERARY_KEEPALIVE_DURATION_SECONDS as u64
+        );
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_get_itinerary_ttl() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool
+            .get_itinerary_ttl(Uuid::new_v4().to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_itinerary_ttl("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool
+            .get_itinerary_ttl(Uuid::new_v4().to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_booking_job() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let mut job = BookingJob {
+            id: Uuid::new_v4().to_string(),
+            draft_id: Uuid::new_v4().to_string(),
+            user_id: Uuid::new_v4().to_string(),
+            status: BookingJobStatus::Pending,
+            itinerary_id: None,
+            parcels: vec![],
+            error: None,
+        };
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.store_booking_job(&job).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_booking_job(job.id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_booking_job("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool.get_booking_job(job.id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        // new job, then updated job
+        pool.store_booking_job(&job).await.unwrap();
+        let result = pool.get_booking_job(job.id.clone()).await.unwrap();
+        assert_eq!(result.status, BookingJobStatus::Pending);
+
+        job.status = BookingJobStatus::Complete;
+        job.itinerary_id = Some(Uuid::new_v4().to_string());
+        pool.store_booking_job(&job).await.unwrap();
+        let result = pool.get_booking_job(job.id.clone()).await.unwrap();
+        assert_eq!(result.status, BookingJobStatus::Complete);
+        assert_eq!(result.itinerary_id, job.itinerary_id);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_idempotency_record() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let key = Uuid::new_v4().to_string();
+        let mut record = IdempotencyRecord {
+            fingerprint: "fingerprint".to_string(),
+            status: None,
+            headers: vec![],
+            body: String::new(),
+        };
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool
+            .claim_idempotency_key(key.clone(), &record, 60)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_idempotency_record(key.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool
+            .claim_idempotency_key("".to_string(), &record, 60)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool.get_idempotency_record(key.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        // first use, then reuse
+        assert!(pool
+            .claim_idempotency_key(key.clone(), &record, 60)
+            .await
+            .unwrap());
+        assert!(!pool
+            .claim_idempotency_key(key.clone(), &record, 60)
+            .await
+            .unwrap());
+
+        record.status = Some(202);
+        record.body = "{}".to_string();
+        pool.store_idempotency_record(key.clone(), &record, 60)
+            .await
+            .unwrap();
+        let result = pool.get_idempotency_record(key.clone()).await.unwrap();
+        assert_eq!(result, record);
+
+        // released keys can be used again
+        pool.remove_idempotency_key(key.clone()).await.unwrap();
+        assert!(pool.claim_idempotency_key(key, &record, 60).await.unwrap());
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_booked_itinerary() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let itinerary = Itinerary {
+            cargo_weight_g: 10,
+            user_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let itinerary_id = Uuid::new_v4().to_string();
+        let result = pool
+            .store_booked_itinerary(itinerary_id.clone(), &itinerary, 60)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool
+            .get_booked_itinerary(itinerary_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool
+            .remove_booked_itinerary(&itinerary_id)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool
+            .store_booked_itinerary("".to_string(), &itinerary, 60)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool
+            .get_booked_itinerary(itinerary_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        pool.store_booked_itinerary(itinerary_id.clone(), &itinerary, 60)
+            .await
+            .unwrap();
+        let result = pool
+            .get_booked_itinerary(itinerary_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(result.cargo_weight_g, 10);
+
+        pool.remove_booked_itinerary(&itinerary_id).await.unwrap();
+        let result = pool.get_booked_itinerary(itinerary_id).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_compensation() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let itinerary = Itinerary {
+            user_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+        let mut compensation = Compensation::new(&Uuid::new_v4().to_string(), &itinerary, 10.0);
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.store_compensation(&compensation).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool
+            .get_compensation(compensation.itinerary_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_compensation("".to_string()).await.unwrap_err();
         assert_eq!(result, CacheError::OperationFailed);
 
         // trigger not found
-        let itinerary_id = Uuid::new_v4().to_string();
-        let result = pool.get_itinerary(itinerary_id).await.unwrap_err();
+        let result = pool
+            .get_compensation(compensation.itinerary_id.clone())
+            .await
+            .unwrap_err();
         assert_eq!(result, CacheError::NotFound);
 
-        // successful get
-        let itinerary = Itinerary {
-            flight_plans: vec![],
-            invoice: vec![],
-            currency_unit: CurrencyUnit::Usd,
-            cargo_weight_g: 10,
+        // new compensation, then updated compensation
+        pool.store_compensation(&compensation).await.unwrap();
+        let result = pool
+            .get_compensation(compensation.itinerary_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(result, compensation);
+
+        compensation.parcel_ids = vec![Uuid::new_v4().to_string()];
+        pool.store_compensation(&compensation).await.unwrap();
+        let result = pool
+            .get_compensation(compensation.itinerary_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(result.parcel_ids, compensation.parcel_ids);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_parcel() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel = CargoInfo::new(
+            Uuid::new_v4().to_string(),
+            &Parcel {
+                weight_g: 100,
+                handling: vec![HandlingClass::Refrigerated],
+                ..Default::default()
+            },
+        );
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.store_parcel(&parcel).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_parcel(parcel.parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_parcel("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool.get_parcel(parcel.parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        pool.store_parcel(&parcel).await.unwrap();
+        let result = pool.get_parcel(parcel.parcel_id.clone()).await.unwrap();
+        assert_eq!(result.parcel_id, parcel.parcel_id);
+        assert_eq!(result.weight_g, 100);
+        assert_eq!(result.handling, Some(vec![HandlingClass::Refrigerated]));
+
+        // kept without expiry
+        let key = format!("cargo:parcel:{}", parcel.parcel_id);
+        let ttl = pool.pool.connection.ttl(&key).await.unwrap();
+        assert_eq!(ttl, Value::Int(-1));
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_delivery() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+        let record = DeliveryRecord {
+            pin_hash: Some("hash".to_string()),
+            proof: None,
+        };
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.store_delivery(&parcel_id, &record).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_delivery(parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_delivery("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool.get_delivery(parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        pool.store_delivery(&parcel_id, &record).await.unwrap();
+        let result = pool.get_delivery(parcel_id.clone()).await.unwrap();
+        assert_eq!(result, record);
+
+        // kept without expiry
+        let key = format!("cargo:delivery:{parcel_id}");
+        let ttl = pool.pool.connection.ttl(&key).await.unwrap();
+        assert_eq!(ttl, Value::Int(-1));
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_pin_failures() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.count_pin_failure(&parcel_id).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.reset_pin_failures(&parcel_id).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.count_pin_failure("").await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        assert_eq!(pool.count_pin_failure(&parcel_id).await.unwrap(), 1);
+        assert_eq!(pool.count_pin_failure(&parcel_id).await.unwrap(), 2);
+
+        pool.reset_pin_failures(&parcel_id).await.unwrap();
+        assert_eq!(pool.count_pin_failure(&parcel_id).await.unwrap(), 1);
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_parcel_contacts() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+        let contacts = ParcelContacts {
             user_id: Uuid::new_v4().to_string(),
-            acquisition_vertiport_id: Uuid::new_v4().to_string(),
-            delivery_vertiport_id: Uuid::new_v4().to_string(),
+            recipient: Some(Recipient {
+                name: "J. Doe".to_string(),
+                email: Some("j.doe@example.com".to_string()),
+                phone: None,
+            }),
+            delay_notified: false,
         };
 
-        let itinerary_id = Uuid::new_v4().to_string();
-        pool.store_itinerary(itinerary_id.clone(), &itinerary)
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool
+            .store_parcel_contacts(&parcel_id, &contacts)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool
+            .get_parcel_contacts(parcel_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_parcel_contacts("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool
+            .get_parcel_contacts(parcel_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        pool.store_parcel_contacts(&parcel_id, &contacts)
             .await
             .unwrap();
+        let result = pool.get_parcel_contacts(parcel_id.clone()).await.unwrap();
+        assert_eq!(result, contacts);
 
-        assert!(pool.get_itinerary(itinerary_id).await.is_ok());
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_delay_checks() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+        let other_id = Uuid::new_v4().to_string();
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.schedule_delay_check(&parcel_id, 10).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_due_delay_checks(10).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.remove_delay_check(&parcel_id).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        pool.pool.fail = false;
+        pool.schedule_delay_check(&parcel_id, 20).await.unwrap();
+        pool.schedule_delay_check(&other_id, 30).await.unwrap();
+        assert!(pool.get_due_delay_checks(10).await.unwrap().is_empty());
+
+        // rescheduled earlier
+        pool.schedule_delay_check(&other_id, 10).await.unwrap();
+        assert_eq!(
+            pool.get_due_delay_checks(20).await.unwrap(),
+            vec![other_id.clone(), parcel_id.clone()]
+        );
+
+        // removed once checked, kept without expiry until then
+        pool.remove_delay_check(&other_id).await.unwrap();
+        pool.remove_delay_check(&other_id).await.unwrap();
+        assert_eq!(
+            pool.get_due_delay_checks(20).await.unwrap(),
+            vec![parcel_id]
+        );
+        let ttl = pool.pool.connection.ttl(DELAY_CHECKS_KEY).await.unwrap();
+        assert_eq!(ttl, Value::Int(-1));
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_scan_receipts() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+        let scan_id = Uuid::new_v4().to_string();
+        let received_at = Utc::now();
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool
+            .store_scan_receipt(&parcel_id, &scan_id, received_at)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.get_scan_receipts(parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool.get_scan_receipts("".to_string()).await.unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool.get_scan_receipts(parcel_id.clone()).await.unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        // each scan is kept apart, without expiry
+        let other_id = Uuid::new_v4().to_string();
+        pool.store_scan_receipt(&parcel_id, &scan_id, received_at)
+            .await
+            .unwrap();
+        pool.store_scan_receipt(&parcel_id, &other_id, received_at)
+            .await
+            .unwrap();
+
+        let mut receipts = ScanReceipts::default();
+        receipts.received.insert(scan_id, received_at);
+        receipts.received.insert(other_id, received_at);
+        let result = pool.get_scan_receipts(parcel_id.clone()).await.unwrap();
+        assert_eq!(result, receipts);
+
+        let key = format!("cargo:receipts:{parcel_id}");
+        let ttl = pool.pool.connection.ttl(&key).await.unwrap();
+        assert_eq!(ttl, Value::Int(-1));
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_parcel_lock() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let parcel_id = Uuid::new_v4().to_string();
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool.claim_parcel_lock(&parcel_id, 10).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool.release_parcel_lock(&parcel_id).await.unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        pool.pool.fail = false;
+        assert!(pool.claim_parcel_lock(&parcel_id, 10).await.unwrap());
+        assert!(!pool.claim_parcel_lock(&parcel_id, 10).await.unwrap());
+
+        pool.release_parcel_lock(&parcel_id).await.unwrap();
+        assert!(pool.claim_parcel_lock(&parcel_id, 10).await.unwrap());
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_notification_settings() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let config = crate::config::Config::default();
+        let mut pool = CargoPool::new(config).unwrap();
+        let user_id = Uuid::new_v4().to_string();
+        let settings = NotificationSettings {
+            sender: false,
+            recipient: true,
+        };
+
+        // trigger failing pool get
+        pool.pool.fail = true;
+        let result = pool
+            .store_notification_settings(&user_id, &settings)
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+        let result = pool
+            .get_notification_settings(user_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::PoolUnavailable);
+
+        // path to get Err(()) route
+        pool.pool.fail = false;
+        let result = pool
+            .get_notification_settings("".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::OperationFailed);
+
+        // trigger not found
+        let result = pool
+            .get_notification_settings(user_id.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, CacheError::NotFound);
+
+        pool.store_notification_settings(&user_id, &settings)
+            .await
+            .unwrap();
+        let result = pool
+            .get_notification_settings(user_id.clone())
+            .await
+            .unwrap();
+        assert_eq!(result, settings);
 
         ut_info!("success");
     }
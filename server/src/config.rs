@@ -2,10 +2,91 @@
 //!
 //! Define and implement config options for module
 
+use crate::rest::api::rest_types::HandlingClass;
 use anyhow::Result;
 use config::{ConfigError, Environment};
 use dotenv::dotenv;
-use serde::Deserialize;
+use serde::{de::Error as _, Deserialize, Deserializer};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Deserialize a comma-separated list, blank entries are ignored
+fn comma_separated<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .split(',')
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse().map_err(D::Error::custom))
+        .collect()
+}
+
+/// Cargo capacity of a vehicle model
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct VehicleCapacity {
+    /// maximum payload in grams
+    pub payload_g: u32,
+    /// length of the cargo bay in millimeters
+    pub bay_length_mm: u32,
+    /// width of the cargo bay in millimeters
+    pub bay_width_mm: u32,
+    /// height of the cargo bay in millimeters
+    pub bay_height_mm: u32,
+    /// parcel handling classes the vehicle is cleared for, comma-separated
+    #[serde(deserialize_with = "comma_separated")]
+    pub handling: Vec<HandlingClass>,
+}
+
+impl Default for VehicleCapacity {
+    fn default() -> Self {
+        VehicleCapacity {
+            payload_g: 1_000_000,
+            bay_length_mm: 2000,
+            bay_width_mm: 1500,
+            bay_height_mm: 1000,
+            handling: vec![HandlingClass::Fragile, HandlingClass::Refrigerated],
+        }
+    }
+}
+
+/// Surcharge per parcel for each handling class, in the pricing currency
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct HandlingSurcharge {
+    /// surcharge for a fragile parcel
+    pub fragile: f32,
+    /// surcharge for a refrigerated parcel
+    pub refrigerated: f32,
+    /// surcharge for a parcel of dangerous goods
+    pub hazmat: f32,
+}
+
+impl Default for HandlingSurcharge {
+    fn default() -> Self {
+        HandlingSurcharge {
+            fragile: 5.0,
+            refrigerated: 15.0,
+            hazmat: 25.0,
+        }
+    }
+}
+
+impl HandlingSurcharge {
+    /// The surcharge per parcel for a handling class
+    pub fn of(&self, class: HandlingClass) -> f32 {
+        match class {
+            HandlingClass::Fragile => self.fragile,
+            HandlingClass::Refrigerated => self.refrigerated,
+            HandlingClass::Hazmat => self.hazmat,
+        }
+    }
+}
 
 /// struct holding configuration options
 #[derive(Debug, Deserialize, Clone)]
@@ -32,13 +113,65 @@ pub struct Config {
     pub contact_port_grpc: u16,
     /// path to log configuration YAML file
     pub log_config: String,
-    /// Rate limit - requests per second for REST requests
-    pub rest_request_limit_per_second: u8,
+    /// comma-separated API keys of integrations, requests with a listed `x-api-key` are rate
+    /// limited by key rather than by address
+    #[serde(default, deserialize_with = "comma_separated")]
+    pub rest_api_keys: Vec<String>,
+    /// Rate limit - requests per second per client for REST routes acting on behalf of a user
+    pub rest_user_limit_per_second: u32,
+    /// Rate limit - burst size per client for REST routes acting on behalf of a user
+    pub rest_user_limit_burst: u32,
+    /// Rate limit - requests per second per client for REST parcel scans
+    pub rest_scan_limit_per_second: u32,
+    /// Rate limit - burst size per client for REST parcel scans
+    pub rest_scan_limit_burst: u32,
+    /// Rate limit - requests per second per client for other REST queries
+    pub rest_query_limit_per_second: u32,
+    /// Rate limit - burst size per client for other REST queries
+    pub rest_query_limit_burst: u32,
+    /// Seconds during which a response is replayed for requests retried with the same
+    /// `Idempotency-Key`
+    pub rest_idempotency_window_seconds: u32,
+    /// Seconds an `Idempotency-Key` is held while its first request runs, longer than the
+    /// slowest request
+    pub rest_idempotency_lease_seconds: u32,
     /// Enforces a limit on the concurrent number of requests the underlying service can handle
     pub rest_concurrency_limit_per_service: u8,
     /// Full url (including port number) to be allowed as request origin for
     /// REST requests
     pub rest_cors_allowed_origin: String,
+    /// path to the JSON Web Key Set used to validate REST bearer tokens
+    pub rest_auth_jwks_path: String,
+    /// required issuer (`iss` claim) of REST bearer tokens, if any
+    pub rest_auth_issuer: Option<String>,
+    /// required audience (`aud` claim) of REST bearer tokens, if any
+    pub rest_auth_audience: Option<String>,
+    /// itineraries can't be cancelled within this many minutes of departure
+    pub cancel_cutoff_minutes: u32,
+    /// itineraries cancelled at least this many minutes before departure are refunded
+    pub cancel_refund_cutoff_minutes: u32,
+    /// secret key signing the codes of parcel labels, labels are not issued without one
+    pub label_signing_key: Option<String>,
+    /// scans inside a vertiport, or within this many meters of it, are taken at the vertiport
+    pub scan_vertiport_tolerance_meters: u32,
+    /// scans away from the vertiports are rejected if passing through them detours a flight of
+    /// the parcel by more than this many meters
+    pub scan_route_tolerance_meters: u32,
+    /// scans are rejected if received more than this many seconds after the scanner's timestamp,
+    /// scanners may keep scans while offline
+    pub scan_max_delay_seconds: u32,
+    /// scans are rejected if the scanner's timestamp is more than this many seconds ahead of the
+    /// server clock
+    pub scan_max_lead_seconds: u32,
+    /// cargo capacity of vehicles whose model is not listed in `vehicle_models`
+    #[serde(default)]
+    pub vehicle_capacity: VehicleCapacity,
+    /// cargo capacity of vehicle models, by vehicle model ID
+    #[serde(default)]
+    pub vehicle_models: HashMap<String, VehicleCapacity>,
+    /// surcharge per parcel for each handling class
+    #[serde(default)]
+    pub handling_surcharge: HandlingSurcharge,
     /// config to be used for the Redis server
     pub redis: deadpool_redis::Config,
 }
@@ -65,9 +198,30 @@ impl Config {
             contact_host_grpc: String::from("svc-contact"),
             contact_port_grpc: 50051,
             log_config: String::from("log4rs.yaml"),
-            rest_request_limit_per_second: 2,
+            rest_api_keys: vec![],
+            rest_user_limit_per_second: 2,
+            rest_user_limit_burst: 5,
+            rest_scan_limit_per_second: 20,
+            rest_scan_limit_burst: 50,
+            rest_query_limit_per_second: 5,
+            rest_query_limit_burst: 10,
+            rest_idempotency_window_seconds: 86400,
+            rest_idempotency_lease_seconds: 120,
             rest_concurrency_limit_per_service: 5,
             rest_cors_allowed_origin: String::from("http://localhost:3000"),
+            rest_auth_jwks_path: String::from("jwks.json"),
+            rest_auth_issuer: None,
+            rest_auth_audience: None,
+            cancel_cutoff_minutes: 60,
+            cancel_refund_cutoff_minutes: 1440,
+            label_signing_key: None,
+            scan_vertiport_tolerance_meters: 500,
+            scan_route_tolerance_meters: 5000,
+            scan_max_delay_seconds: 86400,
+            scan_max_lead_seconds: 30,
+            vehicle_capacity: VehicleCapacity::default(),
+            vehicle_models: HashMap::new(),
+            handling_surcharge: HandlingSurcharge::default(),
             redis: deadpool_redis::Config {
                 url: None,
                 pool: None,
@@ -99,13 +253,66 @@ impl Config {
                 default_config.rest_concurrency_limit_per_service,
             )?
             .set_default(
-                "rest_request_limit_per_seconds",
-                default_config.rest_request_limit_per_second,
+                "rest_user_limit_per_second",
+                default_config.rest_user_limit_per_second,
+            )?
+            .set_default(
+                "rest_user_limit_burst",
+                default_config.rest_user_limit_burst,
+            )?
+            .set_default(
+                "rest_scan_limit_per_second",
+                default_config.rest_scan_limit_per_second,
+            )?
+            .set_default(
+                "rest_scan_limit_burst",
+                default_config.rest_scan_limit_burst,
+            )?
+            .set_default(
+                "rest_query_limit_per_second",
+                default_config.rest_query_limit_per_second,
+            )?
+            .set_default(
+                "rest_query_limit_burst",
+                default_config.rest_query_limit_burst,
+            )?
+            .set_default(
+                "rest_idempotency_window_seconds",
+                default_config.rest_idempotency_window_seconds,
+            )?
+            .set_default(
+                "rest_idempotency_lease_seconds",
+                default_config.rest_idempotency_lease_seconds,
             )?
             .set_default(
                 "rest_cors_allowed_origin",
                 default_config.rest_cors_allowed_origin,
             )?
+            .set_default("rest_auth_jwks_path", default_config.rest_auth_jwks_path)?
+            .set_default(
+                "cancel_cutoff_minutes",
+                default_config.cancel_cutoff_minutes,
+            )?
+            .set_default(
+                "cancel_refund_cutoff_minutes",
+                default_config.cancel_refund_cutoff_minutes,
+            )?
+            .set_default(
+                "scan_vertiport_tolerance_meters",
+                default_config.scan_vertiport_tolerance_meters,
+            )?
+            .set_default(
+                "scan_route_tolerance_meters",
+                default_config.scan_route_tolerance_meters,
+            )?
+            .set_default(
+                "scan_max_delay_seconds",
+                default_config.scan_max_delay_seconds,
+            )?
+            .set_default(
+                "scan_max_lead_seconds",
+                default_config.scan_max_lead_seconds,
+            )?
             .add_source(Environment::default().separator("__"))
             .build()?
             .try_deserialize()
@@ -114,7 +321,7 @@ impl Config {
 
 #[cfg(test)]
 mod tests {
-    use super::Config;
+    use super::{Config, HandlingClass, HandlingSurcharge, VehicleCapacity};
 
     #[tokio::test]
     async fn test_config_from_default() {
@@ -135,11 +342,32 @@ mod tests {
         assert_eq!(config.contact_port_grpc, 50051);
         assert_eq!(config.log_config, String::from("log4rs.yaml"));
         assert_eq!(config.rest_concurrency_limit_per_service, 5);
-        assert_eq!(config.rest_request_limit_per_second, 2);
+        assert!(config.rest_api_keys.is_empty());
+        assert_eq!(config.rest_user_limit_per_second, 2);
+        assert_eq!(config.rest_user_limit_burst, 5);
+        assert_eq!(config.rest_scan_limit_per_second, 20);
+        assert_eq!(config.rest_scan_limit_burst, 50);
+        assert_eq!(config.rest_query_limit_per_second, 5);
+        assert_eq!(config.rest_query_limit_burst, 10);
+        assert_eq!(config.rest_idempotency_window_seconds, 86400);
+        assert_eq!(config.rest_idempotency_lease_seconds, 120);
         assert_eq!(
             config.rest_cors_allowed_origin,
             String::from("http://localhost:3000")
         );
+        assert_eq!(config.rest_auth_jwks_path, String::from("jwks.json"));
+        assert!(config.rest_auth_issuer.is_none());
+        assert!(config.rest_auth_audience.is_none());
+        assert_eq!(config.cancel_cutoff_minutes, 60);
+        assert_eq!(config.cancel_refund_cutoff_minutes, 1440);
+        assert!(config.label_signing_key.is_none());
+        assert_eq!(config.scan_vertiport_tolerance_meters, 500);
+        assert_eq!(config.scan_route_tolerance_meters, 5000);
+        assert_eq!(config.scan_max_delay_seconds, 86400);
+        assert_eq!(config.scan_max_lead_seconds, 30);
+        assert_eq!(config.vehicle_capacity, VehicleCapacity::default());
+        assert!(config.vehicle_models.is_empty());
+        assert_eq!(config.handling_surcharge, HandlingSurcharge::default());
         assert!(config.redis.url.is_none());
         assert!(config.redis.pool.is_none());
         assert!(config.redis.connection.is_none());
@@ -164,11 +392,36 @@ mod tests {
         std::env::set_var("CONTACT_PORT_GRPC", "11111");
         std::env::set_var("LOG_CONFIG", "config_file.yaml");
         std::env::set_var("REST_CONCURRENCY_LIMIT_PER_SERVICE", "255");
-        std::env::set_var("REST_REQUEST_LIMIT_PER_SECOND", "255");
+        std::env::set_var("REST_API_KEYS", "key-one, key-two,");
+        std::env::set_var("REST_USER_LIMIT_PER_SECOND", "1");
+        std::env::set_var("REST_USER_LIMIT_BURST", "2");
+        std::env::set_var("REST_SCAN_LIMIT_PER_SECOND", "100");
+        std::env::set_var("REST_SCAN_LIMIT_BURST", "200");
+        std::env::set_var("REST_QUERY_LIMIT_PER_SECOND", "10");
+        std::env::set_var("REST_QUERY_LIMIT_BURST", "20");
+        std::env::set_var("REST_IDEMPOTENCY_WINDOW_SECONDS", "3600");
+        std::env::set_var("REST_IDEMPOTENCY_LEASE_SECONDS", "30");
         std::env::set_var(
             "REST_CORS_ALLOWED_ORIGIN",
             "https://allowed.origin.host:443",
         );
+        std::env::set_var("REST_AUTH_JWKS_PATH", "/etc/svc-cargo/jwks.json");
+        std::env::set_var("REST_AUTH_ISSUER", "https://auth.aetheric.nl");
+        std::env::set_var("REST_AUTH_AUDIENCE", "svc-cargo");
+        std::env::set_var("CANCEL_CUTOFF_MINUTES", "30");
+        std::env::set_var("CANCEL_REFUND_CUTOFF_MINUTES", "720");
+        std::env::set_var("LABEL_SIGNING_KEY", "label secret");
+        std::env::set_var("SCAN_VERTIPORT_TOLERANCE_METERS", "250");
+        std::env::set_var("SCAN_ROUTE_TOLERANCE_METERS", "2000");
+        std::env::set_var("SCAN_MAX_DELAY_SECONDS", "3600");
+        std::env::set_var("SCAN_MAX_LEAD_SECONDS", "10");
+        std::env::set_var("VEHICLE_CAPACITY__PAYLOAD_G", "200000");
+        std::env::set_var("VEHICLE_CAPACITY__HANDLING", "Fragile, hazmat,");
+        std::env::set_var(
+            "VEHICLE_MODELS__8c4b2e1a-0f3d-4a6e-9b7c-5d1e2f3a4b5c__BAY_HEIGHT_MM",
+            "500",
+        );
+        std::env::set_var("HANDLING_SURCHARGE__HAZMAT", "40.5");
         std::env::set_var("REDIS__URL", "redis://test_redis:6379");
         std::env::set_var("REDIS__POOL__MAX_SIZE", "16");
         std::env::set_var("REDIS__POOL__TIMEOUTS__WAIT__SECS", "2");
@@ -193,11 +446,56 @@ mod tests {
         );
         assert_eq!(config.log_config, String::from("config_file.yaml"));
         assert_eq!(config.rest_concurrency_limit_per_service, 255);
-        assert_eq!(config.rest_request_limit_per_second, 255);
+        assert_eq!(config.rest_api_keys, vec!["key-one", "key-two"]);
+        assert_eq!(config.rest_user_limit_per_second, 1);
+        assert_eq!(config.rest_user_limit_burst, 2);
+        assert_eq!(config.rest_scan_limit_per_second, 100);
+        assert_eq!(config.rest_scan_limit_burst, 200);
+        assert_eq!(config.rest_query_limit_per_second, 10);
+        assert_eq!(config.rest_query_limit_burst, 20);
+        assert_eq!(config.rest_idempotency_window_seconds, 3600);
+        assert_eq!(config.rest_idempotency_lease_seconds, 30);
         assert_eq!(
             config.rest_cors_allowed_origin,
             String::from("https://allowed.origin.host:443")
         );
+        assert_eq!(
+            config.rest_auth_jwks_path,
+            String::from("/etc/svc-cargo/jwks.json")
+        );
+        assert_eq!(
+            config.rest_auth_issuer,
+            Some(String::from("https://auth.aetheric.nl"))
+        );
+        assert_eq!(config.rest_auth_audience, Some(String::from("svc-cargo")));
+        assert_eq!(config.cancel_cutoff_minutes, 30);
+        assert_eq!(config.cancel_refund_cutoff_minutes, 720);
+        assert_eq!(config.label_signing_key, Some(String::from("label secret")));
+        assert_eq!(config.scan_vertiport_tolerance_meters, 250);
+        assert_eq!(config.scan_route_tolerance_meters, 2000);
+        assert_eq!(config.scan_max_delay_seconds, 3600);
+        assert_eq!(config.scan_max_lead_seconds, 10);
+        assert_eq!(config.vehicle_capacity.payload_g, 200000);
+        assert_eq!(
+            config.vehicle_capacity.handling,
+            vec![HandlingClass::Fragile, HandlingClass::Hazmat]
+        );
+        assert_eq!(
+            config.vehicle_capacity.bay_length_mm,
+            VehicleCapacity::default().bay_length_mm
+        );
+        let model = config
+            .vehicle_models
+            .get("8c4b2e1a-0f3d-4a6e-9b7c-5d1e2f3a4b5c")
+            .unwrap();
+        assert_eq!(model.bay_height_mm, 500);
+        assert_eq!(model.payload_g, VehicleCapacity::default().payload_g);
+        assert_eq!(model.handling, VehicleCapacity::default().handling);
+        assert_eq!(config.handling_surcharge.hazmat, 40.5);
+        assert_eq!(
+            config.handling_surcharge.fragile,
+            HandlingSurcharge::default().fragile
+        );
         assert_eq!(
             config.redis.url,
             Some(String::from("redis://test_redis:6379"))
@@ -206,4 +504,22 @@ mod tests {
 
         ut_info!("success");
     }
+
+    #[test]
+    fn test_vehicle_capacity_handling() {
+        let capacity: VehicleCapacity =
+            serde_json::from_str(r#"{"handling": "refrigerated, "}"#).unwrap();
+        assert_eq!(capacity.handling, vec![HandlingClass::Refrigerated]);
+
+        let capacity: VehicleCapacity = serde_json::from_str(r#"{"handling": ""}"#).unwrap();
+        assert!(capacity.handling.is_empty());
+
+        // unknown names are rejected
+        let error =
+            serde_json::from_str::<VehicleCapacity>(r#"{"handling": "Fragile,radioactive"}"#)
+                .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("unknown handling class radioactive"));
+    }
 }
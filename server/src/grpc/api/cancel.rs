@@ -0,0 +1,62 @@
+//! Itinerary cancellation over gRPC
+
+use crate::grpc::client::get_clients;
+use crate::grpc::server::grpc_server::{
+    CancelItineraryRequest, CancelItineraryResponse, CurrencyUnit,
+};
+use crate::service::cancel::CancelPolicy;
+use tonic::Status;
+
+/// Cancel a booked itinerary
+pub async fn cancel_itinerary(
+    request: CancelItineraryRequest,
+    policy: &CancelPolicy,
+) -> Result<CancelItineraryResponse, Status> {
+    grpc_debug!("entry.");
+
+    let grpc_clients = get_clients().await;
+    let response = crate::service::cancel::cancel_itinerary(
+        &request.id,
+        &request.user_id,
+        policy,
+        grpc_clients,
+    )
+    .await
+    .map_err(|e| {
+        grpc_error!("could not cancel itinerary: {e}");
+        Status::from(e)
+    })?;
+
+    Ok(CancelItineraryResponse {
+        itinerary_id: response.itinerary_id,
+        refund: response.refund,
+        refund_amount: response.refund_amount,
+        currency_unit: CurrencyUnit::from(response.currency_unit) as i32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_cancel_itinerary() {
+        let policy = CancelPolicy::default();
+        let request = CancelItineraryRequest {
+            id: "invalid".to_string(),
+            user_id: Uuid::new_v4().to_string(),
+        };
+
+        let error = cancel_itinerary(request, &policy).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        let request = CancelItineraryRequest {
+            id: Uuid::new_v4().to_string(),
+            user_id: Uuid::new_v4().to_string(),
+        };
+
+        let error = cancel_itinerary(request, &policy).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::NotFound);
+    }
+}
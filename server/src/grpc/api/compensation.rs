@@ -0,0 +1,54 @@
+//! Compensation of failed bookings over gRPC
+
+use crate::grpc::client::get_clients;
+use crate::grpc::server::grpc_server::{RetryCompensationRequest, RetryCompensationResponse};
+use tonic::Status;
+
+/// Retry the failed steps undoing a failed booking
+pub async fn retry_compensation(
+    request: RetryCompensationRequest,
+) -> Result<RetryCompensationResponse, Status> {
+    grpc_debug!("entry.");
+
+    let grpc_clients = get_clients().await;
+    let compensation =
+        crate::service::compensation::retry_compensation(&request.itinerary_id, grpc_clients)
+            .await
+            .map_err(|e| {
+                grpc_error!("could not retry compensation: {e}");
+                Status::from(e)
+            })?;
+
+    Ok(RetryCompensationResponse {
+        complete: compensation.is_complete(),
+        pending_steps: compensation
+            .pending()
+            .into_iter()
+            .map(|action| format!("{action:?}"))
+            .collect(),
+        itinerary_id: compensation.itinerary_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    #[tokio::test]
+    async fn test_retry_compensation() {
+        let request = RetryCompensationRequest {
+            itinerary_id: "invalid".to_string(),
+        };
+
+        let error = retry_compensation(request).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+
+        let request = RetryCompensationRequest {
+            itinerary_id: Uuid::new_v4().to_string(),
+        };
+
+        let error = retry_compensation(request).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::NotFound);
+    }
+}
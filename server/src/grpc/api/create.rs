@@ -0,0 +1,95 @@
+//! Itinerary booking over gRPC
+
+use crate::grpc::client::get_clients;
+use crate::grpc::server::grpc_server::{
+    CargoInfo, CreateItineraryRequest, CreateItineraryResponse, HandlingClass, Parcel,
+};
+use crate::rest::api::rest_types;
+use tonic::Status;
+
+impl From<rest_types::CargoInfo> for CargoInfo {
+    fn from(info: rest_types::CargoInfo) -> Self {
+        CargoInfo {
+            parcel_id: info.parcel_id,
+            parcel: Some(Parcel {
+                weight_g: info.weight_g,
+                length_mm: info.length_mm,
+                width_mm: info.width_mm,
+                height_mm: info.height_mm,
+                reference: info.reference.unwrap_or_default(),
+                handling: info
+                    .handling
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|class| HandlingClass::from(class) as i32)
+                    .collect(),
+                recipient: None,
+                delivery_pin: false,
+            }),
+        }
+    }
+}
+
+/// Book a draft itinerary of the user
+/// Drafts of other users are reported as not found.
+pub async fn create_itinerary(
+    request: CreateItineraryRequest,
+) -> Result<CreateItineraryResponse, Status> {
+    grpc_debug!("entry.");
+
+    let grpc_clients = get_clients().await;
+    let booking =
+        crate::service::create::create_itinerary(&request.id, &request.user_id, grpc_clients)
+            .await
+            .map_err(|e| {
+                grpc_error!("could not create itinerary: {e}");
+                Status::from(e)
+            })?;
+
+    Ok(CreateItineraryResponse {
+        itinerary_id: booking.itinerary_id,
+        parcels: booking.parcels.into_iter().map(Into::into).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    #[test]
+    fn test_cargo_info_from() {
+        let info = rest_types::CargoInfo {
+            parcel_id: Uuid::new_v4().to_string(),
+            weight_g: 100,
+            length_mm: 300,
+            width_mm: 200,
+            height_mm: 100,
+            reference: Some("samples".to_string()),
+            handling: Some(vec![rest_types::HandlingClass::Fragile]),
+            itinerary_id: None,
+        };
+
+        let result = CargoInfo::from(info.clone());
+        assert_eq!(result.parcel_id, info.parcel_id);
+
+        let parcel = result.parcel.unwrap();
+        assert_eq!(parcel.weight_g, 100);
+        assert_eq!(parcel.length_mm, 300);
+        assert_eq!(parcel.width_mm, 200);
+        assert_eq!(parcel.height_mm, 100);
+        assert_eq!(parcel.reference, "samples");
+        assert_eq!(parcel.handling, vec![HandlingClass::Fragile as i32]);
+    }
+
+    #[tokio::test]
+    async fn test_create_itinerary_invalid_argument() {
+        let request = CreateItineraryRequest {
+            id: "invalid".to_string(),
+            user_id: Uuid::new_v4().to_string(),
+        };
+
+        let error = create_itinerary(request).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,96 @@
+//! gRPC request handlers
+//! Translate gRPC messages to the internal types used by svc-cargo
+
+pub mod cancel;
+pub mod compensation;
+pub mod create;
+pub mod query;
+pub mod scan;
+pub mod track;
+
+use crate::service::ServiceError;
+use hyper::StatusCode;
+use tonic::Status;
+
+impl From<ServiceError> for Status {
+    fn from(error: ServiceError) -> Self {
+        let message = error.to_string();
+        match error {
+            ServiceError::InvalidArgument => Status::invalid_argument(message),
+            ServiceError::NotFound => Status::not_found(message),
+            ServiceError::Rejected => Status::failed_precondition(message),
+            ServiceError::Timeout => Status::deadline_exceeded(message),
+            ServiceError::Unavailable => Status::unavailable(message),
+            ServiceError::Internal => Status::internal(message),
+        }
+    }
+}
+
+/// Converts an HTTP status code returned by the shared handler logic
+///  into a [`tonic::Status`]
+pub(crate) fn status_from_http(code: StatusCode, message: &str) -> Status {
+    match code {
+        StatusCode::BAD_REQUEST => Status::invalid_argument(message),
+        StatusCode::NOT_FOUND => Status::not_found(message),
+        StatusCode::REQUEST_TIMEOUT => Status::deadline_exceeded(message),
+        StatusCode::SERVICE_UNAVAILABLE => Status::unavailable(message),
+        _ => Status::internal(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    #[test]
+    fn test_status_from_http() {
+        let message = "test";
+        assert_eq!(
+            status_from_http(StatusCode::BAD_REQUEST, message).code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            status_from_http(StatusCode::NOT_FOUND, message).code(),
+            Code::NotFound
+        );
+        assert_eq!(
+            status_from_http(StatusCode::REQUEST_TIMEOUT, message).code(),
+            Code::DeadlineExceeded
+        );
+        assert_eq!(
+            status_from_http(StatusCode::SERVICE_UNAVAILABLE, message).code(),
+            Code::Unavailable
+        );
+        assert_eq!(
+            status_from_http(StatusCode::INTERNAL_SERVER_ERROR, message).code(),
+            Code::Internal
+        );
+        assert_eq!(
+            status_from_http(StatusCode::BAD_REQUEST, message).message(),
+            message
+        );
+    }
+
+    #[test]
+    fn test_status_from_service_error() {
+        assert_eq!(
+            Status::from(ServiceError::InvalidArgument).code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(Status::from(ServiceError::NotFound).code(), Code::NotFound);
+        assert_eq!(
+            Status::from(ServiceError::Rejected).code(),
+            Code::FailedPrecondition
+        );
+        assert_eq!(
+            Status::from(ServiceError::Timeout).code(),
+            Code::DeadlineExceeded
+        );
+        assert_eq!(
+            Status::from(ServiceError::Unavailable).code(),
+            Code::Unavailable
+        );
+        assert_eq!(Status::from(ServiceError::Internal).code(), Code::Internal);
+    }
+}
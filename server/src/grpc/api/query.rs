@@ -0,0 +1,453 @@
+//! Itinerary search over gRPC
+
+use super::status_from_http;
+use crate::grpc::client::get_clients;
+use crate::grpc::server::grpc_server::{
+    CurrencyUnit, DraftItinerary, FlightPlan, GeoPointZ, HandlingClass, InvoiceItem, Itinerary,
+    Parcel, QueryItineraryRequest, QueryItineraryResponse, Recipient,
+};
+use crate::rest::api::request::query_itineraries;
+use crate::rest::api::rest_types;
+use std::fmt::{self, Display, Formatter};
+use tonic::Status;
+
+/// Errors converting a gRPC itinerary request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QueryItineraryError {
+    /// The departure window was not provided
+    TimeWindow,
+
+    /// The departure window start was not provided
+    TimestampMin,
+
+    /// The departure window end was not provided
+    TimestampMax,
+
+    /// A parcel has an unknown handling class
+    HandlingClass,
+}
+
+impl Display for QueryItineraryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            QueryItineraryError::TimeWindow => write!(f, "departure window not provided"),
+            QueryItineraryError::TimestampMin => write!(f, "departure window start not provided"),
+            QueryItineraryError::TimestampMax => write!(f, "departure window end not provided"),
+            QueryItineraryError::HandlingClass => write!(f, "unknown parcel handling class"),
+        }
+    }
+}
+
+impl TryFrom<QueryItineraryRequest> for rest_types::QueryItineraryRequest {
+    type Error = QueryItineraryError;
+
+    fn try_from(request: QueryItineraryRequest) -> Result<Self, Self::Error> {
+        let window = request
+            .time_depart_window
+            .ok_or(QueryItineraryError::TimeWindow)?;
+
+        let timestamp_min = window
+            .timestamp_min
+            .ok_or(QueryItineraryError::TimestampMin)?
+            .into();
+
+        let timestamp_max = window
+            .timestamp_max
+            .ok_or(QueryItineraryError::TimestampMax)?
+            .into();
+
+        let mut payload = rest_types::QueryItineraryRequest {
+            origin_vertiport_id: request.origin_vertiport_id,
+            target_vertiport_id: request.target_vertiport_id,
+            time_depart_window: rest_types::TimeWindow {
+                timestamp_min,
+                timestamp_max,
+            },
+            parcels: request
+                .parcels
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+            cargo_weight_g: request.cargo_weight_g,
+            user_id: request.user_id,
+        };
+
+        payload.normalize();
+        Ok(payload)
+    }
+}
+
+impl From<HandlingClass> for rest_types::HandlingClass {
+    fn from(class: HandlingClass) -> Self {
+        match class {
+            HandlingClass::Fragile => rest_types::HandlingClass::Fragile,
+            HandlingClass::Refrigerated => rest_types::HandlingClass::Refrigerated,
+            HandlingClass::Hazmat => rest_types::HandlingClass::Hazmat,
+        }
+    }
+}
+
+impl From<rest_types::HandlingClass> for HandlingClass {
+    fn from(class: rest_types::HandlingClass) -> Self {
+        match class {
+            rest_types::HandlingClass::Fragile => HandlingClass::Fragile,
+            rest_types::HandlingClass::Refrigerated => HandlingClass::Refrigerated,
+            rest_types::HandlingClass::Hazmat => HandlingClass::Hazmat,
+        }
+    }
+}
+
+impl TryFrom<Parcel> for rest_types::Parcel {
+    type Error = QueryItineraryError;
+
+    fn try_from(parcel: Parcel) -> Result<Self, Self::Error> {
+        // Unknown classes are rejected rather than dropped, they may be dangerous goods
+        let handling = parcel
+            .handling
+            .into_iter()
+            .map(|class| {
+                HandlingClass::try_from(class)
+                    .map(Into::into)
+                    .map_err(|_| QueryItineraryError::HandlingClass)
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(rest_types::Parcel {
+            weight_g: parcel.weight_g,
+            length_mm: parcel.length_mm,
+            width_mm: parcel.width_mm,
+            height_mm: parcel.height_mm,
+            reference: Some(parcel.reference).filter(|reference| !reference.is_empty()),
+            handling,
+            recipient: parcel.recipient.map(Into::into),
+            delivery_pin: parcel.delivery_pin,
+        })
+    }
+}
+
+impl From<Recipient> for rest_types::Recipient {
+    fn from(recipient: Recipient) -> Self {
+        rest_types::Recipient {
+            name: recipient.name,
+            email: Some(recipient.email).filter(|email| !email.is_empty()),
+            phone: Some(recipient.phone).filter(|phone| !phone.is_empty()),
+        }
+    }
+}
+
+impl From<rest_types::Recipient> for Recipient {
+    fn from(recipient: rest_types::Recipient) -> Self {
+        Recipient {
+            name: recipient.name,
+            email: recipient.email.unwrap_or_default(),
+            phone: recipient.phone.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<rest_types::Parcel> for Parcel {
+    fn from(parcel: rest_types::Parcel) -> Self {
+        Parcel {
+            weight_g: parcel.weight_g,
+            length_mm: parcel.length_mm,
+            width_mm: parcel.width_mm,
+            height_mm: parcel.height_mm,
+            reference: parcel.reference.unwrap_or_default(),
+            handling: parcel
+                .handling
+                .into_iter()
+                .map(|class| HandlingClass::from(class) as i32)
+                .collect(),
+            recipient: parcel.recipient.map(Into::into),
+            delivery_pin: parcel.delivery_pin,
+        }
+    }
+}
+
+impl From<rest_types::GeoPointZ> for GeoPointZ {
+    fn from(point: rest_types::GeoPointZ) -> Self {
+        GeoPointZ {
+            x: point.x,
+            y: point.y,
+            z: point.z,
+        }
+    }
+}
+
+impl From<rest_types::CurrencyUnit> for CurrencyUnit {
+    fn from(unit: rest_types::CurrencyUnit) -> Self {
+        match unit {
+            rest_types::CurrencyUnit::Usd => CurrencyUnit::Usd,
+            rest_types::CurrencyUnit::Euro => CurrencyUnit::Euro,
+        }
+    }
+}
+
+impl From<rest_types::InvoiceItem> for InvoiceItem {
+    fn from(item: rest_types::InvoiceItem) -> Self {
+        InvoiceItem {
+            item: item.item,
+            cost: item.cost,
+        }
+    }
+}
+
+impl From<rest_types::FlightPlan> for FlightPlan {
+    fn from(plan: rest_types::FlightPlan) -> Self {
+        FlightPlan {
+            origin_vertiport_id: plan.origin_vertiport_id,
+            origin_vertipad_id: plan.origin_vertipad_id,
+            target_vertiport_id: plan.target_vertiport_id,
+            target_vertipad_id: plan.target_vertipad_id,
+            path: plan.path.into_iter().map(Into::into).collect(),
+            origin_timeslot_start: Some(plan.origin_timeslot_start.into()),
+            origin_timeslot_end: Some(plan.origin_timeslot_end.into()),
+            target_timeslot_start: Some(plan.target_timeslot_start.into()),
+            target_timeslot_end: Some(plan.target_timeslot_end.into()),
+            vehicle_id: plan.vehicle_id,
+            flight_priority: plan.flight_priority,
+        }
+    }
+}
+
+impl From<rest_types::Itinerary> for Itinerary {
+    fn from(itinerary: rest_types::Itinerary) -> Self {
+        Itinerary {
+            flight_plans: itinerary.flight_plans.into_iter().map(Into::into).collect(),
+            currency_unit: CurrencyUnit::from(itinerary.currency_unit) as i32,
+            invoice: itinerary.invoice.into_iter().map(Into::into).collect(),
+            cargo_weight_g: itinerary.cargo_weight_g,
+            user_id: itinerary.user_id,
+            acquisition_vertiport_id: itinerary.acquisition_vertiport_id,
+            delivery_vertiport_id: itinerary.delivery_vertiport_id,
+            parcels: itinerary.parcels.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<rest_types::DraftItinerary> for DraftItinerary {
+    fn from(draft: rest_types::DraftItinerary) -> Self {
+        DraftItinerary {
+            id: draft.id,
+            itinerary: Some(draft.itinerary.into()),
+        }
+    }
+}
+
+/// Search for available itineraries
+/// Uses the same validation, scheduling and pricing steps as the
+///  `/cargo/request` REST endpoint. Results are stored as draft itineraries.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn query_itinerary(
+    request: QueryItineraryRequest,
+) -> Result<QueryItineraryResponse, Status> {
+    grpc_debug!("entry.");
+
+    let payload = rest_types::QueryItineraryRequest::try_from(request).map_err(|e| {
+        grpc_error!("invalid request: {e}");
+        Status::invalid_argument(e.to_string())
+    })?;
+
+    let mut grpc_clients = get_clients().await.clone();
+    let itineraries = query_itineraries(&payload, &mut grpc_clients)
+        .await
+        .map_err(|e| status_from_http(e, "could not query itineraries."))?
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<DraftItinerary>>();
+
+    grpc_debug!("exit with {} itineraries.", itineraries.len());
+    Ok(QueryItineraryResponse { itineraries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grpc::server::grpc_server::TimeWindow;
+    use lib_common::time::{Duration, Utc};
+    use lib_common::uuid::Uuid;
+
+    #[test]
+    fn test_query_itinerary_request_try_from() {
+        let timestamp_min = Utc::now();
+        let timestamp_max = timestamp_min + Duration::try_minutes(10).unwrap();
+        let mut request = QueryItineraryRequest {
+            origin_vertiport_id: Uuid::new_v4().to_string(),
+            target_vertiport_id: Uuid::new_v4().to_string(),
+            time_depart_window: Some(TimeWindow {
+                timestamp_min: Some(timestamp_min.into()),
+                timestamp_max: Some(timestamp_max.into()),
+            }),
+            user_id: Uuid::new_v4().to_string(),
+            parcels: vec![
+                Parcel {
+                    weight_g: 100,
+                    reference: "samples".to_string(),
+                    handling: vec![HandlingClass::Refrigerated as i32],
+                    recipient: Some(Recipient {
+                        name: "J. Doe".to_string(),
+                        email: "j.doe@example.com".to_string(),
+                        phone: String::new(),
+                    }),
+                    ..Default::default()
+                },
+                Parcel {
+                    weight_g: 50,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let result = rest_types::QueryItineraryRequest::try_from(request.clone()).unwrap();
+        assert_eq!(result.origin_vertiport_id, request.origin_vertiport_id);
+        assert_eq!(result.target_vertiport_id, request.target_vertiport_id);
+        assert_eq!(result.parcels.len(), 2);
+        assert_eq!(result.parcels[0].reference, Some("samples".to_string()));
+        assert_eq!(result.parcels[1].reference, None);
+        assert_eq!(
+            result.parcels[0].handling,
+            vec![rest_types::HandlingClass::Refrigerated]
+        );
+        assert!(result.parcels[1].handling.is_empty());
+        assert_eq!(
+            result.parcels[0].recipient,
+            Some(rest_types::Recipient {
+                name: "J. Doe".to_string(),
+                email: Some("j.doe@example.com".to_string()),
+                phone: None,
+            })
+        );
+        assert_eq!(result.parcels[1].recipient, None);
+        assert_eq!(result.cargo_weight_g(), 150);
+        assert_eq!(result.user_id, request.user_id);
+        assert_eq!(result.time_depart_window.timestamp_min, timestamp_min);
+        assert_eq!(result.time_depart_window.timestamp_max, timestamp_max);
+
+        // deprecated cargo weight
+        let mut legacy = request.clone();
+        legacy.parcels.clear();
+        legacy.cargo_weight_g = Some(500);
+        let result = rest_types::QueryItineraryRequest::try_from(legacy).unwrap();
+        assert_eq!(result.parcels.len(), 1);
+        assert_eq!(result.cargo_weight_g(), 500);
+        assert_eq!(result.parcels[0].length_mm, 0);
+        assert_eq!(result.cargo_weight_g, None);
+
+        // unknown handling class
+        let mut unknown = request.clone();
+        unknown.parcels[1].handling = vec![-1];
+        assert_eq!(
+            rest_types::QueryItineraryRequest::try_from(unknown).unwrap_err(),
+            QueryItineraryError::HandlingClass
+        );
+
+        // missing window end
+        request.time_depart_window = Some(TimeWindow {
+            timestamp_min: Some(timestamp_min.into()),
+            timestamp_max: None,
+        });
+        assert_eq!(
+            rest_types::QueryItineraryRequest::try_from(request.clone()).unwrap_err(),
+            QueryItineraryError::TimestampMax
+        );
+
+        // missing window start
+        request.time_depart_window = Some(TimeWindow {
+            timestamp_min: None,
+            timestamp_max: Some(timestamp_max.into()),
+        });
+        assert_eq!(
+            rest_types::QueryItineraryRequest::try_from(request.clone()).unwrap_err(),
+            QueryItineraryError::TimestampMin
+        );
+
+        // missing window
+        request.time_depart_window = None;
+        assert_eq!(
+            rest_types::QueryItineraryRequest::try_from(request).unwrap_err(),
+            QueryItineraryError::TimeWindow
+        );
+    }
+
+    #[test]
+    fn test_draft_itinerary_from() {
+        let draft = rest_types::DraftItinerary {
+            id: Uuid::new_v4().to_string(),
+            itinerary: rest_types::Itinerary {
+                invoice: vec![rest_types::InvoiceItem {
+                    item: "item".to_string(),
+                    cost: 1.0,
+                }],
+                currency_unit: rest_types::CurrencyUnit::Usd,
+                cargo_weight_g: 100,
+                parcels: vec![rest_types::Parcel {
+                    weight_g: 100,
+                    length_mm: 300,
+                    handling: vec![rest_types::HandlingClass::Hazmat],
+                    recipient: Some(rest_types::Recipient {
+                        name: "J. Doe".to_string(),
+                        email: None,
+                        phone: Some("+31 20 123 4567".to_string()),
+                    }),
+                    ..Default::default()
+                }],
+                user_id: Uuid::new_v4().to_string(),
+                ..Default::default()
+            },
+        };
+
+        let result = DraftItinerary::from(draft.clone());
+        assert_eq!(result.id, draft.id);
+
+        let itinerary = result.itinerary.unwrap();
+        assert_eq!(itinerary.currency_unit, CurrencyUnit::Usd as i32);
+        assert_eq!(itinerary.invoice.len(), 1);
+        assert_eq!(itinerary.invoice[0].item, "item");
+        assert_eq!(itinerary.cargo_weight_g, draft.itinerary.cargo_weight_g);
+        assert_eq!(itinerary.parcels.len(), 1);
+        assert_eq!(itinerary.parcels[0].length_mm, 300);
+        assert!(itinerary.parcels[0].reference.is_empty());
+        assert_eq!(
+            itinerary.parcels[0].handling,
+            vec![HandlingClass::Hazmat as i32]
+        );
+        let recipient = itinerary.parcels[0].recipient.clone().unwrap();
+        assert_eq!(recipient.name, "J. Doe");
+        assert!(recipient.email.is_empty());
+        assert_eq!(recipient.phone, "+31 20 123 4567");
+        assert_eq!(itinerary.user_id, draft.itinerary.user_id);
+    }
+
+    #[test]
+    fn test_query_itinerary_error_display() {
+        assert_eq!(
+            QueryItineraryError::TimeWindow.to_string(),
+            "departure window not provided"
+        );
+        assert_eq!(
+            QueryItineraryError::TimestampMin.to_string(),
+            "departure window start not provided"
+        );
+        assert_eq!(
+            QueryItineraryError::TimestampMax.to_string(),
+            "departure window end not provided"
+        );
+        assert_eq!(
+            QueryItineraryError::HandlingClass.to_string(),
+            "unknown parcel handling class"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_itinerary_invalid_argument() {
+        let request = QueryItineraryRequest {
+            time_depart_window: None,
+            ..Default::default()
+        };
+
+        let error = query_itinerary(request).await.unwrap_err();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -0,0 +1,196 @@
+//! Parcel scan ingestion over gRPC
+
+use crate::grpc::client::GrpcClients;
+use crate::grpc::server::grpc_server::{CargoScan, ScanResult, StreamScansResponse};
+use crate::rest::api::rest_types;
+use crate::service::label::LabelSigner;
+use crate::service::scan::{scan_parcel, ScanPolicy};
+use futures::{Stream, StreamExt};
+use lib_common::time::Utc;
+use tonic::Status;
+
+impl From<CargoScan> for rest_types::CargoScan {
+    fn from(scan: CargoScan) -> Self {
+        rest_types::CargoScan {
+            scanner_id: scan.scanner_id,
+            parcel_id: scan.parcel_id,
+            code: Some(scan.code).filter(|code| !code.is_empty()),
+            latitude: scan.latitude,
+            longitude: scan.longitude,
+            altitude: scan.altitude,
+            timestamp: scan.timestamp.map(Into::into).unwrap_or_else(Utc::now),
+            received_at: None,
+        }
+    }
+}
+
+impl From<rest_types::CargoScan> for CargoScan {
+    fn from(scan: rest_types::CargoScan) -> Self {
+        CargoScan {
+            scanner_id: scan.scanner_id,
+            parcel_id: scan.parcel_id,
+            latitude: scan.latitude,
+            longitude: scan.longitude,
+            altitude: scan.altitude,
+            timestamp: Some(scan.timestamp.into()),
+            code: scan.code.unwrap_or_default(),
+            received_at: scan.received_at.map(Into::into),
+        }
+    }
+}
+
+/// Record each scan in the stream
+/// Scans are validated and stored one at a time, in the order received. A
+///  rejected scan does not end the stream; its reason is reported in the
+///  summary returned once the client closes the stream.
+pub async fn stream_scans<S>(
+    stream: S,
+    policy: &ScanPolicy,
+    signer: &LabelSigner,
+    grpc_clients: &GrpcClients,
+) -> Result<StreamScansResponse, Status>
+where
+    S: Stream<Item = Result<CargoScan, Status>>,
+{
+    grpc_debug!("entry.");
+
+    futures::pin_mut!(stream);
+    let mut response = StreamScansResponse::default();
+    let mut index: u32 = 0;
+    while let Some(scan) = stream.next().await {
+        let scan = scan.map_err(|e| {
+            grpc_error!("scan stream error: {e}");
+            e
+        })?;
+
+        let parcel_id = scan.parcel_id.clone();
+        let result = match scan_parcel(scan.into(), policy, signer, grpc_clients).await {
+            Ok(_) => {
+                response.accepted += 1;
+                ScanResult {
+                    index,
+                    parcel_id,
+                    accepted: true,
+                    reason: String::new(),
+                }
+            }
+            Err(e) => {
+                grpc_warn!("scan {index} rejected: {e}");
+                response.rejected += 1;
+                ScanResult {
+                    index,
+                    parcel_id,
+                    accepted: false,
+                    reason: e.to_string(),
+                }
+            }
+        };
+
+        response.results.push(result);
+        index += 1;
+    }
+
+    grpc_info!(
+        "scan stream closed: {} accepted, {} rejected.",
+        response.accepted,
+        response.rejected
+    );
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_storage::register_parcel;
+
+    fn scan() -> CargoScan {
+        CargoScan {
+            scanner_id: "00000000-0000-0000-0000-000000000001".to_string(),
+            parcel_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude: 0.0,
+            timestamp: Some(Utc::now().into()),
+            code: String::new(),
+            received_at: None,
+        }
+    }
+
+    #[test]
+    fn test_cargo_scan_from() {
+        let data = scan();
+        let result = rest_types::CargoScan::from(data.clone());
+        assert_eq!(result.scanner_id, data.scanner_id);
+        assert_eq!(result.parcel_id, data.parcel_id);
+        assert_eq!(result.latitude, data.latitude);
+        assert_eq!(result.longitude, data.longitude);
+        assert_eq!(result.altitude, data.altitude);
+
+        let result = CargoScan::from(result);
+        assert_eq!(result, data);
+    }
+
+    #[tokio::test]
+    async fn test_stream_scans() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+
+        let mut at_acquisition = scan();
+        at_acquisition.parcel_id = parcel.parcel_id.clone();
+        at_acquisition.latitude = f64::from(parcel.acquisition.latitude);
+        at_acquisition.longitude = f64::from(parcel.acquisition.longitude);
+        let mut bad_id = at_acquisition.clone();
+        bad_id.parcel_id = "invalid".to_string();
+        let mut bad_location = at_acquisition.clone();
+        bad_location.latitude = 90.01;
+        let mut unregistered = scan();
+        unregistered.parcel_id = lib_common::uuid::Uuid::new_v4().to_string();
+
+        let stream = futures::stream::iter(vec![
+            Ok(at_acquisition.clone()),
+            Ok(bad_id),
+            Ok(bad_location),
+            Ok(at_acquisition),
+            Ok(unregistered),
+        ]);
+
+        // dropped off, then loaded
+        let response = stream_scans(
+            stream,
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert_eq!(response.accepted, 2);
+        assert_eq!(response.rejected, 3);
+        assert_eq!(response.results.len(), 5);
+        assert!(response.results[0].accepted);
+        assert_eq!(response.results[0].parcel_id, parcel.parcel_id);
+        assert_eq!(response.results[1].index, 1);
+        assert_eq!(response.results[1].reason, "parcel ID not in UUID format");
+        assert_eq!(response.results[2].reason, "coordinates out of range");
+        assert!(response.results[3].accepted);
+        assert_eq!(response.results[4].index, 4);
+        assert_eq!(response.results[4].reason, "parcel not registered");
+    }
+
+    #[tokio::test]
+    async fn test_stream_scans_transport_error() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let stream = futures::stream::iter(vec![Ok(scan()), Err(Status::aborted("closed"))]);
+
+        let error = stream_scans(
+            stream,
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error.code(), tonic::Code::Aborted);
+    }
+}
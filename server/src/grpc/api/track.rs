@@ -0,0 +1,42 @@
+//! Parcel tracking over gRPC
+
+use crate::grpc::client::GrpcClients;
+use crate::grpc::server::grpc_server::{CargoScan, WatchParcelRequest};
+use futures::stream::{BoxStream, StreamExt};
+use tonic::Status;
+
+/// Watch the scans of a parcel
+/// Sends the recorded scans, followed by each new scan of the parcel as it
+///  is accepted.
+pub async fn watch_parcel(
+    request: WatchParcelRequest,
+    grpc_clients: &GrpcClients,
+) -> Result<BoxStream<'static, Result<CargoScan, Status>>, Status> {
+    grpc_debug!("entry.");
+
+    let scans = crate::service::track::watch_parcel(&request.parcel_id, grpc_clients)
+        .await
+        .map_err(|e| {
+            grpc_error!("could not watch parcel: {e}");
+            Status::from(e)
+        })?;
+
+    Ok(scans.map(|scan| Ok(CargoScan::from(scan))).boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_watch_parcel_invalid_argument() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let request = WatchParcelRequest {
+            parcel_id: "invalid".to_string(),
+        };
+
+        let error = watch_parcel(request, &grpc_clients).await.err().unwrap();
+        assert_eq!(error.code(), tonic::Code::InvalidArgument);
+    }
+}
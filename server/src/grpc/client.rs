@@ -1,4 +1,5 @@
 //! gRPC client helpers implementation
+use super::contact::NotificationClient;
 use tokio::sync::OnceCell;
 
 use svc_contact_client_grpc::prelude::Client as _;
@@ -34,6 +35,8 @@ pub struct GrpcClients {
     pub pricing: PricingClient,
     /// A GrpcClient provided by the svc_contact_grpc_client module
     pub contact: ContactClient,
+    /// A client of the svc-contact parcel notifications
+    pub notifications: NotificationClient,
 }
 
 impl GrpcClients {
@@ -58,6 +61,10 @@ impl GrpcClients {
                 config.contact_port_grpc,
                 "contact",
             ),
+            notifications: NotificationClient::new(
+                &config.contact_host_grpc,
+                config.contact_port_grpc,
+            ),
         }
     }
 }
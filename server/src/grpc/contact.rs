@@ -0,0 +1,133 @@
+//! svc-contact parcel notifications
+//!
+//! svc-contact-client-grpc only exposes the booking confirmation. The
+//!  events of a parcel are sent to each addressee with the
+//!  `cargoNotification` call of svc-contact, through this client.
+
+use tonic::codegen::http;
+use tonic::transport::Endpoint;
+use tonic::{GrpcMethod, IntoRequest, Response, Status};
+
+/// The person receiving a parcel
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CargoRecipient {
+    /// The recipient's name
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    /// The recipient's email address
+    #[prost(string, optional, tag = "2")]
+    pub email: ::core::option::Option<::prost::alloc::string::String>,
+    /// The recipient's phone number
+    #[prost(string, optional, tag = "3")]
+    pub phone: ::core::option::Option<::prost::alloc::string::String>,
+}
+
+/// Cargo Notification Request object
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CargoNotificationRequest {
+    /// The parcel ID
+    #[prost(string, tag = "1")]
+    pub parcel_id: ::prost::alloc::string::String,
+    /// What happened to the parcel
+    #[prost(enumeration = "CargoEvent", tag = "2")]
+    pub event: i32,
+    /// The user who booked the parcel, if notified
+    #[prost(string, optional, tag = "3")]
+    pub user_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// The person receiving the parcel, if notified
+    #[prost(message, optional, tag = "4")]
+    pub recipient: ::core::option::Option<CargoRecipient>,
+    /// The delivery PIN issued, only sent to the user who booked the parcel
+    #[prost(string, optional, tag = "5")]
+    pub delivery_pin: ::core::option::Option<::prost::alloc::string::String>,
+}
+
+/// Cargo Notification Response object
+#[derive(Eq, Copy)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CargoNotificationResponse {
+    /// True if the notification was sent
+    #[prost(bool, tag = "1")]
+    pub sent: bool,
+}
+
+/// Parcel events notified by svc-contact
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum CargoEvent {
+    /// The parcel was dropped off at the acquisition vertiport
+    DroppedOff = 0,
+    /// The parcel was loaded on board for departure
+    Departed = 1,
+    /// The parcel arrived at the delivery vertiport
+    Arrived = 2,
+    /// The parcel was handed over to the recipient
+    Delivered = 3,
+    /// The parcel has not arrived by the end of its arrival window
+    Delayed = 4,
+    /// A delivery PIN was issued for the parcel
+    PinIssued = 5,
+}
+
+/// Client of the svc-contact parcel notifications
+#[derive(Debug, Clone)]
+pub struct NotificationClient {
+    /// The address of svc-contact
+    address: String,
+}
+
+impl NotificationClient {
+    /// Create a client of svc-contact at `host`:`port`
+    /// The connection is made for each notification.
+    pub fn new(host: &str, port: u16) -> Self {
+        NotificationClient {
+            address: format!("http://{host}:{port}"),
+        }
+    }
+
+    /// Notify an addressee of a parcel event
+    pub async fn cargo_notification(
+        &self,
+        request: impl IntoRequest<CargoNotificationRequest>,
+    ) -> Result<Response<CargoNotificationResponse>, Status> {
+        let channel = Endpoint::from_shared(self.address.clone())
+            .map_err(|e| Status::unavailable(format!("invalid svc-contact address: {e}")))?
+            .connect()
+            .await
+            .map_err(|e| Status::unavailable(format!("could not connect to svc-contact: {e}")))?;
+
+        let mut inner = tonic::client::Grpc::new(channel);
+        inner
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(format!("Service was not ready: {e}")))?;
+
+        let codec = tonic::codec::ProstCodec::default();
+        let path = http::uri::PathAndQuery::from_static("/grpc.RpcService/cargoNotification");
+        let mut req = request.into_request();
+        req.extensions_mut()
+            .insert(GrpcMethod::new("grpc.RpcService", "cargoNotification"));
+        inner.unary(req, path, codec).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cargo_notification_unavailable() {
+        let client = NotificationClient::new("localhost", 1);
+        let request = CargoNotificationRequest {
+            parcel_id: "parcel".to_string(),
+            event: CargoEvent::Arrived as i32,
+            user_id: Some("user".to_string()),
+            recipient: None,
+            delivery_pin: None,
+        };
+
+        let status = client.cargo_notification(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+    }
+}
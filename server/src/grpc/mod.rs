@@ -3,5 +3,7 @@
 
 #[macro_use]
 pub mod macros;
+pub(crate) mod api;
 pub mod client;
+pub mod contact;
 pub mod server;
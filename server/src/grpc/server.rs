@@ -5,23 +5,44 @@ pub mod grpc_server {
     tonic::include_proto!("grpc");
 }
 pub use grpc_server::rpc_service_server::{RpcService, RpcServiceServer};
-pub use grpc_server::{ReadyRequest, ReadyResponse};
+pub use grpc_server::{
+    CancelItineraryRequest, CancelItineraryResponse, CargoInfo, CargoScan, CreateItineraryRequest,
+    CreateItineraryResponse, CurrencyUnit, DraftItinerary, FlightPlan, GeoPointZ, HandlingClass,
+    InvoiceItem, Itinerary, Parcel, QueryItineraryRequest, QueryItineraryResponse, ReadyRequest,
+    ReadyResponse, RetryCompensationRequest, RetryCompensationResponse, ScanResult,
+    StreamScansResponse, TimeWindow, WatchParcelRequest,
+};
 
+use crate::service::cancel::CancelPolicy;
+use crate::service::label::LabelSigner;
+use crate::service::scan::ScanPolicy;
 use crate::shutdown_signal;
 use crate::Config;
 
+use futures::stream::BoxStream;
 use std::fmt::Debug;
 use std::net::SocketAddr;
 use tonic::transport::Server;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
 
 /// struct to implement the gRPC server functions
-#[derive(Debug, Default, Copy, Clone)]
-pub struct ServerImpl {}
+#[derive(Debug, Default, Clone)]
+pub struct ServerImpl {
+    /// Cancellation deadlines of booked itineraries
+    pub cancel_policy: CancelPolicy,
+
+    /// Where parcel scans are accepted
+    pub scan_policy: ScanPolicy,
+
+    /// Verifies the label codes of scanned parcels
+    pub label_signer: LabelSigner,
+}
 
 #[cfg(not(feature = "stub_server"))]
 #[tonic::async_trait]
 impl RpcService for ServerImpl {
+    type WatchParcelStream = BoxStream<'static, Result<CargoScan, Status>>;
+
     /// Returns ready:true when service is available
     async fn is_ready(
         &self,
@@ -33,6 +54,83 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    /// Returns a list of priced draft itineraries
+    async fn query_itinerary(
+        &self,
+        request: Request<QueryItineraryRequest>,
+    ) -> Result<Response<QueryItineraryResponse>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::query::query_itinerary(request.into_inner())
+            .await
+            .map(Response::new)
+    }
+
+    /// Books a draft itinerary
+    async fn create_itinerary(
+        &self,
+        request: Request<CreateItineraryRequest>,
+    ) -> Result<Response<CreateItineraryResponse>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::create::create_itinerary(request.into_inner())
+            .await
+            .map(Response::new)
+    }
+
+    /// Cancels a booked itinerary
+    async fn cancel_itinerary(
+        &self,
+        request: Request<CancelItineraryRequest>,
+    ) -> Result<Response<CancelItineraryResponse>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::cancel::cancel_itinerary(request.into_inner(), &self.cancel_policy)
+            .await
+            .map(Response::new)
+    }
+
+    /// Retries the failed steps undoing a failed booking
+    async fn retry_compensation(
+        &self,
+        request: Request<RetryCompensationRequest>,
+    ) -> Result<Response<RetryCompensationResponse>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::compensation::retry_compensation(request.into_inner())
+            .await
+            .map(Response::new)
+    }
+
+    /// Records a stream of parcel scans
+    async fn stream_scans(
+        &self,
+        request: Request<Streaming<CargoScan>>,
+    ) -> Result<Response<StreamScansResponse>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::scan::stream_scans(
+            request.into_inner(),
+            &self.scan_policy,
+            &self.label_signer,
+            super::client::get_clients().await,
+        )
+        .await
+        .map(Response::new)
+    }
+
+    /// Sends the scan history of a parcel, then each new scan as it is accepted
+    async fn watch_parcel(
+        &self,
+        request: Request<WatchParcelRequest>,
+    ) -> Result<Response<Self::WatchParcelStream>, Status> {
+        grpc_info!("cargo server.");
+        grpc_debug!("request: {:?}", request);
+        super::api::track::watch_parcel(request.into_inner(), super::client::get_clients().await)
+            .await
+            .map(Response::new)
+    }
 }
 
 /// Starts the grpc servers for this microservice using the provided configuration
@@ -60,7 +158,11 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
         }
     };
 
-    let imp = ServerImpl::default();
+    let imp = ServerImpl {
+        cancel_policy: CancelPolicy::from(&config),
+        scan_policy: ScanPolicy::from(&config),
+        label_signer: LabelSigner::from(&config),
+    };
     let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
     health_reporter
         .set_serving::<RpcServiceServer<ServerImpl>>()
@@ -85,6 +187,8 @@ pub async fn grpc_server(config: Config, shutdown_rx: Option<tokio::sync::onesho
 #[cfg(feature = "stub_server")]
 #[tonic::async_trait]
 impl RpcService for ServerImpl {
+    type WatchParcelStream = BoxStream<'static, Result<CargoScan, Status>>;
+
     async fn is_ready(
         &self,
         request: Request<ReadyRequest>,
@@ -94,6 +198,117 @@ impl RpcService for ServerImpl {
         let response = ReadyResponse { ready: true };
         Ok(Response::new(response))
     }
+
+    async fn query_itinerary(
+        &self,
+        request: Request<QueryItineraryRequest>,
+    ) -> Result<Response<QueryItineraryResponse>, Status> {
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let request = request.into_inner();
+        let itinerary = Itinerary {
+            cargo_weight_g: request.parcels.iter().map(|parcel| parcel.weight_g).sum(),
+            parcels: request.parcels,
+            user_id: request.user_id,
+            acquisition_vertiport_id: request.origin_vertiport_id,
+            delivery_vertiport_id: request.target_vertiport_id,
+            ..Default::default()
+        };
+
+        let response = QueryItineraryResponse {
+            itineraries: vec![DraftItinerary {
+                id: lib_common::uuid::Uuid::new_v4().to_string(),
+                itinerary: Some(itinerary),
+            }],
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn create_itinerary(
+        &self,
+        request: Request<CreateItineraryRequest>,
+    ) -> Result<Response<CreateItineraryResponse>, Status> {
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let response = CreateItineraryResponse {
+            itinerary_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            parcels: vec![CargoInfo {
+                parcel_id: lib_common::uuid::Uuid::new_v4().to_string(),
+                parcel: Some(Parcel::default()),
+            }],
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn cancel_itinerary(
+        &self,
+        request: Request<CancelItineraryRequest>,
+    ) -> Result<Response<CancelItineraryResponse>, Status> {
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let response = CancelItineraryResponse {
+            itinerary_id: request.into_inner().id,
+            refund: false,
+            refund_amount: 0.0,
+            currency_unit: CurrencyUnit::Usd as i32,
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn retry_compensation(
+        &self,
+        request: Request<RetryCompensationRequest>,
+    ) -> Result<Response<RetryCompensationResponse>, Status> {
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let response = RetryCompensationResponse {
+            itinerary_id: request.into_inner().itinerary_id,
+            complete: true,
+            pending_steps: vec![],
+        };
+        Ok(Response::new(response))
+    }
+
+    async fn stream_scans(
+        &self,
+        request: Request<Streaming<CargoScan>>,
+    ) -> Result<Response<StreamScansResponse>, Status> {
+        use futures::StreamExt;
+
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let mut stream = request.into_inner();
+        let mut response = StreamScansResponse::default();
+        while let Some(scan) = stream.next().await {
+            let scan = scan?;
+            let result = ScanResult {
+                index: response.accepted,
+                parcel_id: scan.parcel_id,
+                accepted: true,
+                reason: String::new(),
+            };
+            response.accepted += 1;
+            response.results.push(result);
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn watch_parcel(
+        &self,
+        request: Request<WatchParcelRequest>,
+    ) -> Result<Response<Self::WatchParcelStream>, Status> {
+        use futures::StreamExt;
+
+        grpc_warn!("(MOCK) cargo server.");
+        grpc_debug!("(MOCK) request: {:?}", request);
+        let scan = CargoScan {
+            parcel_id: request.into_inner().parcel_id,
+            scanner_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            timestamp: Some(lib_common::time::Utc::now().into()),
+            ..Default::default()
+        };
+        Ok(Response::new(futures::stream::iter(vec![Ok(scan)]).boxed()))
+    }
 }
 
 #[cfg(test)]
@@ -12,6 +12,7 @@ pub use clap::Parser;
 pub mod cache;
 /// rest implementation module
 pub mod rest;
+pub mod service;
 
 /// struct holding cli configuration options
 #[derive(Parser, Debug, Clone)]
@@ -30,10 +30,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     // REST Server
-    tokio::spawn(rest_server(config.clone(), None));
+    let rest = tokio::spawn(rest_server(config.clone(), None));
 
     // GRPC Server
-    tokio::spawn(grpc_server(config, None)).await?;
+    let grpc = tokio::spawn(grpc_server(config, None));
+
+    // Parcel delay checks, scheduled at booking
+    tokio::spawn(service::notify::watch_delays());
+
+    // The service does not run without its REST API, e.g. without a key set
+    if rest.await?.is_err() {
+        return Err("Failed to start REST server".into());
+    }
+
+    grpc.await?;
 
     info!("(main) Server shutdown.");
 
@@ -1,20 +1,21 @@
-use super::rest_types::ItineraryCancelRequest;
+use super::rest_types::{ItineraryCancelRequest, ItineraryCancelResponse};
 use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
+use crate::service::cancel::CancelPolicy;
 use axum::{extract::Extension, Json};
 use hyper::StatusCode;
-use lib_common::uuid::to_uuid;
-use svc_scheduler_client_grpc::prelude::scheduler_storage::flight_plan::FlightPriority;
-use svc_scheduler_client_grpc::prelude::SchedulerServiceClient;
-use svc_storage_client_grpc::prelude::*;
 
 /// Cancel a Flight
 #[utoipa::path(
     delete,
     path = "/cargo/cancel",
     tag = "svc-cargo",
+    security(("bearer" = [])),
     responses(
-        (status = 200, description = "Flight cancelled successfully"),
-        (status = 400, description = "Request body is invalid format"),
+        (status = 200, description = "Flight cancelled successfully", body = ItineraryCancelResponse),
+        (status = 400, description = "Request body is invalid format, the itinerary is not active or departs too soon to cancel"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "Itinerary not found"),
         (status = 500, description = "svc-scheduler returned error"),
         (status = 503, description = "Could not connect to other microservice dependencies")
     ),
@@ -22,83 +23,30 @@ use svc_storage_client_grpc::prelude::*;
 )]
 pub async fn cancel_itinerary(
     Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Extension(policy): Extension<CancelPolicy>,
     Json(payload): Json<ItineraryCancelRequest>,
-) -> Result<(), StatusCode> {
+) -> Result<Json<ItineraryCancelResponse>, StatusCode> {
     rest_debug!("entry.");
 
-    to_uuid(&payload.id).ok_or_else(|| {
-        rest_error!("itinerary ID not in UUID format.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    to_uuid(&payload.user_id).ok_or_else(|| {
-        rest_error!("user ID not in UUID format.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    // Make request, process response
-    grpc_clients
-        .scheduler
-        .cancel_itinerary(svc_scheduler_client_grpc::client::CancelItineraryRequest {
-            priority: FlightPriority::Medium as i32,
-            itinerary_id: payload.id.clone(),
-            user_id: payload.user_id,
-        })
-        .await
-        .map_err(|e| {
-            rest_error!("svc-scheduler request fail. {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?;
-
-    rest_info!("cancellation added to scheduler queue.");
-
-    //
-    // Get parcel from id
-    //
-    let filter =
-        AdvancedSearchFilter::search_equals("itinerary_id".to_string(), payload.id.clone());
-
-    let futures = grpc_clients
-        .storage
-        .parcel
-        .search(filter)
-        .await
-        .map_err(|e| {
-            rest_error!("svc-parcel-storage error {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .into_inner()
-        .list
-        .into_iter()
-        .map(|parcel| async {
-            grpc_clients
-                .storage
-                .parcel
-                .delete(Id { id: parcel.id })
-                .await
-                .map_err(|e| {
-                    rest_error!("svc-storage error: {:?}", e);
-                })
-        })
-        .collect::<Vec<_>>();
-
-    {
-        if !futures::future::join_all(futures)
-            .await
-            .into_iter()
-            .all(|r| r.is_ok())
-        {
-            rest_error!("could not delete all parcels.");
-        }
-    }
-
-    // If the customer's itinerary was cancelled, but the parcels were not, it's still a success for them
-    Ok(())
+    crate::service::cancel::cancel_itinerary(
+        &payload.id,
+        &principal.user_id,
+        &policy,
+        &grpc_clients,
+    )
+    .await
+    .map(Json)
+    .map_err(|e| {
+        rest_error!("could not cancel itinerary: {e}");
+        StatusCode::from(e)
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rest::auth::tests::principal;
 
     #[tokio::test]
     async fn test_cancel_itinerary() {
@@ -108,32 +56,51 @@ mod tests {
         // invalid itinerary UUID
         let payload = ItineraryCancelRequest {
             id: "".to_string(),
-            user_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            user_id: String::new(),
         };
 
-        let result = cancel_itinerary(Extension(grpc_clients.clone()), Json(payload))
-            .await
-            .unwrap_err();
+        let result = cancel_itinerary(
+            Extension(grpc_clients.clone()),
+            Extension(principal()),
+            Extension(CancelPolicy::default()),
+            Json(payload),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(result, StatusCode::BAD_REQUEST);
 
-        // invalid user UUID
+        // invalid user UUID in the principal, body user ID is ignored
         let payload = ItineraryCancelRequest {
             id: "00000000-0000-0000-0000-000000000000".to_string(),
-            user_id: "".to_string(),
+            user_id: "00000000-0000-0000-0000-000000000000".to_string(),
         };
 
-        let result = cancel_itinerary(Extension(grpc_clients.clone()), Json(payload))
-            .await
-            .unwrap_err();
+        let result = cancel_itinerary(
+            Extension(grpc_clients.clone()),
+            Extension(Principal {
+                user_id: "".to_string(),
+            }),
+            Extension(CancelPolicy::default()),
+            Json(payload),
+        )
+        .await
+        .unwrap_err();
         assert_eq!(result, StatusCode::BAD_REQUEST);
 
+        // unknown itinerary
         let payload = ItineraryCancelRequest {
             id: "00000000-0000-0000-0000-000000000000".to_string(),
-            user_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            user_id: String::new(),
         };
 
-        cancel_itinerary(Extension(grpc_clients), Json(payload))
-            .await
-            .unwrap();
+        let result = cancel_itinerary(
+            Extension(grpc_clients),
+            Extension(principal()),
+            Extension(CancelPolicy::default()),
+            Json(payload),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result, StatusCode::NOT_FOUND);
     }
 }
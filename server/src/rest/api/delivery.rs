@@ -0,0 +1,72 @@
+pub use super::rest_types::DeliveryPin;
+use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
+use axum::extract::{Extension, Path};
+use axum::Json;
+use hyper::StatusCode;
+
+/// Issue a delivery PIN for a parcel of the user
+/// The PIN is shown once and replaces any PIN issued before; the recipient
+///  gives it when the parcel is delivered.
+#[utoipa::path(
+    post,
+    path = "/cargo/parcel/{id}/pin",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Parcel id"),
+    ),
+    responses(
+        (status = 200, description = "Delivery PIN issued", body = DeliveryPin),
+        (status = 400, description = "Invalid parcel ID or parcel already delivered"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No parcel with this ID for the user"),
+        (status = 500, description = "Dependencies returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+pub async fn issue_delivery_pin(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Path(parcel_id): Path<String>,
+) -> Result<Json<DeliveryPin>, StatusCode> {
+    rest_debug!("entry.");
+
+    crate::service::delivery::issue_delivery_pin(&parcel_id, &principal.user_id, &grpc_clients)
+        .await
+        .map(Json)
+        .map_err(|e| {
+            rest_error!("could not issue delivery PIN of parcel {parcel_id}: {e}");
+            StatusCode::from(e)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::auth::tests::principal;
+
+    #[tokio::test]
+    async fn test_issue_delivery_pin_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = issue_delivery_pin(
+            Extension(grpc_clients.clone()),
+            Extension(principal()),
+            Path("invalid".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let error = issue_delivery_pin(
+            Extension(grpc_clients),
+            Extension(principal()),
+            Path(lib_common::uuid::Uuid::new_v4().to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,104 @@
+pub use super::rest_types::DraftItineraryDetails;
+use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
+use axum::extract::{Extension, Path};
+use axum::Json;
+use hyper::StatusCode;
+
+/// Get a draft itinerary of the user
+/// Returns the draft and the number of seconds left to book it.
+#[utoipa::path(
+    get,
+    path = "/cargo/draft/{id}",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Draft itinerary id"),
+    ),
+    responses(
+        (status = 200, description = "Draft retrieved successfully", body = DraftItineraryDetails),
+        (status = 400, description = "Invalid draft ID"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No draft with this ID for the user, or the draft expired"),
+        (status = 500, description = "Cache returned error"),
+        (status = 503, description = "Could not connect to the cache")
+    )
+)]
+pub async fn get_draft(
+    Extension(principal): Extension<Principal>,
+    Path(draft_id): Path<String>,
+) -> Result<Json<DraftItineraryDetails>, StatusCode> {
+    rest_debug!("entry.");
+
+    let details = crate::service::draft::get_draft(&draft_id, &principal.user_id)
+        .await
+        .map_err(|e| {
+            rest_error!("could not get draft {draft_id}: {e}");
+            StatusCode::from(e)
+        })?;
+
+    Ok(Json(details))
+}
+
+/// Refresh a draft itinerary of the user
+/// Checks that the flights of the draft are still available, prices them
+///  again and resets the expiry of the draft.
+#[utoipa::path(
+    post,
+    path = "/cargo/draft/{id}/refresh",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Draft itinerary id"),
+    ),
+    responses(
+        (status = 200, description = "Draft refreshed", body = DraftItineraryDetails),
+        (status = 304, description = "The flights of the draft are no longer available"),
+        (status = 400, description = "Invalid draft ID or the draft departs too soon"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No draft with this ID for the user, or the draft expired"),
+        (status = 500, description = "svc-scheduler or svc-pricing returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn refresh_draft(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Path(draft_id): Path<String>,
+) -> Result<Json<DraftItineraryDetails>, StatusCode> {
+    rest_debug!("entry.");
+
+    let details =
+        crate::service::draft::refresh_draft(&draft_id, &principal.user_id, &grpc_clients)
+            .await
+            .map_err(|e| {
+                rest_error!("could not refresh draft {draft_id}: {e}");
+                StatusCode::from(e)
+            })?;
+
+    Ok(Json(details))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::auth::tests::principal;
+
+    #[tokio::test]
+    async fn test_get_draft() {
+        let error = get_draft(Extension(principal()), Path("invalid".to_string()))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let error = get_draft(
+            Extension(principal()),
+            Path(lib_common::uuid::Uuid::new_v4().to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,238 @@
+pub use super::rest_types::{
+    DraftItinerary, ItineraryDetails, ItineraryModifyConfirmRequest, ItineraryModifyRequest,
+    ItineraryModifyResponse, QueryItinerariesRequest, QueryItinerariesResponse,
+};
+use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
+use axum::extract::{Extension, Path, Query};
+use axum::Json;
+use hyper::StatusCode;
+
+/// List the booked itineraries of the user
+/// Itineraries are returned latest booking first, with their flight
+///  plans and parcels.
+#[utoipa::path(
+    get,
+    path = "/cargo/itineraries",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(QueryItinerariesRequest),
+    responses(
+        (status = 200, description = "Itineraries retrieved successfully", body = QueryItinerariesResponse),
+        (status = 400, description = "Invalid filters or paging"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Dependencies returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+pub async fn query_itineraries(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Query(request): Query<QueryItinerariesRequest>,
+) -> Result<Json<QueryItinerariesResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let response =
+        crate::service::itinerary::list_itineraries(&principal.user_id, &request, &grpc_clients)
+            .await
+            .map_err(|e| {
+                rest_error!("could not list itineraries: {e}");
+                StatusCode::from(e)
+            })?;
+
+    rest_info!("found {} itineraries.", response.total);
+    Ok(Json(response))
+}
+
+/// Get a booked itinerary of the user
+/// Returns the confirmed flight plans, the acquisition and delivery
+///  vertiports, and each parcel with its latest scan.
+#[utoipa::path(
+    get,
+    path = "/cargo/itinerary/{id}",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Itinerary id"),
+    ),
+    responses(
+        (status = 200, description = "Itinerary retrieved successfully", body = ItineraryDetails),
+        (status = 400, description = "Invalid itinerary ID"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No itinerary with this ID for the user"),
+        (status = 500, description = "Dependencies returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+pub async fn get_itinerary(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Path(itinerary_id): Path<String>,
+) -> Result<Json<ItineraryDetails>, StatusCode> {
+    rest_debug!("entry.");
+
+    let details =
+        crate::service::itinerary::get_itinerary(&itinerary_id, &principal.user_id, &grpc_clients)
+            .await
+            .map_err(|e| {
+                rest_error!("could not get itinerary {itinerary_id}: {e}");
+                StatusCode::from(e)
+            })?;
+
+    Ok(Json(details))
+}
+
+/// Get replacements for a booked itinerary of the user
+/// Searches for itineraries on the same route with a new departure window
+///  and/or cargo weight. The priced replacements are returned as drafts to
+///  confirm with `PUT /cargo/itinerary/{id}/modify`.
+#[utoipa::path(
+    post,
+    path = "/cargo/itinerary/{id}/modify",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Itinerary id"),
+    ),
+    request_body = ItineraryModifyRequest,
+    responses(
+        (status = 200, description = "List available replacements", body = [DraftItinerary]),
+        (status = 400, description = "Invalid request or the itinerary can no longer be modified"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No itinerary with this ID for the user"),
+        (status = 500, description = "svc-scheduler or svc-pricing returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn query_modifications(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Path(itinerary_id): Path<String>,
+    Json(payload): Json<ItineraryModifyRequest>,
+) -> Result<Json<Vec<DraftItinerary>>, StatusCode> {
+    rest_debug!("entry.");
+
+    let drafts = crate::service::modify::query_modifications(
+        &itinerary_id,
+        &principal.user_id,
+        &payload,
+        &grpc_clients,
+    )
+    .await
+    .map_err(|e| {
+        rest_error!("could not get replacements for itinerary {itinerary_id}: {e}");
+        StatusCode::from(e)
+    })?;
+
+    rest_debug!("exit with {} replacements.", drafts.len());
+    Ok(Json(drafts))
+}
+
+/// Replace a booked itinerary of the user
+/// Books a draft returned by `POST /cargo/itinerary/{id}/modify` and moves
+///  the parcels to it before cancelling the original itinerary. The
+///  original itinerary is kept if the replacement can't be completed.
+#[utoipa::path(
+    put,
+    path = "/cargo/itinerary/{id}/modify",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Itinerary id"),
+    ),
+    request_body = ItineraryModifyConfirmRequest,
+    responses(
+        (status = 200, description = "Itinerary replaced", body = ItineraryModifyResponse),
+        (status = 400, description = "Invalid request or the itinerary can no longer be modified"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No itinerary with this ID for the user"),
+        (status = 500, description = "Microservice dependency returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+pub async fn modify_itinerary(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Path(itinerary_id): Path<String>,
+    Json(payload): Json<ItineraryModifyConfirmRequest>,
+) -> Result<Json<ItineraryModifyResponse>, StatusCode> {
+    rest_debug!("entry.");
+
+    let new_itinerary_id = crate::service::modify::modify_itinerary(
+        &itinerary_id,
+        &payload.draft_id,
+        &principal.user_id,
+        &grpc_clients,
+    )
+    .await
+    .map_err(|e| {
+        rest_error!("could not modify itinerary {itinerary_id}: {e}");
+        StatusCode::from(e)
+    })?;
+
+    rest_info!("itinerary {itinerary_id} replaced by {new_itinerary_id}.");
+    Ok(Json(ItineraryModifyResponse {
+        itinerary_id: new_itinerary_id,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::auth::tests::principal;
+
+    #[tokio::test]
+    async fn test_query_itineraries_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let request = QueryItinerariesRequest {
+            per_page: Some(0),
+            ..Default::default()
+        };
+
+        let error = query_itineraries(
+            Extension(grpc_clients),
+            Extension(principal()),
+            Query(request),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_get_itinerary_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = get_itinerary(
+            Extension(grpc_clients),
+            Extension(principal()),
+            Path("invalid".to_string()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_modify_itinerary_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = modify_itinerary(
+            Extension(grpc_clients),
+            Extension(principal()),
+            Path("invalid".to_string()),
+            Json(ItineraryModifyConfirmRequest {
+                draft_id: lib_common::uuid::Uuid::new_v4().to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+    }
+}
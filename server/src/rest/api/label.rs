@@ -0,0 +1,100 @@
+pub use super::rest_types::{LabelFormat, LabelQuery, ParcelLabel};
+use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
+use crate::service::label::{render_zpl, LabelSigner};
+use axum::extract::{Extension, Path, Query};
+use axum::http::header;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use hyper::StatusCode;
+
+/// Content type of labels for Zebra printers
+const ZPL_CONTENT_TYPE: &str = "application/zpl";
+
+/// Get the shipping label of a parcel of the user
+/// The label code is signed; scanners may send it instead of the parcel ID.
+///  Labels are returned as JSON, or rendered for Zebra printers with the
+///  code as a QR code.
+#[utoipa::path(
+    get,
+    path = "/cargo/parcel/{id}/label",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    params(
+        ("id" = String, Path, description = "Parcel id"),
+        LabelQuery,
+    ),
+    responses(
+        (status = 200, description = "Label retrieved successfully", body = ParcelLabel, content_type = ["application/json", "application/zpl"]),
+        (status = 400, description = "Invalid parcel ID"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 404, description = "No parcel with this ID for the user"),
+        (status = 500, description = "Dependencies returned error"),
+        (status = 503, description = "No label signing key configured or could not connect to other microservice dependencies")
+    )
+)]
+pub async fn get_parcel_label(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(signer): Extension<LabelSigner>,
+    Extension(principal): Extension<Principal>,
+    Path(parcel_id): Path<String>,
+    Query(query): Query<LabelQuery>,
+) -> Result<Response, StatusCode> {
+    rest_debug!("entry.");
+
+    let label =
+        crate::service::label::get_label(&parcel_id, &principal.user_id, &signer, &grpc_clients)
+            .await
+            .map_err(|e| {
+                rest_error!("could not get label of parcel {parcel_id}: {e}");
+                StatusCode::from(e)
+            })?;
+
+    let response = match query.format.unwrap_or(LabelFormat::Json) {
+        LabelFormat::Json => Json(label).into_response(),
+        LabelFormat::Zpl => (
+            [(header::CONTENT_TYPE, ZPL_CONTENT_TYPE)],
+            render_zpl(&label),
+        )
+            .into_response(),
+    };
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::auth::tests::principal;
+
+    #[tokio::test]
+    async fn test_get_parcel_label_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let signer = LabelSigner::new(Some("label secret"));
+
+        let error = get_parcel_label(
+            Extension(grpc_clients.clone()),
+            Extension(signer.clone()),
+            Extension(principal()),
+            Path("invalid".to_string()),
+            Query(LabelQuery::default()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::BAD_REQUEST);
+
+        let error = get_parcel_label(
+            Extension(grpc_clients),
+            Extension(signer),
+            Extension(principal()),
+            Path(lib_common::uuid::Uuid::new_v4().to_string()),
+            Query(LabelQuery {
+                format: Some(LabelFormat::Zpl),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, StatusCode::NOT_FOUND);
+    }
+}
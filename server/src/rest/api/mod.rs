@@ -4,8 +4,63 @@ pub mod rest_types {
 }
 pub mod cancel;
 pub mod create;
+pub mod delivery;
+pub mod draft;
 pub mod health;
+pub mod itinerary;
+pub mod label;
+pub mod notification;
 pub mod query;
 pub mod request;
 pub mod scan;
 pub mod utils;
+pub mod vehicle;
+
+use crate::service::ServiceError;
+use hyper::StatusCode;
+
+impl From<ServiceError> for StatusCode {
+    fn from(error: ServiceError) -> Self {
+        match error {
+            ServiceError::InvalidArgument => StatusCode::BAD_REQUEST,
+            ServiceError::NotFound => StatusCode::NOT_FOUND,
+            ServiceError::Rejected => StatusCode::NOT_MODIFIED,
+            ServiceError::Timeout => StatusCode::REQUEST_TIMEOUT,
+            ServiceError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ServiceError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_code_from_service_error() {
+        assert_eq!(
+            StatusCode::from(ServiceError::InvalidArgument),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(
+            StatusCode::from(ServiceError::NotFound),
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(
+            StatusCode::from(ServiceError::Rejected),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            StatusCode::from(ServiceError::Timeout),
+            StatusCode::REQUEST_TIMEOUT
+        );
+        assert_eq!(
+            StatusCode::from(ServiceError::Unavailable),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            StatusCode::from(ServiceError::Internal),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
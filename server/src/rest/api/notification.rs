@@ -0,0 +1,97 @@
+pub use super::rest_types::NotificationSettings;
+use crate::rest::auth::Principal;
+use axum::extract::Extension;
+use axum::Json;
+use hyper::StatusCode;
+
+/// Get the parcel notification settings of the user
+/// Users who never changed their settings are notified of every event, and
+///  so are the recipients of their parcels.
+#[utoipa::path(
+    get,
+    path = "/cargo/notifications",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "Settings retrieved successfully", body = NotificationSettings),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Cache returned error"),
+        (status = 503, description = "Could not connect to the cache")
+    )
+)]
+pub async fn get_notification_settings(
+    Extension(principal): Extension<Principal>,
+) -> Result<Json<NotificationSettings>, StatusCode> {
+    rest_debug!("entry.");
+
+    let settings = crate::service::notify::get_settings(&principal.user_id)
+        .await
+        .map_err(|e| {
+            rest_error!("could not get notification settings: {e}");
+            StatusCode::from(e)
+        })?;
+
+    Ok(Json(settings))
+}
+
+/// Replace the parcel notification settings of the user
+/// Users opt out of notifications for themselves, for the recipients of
+///  their parcels, or both.
+#[utoipa::path(
+    put,
+    path = "/cargo/notifications",
+    tag = "svc-cargo",
+    security(("bearer" = [])),
+    request_body = NotificationSettings,
+    responses(
+        (status = 200, description = "Settings updated", body = NotificationSettings),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "Cache returned error"),
+        (status = 503, description = "Could not connect to the cache")
+    )
+)]
+pub async fn update_notification_settings(
+    Extension(principal): Extension<Principal>,
+    Json(payload): Json<NotificationSettings>,
+) -> Result<Json<NotificationSettings>, StatusCode> {
+    rest_debug!("entry.");
+
+    let settings = crate::service::notify::update_settings(&principal.user_id, payload)
+        .await
+        .map_err(|e| {
+            rest_error!("could not update notification settings: {e}");
+            StatusCode::from(e)
+        })?;
+
+    Ok(Json(settings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::auth::tests::principal;
+
+    #[tokio::test]
+    async fn test_notification_settings() {
+        let principal = principal();
+        let Json(settings) = get_notification_settings(Extension(principal.clone()))
+            .await
+            .unwrap();
+        assert_eq!(settings, NotificationSettings::default());
+
+        let opt_out = NotificationSettings {
+            sender: false,
+            recipient: true,
+        };
+        let Json(settings) =
+            update_notification_settings(Extension(principal.clone()), Json(opt_out))
+                .await
+                .unwrap();
+        assert_eq!(settings, opt_out);
+
+        let Json(settings) = get_notification_settings(Extension(principal))
+            .await
+            .unwrap();
+        assert_eq!(settings, opt_out);
+    }
+}
@@ -3,7 +3,9 @@ use super::rest_types::{
     QueryVertiportsRequest, TimeWindow, Vertiport, MAX_LANDINGS_TO_RETURN,
 };
 use crate::grpc::client::GrpcClients;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::{extract::Path, Extension, Json};
+use futures::{Stream, StreamExt};
 use hyper::StatusCode;
 use lib_common::uuid::{to_uuid, Uuid};
 use std::fmt::{self, Display, Formatter};
@@ -40,11 +42,13 @@ impl TryFrom<parcel_scan::Object> for CargoScan {
 
         Ok(CargoScan {
             parcel_id: obj.id,
+            code: None,
             scanner_id: data.scanner_id,
             latitude: geo_location.y,
             longitude: geo_location.x,
             altitude: geo_location.z,
             timestamp: created_at.into(),
+            received_at: None,
         })
     }
 }
@@ -84,6 +88,7 @@ impl TryFrom<vertiport::Object> for Vertiport {
             label: data.name,
             latitude: latitude as f32,
             longitude: longitude as f32,
+            boundary: points,
         })
     }
 }
@@ -310,12 +315,26 @@ pub async fn query_occupations(
                     None
                 }
             };
+
+        // Handling requirements come with the parcel descriptions
+        match crate::service::parcel::get_flight_plan_cargo(
+            &occupation.flight_plan_id,
+            &grpc_clients,
+        )
+        .await
+        {
+            Ok((acquire, deliver)) => {
+                occupation.cargo_acquire = acquire;
+                occupation.cargo_deliver = deliver;
+            }
+            Err(e) => rest_warn!("couldn't get parcels of occupation: {e}"),
+        }
     }
 
     Ok(Json(QueryScheduleResponse { occupations }))
 }
 
-/// Request a list of scans for a parcel.
+/// Request the status and list of scans for a parcel.
 #[utoipa::path(
     get,
     path = "/cargo/track/{id}",
@@ -323,6 +342,7 @@ pub async fn query_occupations(
     responses(
         (status = 200, description = "Parcel scans retrieved successfully", body = QueryParcelResponse),
         (status = 400, description = "Request body is invalid format"),
+        (status = 404, description = "Parcel not found"),
         (status = 500, description = "Dependencies returned error"),
         (status = 503, description = "Could not connect to other microservice dependencies")
     ),
@@ -337,43 +357,56 @@ pub async fn query_scans(
     Path(parcel_id): Path<String>,
 ) -> Result<Json<QueryParcelResponse>, StatusCode> {
     rest_info!("entry.");
-    to_uuid(&parcel_id).ok_or_else(|| {
-        rest_error!("parcel ID not in UUID format.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    //
-    // Request parcel scans
-    //
-    let mut filter =
-        AdvancedSearchFilter::search_equals("parcel_id".to_string(), parcel_id.clone());
+    let response = crate::service::track::track_parcel(&parcel_id, &grpc_clients)
+        .await
+        .map_err(|e| {
+            rest_error!("could not get parcel scans: {e}");
+            StatusCode::from(e)
+        })?;
 
-    filter.order_by = vec![SortOption {
-        sort_field: "created_at".to_string(),
-        sort_order: SortOrder::Asc as i32,
-    }];
+    Ok(Json(response))
+}
 
-    let scans = grpc_clients
-        .storage
-        .parcel_scan
-        .search(filter)
+/// Watch the scans of a parcel.
+/// Sends the recorded scans as Server-Sent Events, followed by each new
+///  scan of the parcel as it is accepted.
+#[utoipa::path(
+    get,
+    path = "/cargo/track/{id}/watch",
+    tag = "svc-cargo",
+    responses(
+        (status = 200, description = "Stream of parcel scans", body = CargoScan, content_type = "text/event-stream"),
+        (status = 400, description = "Request body is invalid format"),
+        (status = 404, description = "Parcel scans could not be retrieved"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    ),
+    params(
+        ("id" = String, Path, description = "Parcel id"),
+    )
+)]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) long-lived stream, tested in service::track
+pub async fn watch_parcel(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Path(parcel_id): Path<String>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, StatusCode> {
+    rest_info!("entry.");
+    let scans = crate::service::track::watch_parcel(&parcel_id, &grpc_clients)
         .await
         .map_err(|e| {
-            rest_error!("svc-storage error {:?}", e);
-            StatusCode::NOT_FOUND
-        })?
-        .into_inner()
-        .list
-        .into_iter()
-        .filter_map(|scan| CargoScan::try_from(scan).ok())
-        .collect::<Vec<CargoScan>>();
+            rest_error!("could not watch parcel: {e}");
+            StatusCode::from(e)
+        })?;
 
-    Ok(Json(QueryParcelResponse { scans }))
+    let events = scans.map(|scan| Event::default().event("scan").json_data(scan));
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rest::api::rest_types::ParcelStatus;
+    use crate::test_util::test_storage::register_parcel;
     use lib_common::time::Utc;
     use lib_common::uuid::to_uuid;
     use svc_storage_client_grpc::prelude::GeoPolygonZ;
@@ -575,11 +608,20 @@ mod tests {
             .unwrap_err();
         assert_eq!(result, StatusCode::BAD_REQUEST);
 
-        // valid
+        // not registered
         let parcel_id = Uuid::new_v4().to_string();
-        let _ = query_scans(Extension(grpc_clients.clone()), Path(parcel_id.clone()))
+        let result = query_scans(Extension(grpc_clients.clone()), Path(parcel_id))
+            .await
+            .unwrap_err();
+        assert_eq!(result, StatusCode::NOT_FOUND);
+
+        // valid
+        let parcel = register_parcel(&grpc_clients).await;
+        let response = query_scans(Extension(grpc_clients.clone()), Path(parcel.parcel_id))
             .await
             .unwrap();
+        assert_eq!(response.status, ParcelStatus::NotDroppedOff);
+        assert!(response.scans.is_empty());
     }
 
     #[test]
@@ -1,12 +1,17 @@
 pub use super::rest_types::{
-    DraftItinerary, FlightPlan, InvoiceItem, Itinerary, QueryItineraryRequest,
+    DraftItinerary, FlightPlan, HandlingClass, InvoiceItem, Itinerary, Parcel,
+    QueryItineraryRequest,
 };
+use super::vehicle::{filter_by_capacity, get_fleet, Fleet};
+use crate::config::{Config, HandlingSurcharge};
 use crate::grpc::client::GrpcClients;
+use crate::rest::auth::Principal;
 use axum::{extract::Extension, Json};
 use hyper::StatusCode;
 use lib_common::time::{DateTime, Duration, Utc};
 use lib_common::uuid::{to_uuid, Uuid};
 use std::fmt::{self, Display, Formatter};
+use tokio::sync::OnceCell;
 
 //
 // Other Service Dependencies
@@ -28,6 +33,25 @@ const MAX_CARGO_WEIGHT_G: u32 = 1_000_000; // 1000 kg
 /// Advance notice required
 const ADVANCE_NOTICE_MINUTES: i64 = 5;
 
+/// Currency itineraries are priced in
+pub(crate) const PRICING_CURRENCY: CurrencyUnit = CurrencyUnit::Euro;
+
+static HANDLING_SURCHARGE: OnceCell<HandlingSurcharge> = OnceCell::const_new();
+
+/// Returns HANDLING_SURCHARGE, the handling surcharges configured with
+///  environment variables.
+/// Initializes HANDLING_SURCHARGE if it hasn't been initialized yet.
+/// TODO(R5): Get surcharges from svc-pricing once it prices handling
+async fn get_handling_surcharge() -> &'static HandlingSurcharge {
+    HANDLING_SURCHARGE
+        .get_or_init(|| async move {
+            // TODO(R5): don't default
+            let config = Config::try_from_env().unwrap_or_default();
+            config.handling_surcharge
+        })
+        .await
+}
+
 /// Max window to search within
 /// TODO(R5): for the demo
 const MAX_TIME_WINDOW_HOURS: i64 = 8;
@@ -37,6 +61,16 @@ enum ValidationError {
     /// The weight is too high or too low
     Weight,
 
+    /// No parcels, or a parcel without weight or with partial dimensions
+    Parcels,
+
+    /// No vehicle model can carry the parcels
+    Vehicle,
+
+    /// A recipient without a name, or without an email address or phone
+    ///  number to notify
+    Recipient,
+
     /// The minimum time is invalid
     TimeWindowMin,
 
@@ -60,6 +94,9 @@ impl Display for ValidationError {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
             ValidationError::Weight => write!(f, "invalid weight"),
+            ValidationError::Parcels => write!(f, "invalid parcels"),
+            ValidationError::Vehicle => write!(f, "no vehicle can carry the parcels"),
+            ValidationError::Recipient => write!(f, "invalid recipient"),
             ValidationError::TimeWindowMin => write!(f, "invalid time window min"),
             ValidationError::TimeWindowMax => write!(f, "invalid time window max"),
             ValidationError::OriginVertiportId => write!(f, "invalid origin vertiport id"),
@@ -70,15 +107,55 @@ impl Display for ValidationError {
     }
 }
 
-/// Confirms that a payload has valid fields
-fn validate_payload(payload: &QueryItineraryRequest) -> Result<(), ValidationError> {
+/// Confirms that a payload has valid fields and can be carried by
+///  a vehicle of the fleet
+fn validate_payload(payload: &QueryItineraryRequest, fleet: &Fleet) -> Result<(), ValidationError> {
+    if payload.parcels.is_empty() {
+        rest_error!("request has no parcels.");
+        return Err(ValidationError::Parcels);
+    }
+
+    if payload.cargo_weight_g.is_some() {
+        rest_error!("request has both parcels and a cargo weight.");
+        return Err(ValidationError::Parcels);
+    }
+
+    // Dimensions are either all given or all unknown
+    if payload.parcels.iter().any(|parcel| {
+        let dimensions = [parcel.length_mm, parcel.width_mm, parcel.height_mm];
+        parcel.weight_g == 0 || (dimensions.contains(&0) && dimensions.iter().any(|&d| d != 0))
+    }) {
+        rest_error!("request has parcels without weight or with partial dimensions.");
+        return Err(ValidationError::Parcels);
+    }
+
+    if payload
+        .parcels
+        .iter()
+        .filter_map(|parcel| parcel.recipient.as_ref())
+        .any(|recipient| {
+            let given =
+                |field: &Option<String>| field.as_ref().is_some_and(|f| !f.trim().is_empty());
+            recipient.name.trim().is_empty()
+                || !(given(&recipient.email) || given(&recipient.phone))
+        })
+    {
+        rest_error!("request has recipients without a name or contact details.");
+        return Err(ValidationError::Recipient);
+    }
+
     // Reject extreme weights
-    if payload.cargo_weight_g > MAX_CARGO_WEIGHT_G {
+    if payload.cargo_weight_g() > MAX_CARGO_WEIGHT_G {
         let error_msg = format!("request cargo weight exceeds {MAX_CARGO_WEIGHT_G}.");
         rest_error!("{}", &error_msg);
         return Err(ValidationError::Weight);
     }
 
+    if !fleet.can_carry(&payload.parcels) {
+        rest_error!("no vehicle model can carry the parcels.");
+        return Err(ValidationError::Vehicle);
+    }
+
     let time_window: &TimeWindow = &payload.time_depart_window;
     if time_window.timestamp_min >= time_window.timestamp_max {
         rest_error!("invalid departure time window.");
@@ -126,13 +203,13 @@ fn validate_payload(payload: &QueryItineraryRequest) -> Result<(), ValidationErr
 }
 
 /// Query the scheduler for flight plans
-async fn scheduler_query(
+pub(crate) async fn scheduler_query(
     payload: &QueryItineraryRequest,
     grpc_clients: &mut GrpcClients,
 ) -> Result<Vec<SchedulerItinerary>, StatusCode> {
     //
     // Validate Request
-    validate_payload(payload).map_err(|e| {
+    validate_payload(payload, get_fleet().await).map_err(|e| {
         rest_error!("invalid request: {:?}", e);
         StatusCode::BAD_REQUEST
     })?;
@@ -140,7 +217,7 @@ async fn scheduler_query(
     let mut flight_query = QueryFlightRequest {
         is_cargo: true,
         persons: None,
-        weight_grams: Some(payload.cargo_weight_g),
+        weight_grams: Some(payload.cargo_weight_g()),
         origin_vertiport_id: payload.origin_vertiport_id.clone(),
         target_vertiport_id: payload.target_vertiport_id.clone(),
         earliest_departure_time: None,
@@ -182,7 +259,7 @@ async fn scheduler_query(
 
 /// Unpacks flight plans from the scheduler into a format that
 ///  can be returned to the customer
-fn unpack_itineraries(itineraries: Vec<SchedulerItinerary>) -> Vec<Itinerary> {
+pub(crate) fn unpack_itineraries(itineraries: Vec<SchedulerItinerary>) -> Vec<Itinerary> {
     itineraries
         .into_iter()
         .filter_map(|itinerary| {
@@ -197,7 +274,7 @@ fn unpack_itineraries(itineraries: Vec<SchedulerItinerary>) -> Vec<Itinerary> {
                 .map(|plans| Itinerary {
                     flight_plans: plans,
                     invoice: vec![],
-                    currency_unit: CurrencyUnit::Euro,
+                    currency_unit: PRICING_CURRENCY,
                     ..Default::default()
                 })
                 .ok()
@@ -205,10 +282,33 @@ fn unpack_itineraries(itineraries: Vec<SchedulerItinerary>) -> Vec<Itinerary> {
         .collect::<Vec<Itinerary>>()
 }
 
+/// Invoice lines for the special handling of the parcels, one line per
+///  handling class
+fn handling_invoice(parcels: &[Parcel], surcharge: &HandlingSurcharge) -> Vec<InvoiceItem> {
+    [
+        HandlingClass::Fragile,
+        HandlingClass::Refrigerated,
+        HandlingClass::Hazmat,
+    ]
+    .into_iter()
+    .filter_map(|class| {
+        let count = parcels
+            .iter()
+            .filter(|parcel| parcel.handling.contains(&class))
+            .count();
+
+        (count > 0).then(|| InvoiceItem {
+            item: format!("{class:?} handling x{count}"),
+            cost: surcharge.of(class) * count as f32,
+        })
+    })
+    .collect()
+}
+
 /// Get the price for each itinerary
 #[cfg(not(tarpaulin_include))]
 // no_coverage: (R5) function test not yet created
-async fn update_pricing(
+pub(crate) async fn update_pricing(
     payload: &QueryItineraryRequest,
     itinerary: &mut Itinerary,
     grpc_clients: &mut GrpcClients,
@@ -219,11 +319,11 @@ async fn update_pricing(
         .filter_map(|flight_plan| {
             let mut weight_g: u32 = 0;
 
-            // add parcel weight
+            // add the weight of all parcels
             if flight_plan.origin_vertiport_id == payload.origin_vertiport_id
                 || flight_plan.target_vertiport_id == payload.target_vertiport_id
             {
-                weight_g = payload.cargo_weight_g;
+                weight_g = payload.cargo_weight_g();
             }
 
             let Some(distance_meters) = super::utils::get_distance_meters(&flight_plan.path) else {
@@ -330,41 +430,30 @@ async fn update_pricing(
         })
         .collect();
 
+    itinerary.invoice.extend(handling_invoice(
+        &itinerary.parcels,
+        get_handling_surcharge().await,
+    ));
+
     Ok(())
 }
 
-/// Get Available Flights
-///
-/// Search for available trips and return a list of [`Itinerary`].
-#[utoipa::path(
-    post,
-    path = "/cargo/request",
-    tag = "svc-cargo",
-    request_body = QueryItineraryRequest,
-    responses(
-        (status = 200, description = "List available flight plans", body = [Itinerary]),
-        (status = 400, description = "Request body is invalid format"),
-        (status = 500, description = "svc-scheduler or svc-pricing returned error"),
-        (status = 503, description = "Could not connect to other microservice dependencies")
-    )
-)]
+/// Search for available itineraries, price them and store them as drafts
+///  in the cache so they can be booked later on.
 #[cfg(not(tarpaulin_include))]
-// no_coverage: (R5) function test not yet created
-pub async fn request_flight(
-    Extension(mut grpc_clients): Extension<GrpcClients>,
-    Json(payload): Json<QueryItineraryRequest>,
-) -> Result<Json<Vec<DraftItinerary>>, StatusCode> {
-    rest_debug!("entry.");
+// no_coverage: (R5) need backends to test (integration)
+pub(crate) async fn query_itineraries(
+    payload: &QueryItineraryRequest,
+    grpc_clients: &mut GrpcClients,
+) -> Result<Vec<DraftItinerary>, StatusCode> {
     //
     // Query Flight with Scheduler
-    let itineraries = scheduler_query(&payload, &mut grpc_clients).await?;
+    let itineraries = scheduler_query(payload, grpc_clients).await?;
 
     //
     // Unpack flight itineraries
     let mut itineraries: Vec<Itinerary> = unpack_itineraries(itineraries);
 
-    //
-    // Get pricing for each itinerary
     for itinerary in itineraries.iter_mut() {
         itinerary
             .acquisition_vertiport_id
@@ -373,8 +462,24 @@ pub async fn request_flight(
             .delivery_vertiport_id
             .clone_from(&payload.target_vertiport_id);
         itinerary.user_id.clone_from(&payload.user_id);
-        itinerary.cargo_weight_g = payload.cargo_weight_g;
-        update_pricing(&payload, itinerary, &mut grpc_clients).await?;
+        itinerary.cargo_weight_g = payload.cargo_weight_g();
+        itinerary.parcels.clone_from(&payload.parcels);
+    }
+
+    //
+    // Drop itineraries with vehicles that can't carry the parcels
+    let mut itineraries = filter_by_capacity(
+        itineraries,
+        &payload.parcels,
+        get_fleet().await,
+        grpc_clients,
+    )
+    .await;
+
+    //
+    // Get pricing for each itinerary
+    for itinerary in itineraries.iter_mut() {
+        update_pricing(payload, itinerary, grpc_clients).await?;
     }
 
     //
@@ -404,6 +509,38 @@ pub async fn request_flight(
         });
     }
 
+    Ok(draft_itineraries)
+}
+
+/// Get Available Flights
+///
+/// Search for available trips and return a list of [`Itinerary`].
+#[utoipa::path(
+    post,
+    path = "/cargo/request",
+    tag = "svc-cargo",
+    request_body = QueryItineraryRequest,
+    security(("bearer" = [])),
+    responses(
+        (status = 200, description = "List available flight plans", body = [Itinerary]),
+        (status = 400, description = "Request body is invalid format"),
+        (status = 401, description = "Missing or invalid bearer token"),
+        (status = 500, description = "svc-scheduler or svc-pricing returned error"),
+        (status = 503, description = "Could not connect to other microservice dependencies")
+    )
+)]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) function test not yet created
+pub async fn request_flight(
+    Extension(mut grpc_clients): Extension<GrpcClients>,
+    Extension(principal): Extension<Principal>,
+    Json(mut payload): Json<QueryItineraryRequest>,
+) -> Result<Json<Vec<DraftItinerary>>, StatusCode> {
+    rest_debug!("entry.");
+    payload.user_id = principal.user_id;
+    payload.normalize();
+    let draft_itineraries = query_itineraries(&payload, &mut grpc_clients).await?;
+
     rest_debug!("exit with {} itineraries.", draft_itineraries.len());
 
     Ok(Json(draft_itineraries))
@@ -412,6 +549,7 @@ pub async fn request_flight(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rest::api::rest_types::Recipient;
     use lib_common::time::{Duration, Utc};
     use lib_common::uuid::Uuid;
     use svc_scheduler_client_grpc::prelude::scheduler_storage::flight_plan;
@@ -474,6 +612,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handling_invoice() {
+        let surcharge = HandlingSurcharge {
+            fragile: 5.0,
+            refrigerated: 15.0,
+            hazmat: 25.0,
+        };
+        assert!(handling_invoice(&[parcel(100)], &surcharge).is_empty());
+
+        let mut parcels = vec![parcel(100), parcel(100), parcel(100)];
+        parcels[0].handling = vec![HandlingClass::Refrigerated, HandlingClass::Fragile];
+        parcels[1].handling = vec![HandlingClass::Refrigerated];
+
+        let invoice = handling_invoice(&parcels, &surcharge);
+        assert_eq!(invoice.len(), 2);
+        assert_eq!(invoice[0].item, "Fragile handling x1");
+        assert_eq!(invoice[0].cost, 5.0);
+        assert_eq!(invoice[1].item, "Refrigerated handling x2");
+        assert_eq!(invoice[1].cost, 30.0);
+    }
+
     #[test]
     fn test_unpack_itineraries() {
         let mut data = flight_plan::mock::get_data_obj();
@@ -511,32 +670,130 @@ mod tests {
         assert_eq!(result.len(), 1);
     }
 
+    fn parcel(weight_g: u32) -> Parcel {
+        Parcel {
+            weight_g,
+            length_mm: 300,
+            width_mm: 200,
+            height_mm: 100,
+            reference: None,
+            handling: vec![],
+            recipient: None,
+            delivery_pin: false,
+        }
+    }
+
     #[test]
     fn test_validate_payload() {
+        let mut fleet = Fleet::default();
         let mut payload = QueryItineraryRequest {
-            cargo_weight_g: MAX_CARGO_WEIGHT_G,
+            parcels: vec![
+                parcel(MAX_CARGO_WEIGHT_G / 2),
+                parcel(MAX_CARGO_WEIGHT_G / 2),
+            ],
             time_depart_window: TimeWindow {
                 timestamp_min: Utc::now()
                     + Duration::try_minutes(ADVANCE_NOTICE_MINUTES + 1).unwrap(),
                 timestamp_max: Utc::now() + Duration::try_minutes(10).unwrap(),
             },
             target_vertiport_id: Uuid::new_v4().to_string(),
+            cargo_weight_g: None,
             user_id: Uuid::new_v4().to_string(),
             origin_vertiport_id: Uuid::new_v4().to_string(),
         };
 
-        validate_payload(&payload).unwrap();
+        validate_payload(&payload, &fleet).unwrap();
 
         // bad weight
-        payload.cargo_weight_g = MAX_CARGO_WEIGHT_G + 1;
-        assert_eq!(validate_payload(&payload), Err(ValidationError::Weight));
-        payload.cargo_weight_g = MAX_CARGO_WEIGHT_G;
+        payload.parcels.push(parcel(1));
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Weight)
+        );
+        payload.parcels.pop();
+
+        // no parcels
+        let parcels = std::mem::take(&mut payload.parcels);
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Parcels)
+        );
+        payload.parcels = parcels;
+
+        // parcel without weight or with partial dimensions
+        payload.parcels[1].weight_g = 0;
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Parcels)
+        );
+        payload.parcels[1].weight_g = MAX_CARGO_WEIGHT_G / 2;
+        payload.parcels[1].height_mm = 0;
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Parcels)
+        );
+        payload.parcels[1].height_mm = 100;
+
+        // unknown dimensions
+        let known = std::mem::take(&mut payload.parcels);
+        payload.cargo_weight_g = Some(1000);
+        payload.normalize();
+        assert_eq!(payload.parcels.len(), 1);
+        assert_eq!(payload.cargo_weight_g(), 1000);
+        validate_payload(&payload, &fleet).unwrap();
+
+        // deprecated weight with parcels
+        payload.parcels = known;
+        payload.cargo_weight_g = Some(1000);
+        payload.normalize();
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Parcels)
+        );
+        payload.cargo_weight_g = None;
+
+        // recipient without a name or contact details
+        let mut recipient = Recipient {
+            name: "J. Doe".to_string(),
+            email: None,
+            phone: Some("+31 20 123 4567".to_string()),
+        };
+        payload.parcels[0].recipient = Some(recipient.clone());
+        validate_payload(&payload, &fleet).unwrap();
+        recipient.phone = None;
+        payload.parcels[0].recipient = Some(recipient.clone());
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Recipient)
+        );
+        recipient.email = Some("j.doe@example.com".to_string());
+        recipient.name = " ".to_string();
+        payload.parcels[0].recipient = Some(recipient);
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Recipient)
+        );
+        payload.parcels[0].recipient = None;
+
+        // no vehicle can carry the parcels
+        fleet.default.payload_g = MAX_CARGO_WEIGHT_G / 2;
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Vehicle)
+        );
+        fleet.default.payload_g = MAX_CARGO_WEIGHT_G;
+        fleet.default.bay_height_mm = 50;
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::Vehicle)
+        );
+        fleet.default = Fleet::default().default;
 
         // min time too soon
         payload.time_depart_window.timestamp_min =
             Utc::now() + Duration::try_minutes(ADVANCE_NOTICE_MINUTES - 1).unwrap();
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::TimeWindowMin)
         );
         payload.time_depart_window.timestamp_min =
@@ -545,13 +802,13 @@ mod tests {
         // bad max time (<= min time)
         payload.time_depart_window.timestamp_max = payload.time_depart_window.timestamp_min; // same as min
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::TimeWindowMax)
         );
         payload.time_depart_window.timestamp_max =
             payload.time_depart_window.timestamp_min - Duration::try_milliseconds(1).unwrap(); // less than min
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::TimeWindowMax)
         );
 
@@ -560,7 +817,7 @@ mod tests {
         payload.time_depart_window.timestamp_min =
             payload.time_depart_window.timestamp_max - Duration::try_seconds(10).unwrap();
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::TimeWindowMax)
         );
 
@@ -572,20 +829,23 @@ mod tests {
         // bad target vertiport id
         payload.target_vertiport_id = "not a uuid".to_string();
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::TargetVertiportId)
         );
         payload.target_vertiport_id = Uuid::new_v4().to_string();
 
         // bad user id
         payload.user_id = "not a uuid".to_string();
-        assert_eq!(validate_payload(&payload), Err(ValidationError::UserId));
+        assert_eq!(
+            validate_payload(&payload, &fleet),
+            Err(ValidationError::UserId)
+        );
         payload.user_id = Uuid::new_v4().to_string();
 
         // bad origin vertiport id
         payload.origin_vertiport_id = "not a uuid".to_string();
         assert_eq!(
-            validate_payload(&payload),
+            validate_payload(&payload, &fleet),
             Err(ValidationError::OriginVertiportId)
         );
         payload.origin_vertiport_id = Uuid::new_v4().to_string();
@@ -597,13 +857,14 @@ mod tests {
         let mut grpc_clients = GrpcClients::default(config);
 
         let mut payload = QueryItineraryRequest {
-            cargo_weight_g: 100,
+            parcels: vec![parcel(100)],
             time_depart_window: TimeWindow {
                 timestamp_min: Utc::now()
                     + Duration::try_minutes(ADVANCE_NOTICE_MINUTES + 1).unwrap(),
                 timestamp_max: Utc::now() + Duration::try_minutes(10).unwrap(),
             },
             target_vertiport_id: Uuid::new_v4().to_string(),
+            cargo_weight_g: None,
             user_id: Uuid::new_v4().to_string(),
             origin_vertiport_id: Uuid::new_v4().to_string(),
         };
@@ -611,7 +872,7 @@ mod tests {
         scheduler_query(&payload, &mut grpc_clients).await.unwrap();
 
         // full request validation tested in another UT, so we can just test one error case here
-        payload.cargo_weight_g = MAX_CARGO_WEIGHT_G + 1;
+        payload.parcels = vec![parcel(MAX_CARGO_WEIGHT_G + 1)];
         assert_eq!(
             scheduler_query(&payload, &mut grpc_clients)
                 .await
@@ -626,6 +887,18 @@ mod tests {
             ValidationError::Weight.to_string(),
             "invalid weight".to_string()
         );
+        assert_eq!(
+            ValidationError::Parcels.to_string(),
+            "invalid parcels".to_string()
+        );
+        assert_eq!(
+            ValidationError::Vehicle.to_string(),
+            "no vehicle can carry the parcels".to_string()
+        );
+        assert_eq!(
+            ValidationError::Recipient.to_string(),
+            "invalid recipient".to_string()
+        );
         assert_eq!(
             ValidationError::TimeWindowMin.to_string(),
             "invalid time window min".to_string()
@@ -1,14 +1,20 @@
-use super::rest_types::CargoScan;
+use super::rest_types::{CargoScan, DeliveryConfirmation, DeliveryProof};
 use crate::grpc::client::GrpcClients;
+use crate::service::label::LabelSigner;
+use crate::service::scan::ScanPolicy;
+use crate::service::ServiceError;
 use axum::{extract::Extension, Json};
 use hyper::StatusCode;
-use lib_common::time::Utc;
-use lib_common::uuid::to_uuid;
-use svc_storage_client_grpc::prelude::*;
-use svc_storage_client_grpc::resources::parcel_scan::Data as CargoScanData;
 
 /// Scan a parcel
-/// The provided parcel ID and scanner ID must already exist in the database
+/// The provided parcel ID and scanner ID must already exist in the database.
+/// The parcel may be identified by the signed code of its label instead.
+/// The scan advances the parcel status, scans that don't follow from the
+///  status where they were taken are rejected. Scans away from the parcel's
+///  vertiports are rejected unless they are along its flights. The scan
+///  timestamp is when the scan was taken; scans taken too long ago or ahead
+///  of the server clock are rejected. The reason a scan is rejected is
+///  returned in the response body.
 #[utoipa::path(
     put,
     path = "/cargo/scan",
@@ -16,89 +22,99 @@ use svc_storage_client_grpc::resources::parcel_scan::Data as CargoScanData;
     request_body = CargoScan,
     responses(
         (status = 200, description = "Scan succeeded", body = String),
-        (status = 400, description = "Request body is invalid format"),
-        (status = 500, description = "svc-storage returned error"),
-        (status = 503, description = "Could not connect to other microservice dependencies")
+        (status = 400, description = "Request body is invalid format, the label code is invalid, the scan timestamp is outside the accepted window, the scan is off the parcel route or does not follow from the parcel status", body = String),
+        (status = 404, description = "Parcel not registered for an itinerary, or no longer booked on the itinerary of its label", body = String),
+        (status = 500, description = "svc-storage returned error", body = String),
+        (status = 503, description = "Could not connect to other microservice dependencies", body = String)
     )
 )]
 pub async fn scan_parcel(
     Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(policy): Extension<ScanPolicy>,
+    Extension(signer): Extension<LabelSigner>,
     Json(payload): Json<CargoScan>,
-) -> Result<(), StatusCode> {
+) -> Result<(), (StatusCode, String)> {
     rest_debug!("entry.");
 
-    // TODO(R5): Consider too old timestamps?
-    //  Maybe an offline scanner could store scans until it has a connection
-
-    to_uuid(&payload.parcel_id).ok_or_else(|| {
-        rest_error!("parcel ID not in UUID format.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    to_uuid(&payload.scanner_id).ok_or_else(|| {
-        rest_error!("scanner ID not in UUID format.");
-        StatusCode::BAD_REQUEST
-    })?;
-
-    if payload.latitude < -90.0
-        || payload.latitude > 90.0
-        || payload.longitude < -180.0
-        || payload.longitude > 180.0
-    {
-        let error_msg = "coordinates out of range.".to_string();
-        rest_error!(
-            "{}: (lat: {}, lon: {})",
-            &error_msg,
-            payload.latitude,
-            payload.longitude
-        );
-
-        return Err(StatusCode::BAD_REQUEST);
-    }
+    let status = crate::service::scan::scan_parcel(payload, &policy, &signer, &grpc_clients)
+        .await
+        .map_err(|e| {
+            rest_error!("could not record scan: {e}");
+            (StatusCode::from(ServiceError::from(e)), e.to_string())
+        })?;
+
+    rest_debug!("parcel status is {status:?}.");
+    Ok(())
+}
+
+/// Deliver a parcel
+/// The final scan must be taken at the delivery vertiport after the parcel
+///  arrived. The recipient gives the delivery PIN issued to the customer,
+///  if the parcel was booked with one. The reason a delivery is rejected is
+///  returned in the response body.
+#[utoipa::path(
+    put,
+    path = "/cargo/deliver",
+    tag = "svc-cargo",
+    request_body = DeliveryConfirmation,
+    responses(
+        (status = 200, description = "Parcel delivered", body = DeliveryProof),
+        (status = 400, description = "Request body is invalid format, the PIN is incorrect or the parcel has not arrived", body = String),
+        (status = 404, description = "Parcel not registered for an itinerary, or no longer booked on the itinerary of its label", body = String),
+        (status = 500, description = "svc-storage returned error", body = String),
+        (status = 503, description = "Could not connect to other microservice dependencies", body = String)
+    )
+)]
+pub async fn confirm_delivery(
+    Extension(grpc_clients): Extension<GrpcClients>,
+    Extension(policy): Extension<ScanPolicy>,
+    Extension(signer): Extension<LabelSigner>,
+    Json(payload): Json<DeliveryConfirmation>,
+) -> Result<Json<DeliveryProof>, (StatusCode, String)> {
+    rest_debug!("entry.");
 
-    // Make request, process response
-    let data = CargoScanData {
-        scanner_id: payload.scanner_id,
-        parcel_id: payload.parcel_id,
-        geo_location: Some(GeoPointZ {
-            y: payload.latitude,
-            x: payload.longitude,
-            z: payload.altitude,
-        }),
-        created_at: Some(Utc::now().into()),
-    };
-
-    grpc_clients
-        .storage
-        .parcel_scan
-        .insert(data)
+    crate::service::delivery::confirm_delivery(payload, &policy, &signer, &grpc_clients)
         .await
+        .map(Json)
         .map_err(|e| {
-            rest_error!("svc-storage error: {:?}", e);
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .into_inner()
-        .validation_result
-        .ok_or_else(|| {
-            rest_error!("svc-storage response missing validation result.");
-            StatusCode::INTERNAL_SERVER_ERROR
-        })?
-        .success
-        .then(|| {
-            rest_info!("svc-storage success.");
-        })
-        .ok_or_else(|| {
-            rest_error!("svc-storage failure.");
-            StatusCode::INTERNAL_SERVER_ERROR
+            rest_error!("could not confirm delivery: {e}");
+            (StatusCode::from(ServiceError::from(e)), e.to_string())
         })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::test_storage::register_parcel;
+    use lib_common::time::Utc;
 
     #[tokio::test]
     async fn test_scan_parcel_nominal() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+
+        scan_parcel(
+            Extension(grpc_clients),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(CargoScan {
+                parcel_id: parcel.parcel_id,
+                code: None,
+                scanner_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                latitude: f64::from(parcel.acquisition.latitude),
+                longitude: f64::from(parcel.acquisition.longitude),
+                altitude: 0.0,
+                timestamp: Utc::now(),
+                received_at: None,
+            }),
+        )
+        .await
+        .unwrap(); // should succeed
+    }
+
+    #[tokio::test]
+    async fn test_scan_parcel_unregistered() {
         let config = crate::config::Config::default();
         let grpc_clients = GrpcClients::default(config);
         let parcel_id = "00000000-0000-0000-0000-000000000000";
@@ -108,19 +124,69 @@ mod tests {
         let altitude = 0.0;
         let timestamp = Utc::now().into();
 
-        scan_parcel(
+        let result = scan_parcel(
             Extension(grpc_clients),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
             Json(CargoScan {
                 parcel_id: parcel_id.to_string(),
+                code: None,
                 scanner_id: scanner_id.to_string(),
                 latitude,
                 longitude,
                 altitude,
                 timestamp,
+                received_at: None,
             }),
         )
         .await
-        .unwrap(); // should succeed
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::NOT_FOUND);
+        assert_eq!(result.1, "parcel not registered");
+    }
+
+    #[tokio::test]
+    async fn test_confirm_delivery() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let mut confirmation = DeliveryConfirmation {
+            scan: CargoScan {
+                parcel_id: "00000000-0000-0000-0000-000000000000".to_string(),
+                code: None,
+                scanner_id: "00000000-0000-0000-0000-000000000001".to_string(),
+                latitude: 0.0,
+                longitude: 0.0,
+                altitude: 0.0,
+                timestamp: Utc::now(),
+                received_at: None,
+            },
+            recipient_name: "J. Doe".to_string(),
+            content_hash: "0".repeat(64),
+            pin: None,
+        };
+
+        let result = confirm_delivery(
+            Extension(grpc_clients.clone()),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(confirmation.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::NOT_FOUND);
+        assert_eq!(result.1, "parcel not registered");
+
+        confirmation.recipient_name = String::new();
+        let result = confirm_delivery(
+            Extension(grpc_clients),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(confirmation),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1, "recipient name missing or too long");
     }
 
     #[tokio::test]
@@ -132,50 +198,92 @@ mod tests {
 
         let mut scan_data = CargoScan {
             parcel_id: parcel_id.to_string().replace("-", ""),
+            code: None,
             scanner_id: scanner_id.to_string(),
             latitude: 90.0,
             longitude: 180.0,
             altitude: 0.0,
             timestamp: Utc::now().into(),
+            received_at: None,
         };
 
-        let result = scan_parcel(Extension(grpc_clients.clone()), Json(scan_data.clone()))
-            .await
-            .unwrap_err();
-        assert_eq!(result, StatusCode::BAD_REQUEST);
+        let result = scan_parcel(
+            Extension(grpc_clients.clone()),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(scan_data.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1, "parcel ID not in UUID format");
         scan_data.parcel_id = parcel_id.to_string();
 
         // Bad scanner ID
         scan_data.scanner_id = scanner_id.to_string().replace("-", "");
-        let result = scan_parcel(Extension(grpc_clients.clone()), Json(scan_data.clone()))
-            .await
-            .unwrap_err();
-        assert_eq!(result, StatusCode::BAD_REQUEST);
+        let result = scan_parcel(
+            Extension(grpc_clients.clone()),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(scan_data.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
         scan_data.scanner_id = scanner_id.to_string();
 
-        // reset
-        scan_parcel(Extension(grpc_clients.clone()), Json(scan_data.clone()))
-            .await
-            .unwrap(); // should succeed
+        // reset, valid but not registered
+        let result = scan_parcel(
+            Extension(grpc_clients.clone()),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(scan_data.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::NOT_FOUND);
 
         // bad latitude > 90
         for latitude in [-90.01, 90.01] {
             scan_data.latitude = latitude;
-            let result = scan_parcel(Extension(grpc_clients.clone()), Json(scan_data.clone()))
-                .await
-                .unwrap_err();
-            assert_eq!(result, StatusCode::BAD_REQUEST);
+            let result = scan_parcel(
+                Extension(grpc_clients.clone()),
+                Extension(ScanPolicy::default()),
+                Extension(LabelSigner::default()),
+                Json(scan_data.clone()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(result.0, StatusCode::BAD_REQUEST);
         }
         scan_data.latitude = 0.0;
 
         // bad longitude
         for longitude in [-180.01, 180.01] {
             scan_data.longitude = longitude;
-            let result = scan_parcel(Extension(grpc_clients.clone()), Json(scan_data.clone()))
-                .await
-                .unwrap_err();
-            assert_eq!(result, StatusCode::BAD_REQUEST);
+            let result = scan_parcel(
+                Extension(grpc_clients.clone()),
+                Extension(ScanPolicy::default()),
+                Extension(LabelSigner::default()),
+                Json(scan_data.clone()),
+            )
+            .await
+            .unwrap_err();
+            assert_eq!(result.0, StatusCode::BAD_REQUEST);
         }
         scan_data.longitude = 0.0;
+
+        // taken ahead of the server clock
+        scan_data.timestamp = Utc::now() + lib_common::time::Duration::try_hours(1).unwrap();
+        let result = scan_parcel(
+            Extension(grpc_clients.clone()),
+            Extension(ScanPolicy::default()),
+            Extension(LabelSigner::default()),
+            Json(scan_data.clone()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(result.0, StatusCode::BAD_REQUEST);
+        assert_eq!(result.1, "scan timestamp ahead of the server clock");
     }
 }
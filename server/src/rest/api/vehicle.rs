@@ -0,0 +1,292 @@
+//! Cargo capacity and handling clearance of the vehicles serving an itinerary
+//!
+//! svc-storage does not record the payload, cargo-bay dimensions or handling
+//!  clearances of vehicles yet, so they are configured per vehicle model.
+//!  TODO(R5): take them from svc-storage vehicle models
+
+use super::rest_types::{Itinerary, Parcel};
+use crate::config::{Config, VehicleCapacity};
+use crate::grpc::client::GrpcClients;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use tokio::sync::OnceCell;
+
+static FLEET: OnceCell<Fleet> = OnceCell::const_new();
+
+/// Returns FLEET, the vehicle capacities configured with environment
+///  variables.
+/// Initializes FLEET if it hasn't been initialized yet.
+pub async fn get_fleet() -> &'static Fleet {
+    FLEET
+        .get_or_init(|| async move {
+            // TODO(R5): don't default
+            let config = Config::try_from_env().unwrap_or_default();
+            Fleet::from(&config)
+        })
+        .await
+}
+
+/// Reasons a vehicle can't carry a load
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadError {
+    /// The parcels are heavier than the payload
+    Weight,
+
+    /// A parcel does not fit in the cargo bay
+    Dimensions,
+
+    /// The parcels together don't fit in the cargo bay
+    Volume,
+
+    /// A parcel needs handling the vehicle is not cleared for
+    Handling,
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LoadError::Weight => write!(f, "parcels exceed the payload"),
+            LoadError::Dimensions => write!(f, "parcel does not fit in the cargo bay"),
+            LoadError::Volume => write!(f, "parcels exceed the cargo bay volume"),
+            LoadError::Handling => write!(f, "parcel handling not allowed"),
+        }
+    }
+}
+
+/// Dimensions from smallest to largest, to compare any orientation
+fn sorted(mut dimensions: [u32; 3]) -> [u32; 3] {
+    dimensions.sort_unstable();
+    dimensions
+}
+
+/// Volume in cubic millimeters
+fn volume(dimensions: [u32; 3]) -> u64 {
+    dimensions.iter().map(|d| u64::from(*d)).product()
+}
+
+/// Check that a vehicle can carry the parcels
+/// Parcels may be turned to fit the cargo bay.
+pub fn check_load(capacity: &VehicleCapacity, parcels: &[Parcel]) -> Result<(), LoadError> {
+    if parcels
+        .iter()
+        .flat_map(|parcel| parcel.handling.iter())
+        .any(|class| !capacity.handling.contains(class))
+    {
+        return Err(LoadError::Handling);
+    }
+
+    let weight_g = parcels
+        .iter()
+        .fold(0u64, |total, parcel| total + u64::from(parcel.weight_g));
+
+    if weight_g > u64::from(capacity.payload_g) {
+        return Err(LoadError::Weight);
+    }
+
+    let bay = sorted([
+        capacity.bay_length_mm,
+        capacity.bay_width_mm,
+        capacity.bay_height_mm,
+    ]);
+
+    let mut total_volume: u64 = 0;
+    for parcel in parcels {
+        let dimensions = sorted([parcel.length_mm, parcel.width_mm, parcel.height_mm]);
+        if dimensions.iter().zip(bay.iter()).any(|(d, b)| d > b) {
+            return Err(LoadError::Dimensions);
+        }
+
+        total_volume += volume(dimensions);
+    }
+
+    if total_volume > volume(bay) {
+        return Err(LoadError::Volume);
+    }
+
+    Ok(())
+}
+
+/// Cargo capacities of the vehicle models in service
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fleet {
+    /// Capacity of vehicles whose model is not listed
+    pub default: VehicleCapacity,
+
+    /// Capacities by vehicle model ID
+    pub models: HashMap<String, VehicleCapacity>,
+}
+
+impl From<&Config> for Fleet {
+    fn from(config: &Config) -> Self {
+        Fleet {
+            default: config.vehicle_capacity.clone(),
+            models: config.vehicle_models.clone(),
+        }
+    }
+}
+
+impl Default for Fleet {
+    fn default() -> Self {
+        Fleet::from(&Config::new())
+    }
+}
+
+impl Fleet {
+    /// The capacity of a vehicle model
+    pub fn capacity(&self, vehicle_model_id: &str) -> &VehicleCapacity {
+        self.models.get(vehicle_model_id).unwrap_or(&self.default)
+    }
+
+    /// Whether any vehicle model could carry the parcels
+    pub fn can_carry(&self, parcels: &[Parcel]) -> bool {
+        std::iter::once(&self.default)
+            .chain(self.models.values())
+            .any(|capacity| check_load(capacity, parcels).is_ok())
+    }
+}
+
+/// Drop the itineraries with a vehicle that can't carry the parcels
+/// Only the flights from the acquisition vertiport or to the delivery
+///  vertiport carry the parcels. Vehicles that can't be found in
+///  svc-storage are left to svc-scheduler, which checks the weight.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn filter_by_capacity(
+    itineraries: Vec<Itinerary>,
+    parcels: &[Parcel],
+    fleet: &Fleet,
+    grpc_clients: &GrpcClients,
+) -> Vec<Itinerary> {
+    let mut checked: HashMap<String, Result<(), LoadError>> = HashMap::new();
+    let mut result = vec![];
+
+    'itineraries: for itinerary in itineraries {
+        for flight_plan in &itinerary.flight_plans {
+            if flight_plan.origin_vertiport_id != itinerary.acquisition_vertiport_id
+                && flight_plan.target_vertiport_id != itinerary.delivery_vertiport_id
+            {
+                continue;
+            }
+
+            let vehicle_id = &flight_plan.vehicle_id;
+            if !checked.contains_key(vehicle_id) {
+                let load = match super::utils::get_vehicle_data(vehicle_id, grpc_clients).await {
+                    Ok(vehicle) => check_load(fleet.capacity(&vehicle.vehicle_model_id), parcels),
+                    Err(e) => {
+                        rest_warn!("couldn't check capacity of vehicle {vehicle_id}: {:?}", e);
+                        Ok(())
+                    }
+                };
+
+                checked.insert(vehicle_id.clone(), load);
+            }
+
+            if let Some(Err(e)) = checked.get(vehicle_id) {
+                rest_info!(
+                    "vehicle {vehicle_id} can't carry the parcels ({e}), skipping itinerary."
+                );
+                continue 'itineraries;
+            }
+        }
+
+        result.push(itinerary);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::HandlingClass;
+
+    fn parcel(weight_g: u32, dimensions: [u32; 3]) -> Parcel {
+        Parcel {
+            weight_g,
+            length_mm: dimensions[0],
+            width_mm: dimensions[1],
+            height_mm: dimensions[2],
+            reference: None,
+            handling: vec![],
+            recipient: None,
+            delivery_pin: false,
+        }
+    }
+
+    fn capacity() -> VehicleCapacity {
+        VehicleCapacity {
+            payload_g: 10_000,
+            bay_length_mm: 1000,
+            bay_width_mm: 500,
+            bay_height_mm: 400,
+            handling: vec![HandlingClass::Fragile, HandlingClass::Refrigerated],
+        }
+    }
+
+    #[test]
+    fn test_check_load() {
+        let capacity = capacity();
+
+        check_load(&capacity, &[parcel(10_000, [1000, 500, 400])]).unwrap();
+
+        // turned to fit
+        check_load(&capacity, &[parcel(100, [400, 1000, 500])]).unwrap();
+
+        // too heavy together
+        let parcels = [parcel(6000, [100, 100, 100]), parcel(6000, [100, 100, 100])];
+        assert_eq!(check_load(&capacity, &parcels), Err(LoadError::Weight));
+
+        // too long in any orientation
+        let parcels = [parcel(100, [1001, 100, 100])];
+        assert_eq!(check_load(&capacity, &parcels), Err(LoadError::Dimensions));
+
+        // too many to fit
+        let parcels = [parcel(100, [1000, 500, 300]), parcel(100, [1000, 500, 200])];
+        assert_eq!(check_load(&capacity, &parcels), Err(LoadError::Volume));
+
+        // handling
+        let mut parcels = [parcel(100, [100, 100, 100])];
+        parcels[0].handling = vec![HandlingClass::Refrigerated, HandlingClass::Fragile];
+        check_load(&capacity, &parcels).unwrap();
+        parcels[0].handling.push(HandlingClass::Hazmat);
+        assert_eq!(check_load(&capacity, &parcels), Err(LoadError::Handling));
+    }
+
+    #[test]
+    fn test_fleet() {
+        let mut config = Config::new();
+        config.vehicle_capacity = capacity();
+        config.vehicle_models.insert(
+            "large".to_string(),
+            VehicleCapacity {
+                payload_g: 100_000,
+                ..capacity()
+            },
+        );
+
+        let fleet = Fleet::from(&config);
+        assert_eq!(fleet.capacity("large").payload_g, 100_000);
+        assert_eq!(fleet.capacity("unknown"), &capacity());
+
+        assert!(fleet.can_carry(&[parcel(50_000, [100, 100, 100])]));
+        assert!(!fleet.can_carry(&[parcel(200_000, [100, 100, 100])]));
+        assert!(!fleet.can_carry(&[parcel(100, [2000, 100, 100])]));
+    }
+
+    #[test]
+    fn test_load_error_display() {
+        assert_eq!(LoadError::Weight.to_string(), "parcels exceed the payload");
+        assert_eq!(
+            LoadError::Dimensions.to_string(),
+            "parcel does not fit in the cargo bay"
+        );
+        assert_eq!(
+            LoadError::Volume.to_string(),
+            "parcels exceed the cargo bay volume"
+        );
+        assert_eq!(
+            LoadError::Handling.to_string(),
+            "parcel handling not allowed"
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Bearer token authentication
+//!
+//! Requests to user-scoped routes must carry an `Authorization: Bearer <JWT>`
+//!  header. Tokens are validated against a locally configured JSON Web Key
+//!  Set and the token subject becomes the [`Principal`] of the request.
+
+use crate::Config;
+use axum::extract::State;
+use axum::http::{header, HeaderMap, Request};
+use axum::middleware::Next;
+use axum::response::Response;
+use hyper::StatusCode;
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use lib_common::uuid::to_uuid;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+
+/// The authenticated caller of a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Principal {
+    /// The user ID (UUID), from the token subject
+    pub user_id: String,
+}
+
+/// Errors authenticating a request
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthError {
+    /// The key set file could not be read or parsed
+    KeySet,
+
+    /// No bearer token in the request
+    MissingToken,
+
+    /// The token has no key ID or the key ID is unknown
+    UnknownKey,
+
+    /// The token signature, algorithm or claims are invalid
+    InvalidToken,
+
+    /// The token subject is not a user ID
+    Subject,
+}
+
+impl Display for AuthError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            AuthError::KeySet => write!(f, "could not load key set"),
+            AuthError::MissingToken => write!(f, "missing bearer token"),
+            AuthError::UnknownKey => write!(f, "unknown signing key"),
+            AuthError::InvalidToken => write!(f, "invalid token"),
+            AuthError::Subject => write!(f, "token subject is not a user ID"),
+        }
+    }
+}
+
+impl std::error::Error for AuthError {}
+
+/// Token claims used by svc-cargo
+#[derive(Debug, Deserialize)]
+struct Claims {
+    sub: String,
+}
+
+/// Signature algorithm of a key algorithm, None for encryption algorithms
+fn algorithm(key_algorithm: KeyAlgorithm) -> Option<Algorithm> {
+    match key_algorithm {
+        KeyAlgorithm::HS256 => Some(Algorithm::HS256),
+        KeyAlgorithm::HS384 => Some(Algorithm::HS384),
+        KeyAlgorithm::HS512 => Some(Algorithm::HS512),
+        KeyAlgorithm::ES256 => Some(Algorithm::ES256),
+        KeyAlgorithm::ES384 => Some(Algorithm::ES384),
+        KeyAlgorithm::RS256 => Some(Algorithm::RS256),
+        KeyAlgorithm::RS384 => Some(Algorithm::RS384),
+        KeyAlgorithm::RS512 => Some(Algorithm::RS512),
+        KeyAlgorithm::PS256 => Some(Algorithm::PS256),
+        KeyAlgorithm::PS384 => Some(Algorithm::PS384),
+        KeyAlgorithm::PS512 => Some(Algorithm::PS512),
+        KeyAlgorithm::EdDSA => Some(Algorithm::EdDSA),
+        _ => None,
+    }
+}
+
+/// Signature algorithm of a key
+/// Keys without `alg` are only accepted if their key type allows a single
+///  algorithm, so tokens can't choose the algorithm they are checked with.
+fn key_algorithm(jwk: &Jwk) -> Option<Algorithm> {
+    if let Some(key_algorithm) = jwk.common.key_algorithm {
+        return algorithm(key_algorithm);
+    }
+
+    match &jwk.algorithm {
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => Some(Algorithm::ES256),
+            EllipticCurve::P384 => Some(Algorithm::ES384),
+            _ => None,
+        },
+        AlgorithmParameters::OctetKeyPair(params) => {
+            matches!(params.curve, EllipticCurve::Ed25519).then_some(Algorithm::EdDSA)
+        }
+        // RSA and symmetric keys are used with several algorithms
+        _ => None,
+    }
+}
+
+/// Validates bearer tokens against a key set
+#[derive(Clone, Default)]
+pub struct Authenticator {
+    keys: HashMap<String, (DecodingKey, Algorithm)>,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+impl fmt::Debug for Authenticator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Authenticator")
+            .field("keys", &self.keys.keys())
+            .field("issuer", &self.issuer)
+            .field("audience", &self.audience)
+            .finish()
+    }
+}
+
+impl Authenticator {
+    /// Create an authenticator for the keys of a JSON Web Key Set.
+    /// Keys without a key ID, meant for encryption, or without a known
+    ///  signature algorithm are skipped.
+    pub fn new(key_set: &JwkSet, issuer: Option<String>, audience: Option<String>) -> Self {
+        let keys = key_set
+            .keys
+            .iter()
+            .filter_map(|jwk| {
+                let kid = jwk.common.key_id.clone()?;
+                let Some(algorithm) = key_algorithm(jwk) else {
+                    rest_warn!("skipping key {kid}: no signature algorithm.");
+                    return None;
+                };
+
+                let key = DecodingKey::from_jwk(jwk)
+                    .map_err(|e| rest_warn!("skipping key {kid}: {e}"))
+                    .ok()?;
+
+                Some((kid, (key, algorithm)))
+            })
+            .collect();
+
+        Authenticator {
+            keys,
+            issuer,
+            audience,
+        }
+    }
+
+    /// Create an authenticator from the key set file in the configuration
+    pub fn try_from_config(config: &Config) -> Result<Self, AuthError> {
+        let data = std::fs::read_to_string(&config.rest_auth_jwks_path).map_err(|e| {
+            rest_error!("could not read key set {}: {e}", config.rest_auth_jwks_path);
+            AuthError::KeySet
+        })?;
+
+        let key_set: JwkSet = serde_json::from_str(&data).map_err(|e| {
+            rest_error!("invalid key set: {e}");
+            AuthError::KeySet
+        })?;
+
+        Ok(Authenticator::new(
+            &key_set,
+            config.rest_auth_issuer.clone(),
+            config.rest_auth_audience.clone(),
+        ))
+    }
+
+    /// Validate the bearer token of a request
+    pub fn authenticate(&self, headers: &HeaderMap) -> Result<Principal, AuthError> {
+        let token = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(AuthError::MissingToken)?;
+
+        let token_header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+        let (key, algorithm) = token_header
+            .kid
+            .as_ref()
+            .and_then(|kid| self.keys.get(kid))
+            .ok_or(AuthError::UnknownKey)?;
+
+        // Never let the token pick a different algorithm than its key
+        let mut validation = Validation::new(*algorithm);
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let claims = decode::<Claims>(token, key, &validation)
+            .map_err(|e| {
+                rest_debug!("token rejected: {e}");
+                AuthError::InvalidToken
+            })?
+            .claims;
+
+        to_uuid(&claims.sub).ok_or(AuthError::Subject)?;
+        Ok(Principal {
+            user_id: claims.sub,
+        })
+    }
+}
+
+/// Middleware for routes that act on behalf of a user.
+/// Rejects requests without a valid bearer token with 401, otherwise
+///  inserts the [`Principal`] into the request extensions.
+pub async fn require_principal<B>(
+    State(authenticator): State<Arc<Authenticator>>,
+    mut request: Request<B>,
+    next: Next<B>,
+) -> Result<Response, StatusCode> {
+    let principal = authenticator.authenticate(request.headers()).map_err(|e| {
+        rest_warn!("unauthorized request to {}: {e}", request.uri().path());
+        StatusCode::UNAUTHORIZED
+    })?;
+
+    rest_debug!("authenticated user {}.", principal.user_id);
+    request.extensions_mut().insert(principal);
+    Ok(next.run(request).await)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use lib_common::time::{Duration, Utc};
+    use lib_common::uuid::Uuid;
+    use serde::Serialize;
+
+    const KID: &str = "test";
+    const SECRET: &[u8] = b"svc-cargo-test-secret-0123456789";
+    const KEY_SET: &str = r#"{"keys": [
+        {"kty": "oct", "kid": "test", "alg": "HS256", "k": "c3ZjLWNhcmdvLXRlc3Qtc2VjcmV0LTAxMjM0NTY3ODk"},
+        {"kty": "oct", "alg": "HS256", "k": "c3ZjLWNhcmdvLXRlc3Qtc2VjcmV0LTAxMjM0NTY3ODk"},
+        {"kty": "oct", "kid": "no-alg", "k": "c3ZjLWNhcmdvLXRlc3Qtc2VjcmV0LTAxMjM0NTY3ODk"}
+    ]}"#;
+
+    #[derive(Serialize)]
+    struct TestClaims {
+        sub: String,
+        exp: i64,
+        iss: String,
+    }
+
+    fn authenticator() -> Authenticator {
+        let key_set: JwkSet = serde_json::from_str(KEY_SET).unwrap();
+        Authenticator::new(&key_set, Some("aetheric".to_string()), None)
+    }
+
+    fn token(kid: Option<&str>, sub: &str, exp: i64, iss: &str) -> String {
+        let mut header = Header::new(Algorithm::HS256);
+        header.kid = kid.map(str::to_string);
+        let claims = TestClaims {
+            sub: sub.to_string(),
+            exp,
+            iss: iss.to_string(),
+        };
+
+        encode(&header, &claims, &EncodingKey::from_secret(SECRET)).unwrap()
+    }
+
+    fn headers(token: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {token}")).unwrap(),
+        );
+        headers
+    }
+
+    /// A principal for handler tests
+    pub(crate) fn principal() -> Principal {
+        Principal {
+            user_id: Uuid::new_v4().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_authenticate() {
+        let authenticator = authenticator();
+        let user_id = Uuid::new_v4().to_string();
+        let exp = (Utc::now() + Duration::try_minutes(5).unwrap()).timestamp();
+
+        let principal = authenticator
+            .authenticate(&headers(&token(Some(KID), &user_id, exp, "aetheric")))
+            .unwrap();
+        assert_eq!(principal.user_id, user_id);
+
+        // no header
+        assert_eq!(
+            authenticator.authenticate(&HeaderMap::new()).unwrap_err(),
+            AuthError::MissingToken
+        );
+
+        // not a token
+        assert_eq!(
+            authenticator.authenticate(&headers("invalid")).unwrap_err(),
+            AuthError::InvalidToken
+        );
+
+        // no key ID, or unknown key ID
+        for kid in [None, Some("unknown")] {
+            assert_eq!(
+                authenticator
+                    .authenticate(&headers(&token(kid, &user_id, exp, "aetheric")))
+                    .unwrap_err(),
+                AuthError::UnknownKey
+            );
+        }
+
+        // expired
+        let expired = (Utc::now() - Duration::try_minutes(5).unwrap()).timestamp();
+        assert_eq!(
+            authenticator
+                .authenticate(&headers(&token(Some(KID), &user_id, expired, "aetheric")))
+                .unwrap_err(),
+            AuthError::InvalidToken
+        );
+
+        // wrong issuer
+        assert_eq!(
+            authenticator
+                .authenticate(&headers(&token(Some(KID), &user_id, exp, "other")))
+                .unwrap_err(),
+            AuthError::InvalidToken
+        );
+
+        // subject is not a user ID
+        assert_eq!(
+            authenticator
+                .authenticate(&headers(&token(Some(KID), "admin", exp, "aetheric")))
+                .unwrap_err(),
+            AuthError::Subject
+        );
+    }
+
+    #[test]
+    fn test_authenticator_skips_keys_without_id_or_algorithm() {
+        let authenticator = authenticator();
+        assert_eq!(authenticator.keys.len(), 1);
+        assert!(authenticator.keys.contains_key(KID));
+
+        // the token can't choose the algorithm of a key without one
+        let user_id = Uuid::new_v4().to_string();
+        let exp = (Utc::now() + Duration::try_minutes(5).unwrap()).timestamp();
+        assert_eq!(
+            authenticator
+                .authenticate(&headers(&token(Some("no-alg"), &user_id, exp, "aetheric")))
+                .unwrap_err(),
+            AuthError::UnknownKey
+        );
+    }
+
+    #[test]
+    fn test_key_algorithm_from_key_type() {
+        let key_set: JwkSet = serde_json::from_str(
+            r#"{"keys": [
+                {"kty": "EC", "crv": "P-256", "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU", "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0"},
+                {"kty": "RSA", "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw", "e": "AQAB"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(key_algorithm(&key_set.keys[0]), Some(Algorithm::ES256));
+        assert_eq!(key_algorithm(&key_set.keys[1]), None);
+    }
+
+    #[test]
+    fn test_authenticator_from_config() {
+        let mut config = Config::default();
+        config.rest_auth_jwks_path = "does-not-exist.json".to_string();
+        assert_eq!(
+            Authenticator::try_from_config(&config).unwrap_err(),
+            AuthError::KeySet
+        );
+
+        let path = std::env::temp_dir().join("svc-cargo-test-jwks.json");
+        std::fs::write(&path, KEY_SET).unwrap();
+        config.rest_auth_jwks_path = path.to_string_lossy().to_string();
+        let authenticator = Authenticator::try_from_config(&config).unwrap();
+        assert_eq!(authenticator.keys.len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_auth_error_display() {
+        assert_eq!(AuthError::KeySet.to_string(), "could not load key set");
+        assert_eq!(AuthError::MissingToken.to_string(), "missing bearer token");
+        assert_eq!(AuthError::UnknownKey.to_string(), "unknown signing key");
+        assert_eq!(AuthError::InvalidToken.to_string(), "invalid token");
+        assert_eq!(
+            AuthError::Subject.to_string(),
+            "token subject is not a user ID"
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! Idempotent retries of mutating routes
+//!
+//! Clients may send an `Idempotency-Key` header with a unique value per
+//!  operation. The first response for a key is kept in the cache for the
+//!  configured window and replayed when the request is retried, so a retry
+//!  after a network failure does not book or scan twice. Keys are scoped to
+//!  the client and route, and may not be reused with a different body.
+
+use super::limit::ClientKey;
+use crate::cache::pool::{get_pool, CacheError, ItineraryPool};
+use crate::service::{sha256_hex, ServiceError};
+use axum::body::{boxed, Body, Bytes, Full};
+use axum::extract::State;
+use axum::http::{header, HeaderName, HeaderValue, Method, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use hyper::body::HttpBody;
+use hyper::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Header carrying the client's key for an operation
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Header set on responses replayed from the cache
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Longest accepted idempotency key
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Largest request body kept for comparison with retries
+const MAX_BODY_BYTES: usize = 64 * 1024;
+
+/// Response headers replayed with the response
+const REPLAYED_HEADERS: [HeaderName; 2] = [header::CONTENT_TYPE, header::LOCATION];
+
+/// The outcome of the first request made with an idempotency key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdempotencyRecord {
+    /// SHA-256 of the request body
+    pub fingerprint: String,
+
+    /// The response status, none while the first request is running
+    pub status: Option<u16>,
+
+    /// The replayed response headers
+    pub headers: Vec<(String, String)>,
+
+    /// The response body
+    pub body: String,
+}
+
+/// Settings of the [`idempotent`] middleware
+#[derive(Clone)]
+pub struct IdempotencyState {
+    /// Seconds a response is replayed for
+    pub window: usize,
+
+    /// Seconds a key is held while its first request runs
+    pub lease: usize,
+
+    /// API keys identifying clients, see [`ClientKey`]
+    pub api_keys: Arc<HashSet<String>>,
+}
+
+impl IdempotencyState {
+    /// Replay responses for `window` seconds, holding keys for `lease`
+    ///  seconds while the first request runs. Keys of clients sending one
+    ///  of `api_keys` are scoped to that key.
+    pub fn new(window: usize, lease: usize, api_keys: &[String]) -> Self {
+        Self {
+            window,
+            lease,
+            api_keys: Arc::new(
+                api_keys
+                    .iter()
+                    .filter(|key| !key.is_empty())
+                    .cloned()
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The client's key, if it is a short printable string
+fn valid_key(value: &HeaderValue) -> Option<&str> {
+    value
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+}
+
+/// The cache key of a client's idempotency key for a route
+fn cache_key(client: &ClientKey, method: &Method, path: &str, key: &str) -> String {
+    sha256_hex(format!("{client:?}\n{method}\n{path}\n{key}").as_bytes())
+}
+
+/// Read a request body, up to [`MAX_BODY_BYTES`]
+async fn read_body(mut body: Body) -> Result<Bytes, StatusCode> {
+    let mut data = vec![];
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| {
+            rest_warn!("could not read request body: {e}");
+            StatusCode::BAD_REQUEST
+        })?;
+
+        if data.len() + chunk.len() > MAX_BODY_BYTES {
+            rest_warn!("request body too large for idempotent request.");
+            return Err(StatusCode::PAYLOAD_TOO_LARGE);
+        }
+
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(Bytes::from(data))
+}
+
+/// Status code reported for cache errors
+fn cache_status(e: CacheError) -> StatusCode {
+    rest_error!("idempotency cache error: {e}");
+    StatusCode::from(ServiceError::from(e))
+}
+
+/// Record the first request made with a key, holding the key for `lease`
+///  seconds until the response is recorded
+/// Returns false if the key was already used.
+async fn claim(key: &str, record: &IdempotencyRecord, lease: usize) -> Result<bool, StatusCode> {
+    get_pool()
+        .await
+        .map_err(cache_status)?
+        .lock()
+        .await
+        .claim_idempotency_key(key.to_string(), record, lease)
+        .await
+        .map_err(cache_status)
+}
+
+/// Release a key, so that the client can retry the request
+async fn release(key: &str) {
+    let result = match get_pool().await {
+        Ok(pool) => {
+            pool.lock()
+                .await
+                .remove_idempotency_key(key.to_string())
+                .await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        rest_error!("could not release idempotency key: {e}");
+    }
+}
+
+/// The response to a retried request
+async fn replay(key: &str, fingerprint: &str) -> Result<Response, StatusCode> {
+    let record = get_pool()
+        .await
+        .map_err(cache_status)?
+        .lock()
+        .await
+        .get_idempotency_record(key.to_string())
+        .await
+        .map_err(|e| match e {
+            // released or expired since it was claimed
+            CacheError::NotFound => StatusCode::CONFLICT,
+            e => cache_status(e),
+        })?;
+
+    if record.fingerprint != fingerprint {
+        rest_warn!("idempotency key reused with a different request body.");
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    let Some(status) = record.status else {
+        rest_info!("request with the same idempotency key in progress.");
+        return Err(StatusCode::CONFLICT);
+    };
+
+    let status = StatusCode::from_u16(status).map_err(|_| {
+        rest_error!("invalid status in idempotency record: {status}");
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    rest_info!("replaying response to idempotent request.");
+    let mut response = (status, record.body).into_response();
+    let headers = response.headers_mut();
+    headers.remove(header::CONTENT_TYPE);
+    for (name, value) in record.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            headers.insert(name, value);
+        }
+    }
+
+    headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    Ok(response)
+}
+
+/// Keep the response to the first request made with a key for `window`
+///  seconds
+/// Server errors are not kept, the request can be retried with the same key.
+async fn record(key: &str, fingerprint: String, response: Response, window: usize) -> Response {
+    if response.status().is_server_error() {
+        release(key).await;
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = hyper::body::to_bytes(body).await else {
+        rest_error!("could not read response body.");
+        release(key).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let record = IdempotencyRecord {
+        fingerprint,
+        status: Some(parts.status.as_u16()),
+        headers: REPLAYED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = parts.headers.get(name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect(),
+        body: String::from_utf8_lossy(&bytes).to_string(),
+    };
+
+    let result = match get_pool().await {
+        Ok(pool) => {
+            pool.lock()
+                .await
+                .store_idempotency_record(key.to_string(), &record, window)
+                .await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        // the pending record stays, retries get a conflict until it expires
+        rest_error!("could not keep response to idempotent request: {e}");
+    }
+
+    Response::from_parts(parts, boxed(Full::from(bytes)))
+}
+
+/// Middleware replaying the response to requests retried with the same
+///  `Idempotency-Key` within the configured window
+/// Requests without the header are passed through.
+pub async fn idempotent(
+    State(state): State<IdempotencyState>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let Some(value) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+
+    let Some(idempotency_key) = valid_key(value) else {
+        rest_warn!("invalid idempotency key.");
+        return (
+            StatusCode::BAD_REQUEST,
+            "(server) invalid idempotency key.".to_string(),
+        )
+            .into_response();
+    };
+
+    let key = cache_key(
+        &ClientKey::from_request(&request, &state.api_keys),
+        request.method(),
+        request.uri().path(),
+        idempotency_key,
+    );
+
+    let (parts, body) = request.into_parts();
+    let bytes = match read_body(body).await {
+        Ok(bytes) => bytes,
+        Err(code) => return code.into_response(),
+    };
+
+    let pending = IdempotencyRecord {
+        fingerprint: sha256_hex(&bytes),
+        status: None,
+        headers: vec![],
+        body: String::new(),
+    };
+
+    match claim(&key, &pending, state.lease).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return replay(&key, &pending.fingerprint)
+                .await
+                .unwrap_or_else(IntoResponse::into_response)
+        }
+        Err(code) => return code.into_response(),
+    }
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(bytes)))
+        .await;
+
+    record(&key, pending.fingerprint, response, state.window).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    #[test]
+    fn test_valid_key() {
+        let value = HeaderValue::from_static("key");
+        assert_eq!(valid_key(&value), Some("key"));
+
+        let value = HeaderValue::from_static("");
+        assert!(valid_key(&value).is_none());
+
+        let value = HeaderValue::from_str(&"a".repeat(MAX_KEY_LENGTH + 1)).unwrap();
+        assert!(valid_key(&value).is_none());
+
+        let value = HeaderValue::from_bytes(b"\xff").unwrap();
+        assert!(valid_key(&value).is_none());
+    }
+
+    #[test]
+    fn test_idempotency_state_drops_empty_keys() {
+        let state = IdempotencyState::new(60, 10, &["key".to_string(), String::new()]);
+        assert_eq!(state.window, 60);
+        assert_eq!(state.lease, 10);
+        assert_eq!(state.api_keys.len(), 1);
+        assert!(state.api_keys.contains("key"));
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let user = ClientKey::User("user".to_string());
+        let other = ClientKey::User("other".to_string());
+        let key = cache_key(&user, &Method::PUT, "/cargo/create", "key");
+
+        assert_eq!(key, cache_key(&user, &Method::PUT, "/cargo/create", "key"));
+        assert_ne!(key, cache_key(&other, &Method::PUT, "/cargo/create", "key"));
+        assert_ne!(
+            key,
+            cache_key(&user, &Method::DELETE, "/cargo/create", "key")
+        );
+        assert_ne!(key, cache_key(&user, &Method::PUT, "/cargo/cancel", "key"));
+        assert_ne!(
+            key,
+            cache_key(&user, &Method::PUT, "/cargo/create", "other")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_read_body() {
+        let bytes = read_body(Body::from("body")).await.unwrap();
+        assert_eq!(bytes, Bytes::from("body"));
+
+        let error = read_body(Body::from(vec![0; MAX_BODY_BYTES + 1]))
+            .await
+            .unwrap_err();
+        assert_eq!(error, StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn test_claim_record_replay() {
+        let key = Uuid::new_v4().to_string();
+        let fingerprint = sha256_hex(b"body");
+        let pending = IdempotencyRecord {
+            fingerprint: fingerprint.clone(),
+            status: None,
+            headers: vec![],
+            body: String::new(),
+        };
+
+        assert!(claim(&key, &pending, 60).await.unwrap());
+        assert!(!claim(&key, &pending, 60).await.unwrap());
+
+        // the first request is still running
+        let error = replay(&key, &fingerprint).await.unwrap_err();
+        assert_eq!(error, StatusCode::CONFLICT);
+
+        let mut response = (StatusCode::ACCEPTED, "{\"id\":\"job\"}").into_response();
+        response.headers_mut().insert(
+            header::LOCATION,
+            HeaderValue::from_static("/cargo/create/job"),
+        );
+        let response = record(&key, fingerprint.clone(), response, 60).await;
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let replayed = replay(&key, &fingerprint).await.unwrap();
+        assert_eq!(replayed.status(), StatusCode::ACCEPTED);
+        assert_eq!(replayed.headers()[header::LOCATION], "/cargo/create/job");
+        assert_eq!(replayed.headers()[IDEMPOTENT_REPLAYED_HEADER], "true");
+        let body = hyper::body::to_bytes(replayed.into_body()).await.unwrap();
+        assert_eq!(body, Bytes::from("{\"id\":\"job\"}"));
+
+        // same key, different body
+        let error = replay(&key, &sha256_hex(b"other")).await.unwrap_err();
+        assert_eq!(error, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_server_errors_release_key() {
+        let key = Uuid::new_v4().to_string();
+        let pending = IdempotencyRecord {
+            fingerprint: sha256_hex(b"body"),
+            status: None,
+            headers: vec![],
+            body: String::new(),
+        };
+
+        assert!(claim(&key, &pending, 60).await.unwrap());
+        let response = StatusCode::SERVICE_UNAVAILABLE.into_response();
+        let response = record(&key, pending.fingerprint.clone(), response, 60).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // the request can be retried
+        assert!(claim(&key, &pending, 60).await.unwrap());
+    }
+}
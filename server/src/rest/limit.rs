@@ -0,0 +1,315 @@
+//! Per-client rate limiting
+//!
+//! Each route class has its own [`RateLimiter`] holding a token bucket per
+//!  client, so a single noisy client only exhausts its own bucket. Clients
+//!  are identified by the authenticated user, then the `x-api-key` header
+//!  if the key is configured, then the remote IP address.
+
+use super::auth::Principal;
+use axum::extract::{ConnectInfo, State};
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use hyper::StatusCode;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+
+/// Header identifying integrations that call without a user token
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Number of tracked clients above which full buckets are dropped
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// The identity a request is rate limited by
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientKey {
+    /// Authenticated user ID
+    User(String),
+
+    /// Configured API key
+    ApiKey(String),
+
+    /// Remote address
+    Ip(IpAddr),
+}
+
+impl Display for ClientKey {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ClientKey::User(user_id) => write!(f, "user {user_id}"),
+            // API keys are secrets, never log them
+            ClientKey::ApiKey(_) => write!(f, "API key"),
+            ClientKey::Ip(ip) => write!(f, "{ip}"),
+        }
+    }
+}
+
+impl ClientKey {
+    /// The key of a request, from the most to the least specific identity
+    /// Unknown API keys are ignored, so clients can't get a fresh bucket by
+    ///  sending a new key with each request.
+    pub fn from_request<B>(request: &Request<B>, api_keys: &HashSet<String>) -> Self {
+        if let Some(principal) = request.extensions().get::<Principal>() {
+            return ClientKey::User(principal.user_id.clone());
+        }
+
+        if let Some(api_key) = request
+            .headers()
+            .get(API_KEY_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| api_keys.contains(*value))
+        {
+            return ClientKey::ApiKey(api_key.to_string());
+        }
+
+        // Forwarded-for headers are not trusted, anyone can set them
+        let ip = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())
+            .unwrap_or(IpAddr::V6(Ipv6Addr::UNSPECIFIED));
+
+        ClientKey::Ip(ip)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket rate limiter keyed by client
+pub struct RateLimiter {
+    /// Tokens added per second
+    rate: f64,
+
+    /// Maximum tokens in a bucket
+    burst: f64,
+
+    /// API keys that identify a client
+    api_keys: HashSet<String>,
+
+    buckets: Mutex<HashMap<ClientKey, Bucket>>,
+}
+
+impl fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Never log the API keys
+        f.debug_struct("RateLimiter")
+            .field("rate", &self.rate)
+            .field("burst", &self.burst)
+            .field("api_keys", &self.api_keys.len())
+            .finish()
+    }
+}
+
+impl RateLimiter {
+    /// Allow `requests_per_second` on average, with bursts of up to `burst`
+    ///  requests. Zero values are raised to one.
+    pub fn new(requests_per_second: u32, burst: u32) -> Self {
+        RateLimiter {
+            rate: requests_per_second.max(1) as f64,
+            burst: burst.max(1) as f64,
+            api_keys: HashSet::new(),
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Limit requests carrying one of these API keys by key
+    pub fn with_api_keys(mut self, api_keys: &[String]) -> Self {
+        self.api_keys = api_keys
+            .iter()
+            .filter(|key| !key.is_empty())
+            .cloned()
+            .collect();
+        self
+    }
+
+    /// Take a token from the client's bucket. If the bucket is empty,
+    ///  returns how long the client should wait before trying again.
+    pub fn check(&self, key: &ClientKey) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = match self.buckets.lock() {
+            Ok(buckets) => buckets,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(key) {
+            // Full buckets hold no state worth keeping
+            let (rate, burst) = (self.rate, self.burst);
+            buckets.retain(|_, bucket| {
+                let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+                bucket.tokens + elapsed * rate < burst
+            });
+        }
+
+        let bucket = buckets.entry(key.clone()).or_insert(Bucket {
+            tokens: self.burst,
+            updated: now,
+        });
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.rate).min(self.burst);
+        bucket.updated = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            return Ok(());
+        }
+
+        Err(Duration::from_secs_f64((1.0 - bucket.tokens) / self.rate))
+    }
+}
+
+/// 429 response telling the client when to retry
+pub fn too_many_requests(retry_after: Duration) -> Response {
+    // Retry-After takes whole seconds, round up so the retry succeeds
+    let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let mut response = (
+        StatusCode::TOO_MANY_REQUESTS,
+        "(server) too many requests.".to_string(),
+    )
+        .into_response();
+
+    response
+        .headers_mut()
+        .insert(header::RETRY_AFTER, HeaderValue::from(seconds.max(1)));
+
+    response
+}
+
+/// Middleware rejecting requests of clients that exceeded their limit
+pub async fn rate_limit<B>(
+    State(limiter): State<Arc<RateLimiter>>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let key = ClientKey::from_request(&request, &limiter.api_keys);
+    match limiter.check(&key) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            rest_warn!("too many requests from {key}.");
+            too_many_requests(retry_after)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use std::net::Ipv4Addr;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter() {
+        let limiter = RateLimiter::new(2, 3);
+        let key = ClientKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST));
+        let other = ClientKey::User("user".to_string());
+
+        // burst
+        for _ in 0..3 {
+            limiter.check(&key).unwrap();
+        }
+
+        let retry_after = limiter.check(&key).unwrap_err();
+        assert_eq!(retry_after, Duration::from_millis(500));
+
+        // other clients are not affected
+        limiter.check(&other).unwrap();
+
+        // refill at the configured rate
+        tokio::time::advance(Duration::from_millis(500)).await;
+        limiter.check(&key).unwrap();
+        limiter.check(&key).unwrap_err();
+
+        // never more than the burst
+        tokio::time::advance(Duration::from_secs(60)).await;
+        for _ in 0..3 {
+            limiter.check(&key).unwrap();
+        }
+        limiter.check(&key).unwrap_err();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_zero_config() {
+        let limiter = RateLimiter::new(0, 0);
+        let key = ClientKey::ApiKey("key".to_string());
+        limiter.check(&key).unwrap();
+        assert_eq!(limiter.check(&key).unwrap_err(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_drops_full_buckets() {
+        let limiter = RateLimiter::new(1, 1);
+        for i in 0..MAX_TRACKED_CLIENTS {
+            limiter.check(&ClientKey::User(i.to_string())).unwrap();
+        }
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        limiter.check(&ClientKey::User("new".to_string())).unwrap();
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_client_key_from_request() {
+        let limiter = RateLimiter::new(1, 1).with_api_keys(&["key".to_string(), String::new()]);
+        let api_keys = &limiter.api_keys;
+        let addr = SocketAddr::from((Ipv4Addr::new(10, 0, 0, 1), 1234));
+        let mut request = Request::builder()
+            .header(API_KEY_HEADER, "key")
+            .body(Body::empty())
+            .unwrap();
+        request.extensions_mut().insert(ConnectInfo(addr));
+        assert_eq!(
+            ClientKey::from_request(&request, api_keys),
+            ClientKey::ApiKey("key".to_string())
+        );
+
+        request.extensions_mut().insert(Principal {
+            user_id: "user".to_string(),
+        });
+        assert_eq!(
+            ClientKey::from_request(&request, api_keys),
+            ClientKey::User("user".to_string())
+        );
+
+        // unknown or empty keys are limited by address
+        for api_key in ["unknown", ""] {
+            let mut request = Request::builder()
+                .header(API_KEY_HEADER, api_key)
+                .body(Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(ConnectInfo(addr));
+            assert_eq!(
+                ClientKey::from_request(&request, api_keys),
+                ClientKey::Ip(addr.ip())
+            );
+        }
+
+        let request = Request::builder().body(Body::empty()).unwrap();
+        assert_eq!(
+            ClientKey::from_request(&request, api_keys),
+            ClientKey::Ip(IpAddr::V6(Ipv6Addr::UNSPECIFIED))
+        );
+    }
+
+    #[test]
+    fn test_client_key_display_hides_api_key() {
+        let key = ClientKey::ApiKey("secret".to_string());
+        assert!(!key.to_string().contains("secret"));
+    }
+
+    #[test]
+    fn test_too_many_requests() {
+        let response = too_many_requests(Duration::from_millis(1500));
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "2");
+
+        let response = too_many_requests(Duration::from_millis(10));
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+}
@@ -1,12 +1,16 @@
 #[macro_use]
 pub mod macros;
+pub mod auth;
+pub mod idempotency;
+pub mod limit;
 pub mod server;
 
 pub(crate) mod api;
 use api::*;
 
 use std::fmt::{self, Display, Formatter};
-use utoipa::OpenApi;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
 /// OpenAPI 3.0 specification for this service
 #[derive(OpenApi, Copy, Clone, Debug)]
@@ -15,10 +19,23 @@ use utoipa::OpenApi;
         request::request_flight,
         query::query_vertiports,
         create::create_itinerary,
+        create::get_booking,
+        draft::get_draft,
+        draft::refresh_draft,
         cancel::cancel_itinerary,
+        itinerary::query_itineraries,
+        itinerary::get_itinerary,
+        itinerary::query_modifications,
+        itinerary::modify_itinerary,
+        notification::get_notification_settings,
+        notification::update_notification_settings,
+        label::get_parcel_label,
+        delivery::issue_delivery_pin,
         scan::scan_parcel,
+        scan::confirm_delivery,
         query::query_occupations,
         query::query_scans,
+        query::watch_parcel,
         health::health_check
     ),
     components(
@@ -28,28 +45,74 @@ use utoipa::OpenApi;
             rest_types::Vertiport,
             rest_types::QueryVertiportsRequest,
             rest_types::ItineraryCancelRequest,
+            rest_types::ItineraryCancelResponse,
             rest_types::QueryItineraryRequest,
             rest_types::DraftItinerary,
+            rest_types::DraftItineraryDetails,
             rest_types::ItineraryCreateRequest,
+            rest_types::BookingJob,
+            rest_types::BookingJobStatus,
             rest_types::CargoScan,
             rest_types::TimeWindow,
             rest_types::Occupation,
             rest_types::CargoInfo,
+            rest_types::Parcel,
+            rest_types::Recipient,
+            rest_types::HandlingClass,
             rest_types::CurrencyUnit,
             rest_types::QueryScheduleRequest,
             rest_types::QueryScheduleResponse,
             rest_types::QueryParcelResponse,
+            rest_types::DeliveryConfirmation,
+            rest_types::DeliveryProof,
+            rest_types::DeliveryPin,
             rest_types::GeoPointZ,
             rest_types::PaymentInfo,
-            rest_types::InvoiceItem
+            rest_types::InvoiceItem,
+            rest_types::ItineraryStatus,
+            rest_types::ParcelStatus,
+            rest_types::ParcelInfo,
+            rest_types::BookedItinerary,
+            rest_types::QueryItinerariesRequest,
+            rest_types::QueryItinerariesResponse,
+            rest_types::ParcelDetails,
+            rest_types::ItineraryDetails,
+            rest_types::ItineraryModifyRequest,
+            rest_types::ItineraryModifyConfirmRequest,
+            rest_types::ItineraryModifyResponse,
+            rest_types::NotificationSettings,
+            rest_types::ParcelLabel,
+            rest_types::LabelFormat,
+            rest_types::LabelQuery
         )
     ),
+    modifiers(&BearerAuth),
     tags(
         (name = "svc-cargo", description = "svc-cargo REST API")
     )
 )]
 pub struct ApiDoc;
 
+/// Adds the bearer token security scheme used by user routes
+#[derive(Copy, Clone, Debug)]
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+    }
+}
+
 /// Errors with OpenAPI generation
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum OpenApiError {
@@ -109,6 +172,13 @@ mod tests {
         // assert_eq!(error, OpenApiError::Json);
     }
 
+    #[test]
+    fn test_openapi_bearer_scheme() {
+        let openapi = ApiDoc::openapi();
+        let components = openapi.components.unwrap();
+        assert!(components.security_schemes.contains_key("bearer"));
+    }
+
     #[test]
     fn test_openapi_error_display() {
         assert_eq!(
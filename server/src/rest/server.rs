@@ -1,21 +1,24 @@
 //! Rest server implementation
 
 use super::api;
+use super::auth::{self, Authenticator};
+use super::idempotency;
+use super::limit::{self, RateLimiter};
 use crate::grpc::client::get_clients;
+use crate::service::cancel::CancelPolicy;
+use crate::service::label::LabelSigner;
+use crate::service::scan::ScanPolicy;
 use crate::shutdown_signal;
 use crate::Config;
 use axum::{
     error_handling::HandleErrorLayer,
     extract::Extension,
     http::{HeaderValue, StatusCode},
-    routing, BoxError, Router,
+    middleware, routing, BoxError, Router,
 };
 use std::net::SocketAddr;
-use tower::{
-    buffer::BufferLayer,
-    limit::{ConcurrencyLimitLayer, RateLimitLayer},
-    ServiceBuilder,
-};
+use std::sync::Arc;
+use tower::{buffer::BufferLayer, limit::ConcurrencyLimitLayer, ServiceBuilder};
 use tower_http::cors::{Any, CorsLayer};
 use tower_http::trace::TraceLayer;
 
@@ -48,8 +51,7 @@ pub async fn rest_server(
             rest_error!("invalid cors_allowed_origin address: {:?}, exiting.", e);
         })?;
 
-    // Rate limiting
-    let rate_limit = config.rest_request_limit_per_second as u64;
+    // Concurrency limiting, requests are rate limited per client below
     let concurrency_limit = config.rest_concurrency_limit_per_service as usize;
     let limit_middleware = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
@@ -61,11 +63,39 @@ pub async fn rest_server(
             )
         }))
         .layer(BufferLayer::new(100))
-        .layer(ConcurrencyLimitLayer::new(concurrency_limit))
-        .layer(RateLimitLayer::new(
-            rate_limit,
-            std::time::Duration::from_secs(1),
-        ));
+        .layer(ConcurrencyLimitLayer::new(concurrency_limit));
+
+    // Rate limiting per client, for each class of routes
+    let user_limiter = Arc::new(
+        RateLimiter::new(
+            config.rest_user_limit_per_second,
+            config.rest_user_limit_burst,
+        )
+        .with_api_keys(&config.rest_api_keys),
+    );
+    let scan_limiter = Arc::new(
+        RateLimiter::new(
+            config.rest_scan_limit_per_second,
+            config.rest_scan_limit_burst,
+        )
+        .with_api_keys(&config.rest_api_keys),
+    );
+    let query_limiter = Arc::new(
+        RateLimiter::new(
+            config.rest_query_limit_per_second,
+            config.rest_query_limit_burst,
+        )
+        .with_api_keys(&config.rest_api_keys),
+    );
+
+    // Responses replayed for retries with the same Idempotency-Key
+    let idempotency = idempotency::IdempotencyState::new(
+        config.rest_idempotency_window_seconds as usize,
+        config.rest_idempotency_lease_seconds as usize,
+        &config.rest_api_keys,
+    );
+    let idempotent =
+        || middleware::from_fn_with_state(idempotency.clone(), idempotency::idempotent);
 
     //
     // Extensions
@@ -73,27 +103,112 @@ pub async fn rest_server(
     // GRPC Clients
     let grpc_clients = get_clients().await;
 
-    let app = Router::new()
-        .route("/health", routing::get(api::health::health_check))
+    //
+    // Authentication
+    //
+    // User routes can't be served without a key set
+    let authenticator = Arc::new(Authenticator::try_from_config(&config).map_err(|e| {
+        rest_error!("{e}, exiting.");
+    })?);
+
+    // Routes acting on behalf of a user, who is taken from the bearer token
+    let user_routes = Router::new()
         .route(
             "/cargo/cancel",
-            routing::delete(api::cancel::cancel_itinerary),
+            routing::delete(api::cancel::cancel_itinerary).route_layer(idempotent()),
         )
         .route(
             "/cargo/request",
             routing::post(api::request::request_flight),
         )
-        .route("/cargo/create", routing::put(api::create::create_itinerary))
+        .route(
+            "/cargo/create",
+            routing::put(api::create::create_itinerary).route_layer(idempotent()),
+        )
+        .route(
+            "/cargo/create/:job_id",
+            routing::get(api::create::get_booking),
+        )
+        .route("/cargo/draft/:id", routing::get(api::draft::get_draft))
+        .route(
+            "/cargo/draft/:id/refresh",
+            routing::post(api::draft::refresh_draft),
+        )
+        .route(
+            "/cargo/itineraries",
+            routing::get(api::itinerary::query_itineraries),
+        )
+        .route(
+            "/cargo/itinerary/:id",
+            routing::get(api::itinerary::get_itinerary),
+        )
+        .route(
+            "/cargo/itinerary/:id/modify",
+            routing::post(api::itinerary::query_modifications)
+                .put(api::itinerary::modify_itinerary),
+        )
+        .route(
+            "/cargo/notifications",
+            routing::get(api::notification::get_notification_settings)
+                .put(api::notification::update_notification_settings),
+        )
+        .route(
+            "/cargo/parcel/:id/label",
+            routing::get(api::label::get_parcel_label),
+        )
+        // Not idempotent, PINs are not kept for replays
+        .route(
+            "/cargo/parcel/:id/pin",
+            routing::post(api::delivery::issue_delivery_pin),
+        )
+        // Authenticate first, so users are limited by their ID
+        .route_layer(middleware::from_fn_with_state(
+            user_limiter,
+            limit::rate_limit,
+        ))
+        .route_layer(middleware::from_fn_with_state(
+            authenticator,
+            auth::require_principal,
+        ));
+
+    let scan_routes = Router::new()
+        .route(
+            "/cargo/scan",
+            routing::put(api::scan::scan_parcel).route_layer(idempotent()),
+        )
+        .route(
+            "/cargo/deliver",
+            routing::put(api::scan::confirm_delivery).route_layer(idempotent()),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            scan_limiter,
+            limit::rate_limit,
+        ));
+
+    let query_routes = Router::new()
         .route(
             "/cargo/vertiports",
             routing::post(api::query::query_vertiports),
         )
-        .route("/cargo/scan", routing::put(api::scan::scan_parcel))
         .route("/cargo/track/:id", routing::get(api::query::query_scans))
+        .route(
+            "/cargo/track/:id/watch",
+            routing::get(api::query::watch_parcel),
+        )
         .route(
             "/cargo/occupations",
             routing::post(api::query::query_occupations),
         )
+        .route_layer(middleware::from_fn_with_state(
+            query_limiter,
+            limit::rate_limit,
+        ));
+
+    let app = Router::new()
+        .route("/health", routing::get(api::health::health_check))
+        .merge(user_routes)
+        .merge(scan_routes)
+        .merge(query_routes)
         .layer(
             CorsLayer::new()
                 .allow_origin(cors_allowed_origin)
@@ -101,13 +216,16 @@ pub async fn rest_server(
                 .allow_methods(Any),
         )
         .layer(limit_middleware)
+        .layer(Extension(CancelPolicy::from(&config)))
+        .layer(Extension(ScanPolicy::from(&config)))
+        .layer(Extension(LabelSigner::from(&config)))
         .layer(Extension(grpc_clients)); // Extension layer must be last
 
     //
     // Bind to address
     //
     match axum::Server::bind(&full_rest_addr)
-        .serve(app.into_make_service())
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
         .with_graceful_shutdown(shutdown_signal("rest", shutdown_rx))
         .await
     {
@@ -132,7 +250,10 @@ mod tests {
         lib_common::logger::get_log_handle().await;
         ut_info!("start");
 
-        let config = Config::default();
+        let mut config = Config::default();
+        let path = std::env::temp_dir().join("svc-cargo-test-server-jwks.json");
+        std::fs::write(&path, r#"{"keys": []}"#).unwrap();
+        config.rest_auth_jwks_path = path.to_string_lossy().to_string();
 
         let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
 
@@ -144,6 +265,19 @@ mod tests {
 
         // Shut down server
         assert!(shutdown_tx.send(()).is_ok());
+        std::fs::remove_file(&path).unwrap();
+
+        ut_info!("success");
+    }
+
+    #[tokio::test]
+    async fn test_server_start_without_key_set() {
+        lib_common::logger::get_log_handle().await;
+        ut_info!("start");
+
+        let mut config = Config::default();
+        config.rest_auth_jwks_path = "does-not-exist.json".to_string();
+        assert!(rest_server(config, None).await.is_err());
 
         ut_info!("success");
     }
@@ -0,0 +1,195 @@
+//! Booking jobs
+//!
+//! Drafts are booked in the background so that requests return before
+//!  svc-scheduler has processed the itinerary. The job state is kept in
+//!  the cache until it expires.
+
+use super::create::create_itinerary;
+use super::draft::load_user_draft;
+use super::ServiceError;
+use crate::cache::pool::{get_pool, ItineraryPool};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{BookingJob, BookingJobStatus};
+use lib_common::uuid::{to_uuid, Uuid};
+
+/// Store the state of a booking job in the cache
+async fn store_job(job: &BookingJob) -> Result<(), ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .store_booking_job(job)
+        .await
+        .map_err(|e| {
+            service_error!("unable to store booking job in redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get the state of a booking job from the cache
+async fn load_job(job_id: &str) -> Result<BookingJob, ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .get_booking_job(job_id.to_string())
+        .await
+        .map_err(|e| {
+            service_error!("unable to get booking job from redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Book the draft of a job and record the outcome
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+async fn run_job(mut job: BookingJob, grpc_clients: GrpcClients) {
+    match create_itinerary(&job.draft_id, &job.user_id, &grpc_clients).await {
+        Ok(booking) => {
+            service_info!(
+                "booking job {} created itinerary {}.",
+                job.id,
+                booking.itinerary_id
+            );
+            job.status = BookingJobStatus::Complete;
+            job.itinerary_id = Some(booking.itinerary_id);
+            job.parcels = booking.parcels;
+        }
+        Err(e) => {
+            service_warn!("booking job {} failed: {e}", job.id);
+            job.status = BookingJobStatus::Failed;
+            job.error = Some(e.to_string());
+        }
+    }
+
+    if let Err(e) = store_job(&job).await {
+        service_error!("could not record outcome of booking job {}: {e}", job.id);
+    }
+}
+
+/// Start booking a draft itinerary of a user
+/// Returns the pending job; the itinerary is created with svc-scheduler,
+///  the parcel registered and the customer charged in the background.
+pub async fn start_booking(
+    draft_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<BookingJob, ServiceError> {
+    service_debug!("entry.");
+
+    // fail early on drafts that can't be booked
+    load_user_draft(draft_id, user_id).await?;
+
+    let job = BookingJob {
+        id: Uuid::new_v4().to_string(),
+        draft_id: draft_id.to_string(),
+        user_id: user_id.to_string(),
+        status: BookingJobStatus::Pending,
+        itinerary_id: None,
+        parcels: vec![],
+        error: None,
+    };
+
+    store_job(&job).await?;
+    tokio::spawn(run_job(job.clone(), grpc_clients.clone()));
+
+    service_info!("started booking job {} for draft {draft_id}.", job.id);
+    Ok(job)
+}
+
+/// Get a booking job of a user
+/// Jobs of other users are reported as not found.
+pub async fn get_booking(job_id: &str, user_id: &str) -> Result<BookingJob, ServiceError> {
+    service_debug!("entry.");
+
+    to_uuid(job_id).ok_or_else(|| {
+        service_error!("job ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let job = load_job(job_id).await?;
+    if job.user_id != user_id {
+        service_warn!("booking job {job_id} does not belong to user {user_id}.");
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_start_booking_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let user_id = Uuid::new_v4().to_string();
+
+        let error = start_booking("invalid", &user_id, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        let error = start_booking(&Uuid::new_v4().to_string(), "invalid", &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // expired or unknown drafts
+        let error = start_booking(&Uuid::new_v4().to_string(), &user_id, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_get_booking() {
+        let user_id = Uuid::new_v4().to_string();
+        let job = BookingJob {
+            id: Uuid::new_v4().to_string(),
+            draft_id: Uuid::new_v4().to_string(),
+            user_id: user_id.clone(),
+            status: BookingJobStatus::Failed,
+            itinerary_id: None,
+            parcels: vec![],
+            error: Some(ServiceError::Rejected.to_string()),
+        };
+        store_job(&job).await.unwrap();
+
+        let result = get_booking(&job.id, &user_id).await.unwrap();
+        assert_eq!(result.status, BookingJobStatus::Failed);
+        assert_eq!(result.error, job.error);
+
+        // jobs of other users
+        let error = get_booking(&job.id, &Uuid::new_v4().to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+
+        // expired or unknown jobs
+        let error = get_booking(&Uuid::new_v4().to_string(), &user_id)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+
+        // bad ids
+        let error = get_booking("invalid", &user_id).await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+        let error = get_booking(&job.id, "invalid").await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+}
@@ -0,0 +1,309 @@
+//! Itinerary cancellation
+
+use super::invoice::{invoice_total, load_invoice};
+use super::itinerary::{get_booked_itinerary, get_user_itinerary};
+use super::ServiceError;
+use crate::config::Config;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{
+    BookedItinerary, CurrencyUnit, ItineraryCancelResponse, ItineraryStatus,
+};
+use lib_common::time::{DateTime, Duration, Utc};
+use svc_scheduler_client_grpc::client::CancelItineraryRequest;
+use svc_scheduler_client_grpc::prelude::scheduler_storage::flight_plan::FlightPriority;
+use svc_scheduler_client_grpc::prelude::SchedulerServiceClient;
+use svc_storage_client_grpc::prelude::*;
+
+/// Cancellation deadlines relative to the departure of an itinerary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CancelPolicy {
+    /// Itineraries can't be cancelled closer to departure than this
+    pub cutoff: Duration,
+
+    /// Itineraries cancelled at least this long before departure are refunded
+    pub refund_cutoff: Duration,
+}
+
+/// Convert a number of minutes from the configuration to a [`Duration`]
+fn minutes(minutes: u32) -> Duration {
+    // u32 minutes are always within range
+    Duration::try_minutes(i64::from(minutes)).unwrap_or_else(Duration::zero)
+}
+
+impl From<&Config> for CancelPolicy {
+    fn from(config: &Config) -> Self {
+        CancelPolicy {
+            cutoff: minutes(config.cancel_cutoff_minutes),
+            refund_cutoff: minutes(config.cancel_refund_cutoff_minutes),
+        }
+    }
+}
+
+impl Default for CancelPolicy {
+    fn default() -> Self {
+        CancelPolicy::from(&Config::new())
+    }
+}
+
+/// Queue the cancellation of an itinerary with svc-scheduler
+pub(super) async fn scheduler_cancel(
+    itinerary_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    // Make request, process response
+    grpc_clients
+        .scheduler
+        .cancel_itinerary(CancelItineraryRequest {
+            priority: FlightPriority::Medium as i32,
+            itinerary_id: itinerary_id.to_string(),
+            user_id: user_id.to_string(),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("svc-scheduler request fail. {:?}", e);
+            ServiceError::Internal
+        })?;
+
+    service_info!("cancellation of {itinerary_id} added to scheduler queue.");
+    Ok(())
+}
+
+/// Refund the customer for a cancelled itinerary
+pub(super) async fn payment_refund(
+    // TODO(R5): user credential or UUID
+    _total: f32,
+    _currency_unit: CurrencyUnit,
+) -> Result<(), ServiceError> {
+    service_debug!("entry.");
+    //
+    // TODO(R5): payment service refund
+    //
+
+    Ok(())
+}
+
+/// Check that an itinerary can still be cancelled at `now`
+/// Returns whether the cancellation is early enough to be refunded.
+fn check_cancellable(
+    booked: &BookedItinerary,
+    policy: &CancelPolicy,
+    now: DateTime<Utc>,
+) -> Result<bool, ServiceError> {
+    if booked.status != ItineraryStatus::Active {
+        service_error!("itinerary {} is not active.", booked.id);
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    let departure = booked
+        .itinerary
+        .flight_plans
+        .first()
+        .map(|fp| fp.origin_timeslot_start)
+        .ok_or_else(|| {
+            service_error!("itinerary {} has no flight plans.", booked.id);
+            ServiceError::Internal
+        })?;
+
+    if now > departure - policy.cutoff {
+        service_warn!("itinerary {} departs too soon to cancel.", booked.id);
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    Ok(now <= departure - policy.refund_cutoff)
+}
+
+/// Cancel an itinerary of a user
+/// Queues the cancellation with svc-scheduler and removes the parcels
+///  registered for the itinerary. Itineraries of other users are reported
+///  as not found. Cancellations made early enough are refunded from the
+///  original invoice.
+pub async fn cancel_itinerary(
+    itinerary_id: &str,
+    user_id: &str,
+    policy: &CancelPolicy,
+    grpc_clients: &GrpcClients,
+) -> Result<ItineraryCancelResponse, ServiceError> {
+    service_debug!("entry.");
+
+    let object = get_user_itinerary(itinerary_id, user_id, grpc_clients).await?;
+    let booked = get_booked_itinerary(object, grpc_clients).await?;
+    let refund = check_cancellable(&booked, policy, Utc::now())?;
+
+    // Pricing is not kept in storage, refunds need the cached invoice.
+    // Bookings fail without one, so a missing invoice is not cancelled
+    //  without the refund it is owed.
+    let invoice = match refund {
+        true => Some(load_invoice(itinerary_id).await.map_err(|e| {
+            service_error!("no invoice to refund itinerary {itinerary_id}: {e}");
+            ServiceError::Internal
+        })?),
+        false => None,
+    };
+
+    scheduler_cancel(itinerary_id, user_id, grpc_clients).await?;
+
+    let futures = booked
+        .parcels
+        .iter()
+        .map(|parcel| async {
+            grpc_clients
+                .storage
+                .parcel
+                .delete(Id {
+                    id: parcel.id.clone(),
+                })
+                .await
+                .map_err(|e| {
+                    service_error!("svc-storage error: {:?}", e);
+                })
+        })
+        .collect::<Vec<_>>();
+
+    // If the customer's itinerary was cancelled, but the parcels were not, it's still a success for them
+    {
+        if !futures::future::join_all(futures)
+            .await
+            .into_iter()
+            .all(|r| r.is_ok())
+        {
+            service_error!("could not delete all parcels.");
+        }
+    }
+
+    let Some(invoice) = invoice else {
+        service_info!("cancelled itinerary {itinerary_id} without refund.");
+        return Ok(ItineraryCancelResponse {
+            itinerary_id: itinerary_id.to_string(),
+            refund: false,
+            refund_amount: 0.0,
+            currency_unit: booked.itinerary.currency_unit,
+        });
+    };
+
+    let refund_amount = invoice_total(&invoice);
+    if let Err(e) = payment_refund(refund_amount, invoice.currency_unit).await {
+        // The refund is owed, it is retried by the payment service
+        service_error!("could not refund itinerary {itinerary_id}: {e}");
+    }
+
+    service_info!(
+        "cancelled itinerary {itinerary_id} with a refund of {refund_amount} {:?}.",
+        invoice.currency_unit
+    );
+
+    Ok(ItineraryCancelResponse {
+        itinerary_id: itinerary_id.to_string(),
+        refund: true,
+        refund_amount,
+        currency_unit: invoice.currency_unit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::{FlightPlan, Itinerary};
+    use lib_common::uuid::Uuid;
+
+    fn booked_itinerary(departure: DateTime<Utc>) -> BookedItinerary {
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(Uuid::new_v4().to_string());
+        data.target_vertiport_id = Some(Uuid::new_v4().to_string());
+        data.origin_timeslot_start = Some(departure.into());
+
+        BookedItinerary {
+            id: Uuid::new_v4().to_string(),
+            status: ItineraryStatus::Active,
+            itinerary: Itinerary {
+                flight_plans: vec![FlightPlan::try_from(data).unwrap()],
+                ..Default::default()
+            },
+            parcels: vec![],
+        }
+    }
+
+    #[test]
+    fn test_cancel_policy_from_config() {
+        let mut config = Config::new();
+        config.cancel_cutoff_minutes = 30;
+        config.cancel_refund_cutoff_minutes = 120;
+
+        let policy = CancelPolicy::from(&config);
+        assert_eq!(policy.cutoff, Duration::try_minutes(30).unwrap());
+        assert_eq!(policy.refund_cutoff, Duration::try_minutes(120).unwrap());
+    }
+
+    #[test]
+    fn test_check_cancellable() {
+        let policy = CancelPolicy {
+            cutoff: Duration::try_hours(1).unwrap(),
+            refund_cutoff: Duration::try_hours(24).unwrap(),
+        };
+        let now = Utc::now();
+        let hour = Duration::try_hours(1).unwrap();
+
+        // refunded
+        let booked = booked_itinerary(now + hour * 48);
+        assert!(check_cancellable(&booked, &policy, now).unwrap());
+        let booked = booked_itinerary(now + hour * 24);
+        assert!(check_cancellable(&booked, &policy, now).unwrap());
+
+        // not refunded
+        let booked = booked_itinerary(now + hour * 2);
+        assert!(!check_cancellable(&booked, &policy, now).unwrap());
+        let booked = booked_itinerary(now + hour);
+        assert!(!check_cancellable(&booked, &policy, now).unwrap());
+
+        // too late
+        let booked = booked_itinerary(now + hour / 2);
+        let error = check_cancellable(&booked, &policy, now).unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+        let booked = booked_itinerary(now - hour);
+        let error = check_cancellable(&booked, &policy, now).unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // already cancelled
+        let mut booked = booked_itinerary(now + hour * 48);
+        booked.status = ItineraryStatus::Cancelled;
+        let error = check_cancellable(&booked, &policy, now).unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // no flight plans
+        let mut booked = booked_itinerary(now + hour * 48);
+        booked.itinerary.flight_plans.clear();
+        let error = check_cancellable(&booked, &policy, now).unwrap_err();
+        assert_eq!(error, ServiceError::Internal);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_itinerary() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let policy = CancelPolicy::default();
+        let valid_id = "00000000-0000-0000-0000-000000000000";
+
+        // invalid itinerary UUID
+        let error = cancel_itinerary("", valid_id, &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // invalid user UUID
+        let error = cancel_itinerary(valid_id, "", &policy, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // not in storage
+        let error = cancel_itinerary(
+            &Uuid::new_v4().to_string(),
+            valid_id,
+            &policy,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+}
@@ -0,0 +1,603 @@
+//! Compensation of failed bookings
+//!
+//! Once svc-scheduler has confirmed an itinerary, a booking that fails
+//!  later leaves a scheduled itinerary, a parcel and its links in
+//!  svc-storage, and a hold on the customer's payment option. A failed
+//!  modification also leaves the replacement itinerary, the links of the
+//!  moved parcels, a changed parcel weight and the charge for the
+//!  replacement. These are undone step by step. The outcome of each step is kept in the cache so
+//!  that failed steps can be retried, either in the background or with
+//!  [`retry_compensation`], exposed as the `retryCompensation` gRPC call.
+
+use super::cancel::{payment_refund, scheduler_cancel};
+use super::create::{payment_release, unlink_parcel};
+use super::modify::update_parcel_weight;
+use super::ServiceError;
+use crate::cache::pool::{get_pool, ItineraryPool};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{CurrencyUnit, Itinerary};
+use lib_common::uuid::to_uuid;
+use serde::{Deserialize, Serialize};
+use svc_storage_client_grpc::prelude::flight_plan_parcel::RowData as FlightPlanParcel;
+use svc_storage_client_grpc::prelude::Id;
+use svc_storage_client_grpc::simple_service::Client as SimpleClient;
+
+/// Number of background retries of failed compensation steps
+const COMPENSATION_RETRY_ATTEMPTS: u64 = 5;
+
+/// Delay before the first background retry, doubled for each retry
+const COMPENSATION_RETRY_INTERVAL_SECONDS: u64 = 30;
+
+/// A link of a parcel to a flight plan, as inserted in svc-storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParcelLink {
+    /// The flight plan carrying the parcel
+    pub flight_plan_id: String,
+
+    /// The linked parcel
+    pub parcel_id: String,
+
+    /// The parcel is acquired on this flight plan
+    pub acquire: bool,
+
+    /// The parcel is delivered on this flight plan
+    pub deliver: bool,
+}
+
+impl From<FlightPlanParcel> for ParcelLink {
+    fn from(record: FlightPlanParcel) -> Self {
+        ParcelLink {
+            flight_plan_id: record.flight_plan_id,
+            parcel_id: record.parcel_id,
+            acquire: record.acquire,
+            deliver: record.deliver,
+        }
+    }
+}
+
+impl From<ParcelLink> for FlightPlanParcel {
+    fn from(link: ParcelLink) -> Self {
+        FlightPlanParcel {
+            flight_plan_id: link.flight_plan_id,
+            parcel_id: link.parcel_id,
+            acquire: link.acquire,
+            deliver: link.deliver,
+        }
+    }
+}
+
+/// A change of a booking to undo, in the order they are undone
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompensationAction {
+    /// Remove the links of the parcel to the flight plans
+    UnlinkParcel,
+
+    /// Restore the weight of a parcel moved to a replacement itinerary
+    RestoreWeight,
+
+    /// Remove the parcel from svc-storage
+    DeleteParcel,
+
+    /// Cancel the itinerary with svc-scheduler
+    CancelItinerary,
+
+    /// Refund the amount charged to the customer
+    RefundPayment,
+
+    /// Release the hold on the customer's payment option
+    ReleasePayment,
+}
+
+/// The outcome of a compensation step
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StepOutcome {
+    /// Not yet attempted
+    Pending,
+
+    /// The change was undone
+    Done,
+
+    /// The last attempt failed, the step can be retried
+    Failed,
+}
+
+/// A compensation step and its outcome
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompensationStep {
+    /// The change to undo
+    pub action: CompensationAction,
+
+    /// The outcome of the last attempt
+    pub outcome: StepOutcome,
+
+    /// The number of attempts made
+    pub attempts: u32,
+
+    /// The error of the last failed attempt
+    pub error: Option<String>,
+}
+
+/// The changes made by a booking so far, and the steps undoing them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Compensation {
+    /// The itinerary confirmed by svc-scheduler
+    pub itinerary_id: String,
+
+    /// The user the itinerary was booked for
+    pub user_id: String,
+
+    /// The parcels registered with svc-storage
+    pub parcel_ids: Vec<String>,
+
+    /// The parcel links inserted in svc-storage
+    pub links: Vec<ParcelLink>,
+
+    /// The amount held on the customer's payment option
+    pub total: f32,
+
+    /// The currency of the held amount
+    pub currency_unit: CurrencyUnit,
+
+    /// The parcel whose weight was changed, with its previous weight
+    #[serde(default)]
+    pub weight: Option<(String, u32)>,
+
+    /// The held amount was charged
+    #[serde(default)]
+    pub charged: bool,
+
+    /// The hold was released
+    #[serde(default)]
+    pub released: bool,
+
+    /// The steps undoing the booking, empty until the compensation starts
+    pub steps: Vec<CompensationStep>,
+}
+
+impl Compensation {
+    /// Start tracking the changes of a booking confirmed by svc-scheduler
+    pub fn new(itinerary_id: &str, itinerary: &Itinerary, total: f32) -> Self {
+        Compensation {
+            itinerary_id: itinerary_id.to_string(),
+            user_id: itinerary.user_id.clone(),
+            parcel_ids: vec![],
+            links: vec![],
+            total,
+            currency_unit: itinerary.currency_unit,
+            weight: None,
+            charged: false,
+            released: false,
+            steps: vec![],
+        }
+    }
+
+    /// Plan the steps undoing the changes recorded so far
+    fn plan(&mut self) {
+        let mut actions = vec![];
+        if !self.links.is_empty() {
+            actions.push(CompensationAction::UnlinkParcel);
+        }
+
+        if self.weight.is_some() {
+            actions.push(CompensationAction::RestoreWeight);
+        }
+
+        if !self.parcel_ids.is_empty() {
+            actions.push(CompensationAction::DeleteParcel);
+        }
+
+        actions.push(CompensationAction::CancelItinerary);
+        if self.charged {
+            actions.push(CompensationAction::RefundPayment);
+        }
+
+        if !self.released {
+            actions.push(CompensationAction::ReleasePayment);
+        }
+
+        self.steps = actions
+            .into_iter()
+            .map(|action| CompensationStep {
+                action,
+                outcome: StepOutcome::Pending,
+                attempts: 0,
+                error: None,
+            })
+            .collect();
+    }
+
+    /// The actions that are not done yet
+    pub fn pending(&self) -> Vec<CompensationAction> {
+        self.steps
+            .iter()
+            .filter(|step| step.outcome != StepOutcome::Done)
+            .map(|step| step.action)
+            .collect()
+    }
+
+    /// Record the outcome of an attempt of a step
+    fn record(&mut self, action: CompensationAction, result: Result<(), ServiceError>) {
+        let Some(step) = self.steps.iter_mut().find(|step| step.action == action) else {
+            return;
+        };
+
+        step.attempts += 1;
+        match result {
+            Ok(()) => {
+                step.outcome = StepOutcome::Done;
+                step.error = None;
+            }
+            Err(e) => {
+                step.outcome = StepOutcome::Failed;
+                step.error = Some(e.to_string());
+            }
+        }
+    }
+
+    /// Whether all changes were undone
+    pub fn is_complete(&self) -> bool {
+        !self.steps.is_empty() && self.pending().is_empty()
+    }
+}
+
+/// Store the state of a compensation in the cache
+async fn store_compensation(compensation: &Compensation) -> Result<(), ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .store_compensation(compensation)
+        .await
+        .map_err(|e| {
+            service_error!("unable to store compensation in redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get the state of a compensation from the cache
+async fn load_compensation(itinerary_id: &str) -> Result<Compensation, ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .get_compensation(itinerary_id.to_string())
+        .await
+        .map_err(|e| {
+            service_error!("unable to get compensation from redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Undo a single change of a booking
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+async fn run_step(
+    action: CompensationAction,
+    compensation: &Compensation,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    match action {
+        CompensationAction::UnlinkParcel => {
+            let records: Vec<FlightPlanParcel> = compensation
+                .links
+                .iter()
+                .cloned()
+                .map(FlightPlanParcel::from)
+                .collect();
+
+            unlink_parcel(&records, grpc_clients).await
+        }
+        CompensationAction::RestoreWeight => match &compensation.weight {
+            Some((parcel_id, weight_g)) => {
+                update_parcel_weight(parcel_id, *weight_g, grpc_clients).await
+            }
+            None => Ok(()),
+        },
+        CompensationAction::DeleteParcel => {
+            let mut result = Ok(());
+            for parcel_id in &compensation.parcel_ids {
+                // Attempt every parcel, the step fails if any of them does
+                if let Err(e) = grpc_clients
+                    .storage
+                    .parcel
+                    .delete(Id {
+                        id: parcel_id.clone(),
+                    })
+                    .await
+                {
+                    service_error!("could not delete parcel {parcel_id}: {:?}", e);
+                    result = Err(ServiceError::Internal);
+                }
+            }
+
+            result
+        }
+        CompensationAction::CancelItinerary => {
+            scheduler_cancel(
+                &compensation.itinerary_id,
+                &compensation.user_id,
+                grpc_clients,
+            )
+            .await
+        }
+        CompensationAction::RefundPayment => {
+            payment_refund(compensation.total, compensation.currency_unit).await
+        }
+        CompensationAction::ReleasePayment => {
+            payment_release(compensation.total, compensation.currency_unit).await
+        }
+    }
+}
+
+/// Attempt the steps that are not done yet, recording each outcome
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+async fn run_steps(compensation: &mut Compensation, grpc_clients: &GrpcClients) {
+    for action in compensation.pending() {
+        let result = run_step(action, compensation, grpc_clients).await;
+        if let Err(e) = &result {
+            service_error!(
+                "could not undo {:?} of itinerary {}: {e}",
+                action,
+                compensation.itinerary_id
+            );
+        }
+
+        compensation.record(action, result);
+
+        // Keep going, a later retry only needs the steps still failing
+        if let Err(e) = store_compensation(compensation).await {
+            service_error!(
+                "could not record compensation of itinerary {}: {e}",
+                compensation.itinerary_id
+            );
+        }
+    }
+}
+
+/// Retry the failed steps of a compensation in the background, waiting
+///  longer between each attempt
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+async fn retry_in_background(itinerary_id: String, grpc_clients: GrpcClients) {
+    for attempt in 0..COMPENSATION_RETRY_ATTEMPTS {
+        let delay = COMPENSATION_RETRY_INTERVAL_SECONDS << attempt;
+        tokio::time::sleep(tokio::time::Duration::from_secs(delay)).await;
+
+        match retry_compensation(&itinerary_id, &grpc_clients).await {
+            Ok(compensation) if compensation.is_complete() => return,
+            Ok(_) => {}
+            Err(e) => service_error!("could not retry compensation of {itinerary_id}: {e}"),
+        }
+    }
+
+    service_error!("gave up compensating itinerary {itinerary_id}, steps left failed.");
+}
+
+/// Undo the changes of a failed booking
+/// Steps that fail are retried in the background.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub(super) async fn compensate(mut compensation: Compensation, grpc_clients: &GrpcClients) {
+    service_warn!(
+        "compensating failed booking of itinerary {}.",
+        compensation.itinerary_id
+    );
+
+    compensation.plan();
+    run_steps(&mut compensation, grpc_clients).await;
+
+    if compensation.is_complete() {
+        service_info!(
+            "compensated failed booking of itinerary {}.",
+            compensation.itinerary_id
+        );
+        return;
+    }
+
+    tokio::spawn(retry_in_background(
+        compensation.itinerary_id,
+        grpc_clients.clone(),
+    ));
+}
+
+/// Retry the failed steps of the compensation of an itinerary
+/// Returns the compensation with the outcome of each step.
+pub async fn retry_compensation(
+    itinerary_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Compensation, ServiceError> {
+    service_debug!("entry.");
+
+    to_uuid(itinerary_id).ok_or_else(|| {
+        service_error!("itinerary ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let mut compensation = load_compensation(itinerary_id).await?;
+    run_steps(&mut compensation, grpc_clients).await;
+
+    Ok(compensation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    fn compensation() -> Compensation {
+        let itinerary = Itinerary {
+            user_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        Compensation::new(&Uuid::new_v4().to_string(), &itinerary, 12.5)
+    }
+
+    fn link(parcel_id: &str) -> ParcelLink {
+        ParcelLink {
+            flight_plan_id: Uuid::new_v4().to_string(),
+            parcel_id: parcel_id.to_string(),
+            acquire: true,
+            deliver: true,
+        }
+    }
+
+    #[test]
+    fn test_parcel_link_conversion() {
+        let link = link("parcel");
+        let record = FlightPlanParcel::from(link.clone());
+        assert_eq!(record.flight_plan_id, link.flight_plan_id);
+        assert_eq!(ParcelLink::from(record), link);
+    }
+
+    #[test]
+    fn test_plan() {
+        // nothing registered with svc-storage yet
+        let mut compensation = compensation();
+        compensation.plan();
+        assert_eq!(
+            compensation.pending(),
+            vec![
+                CompensationAction::CancelItinerary,
+                CompensationAction::ReleasePayment
+            ]
+        );
+
+        // parcels registered, not linked
+        let parcel_id = Uuid::new_v4().to_string();
+        compensation.parcel_ids = vec![parcel_id.clone(), Uuid::new_v4().to_string()];
+        compensation.plan();
+        assert_eq!(compensation.pending()[0], CompensationAction::DeleteParcel);
+        assert_eq!(compensation.steps.len(), 3);
+
+        // parcel registered and linked
+        compensation.links = vec![link(&parcel_id), link(&parcel_id)];
+        compensation.plan();
+        assert_eq!(
+            compensation.pending(),
+            vec![
+                CompensationAction::UnlinkParcel,
+                CompensationAction::DeleteParcel,
+                CompensationAction::CancelItinerary,
+                CompensationAction::ReleasePayment
+            ]
+        );
+        assert!(compensation
+            .steps
+            .iter()
+            .all(|step| step.outcome == StepOutcome::Pending && step.attempts == 0));
+    }
+
+    #[test]
+    fn test_plan_modification() {
+        // parcel moved and its weight changed
+        let mut compensation = compensation();
+        let parcel_id = Uuid::new_v4().to_string();
+        compensation.links = vec![link(&parcel_id)];
+        compensation.weight = Some((parcel_id, 100));
+        compensation.plan();
+        assert_eq!(
+            compensation.pending(),
+            vec![
+                CompensationAction::UnlinkParcel,
+                CompensationAction::RestoreWeight,
+                CompensationAction::CancelItinerary,
+                CompensationAction::ReleasePayment
+            ]
+        );
+
+        // replacement charged, the hold not released yet
+        compensation.charged = true;
+        compensation.plan();
+        assert_eq!(
+            compensation.pending()[2..],
+            [
+                CompensationAction::CancelItinerary,
+                CompensationAction::RefundPayment,
+                CompensationAction::ReleasePayment
+            ]
+        );
+
+        // hold released after the charge
+        compensation.released = true;
+        compensation.plan();
+        assert_eq!(
+            compensation.pending()[2..],
+            [
+                CompensationAction::CancelItinerary,
+                CompensationAction::RefundPayment
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record() {
+        let mut compensation = compensation();
+        assert!(!compensation.is_complete());
+
+        compensation.plan();
+        compensation.record(CompensationAction::CancelItinerary, Ok(()));
+        compensation.record(
+            CompensationAction::ReleasePayment,
+            Err(ServiceError::Unavailable),
+        );
+        assert!(!compensation.is_complete());
+        assert_eq!(
+            compensation.pending(),
+            vec![CompensationAction::ReleasePayment]
+        );
+
+        let step = &compensation.steps[1];
+        assert_eq!(step.outcome, StepOutcome::Failed);
+        assert_eq!(step.attempts, 1);
+        assert_eq!(step.error, Some(ServiceError::Unavailable.to_string()));
+
+        // retried
+        compensation.record(CompensationAction::ReleasePayment, Ok(()));
+        let step = &compensation.steps[1];
+        assert_eq!(step.outcome, StepOutcome::Done);
+        assert_eq!(step.attempts, 2);
+        assert!(step.error.is_none());
+        assert!(compensation.is_complete());
+
+        // steps not planned are ignored
+        compensation.record(CompensationAction::DeleteParcel, Ok(()));
+        assert_eq!(compensation.steps.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_store_load_compensation() {
+        let mut compensation = compensation();
+        compensation.plan();
+        compensation.record(CompensationAction::CancelItinerary, Ok(()));
+        store_compensation(&compensation).await.unwrap();
+
+        let result = load_compensation(&compensation.itinerary_id).await.unwrap();
+        assert_eq!(result, compensation);
+    }
+
+    #[tokio::test]
+    async fn test_retry_compensation() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = retry_compensation("invalid", &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        let error = retry_compensation(&Uuid::new_v4().to_string(), &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+}
@@ -0,0 +1,706 @@
+//! Itinerary booking
+
+use super::compensation::{compensate, Compensation};
+use super::delivery::{open_delivery_record, send_delivery_pin};
+use super::draft::load_user_draft;
+use super::invoice::{self, store_invoice};
+use super::notify::{schedule_delay_check, store_contacts, ParcelContacts};
+use super::parcel::store_parcel;
+use super::ServiceError;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{
+    CargoInfo, CurrencyUnit, DeliveryPin, Itinerary, Parcel, SchedulerFlightPlan,
+};
+use crate::rest::api::utils::get_vertiport_id_from_vertipad_id;
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::to_uuid;
+use num_traits::FromPrimitive;
+use svc_contact_client_grpc::client::CargoConfirmationRequest;
+use svc_contact_client_grpc::prelude::ContactServiceClient;
+use svc_scheduler_client_grpc::client::{
+    CreateItineraryRequest, TaskRequest, TaskResponse, TaskStatus, TaskStatusRationale,
+};
+use svc_scheduler_client_grpc::prelude::scheduler_storage::flight_plan::FlightPriority;
+use svc_scheduler_client_grpc::prelude::SchedulerServiceClient;
+use svc_storage_client_grpc::link_service::Client as LinkClient;
+use svc_storage_client_grpc::prelude::flight_plan_parcel::RowData as FlightPlanParcel;
+use svc_storage_client_grpc::prelude::Id as StorageId;
+use svc_storage_client_grpc::prelude::{FieldValue, Ids};
+use svc_storage_client_grpc::simple_service::Client as SimpleClient;
+use svc_storage_client_grpc::simple_service_linked::Client as SimpleLinkedClient;
+
+/// Polling interval for scheduler task statuses
+const SCHEDULER_TASK_POLL_INTERVAL_SECONDS: u64 = 3;
+
+/// Timeout for scheduler task statuses
+pub(super) const SCHEDULER_TASK_TIMEOUT_SECONDS: i64 = 60;
+
+// use svc_storage_client_grpc::resources::itinerary;
+use svc_storage_client_grpc::resources::parcel::{Data as ParcelData, ParcelStatus};
+
+/// Confirmed booking details
+#[derive(Debug, Clone, PartialEq)]
+pub struct Booking {
+    /// The itinerary ID assigned by svc-scheduler
+    pub itinerary_id: String,
+
+    /// The parcels registered with svc-storage
+    pub parcels: Vec<CargoInfo>,
+}
+
+///
+/// Charge the customer for the itinerary
+///  If 'dry_run' is true, only check the validity of the
+///  payment option first.
+pub(super) async fn payment_confirm(
+    // TODO(R5): user credential or UUID
+    _total: f32,
+    _currency_unit: CurrencyUnit,
+    dry_run: bool,
+) -> Result<(), ServiceError> {
+    service_debug!("entry.");
+    //
+    // TODO(R5): Check if payment options are valid
+    //
+    // Possibly query storage for payment information
+    // Credit Card, ACH, Cryptocurrency Wallet, etc.
+    // In the case of crypto, verify wallet has sufficient
+    // funds
+
+    if dry_run {
+        return Ok(());
+    }
+
+    //
+    // TODO(R5): payment service confirm
+
+    Ok(())
+}
+
+/// Release the hold placed on the customer's payment option by the dry
+///  run of [`payment_confirm`]
+pub(super) async fn payment_release(
+    // TODO(R5): user credential or UUID
+    _total: f32,
+    _currency_unit: CurrencyUnit,
+) -> Result<(), ServiceError> {
+    service_debug!("entry.");
+    //
+    // TODO(R5): payment service release
+
+    Ok(())
+}
+
+/// Make a request to the scheduler to create an itinerary
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) function test not yet created
+pub(super) async fn scheduler_request(
+    itinerary: &Itinerary,
+    expiry: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Result<TaskResponse, ServiceError> {
+    service_debug!("creating itinerary with scheduler.");
+
+    let flight_plans = itinerary
+        .flight_plans
+        .clone()
+        .into_iter()
+        .map(|fp| fp.try_into())
+        .collect::<Result<Vec<SchedulerFlightPlan>, _>>()
+        .map_err(|e| {
+            service_error!("invalid flight plan data: {e}");
+            ServiceError::InvalidArgument
+        })?;
+
+    let data = CreateItineraryRequest {
+        flight_plans,
+        priority: FlightPriority::Low as i32,
+        expiry: Some(expiry.into()),
+        user_id: itinerary.user_id.clone(),
+    };
+
+    grpc_clients
+        .scheduler
+        .create_itinerary(data)
+        .await
+        .map_err(|e| {
+            service_error!("svc-scheduler error {:?}", e);
+            ServiceError::Internal
+        })
+        .map(|response| response.into_inner())
+}
+
+/// Poll the scheduler for the task status for a set amount of time
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) function test not yet created
+pub(super) async fn scheduler_poll(
+    task_id: i64,
+    expiry: DateTime<Utc>,
+    grpc_clients: GrpcClients,
+) -> Result<String, ServiceError> {
+    service_debug!("polling scheduler for task status.");
+
+    // Poll scheduler every few seconds
+    let interval = tokio::time::Duration::from_secs(SCHEDULER_TASK_POLL_INTERVAL_SECONDS);
+    let request = TaskRequest { task_id };
+
+    //  Provide expiry in request to the scheduler.
+    while Utc::now() < expiry {
+        // give the scheduler time to process the request
+        tokio::time::sleep(interval).await;
+
+        let task = grpc_clients
+            .scheduler
+            .get_task_status(request)
+            .await
+            .map_err(|e| {
+                service_error!("svc-scheduler error: {e}");
+                ServiceError::Internal
+            })?
+            .into_inner();
+
+        let task_id = task.task_id;
+        let metadata = task.task_metadata.ok_or_else(|| {
+            service_error!("no metadata for task #{task_id}");
+            ServiceError::Internal
+        })?;
+
+        let status = FromPrimitive::from_i32(metadata.status).ok_or_else(|| {
+            service_error!("unrecognized task status: {:?}", metadata.status);
+            ServiceError::Internal
+        })?;
+
+        match status {
+            TaskStatus::Queued => {
+                // Do nothing
+            }
+            TaskStatus::Complete => return Ok(metadata.result.unwrap_or("".to_string())),
+            TaskStatus::NotFound => {
+                service_error!("svc-scheduler error.");
+                return Err(ServiceError::Internal);
+            }
+            TaskStatus::Rejected => {
+                service_warn!(
+                    "task was rejected by the scheduler: {}",
+                    metadata
+                        .status_rationale
+                        .unwrap_or(TaskStatusRationale::InvalidAction as i32)
+                );
+                return Err(ServiceError::Rejected);
+            }
+        }
+    }
+
+    service_warn!("task timed out.");
+
+    // Fire off task cancellation and don't wait
+    tokio::spawn(async move {
+        let _ = grpc_clients.scheduler.cancel_task(request).await;
+    });
+
+    Err(ServiceError::Timeout)
+}
+
+/// Link a parcel to the flight plans of an itinerary it is acquired and
+///  delivered on. If a link can't be inserted, the links already inserted
+///  are removed.
+pub(super) async fn link_parcel(
+    itinerary_id: &str,
+    parcel_id: &str,
+    acquisition_vertiport_id: &str,
+    delivery_vertiport_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlanParcel>, ServiceError> {
+    //
+    // Get the linked flight plans
+    // Need the IDs of the flight plans to update the flight_plan_parcel table
+    let flight_plans = grpc_clients
+        .storage
+        .itinerary_flight_plan_link
+        .get_linked(StorageId {
+            id: itinerary_id.to_string(),
+        })
+        .await
+        .map_err(|e| {
+            let error_msg = "error on request to svc-storage.".to_string();
+            service_error!("{} {:?}", &error_msg, e);
+            ServiceError::Internal
+        })?
+        .into_inner()
+        .list;
+
+    if flight_plans.is_empty() {
+        let error_str = "no flight plans found for itinerary_id.".to_string();
+        service_error!("{}", &error_str);
+        return Err(ServiceError::Internal);
+    }
+
+    //
+    // Acquisition flight plan
+    //  The itinerary itself has no knowledge of what the "start point" is for
+    //  a package. Could be the first flight, or the second flight (with
+    //  the first being deadhead). Similarly, the delivery flight
+    //  could be the last or second to last flight plan.
+    //
+    // We could maybe indicate which flight(s) are the acquisition and delivery
+    //  in the itinerary record itself, so we don't have to search here.
+
+    let mut fps: Vec<(String, String, String)> = vec![];
+    for fp in flight_plans.into_iter() {
+        let data = fp.data.ok_or_else(|| {
+            let error_str = "flight plan data not found.".to_string();
+            service_error!("{}", &error_str);
+            ServiceError::Internal
+        })?;
+
+        let origin_vertiport_id = match data.origin_vertiport_id {
+            Some(ref id) => id.clone(),
+            None => {
+                get_vertiport_id_from_vertipad_id(grpc_clients, &data.origin_vertipad_id).await?
+            }
+        };
+
+        let target_vertiport_id = match data.target_vertiport_id {
+            Some(ref id) => id.clone(),
+            None => {
+                get_vertiport_id_from_vertipad_id(grpc_clients, &data.target_vertipad_id).await?
+            }
+        };
+
+        fps.push((fp.id, origin_vertiport_id, target_vertiport_id));
+    }
+
+    let acquisition_id = &fps
+        .iter()
+        .find(|fp| fp.1 == acquisition_vertiport_id)
+        .ok_or_else(|| {
+            let error_str = "acquisition flight plan not found.".to_string();
+            service_error!("{}", &error_str);
+            ServiceError::Internal
+        })?
+        .0;
+
+    let delivery_id = &fps
+        .iter()
+        .find(|fp| fp.2 == delivery_vertiport_id)
+        .ok_or_else(|| {
+            let error_str = "delivery flight plan not found.".to_string();
+            service_error!("{}", &error_str);
+            ServiceError::Internal
+        })?
+        .0;
+
+    let records = if acquisition_id == delivery_id {
+        vec![FlightPlanParcel {
+            flight_plan_id: acquisition_id.clone(),
+            parcel_id: parcel_id.to_string(),
+            acquire: true,
+            deliver: true,
+        }]
+    } else {
+        vec![
+            FlightPlanParcel {
+                flight_plan_id: acquisition_id.clone(),
+                parcel_id: parcel_id.to_string(),
+                acquire: true,
+                deliver: false,
+            },
+            FlightPlanParcel {
+                flight_plan_id: delivery_id.clone(),
+                parcel_id: parcel_id.to_string(),
+                acquire: false,
+                deliver: true,
+            },
+        ]
+    };
+
+    let mut inserted = vec![];
+    for parcel_record in records {
+        if let Err(e) = grpc_clients
+            .storage
+            .flight_plan_parcel
+            .insert(parcel_record.clone())
+            .await
+        {
+            let error_msg = "svc-storage error inserting flight_plan_parcel link.".to_string();
+            service_error!("{} {:?}", &error_msg, e);
+            let _ = unlink_parcel(&inserted, grpc_clients).await;
+            return Err(ServiceError::Internal);
+        }
+
+        inserted.push(parcel_record);
+    }
+
+    Ok(inserted)
+}
+
+/// Remove parcel links
+/// Links that can't be removed are logged and skipped, and reported as an
+///  error once the other links are removed.
+pub(super) async fn unlink_parcel(
+    records: &[FlightPlanParcel],
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    let mut result = Ok(());
+    for record in records {
+        let ids = Ids {
+            ids: vec![
+                FieldValue {
+                    field: "flight_plan_id".to_string(),
+                    value: record.flight_plan_id.clone(),
+                },
+                FieldValue {
+                    field: "parcel_id".to_string(),
+                    value: record.parcel_id.clone(),
+                },
+            ],
+        };
+
+        if let Err(e) = grpc_clients.storage.flight_plan_parcel.delete(ids).await {
+            service_error!(
+                "could not remove link of parcel {} to flight plan {}: {:?}",
+                record.parcel_id,
+                record.flight_plan_id,
+                e
+            );
+            result = Err(ServiceError::Internal);
+        }
+    }
+
+    result
+}
+
+/// The parcels of an itinerary
+/// Drafts priced before parcels were listed carry a single parcel of the
+///  total weight.
+fn itinerary_parcels(itinerary: &Itinerary) -> Vec<Parcel> {
+    if !itinerary.parcels.is_empty() {
+        return itinerary.parcels.clone();
+    }
+
+    vec![Parcel {
+        weight_g: itinerary.cargo_weight_g,
+        ..Default::default()
+    }]
+}
+
+/// Create the parcels/book the seats
+/// One parcel record is registered for each parcel of the itinerary, with
+///  its delivery record and the contacts to notify. The delivery PINs of
+///  parcels booked with one are returned, to be sent to the customer once
+///  the booking is paid. The records created are added to `compensation`,
+///  to be undone if the booking fails.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) function test not yet created
+async fn create_cargo(
+    itinerary: &Itinerary,
+    itinerary_id: &str,
+    acquisition_vertiport_id: &str,
+    delivery_vertiport_id: &str,
+    compensation: &mut Compensation,
+    grpc_clients: &GrpcClients,
+) -> Result<(Vec<CargoInfo>, Vec<DeliveryPin>), ServiceError> {
+    service_debug!("creating parcels for itinerary_id {itinerary_id}: acquisition_vertiport_id: {acquisition_vertiport_id}, delivery_vertiport_id: {delivery_vertiport_id}.");
+    //
+    // TODO(R5): Doing all of these in a transaction would be
+    //  nice. For now the records created are undone by the
+    //  compensation of the booking.
+    //
+
+    let mut cargo = vec![];
+    let mut pins = vec![];
+    for parcel in itinerary_parcels(itinerary) {
+        ///////////////////////////////
+        // Register Parcel with Storage
+        ///////////////////////////////
+        let data = ParcelData {
+            user_id: itinerary.user_id.clone(),
+            weight_grams: parcel.weight_g,
+            status: ParcelStatus::Notdroppedoff as i32,
+        };
+
+        // TODO(R5): Push to queue, in case this call fails need a retry mechanism
+        // Make request, process response
+        let object = grpc_clients
+            .storage
+            .parcel
+            .insert(data)
+            .await
+            .map_err(|e| {
+                let error_msg = "svc-parcel-storage insert fail.".to_string();
+                service_error!("{} {:?}", &error_msg, e);
+                ServiceError::Internal
+            })?
+            .into_inner()
+            .object
+            .ok_or_else(|| {
+                let error_msg = "svc-parcel-storage insert fail.".to_string();
+                service_error!("{}", &error_msg);
+                ServiceError::Internal
+            })?;
+
+        let parcel_id = object.id;
+        compensation.parcel_ids.push(parcel_id.clone());
+
+        // link_parcel removes its own links if it fails
+        let links = link_parcel(
+            itinerary_id,
+            &parcel_id,
+            acquisition_vertiport_id,
+            delivery_vertiport_id,
+            grpc_clients,
+        )
+        .await?;
+        compensation.links.extend(links.into_iter().map(Into::into));
+
+        let contacts = ParcelContacts {
+            user_id: itinerary.user_id.clone(),
+            recipient: parcel.recipient.clone(),
+            delay_notified: false,
+        };
+        store_contacts(&parcel_id, &contacts).await?;
+        schedule_delay_check(&parcel_id).await?;
+
+        let mut info = CargoInfo::new(parcel_id, &parcel);
+        info.itinerary_id = Some(itinerary_id.to_string());
+        store_parcel(&info).await?;
+        pins.extend(open_delivery_record(&info.parcel_id, parcel.delivery_pin).await?);
+        cargo.push(info);
+    }
+
+    Ok((cargo, pins))
+}
+
+/// Confirm an itinerary
+/// This will create an itinerary with the scheduler, and will register the parcel with
+///  the storage service. Drafts of other users are reported as not found.
+pub async fn create_itinerary(
+    draft_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Booking, ServiceError> {
+    service_debug!("entry.");
+
+    //
+    // See if the draft exists and belongs to the user
+    let itinerary = load_user_draft(draft_id, user_id).await?;
+
+    let invoice_total = invoice::invoice_total(&itinerary);
+
+    //
+    // Check if payment options are valid/sufficient funds
+    //  in the case of cryptocurrency
+    payment_confirm(
+        invoice_total,
+        itinerary.currency_unit,
+        true, // dry run, don't charge the customer
+    )
+    .await?;
+
+    //
+    // Ask the scheduler to attempt to create the itinerary
+    // This will "reserve" the weight/seats as well so we can
+    //  create the parcel record in storage later without conflicts.
+    let delta = Duration::try_seconds(SCHEDULER_TASK_TIMEOUT_SECONDS).ok_or_else(|| {
+        service_error!("failed to create duration.");
+        ServiceError::Internal
+    })?;
+
+    let expiry = Utc::now() + delta;
+    let task_id = scheduler_request(&itinerary, expiry, grpc_clients)
+        .await?
+        .task_id;
+
+    //
+    // Poll the scheduler for the task status for a set amount of time
+    let itinerary_id = scheduler_poll(task_id, expiry, grpc_clients.clone()).await?;
+    to_uuid(&itinerary_id).ok_or_else(|| {
+        service_error!("invalid itinerary UUID.");
+        ServiceError::Internal
+    })?;
+
+    //
+    // From here on, the itinerary is confirmed with the scheduler and
+    //  every change is undone if the booking fails
+    let mut compensation = Compensation::new(&itinerary_id, &itinerary, invoice_total);
+
+    //
+    // Create the parcels/book the seats
+    //
+    let (parcels, pins) = match create_cargo(
+        &itinerary,
+        &itinerary_id,
+        &itinerary.acquisition_vertiport_id,
+        &itinerary.delivery_vertiport_id,
+        &mut compensation,
+        grpc_clients,
+    )
+    .await
+    {
+        Ok(cargo) => cargo,
+        Err(e) => {
+            service_error!("could not register parcels for itinerary {itinerary_id}: {e}");
+            compensate(compensation, grpc_clients).await;
+            return Err(e);
+        }
+    };
+
+    // Cancellations are refunded from the invoice
+    if let Err(e) = store_invoice(&itinerary_id, &itinerary).await {
+        compensate(compensation, grpc_clients).await;
+        return Err(e);
+    }
+
+    //
+    // If the scheduler task was successful, charge the customer
+    //
+    if let Err(e) = payment_confirm(invoice_total, itinerary.currency_unit, false).await {
+        service_error!("could not charge for itinerary {itinerary_id}: {e}");
+        compensate(compensation, grpc_clients).await;
+        return Err(e);
+    }
+
+    // Continue even if the contact service fails
+    for parcel in &parcels {
+        let data = CargoConfirmationRequest {
+            parcel_id: parcel.parcel_id.clone(),
+            itinerary_id: itinerary_id.clone(),
+        };
+
+        let _ = grpc_clients
+            .contact
+            .cargo_confirmation(data)
+            .await
+            .map_err(|e| {
+                let error_msg = "svc-contact error.".to_string();
+                service_error!("{} {:?}", &error_msg, e);
+            });
+    }
+
+    for pin in &pins {
+        send_delivery_pin(pin, &itinerary.user_id, grpc_clients).await;
+    }
+
+    Ok(Booking {
+        itinerary_id,
+        parcels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::pool::{get_pool, ItineraryPool};
+    use lib_common::uuid::Uuid;
+
+    #[test]
+    fn test_itinerary_parcels() {
+        let parcel = Parcel {
+            weight_g: 100,
+            length_mm: 300,
+            width_mm: 200,
+            height_mm: 100,
+            reference: Some("samples".to_string()),
+            handling: vec![],
+            recipient: None,
+            delivery_pin: false,
+        };
+        let mut itinerary = Itinerary {
+            cargo_weight_g: 150,
+            parcels: vec![parcel.clone(), parcel.clone()],
+            ..Default::default()
+        };
+        assert_eq!(itinerary_parcels(&itinerary), vec![parcel.clone(), parcel]);
+
+        // drafts without parcels
+        itinerary.parcels.clear();
+        let parcels = itinerary_parcels(&itinerary);
+        assert_eq!(parcels.len(), 1);
+        assert_eq!(parcels[0].weight_g, 150);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_poll() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let task_id = 12345;
+
+        // will timeout
+        let expiry = Utc::now() - Duration::seconds(1);
+        let result = scheduler_poll(task_id, expiry, grpc_clients.clone())
+            .await
+            .unwrap_err();
+        assert_eq!(result, ServiceError::Timeout);
+
+        // TODO: tell svc-scheduler to fail the task
+
+        // will complete
+        let expiry = Utc::now() + Duration::seconds(1);
+        let _ = scheduler_poll(task_id, expiry, grpc_clients.clone())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_payment_confirm() {
+        let total = 100.0;
+        let currency_unit = CurrencyUnit::Usd;
+        let dry_run = true;
+
+        let result = payment_confirm(total, currency_unit, dry_run).await;
+        assert!(result.is_ok());
+
+        let dry_run = false;
+        let result = payment_confirm(total, currency_unit, dry_run).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_payment_release() {
+        let result = payment_release(100.0, CurrencyUnit::Usd).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_create_itinerary_invalid_ids() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        // bad itinerary id
+        let error = create_itinerary("invalid", &Uuid::new_v4().to_string(), &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // bad user id
+        let error = create_itinerary(&Uuid::new_v4().to_string(), "invalid", &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_create_itinerary_other_user() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let draft_id = Uuid::new_v4().to_string();
+        let draft = Itinerary {
+            user_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        get_pool()
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .store_itinerary(draft_id.clone(), &draft)
+            .await
+            .unwrap();
+
+        let error = create_itinerary(&draft_id, &Uuid::new_v4().to_string(), &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+}
@@ -0,0 +1,705 @@
+//! Proof of delivery
+//!
+//! A delivery record is kept for each parcel from booking. Parcels booked
+//!  with a delivery PIN have it issued at booking and sent to the customer,
+//!  to pass on to the recipient; only its hash is kept, and the customer can
+//!  ask for a new PIN to replace it. The parcel is delivered with a final
+//!  scan at the delivery vertiport, and the PIN if one was issued, recording
+//!  who received it and the hash of their signature or of a photo of the
+//!  handover. Deliveries are refused after [`MAX_PIN_FAILURES`] incorrect
+//!  PINs, until a new PIN is issued.
+//!  TODO(R5): keep delivery records in svc-storage
+
+use super::itinerary::get_user_parcel_itinerary;
+use super::label::LabelSigner;
+use super::notify::{notify, ParcelEvent};
+use super::scan::{
+    check_booking, check_timestamp, get_parcel_route, next_status, record_transition, resolve_code,
+    scan_place, update_parcel_status, validate_scan, ScanError, ScanPolicy,
+};
+use super::{sha256_hex, ServiceError};
+use crate::cache::pool::{get_pool, CacheError, ItineraryPool};
+use crate::grpc::client::GrpcClients;
+use crate::grpc::contact::{CargoEvent, CargoNotificationRequest};
+use crate::rest::api::rest_types::{
+    DeliveryConfirmation, DeliveryPin, DeliveryProof, ParcelStatus,
+};
+use lib_common::time::Utc;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Number of digits of a delivery PIN
+const PIN_DIGITS: u32 = 6;
+
+/// Incorrect PINs accepted before deliveries of a parcel are refused
+const MAX_PIN_FAILURES: u32 = 5;
+
+/// Longest recipient name accepted
+const MAX_RECIPIENT_NAME_LENGTH: usize = 100;
+
+/// Length of a hexadecimal SHA-256 hash
+const CONTENT_HASH_LENGTH: usize = 64;
+
+/// The delivery PIN and proof of a parcel
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryRecord {
+    /// Hash of the last PIN issued, none for parcels delivered without one
+    pub pin_hash: Option<String>,
+
+    /// The proof of delivery, once delivered
+    pub proof: Option<DeliveryProof>,
+}
+
+/// Reasons a delivery confirmation can be rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeliveryError {
+    /// The final scan was rejected
+    Scan(ScanError),
+
+    /// The parcel has not arrived at the delivery vertiport
+    NotArrived(ParcelStatus),
+
+    /// The recipient name is missing or too long
+    RecipientName,
+
+    /// The content hash is not a hexadecimal SHA-256 hash
+    ContentHash,
+
+    /// The PIN issued was not given or does not match
+    Pin,
+
+    /// Too many incorrect PINs were given since the PIN was issued
+    Locked,
+
+    /// The parcel has no delivery record
+    NoRecord,
+
+    /// The delivery record could not be read or kept
+    Cache,
+}
+
+impl Display for DeliveryError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            DeliveryError::Scan(e) => write!(f, "{e}"),
+            DeliveryError::NotArrived(status) => {
+                write!(f, "parcel {status:?} has not arrived for delivery")
+            }
+            DeliveryError::RecipientName => write!(f, "recipient name missing or too long"),
+            DeliveryError::ContentHash => write!(f, "content hash is not a SHA-256 hash"),
+            DeliveryError::Pin => write!(f, "delivery PIN missing or incorrect"),
+            DeliveryError::Locked => write!(f, "too many incorrect delivery PINs"),
+            DeliveryError::NoRecord => write!(f, "no delivery record for parcel"),
+            DeliveryError::Cache => write!(f, "could not keep proof of delivery"),
+        }
+    }
+}
+
+impl From<ScanError> for DeliveryError {
+    fn from(error: ScanError) -> Self {
+        DeliveryError::Scan(error)
+    }
+}
+
+impl From<DeliveryError> for ServiceError {
+    fn from(error: DeliveryError) -> Self {
+        match error {
+            DeliveryError::Scan(e) => ServiceError::from(e),
+            DeliveryError::NotArrived(_)
+            | DeliveryError::RecipientName
+            | DeliveryError::ContentHash
+            | DeliveryError::Pin
+            | DeliveryError::Locked => ServiceError::InvalidArgument,
+            DeliveryError::NoRecord => ServiceError::Internal,
+            DeliveryError::Cache => ServiceError::Unavailable,
+        }
+    }
+}
+
+/// A random PIN of [`PIN_DIGITS`] digits
+fn new_pin() -> Option<String> {
+    let mut bytes = [0u8; 4];
+    openssl::rand::rand_bytes(&mut bytes)
+        .map_err(|e| service_error!("could not generate delivery PIN: {e}"))
+        .ok()?;
+
+    let pin = u32::from_be_bytes(bytes) % 10u32.pow(PIN_DIGITS);
+    Some(format!("{pin:0width$}", width = PIN_DIGITS as usize))
+}
+
+/// Checks the recipient name and content hash
+pub fn validate_confirmation(confirmation: &DeliveryConfirmation) -> Result<(), DeliveryError> {
+    let name = confirmation.recipient_name.trim();
+    if name.is_empty() || name.chars().count() > MAX_RECIPIENT_NAME_LENGTH {
+        service_error!("recipient name missing or too long.");
+        return Err(DeliveryError::RecipientName);
+    }
+
+    let hash = &confirmation.content_hash;
+    if hash.len() != CONTENT_HASH_LENGTH || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        service_error!("content hash is not a SHA-256 hash.");
+        return Err(DeliveryError::ContentHash);
+    }
+
+    validate_scan(&confirmation.scan)?;
+    Ok(())
+}
+
+/// Checks the PIN given by the recipient against the last PIN issued
+pub fn verify_pin(pin_hash: &str, pin: Option<&str>) -> Result<(), DeliveryError> {
+    let Some(pin) = pin else {
+        service_error!("delivery PIN not given.");
+        return Err(DeliveryError::Pin);
+    };
+
+    let given = sha256_hex(pin.trim().as_bytes());
+    if given.len() != pin_hash.len() || !openssl::memcmp::eq(given.as_bytes(), pin_hash.as_bytes())
+    {
+        service_error!("delivery PIN does not match.");
+        return Err(DeliveryError::Pin);
+    }
+
+    Ok(())
+}
+
+/// Get the delivery record of a parcel, if it has one
+async fn load_delivery(parcel_id: &str) -> Result<Option<DeliveryRecord>, CacheError> {
+    let result = get_pool()
+        .await?
+        .lock()
+        .await
+        .get_delivery(parcel_id.to_string())
+        .await;
+
+    match result {
+        Ok(record) => Ok(Some(record)),
+        Err(CacheError::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Keep the delivery record of a parcel
+async fn store_delivery(parcel_id: &str, record: &DeliveryRecord) -> Result<(), CacheError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .store_delivery(parcel_id, record)
+        .await
+}
+
+/// Count a PIN given for a parcel, before it is checked
+/// Returns the number of PINs given since the PIN was issued or last given
+///  correctly.
+async fn count_pin_attempt(parcel_id: &str) -> Result<u32, DeliveryError> {
+    let result = match get_pool().await {
+        Ok(pool) => pool.lock().await.count_pin_failure(parcel_id).await,
+        Err(e) => Err(e),
+    };
+
+    result.map_err(|e| {
+        service_error!("could not count PIN of parcel {parcel_id}: {e}");
+        DeliveryError::Cache
+    })
+}
+
+/// Clear the PINs counted for a parcel
+async fn reset_pin_failures(parcel_id: &str) -> Result<(), CacheError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .reset_pin_failures(parcel_id)
+        .await
+}
+
+/// Open the delivery record of a newly registered parcel
+/// The delivery PIN is issued if the parcel was booked with one, only its
+///  hash is kept and the PIN is returned to be sent to the customer. A
+///  parcel without a delivery record can't be delivered, so the booking
+///  fails if the record can't be kept.
+pub(super) async fn open_delivery_record(
+    parcel_id: &str,
+    delivery_pin: bool,
+) -> Result<Option<DeliveryPin>, ServiceError> {
+    let pin = if delivery_pin {
+        Some(new_pin().ok_or(ServiceError::Internal)?)
+    } else {
+        None
+    };
+
+    let record = DeliveryRecord {
+        pin_hash: pin.as_ref().map(|pin| sha256_hex(pin.as_bytes())),
+        proof: None,
+    };
+    store_delivery(parcel_id, &record).await.map_err(|e| {
+        service_error!("could not keep delivery record of parcel {parcel_id}: {e}");
+        ServiceError::from(e)
+    })?;
+
+    Ok(pin.map(|pin| DeliveryPin {
+        parcel_id: parcel_id.to_string(),
+        pin,
+    }))
+}
+
+/// Send a delivery PIN issued at booking to the user who booked the parcel
+/// Failures are logged, the user can ask for a new PIN.
+pub(super) async fn send_delivery_pin(
+    pin: &DeliveryPin,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) {
+    let request = CargoNotificationRequest {
+        parcel_id: pin.parcel_id.clone(),
+        event: CargoEvent::PinIssued as i32,
+        user_id: Some(user_id.to_string()),
+        recipient: None,
+        delivery_pin: Some(pin.pin.clone()),
+    };
+
+    if let Err(e) = grpc_clients.notifications.cargo_notification(request).await {
+        service_error!(
+            "could not send delivery PIN of parcel {}: {:?}",
+            pin.parcel_id,
+            e
+        );
+    }
+}
+
+/// Issue a delivery PIN for a parcel of a user
+/// The PIN replaces any PIN issued before and clears the incorrect PINs
+///  given. Parcels booked without a PIN require one from then on. Parcels
+///  of other users are reported as not found.
+pub async fn issue_delivery_pin(
+    parcel_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<DeliveryPin, ServiceError> {
+    service_debug!("entry.");
+
+    get_user_parcel_itinerary(parcel_id, user_id, grpc_clients).await?;
+
+    let mut record = load_delivery(parcel_id)
+        .await
+        .map_err(|e| {
+            service_error!("could not get delivery record of parcel {parcel_id}: {e}");
+            ServiceError::from(e)
+        })?
+        .ok_or_else(|| {
+            service_error!("no delivery record for parcel {parcel_id}.");
+            ServiceError::NotFound
+        })?;
+
+    if record.proof.is_some() {
+        service_error!("parcel {parcel_id} already delivered.");
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    let pin = new_pin().ok_or(ServiceError::Internal)?;
+    record.pin_hash = Some(sha256_hex(pin.as_bytes()));
+    store_delivery(parcel_id, &record).await.map_err(|e| {
+        service_error!("could not keep delivery PIN of parcel {parcel_id}: {e}");
+        ServiceError::from(e)
+    })?;
+
+    reset_pin_failures(parcel_id).await.map_err(|e| {
+        service_error!("could not clear incorrect PINs of parcel {parcel_id}: {e}");
+        ServiceError::from(e)
+    })?;
+
+    service_info!("issued delivery PIN of parcel {parcel_id}.");
+    Ok(DeliveryPin {
+        parcel_id: parcel_id.to_string(),
+        pin,
+    })
+}
+
+/// Get the proof of delivery of a parcel, if it was delivered
+pub async fn get_delivery_proof(parcel_id: &str) -> Result<Option<DeliveryProof>, ServiceError> {
+    load_delivery(parcel_id)
+        .await
+        .map(|record| record.and_then(|record| record.proof))
+        .map_err(|e| {
+            service_error!("could not get delivery record of parcel {parcel_id}: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Deliver a parcel with its final scan at the delivery vertiport
+/// The proof of delivery is kept before the parcel is marked delivered and
+///  the scan is recorded, so a delivered parcel always has its proof.
+pub async fn confirm_delivery(
+    mut confirmation: DeliveryConfirmation,
+    policy: &ScanPolicy,
+    signer: &LabelSigner,
+    grpc_clients: &GrpcClients,
+) -> Result<DeliveryProof, DeliveryError> {
+    service_debug!("entry.");
+
+    let claims = resolve_code(&mut confirmation.scan, signer)?;
+    validate_confirmation(&confirmation)?;
+
+    let mut scan = confirmation.scan;
+    let received_at = Utc::now();
+    scan.received_at = Some(received_at);
+    check_timestamp(&scan, received_at, policy)?;
+    if let Some(claims) = claims {
+        check_booking(&claims, grpc_clients).await?;
+    }
+
+    let route = get_parcel_route(&scan.parcel_id, grpc_clients).await?;
+    let place = scan_place(&scan, &route.acquisition, &route.delivery, policy);
+    if next_status(route.status, place)? != ParcelStatus::Delivered {
+        service_error!("parcel {} has not arrived for delivery.", scan.parcel_id);
+        return Err(DeliveryError::NotArrived(route.status));
+    }
+
+    let mut record = load_delivery(&scan.parcel_id)
+        .await
+        .map_err(|e| {
+            service_error!("could not get delivery record: {e}");
+            DeliveryError::Cache
+        })?
+        .ok_or_else(|| {
+            service_error!("no delivery record for parcel {}.", scan.parcel_id);
+            DeliveryError::NoRecord
+        })?;
+
+    // Parcels booked without a PIN are handed over without one
+    if let Some(pin_hash) = record.pin_hash.as_deref() {
+        // Counted before the PIN is checked, so concurrent deliveries can't
+        //  try more PINs than allowed
+        let attempts = count_pin_attempt(&scan.parcel_id).await?;
+        if attempts > MAX_PIN_FAILURES {
+            service_error!("parcel {} locked after too many PINs.", scan.parcel_id);
+            return Err(DeliveryError::Locked);
+        }
+
+        verify_pin(pin_hash, confirmation.pin.as_deref())?;
+        if let Err(e) = reset_pin_failures(&scan.parcel_id).await {
+            service_warn!("could not clear PINs of parcel {}: {e}", scan.parcel_id);
+        }
+    }
+
+    let proof = DeliveryProof {
+        parcel_id: scan.parcel_id.clone(),
+        scanner_id: scan.scanner_id.clone(),
+        recipient_name: confirmation.recipient_name.trim().to_string(),
+        content_hash: confirmation.content_hash.to_lowercase(),
+        pin_verified: record.pin_hash.is_some(),
+        // The handover is when the final scan was taken
+        delivered_at: scan.timestamp,
+        received_at: Some(received_at),
+    };
+
+    record.proof = Some(proof.clone());
+    store_delivery(&scan.parcel_id, &record)
+        .await
+        .map_err(|e| {
+            service_error!("could not keep proof of delivery: {e}");
+            DeliveryError::Cache
+        })?;
+
+    let parcel_id = scan.parcel_id.clone();
+    update_parcel_status(
+        &parcel_id,
+        route.status,
+        ParcelStatus::Delivered,
+        grpc_clients,
+    )
+    .await?;
+
+    // The proof is only kept for delivered parcels
+    if let Err(e) =
+        record_transition(scan, route.status, ParcelStatus::Delivered, grpc_clients).await
+    {
+        record.proof = None;
+        if let Err(e) = store_delivery(&parcel_id, &record).await {
+            service_warn!("could not discard proof of delivery of parcel {parcel_id}: {e}");
+        }
+
+        return Err(e.into());
+    }
+
+    service_info!("parcel {parcel_id} delivered to {}.", proof.recipient_name);
+    notify(&parcel_id, ParcelEvent::Delivered, grpc_clients).await;
+
+    Ok(proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::CargoScan;
+    use crate::test_util::test_storage::register_parcel;
+    use lib_common::uuid::Uuid;
+
+    fn confirmation() -> DeliveryConfirmation {
+        DeliveryConfirmation {
+            scan: CargoScan {
+                parcel_id: Uuid::new_v4().to_string(),
+                code: None,
+                scanner_id: Uuid::new_v4().to_string(),
+                latitude: 0.0,
+                longitude: 0.0,
+                altitude: 0.0,
+                timestamp: Utc::now(),
+                received_at: None,
+            },
+            recipient_name: "J. Doe".to_string(),
+            content_hash: sha256_hex(b"signature"),
+            pin: None,
+        }
+    }
+
+    #[test]
+    fn test_new_pin() {
+        let pin = new_pin().unwrap();
+        assert_eq!(pin.len(), PIN_DIGITS as usize);
+        assert!(pin.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_validate_confirmation() {
+        validate_confirmation(&confirmation()).unwrap();
+
+        for name in ["", "  ", "a".repeat(MAX_RECIPIENT_NAME_LENGTH + 1).as_str()] {
+            let mut data = confirmation();
+            data.recipient_name = name.to_string();
+            assert_eq!(
+                validate_confirmation(&data).unwrap_err(),
+                DeliveryError::RecipientName
+            );
+        }
+
+        for hash in ["", "not a hash", "g".repeat(CONTENT_HASH_LENGTH).as_str()] {
+            let mut data = confirmation();
+            data.content_hash = hash.to_string();
+            assert_eq!(
+                validate_confirmation(&data).unwrap_err(),
+                DeliveryError::ContentHash
+            );
+        }
+
+        let mut data = confirmation();
+        data.scan.parcel_id = "invalid".to_string();
+        assert_eq!(
+            validate_confirmation(&data).unwrap_err(),
+            DeliveryError::Scan(ScanError::ParcelId)
+        );
+    }
+
+    #[test]
+    fn test_verify_pin() {
+        let pin_hash = sha256_hex(b"123456");
+
+        verify_pin(&pin_hash, Some("123456")).unwrap();
+        verify_pin(&pin_hash, Some(" 123456 ")).unwrap();
+        assert_eq!(
+            verify_pin(&pin_hash, Some("654321")).unwrap_err(),
+            DeliveryError::Pin
+        );
+        assert_eq!(verify_pin(&pin_hash, None).unwrap_err(), DeliveryError::Pin);
+    }
+
+    #[test]
+    fn test_delivery_error_display() {
+        assert_eq!(
+            DeliveryError::Scan(ScanError::Parcel).to_string(),
+            "parcel not registered"
+        );
+        assert_eq!(
+            DeliveryError::NotArrived(ParcelStatus::InFlight).to_string(),
+            "parcel InFlight has not arrived for delivery"
+        );
+        assert_eq!(
+            DeliveryError::RecipientName.to_string(),
+            "recipient name missing or too long"
+        );
+        assert_eq!(
+            DeliveryError::ContentHash.to_string(),
+            "content hash is not a SHA-256 hash"
+        );
+        assert_eq!(
+            DeliveryError::Pin.to_string(),
+            "delivery PIN missing or incorrect"
+        );
+        assert_eq!(
+            DeliveryError::Locked.to_string(),
+            "too many incorrect delivery PINs"
+        );
+        assert_eq!(
+            DeliveryError::NoRecord.to_string(),
+            "no delivery record for parcel"
+        );
+        assert_eq!(
+            DeliveryError::Cache.to_string(),
+            "could not keep proof of delivery"
+        );
+    }
+
+    #[test]
+    fn test_service_error_from_delivery_error() {
+        assert_eq!(
+            ServiceError::from(DeliveryError::Scan(ScanError::Parcel)),
+            ServiceError::NotFound
+        );
+        assert_eq!(
+            ServiceError::from(DeliveryError::Pin),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(DeliveryError::Locked),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(DeliveryError::NoRecord),
+            ServiceError::Internal
+        );
+        assert_eq!(
+            ServiceError::from(DeliveryError::Cache),
+            ServiceError::Unavailable
+        );
+    }
+
+    #[tokio::test]
+    async fn test_confirm_delivery_unregistered() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = confirm_delivery(
+            confirmation(),
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, DeliveryError::Scan(ScanError::Parcel));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_delivery_pin() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+        let policy = ScanPolicy::default();
+        let signer = LabelSigner::default();
+
+        update_parcel_status(
+            &parcel.parcel_id,
+            ParcelStatus::NotDroppedOff,
+            ParcelStatus::Arrived,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+
+        let delivery = |pin: Option<&str>| {
+            let mut data = confirmation();
+            data.scan.parcel_id = parcel.parcel_id.clone();
+            data.scan.latitude = f64::from(parcel.delivery.latitude);
+            data.scan.longitude = f64::from(parcel.delivery.longitude);
+            data.pin = pin.map(str::to_string);
+            data
+        };
+
+        // booked without a delivery record
+        let error = confirm_delivery(delivery(None), &policy, &signer, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, DeliveryError::NoRecord);
+
+        let pin = open_delivery_record(&parcel.parcel_id, true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(pin.parcel_id, parcel.parcel_id);
+        let incorrect = if pin.pin == "000000" {
+            "111111"
+        } else {
+            "000000"
+        };
+
+        // the PIN is required
+        let error = confirm_delivery(delivery(None), &policy, &signer, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, DeliveryError::Pin);
+
+        // locked after too many incorrect PINs
+        for _ in 1..MAX_PIN_FAILURES {
+            let error =
+                confirm_delivery(delivery(Some(incorrect)), &policy, &signer, &grpc_clients)
+                    .await
+                    .unwrap_err();
+            assert_eq!(error, DeliveryError::Pin);
+        }
+
+        let error = confirm_delivery(delivery(Some(&pin.pin)), &policy, &signer, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, DeliveryError::Locked);
+
+        // a new PIN clears the incorrect PINs
+        get_pool()
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .reset_pin_failures(&parcel.parcel_id)
+            .await
+            .unwrap();
+
+        let proof = confirm_delivery(delivery(Some(&pin.pin)), &policy, &signer, &grpc_clients)
+            .await
+            .unwrap();
+        assert!(proof.pin_verified);
+        assert!(proof.received_at.unwrap() >= proof.delivered_at);
+        assert_eq!(
+            get_delivery_proof(&parcel.parcel_id).await.unwrap(),
+            Some(proof)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_confirm_delivery_without_pin() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+
+        update_parcel_status(
+            &parcel.parcel_id,
+            ParcelStatus::NotDroppedOff,
+            ParcelStatus::Arrived,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            open_delivery_record(&parcel.parcel_id, false)
+                .await
+                .unwrap(),
+            None
+        );
+
+        // any PIN given is ignored
+        let mut data = confirmation();
+        data.scan.parcel_id = parcel.parcel_id.clone();
+        data.scan.latitude = f64::from(parcel.delivery.latitude);
+        data.scan.longitude = f64::from(parcel.delivery.longitude);
+        data.pin = Some("123456".to_string());
+
+        let proof = confirm_delivery(
+            data,
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert!(!proof.pin_verified);
+    }
+}
@@ -0,0 +1,311 @@
+//! Draft itineraries
+//!
+//! Drafts are priced itineraries kept in the cache for a short time, until
+//!  they are booked or expire.
+
+use super::ServiceError;
+use crate::cache::pool::{get_pool, ItineraryPool};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::request::{scheduler_query, unpack_itineraries, update_pricing};
+use crate::rest::api::rest_types::{
+    DraftItineraryDetails, FlightPlan, Itinerary, QueryItineraryRequest, TimeWindow,
+};
+use crate::rest::api::vehicle::{filter_by_capacity, get_fleet};
+use lib_common::uuid::to_uuid;
+
+/// Get a draft itinerary from the cache
+pub(super) async fn load_draft(draft_id: &str) -> Result<Itinerary, ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .get_itinerary(draft_id.to_string())
+        .await
+        .map_err(|e| {
+            service_error!("unable to get itinerary from redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get the remaining lifetime of a draft itinerary
+async fn draft_ttl(draft_id: &str) -> Result<u64, ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .get_itinerary_ttl(draft_id.to_string())
+        .await
+        .map_err(|e| {
+            service_error!("unable to get itinerary TTL from redis: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get a draft itinerary of a user
+/// Drafts of other users are reported as not found.
+pub(super) async fn load_user_draft(
+    draft_id: &str,
+    user_id: &str,
+) -> Result<Itinerary, ServiceError> {
+    to_uuid(draft_id).ok_or_else(|| {
+        service_error!("draft ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let draft = load_draft(draft_id).await?;
+    if draft.user_id != user_id {
+        service_warn!("draft {draft_id} does not belong to user {user_id}.");
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(draft)
+}
+
+/// The flight query that returned a draft itinerary
+fn draft_query(draft: &Itinerary) -> Result<QueryItineraryRequest, ServiceError> {
+    let departure = draft.flight_plans.first().ok_or_else(|| {
+        service_error!("draft has no flight plans.");
+        ServiceError::Internal
+    })?;
+
+    Ok(QueryItineraryRequest {
+        origin_vertiport_id: draft.acquisition_vertiport_id.clone(),
+        target_vertiport_id: draft.delivery_vertiport_id.clone(),
+        time_depart_window: TimeWindow {
+            timestamp_min: departure.origin_timeslot_start,
+            timestamp_max: departure.origin_timeslot_end,
+        },
+        parcels: draft.parcels.clone(),
+        cargo_weight_g: None,
+        user_id: draft.user_id.clone(),
+    })
+}
+
+/// Whether two itineraries fly the same aircraft between the same pads
+///  at the same times
+fn same_flights(a: &[FlightPlan], b: &[FlightPlan]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.vehicle_id == b.vehicle_id
+                && a.origin_vertipad_id == b.origin_vertipad_id
+                && a.target_vertipad_id == b.target_vertipad_id
+                && a.origin_timeslot_start == b.origin_timeslot_start
+                && a.target_timeslot_start == b.target_timeslot_start
+        })
+}
+
+/// Get a draft itinerary of a user with its remaining lifetime
+pub async fn get_draft(
+    draft_id: &str,
+    user_id: &str,
+) -> Result<DraftItineraryDetails, ServiceError> {
+    service_debug!("entry.");
+
+    let itinerary = load_user_draft(draft_id, user_id).await?;
+    let ttl_seconds = draft_ttl(draft_id).await?;
+
+    Ok(DraftItineraryDetails {
+        id: draft_id.to_string(),
+        itinerary,
+        ttl_seconds,
+    })
+}
+
+/// Extend the lifetime of a draft itinerary of a user
+/// The flights of the draft are checked again with svc-scheduler and
+///  priced again with svc-pricing before its expiry is reset. Drafts that
+///  are no longer available are rejected and left to expire.
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn refresh_draft(
+    draft_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<DraftItineraryDetails, ServiceError> {
+    service_debug!("entry.");
+
+    let draft = load_user_draft(draft_id, user_id).await?;
+    let payload = draft_query(&draft)?;
+
+    let mut grpc_clients = grpc_clients.clone();
+    let itineraries = scheduler_query(&payload, &mut grpc_clients)
+        .await
+        .map_err(ServiceError::from)?;
+
+    let mut itinerary = unpack_itineraries(itineraries)
+        .into_iter()
+        .find(|itinerary| same_flights(&itinerary.flight_plans, &draft.flight_plans))
+        .ok_or_else(|| {
+            service_warn!("draft {draft_id} is no longer available.");
+            ServiceError::Rejected
+        })?;
+
+    itinerary
+        .acquisition_vertiport_id
+        .clone_from(&draft.acquisition_vertiport_id);
+    itinerary
+        .delivery_vertiport_id
+        .clone_from(&draft.delivery_vertiport_id);
+    itinerary.user_id.clone_from(&draft.user_id);
+    itinerary.cargo_weight_g = draft.cargo_weight_g;
+    itinerary.parcels.clone_from(&draft.parcels);
+
+    // The vehicles may have changed since the draft was priced
+    let mut itinerary = filter_by_capacity(
+        vec![itinerary],
+        &draft.parcels,
+        get_fleet().await,
+        &grpc_clients,
+    )
+    .await
+    .pop()
+    .ok_or_else(|| {
+        service_warn!("vehicles of draft {draft_id} can no longer carry the parcels.");
+        ServiceError::Rejected
+    })?;
+    update_pricing(&payload, &mut itinerary, &mut grpc_clients)
+        .await
+        .map_err(ServiceError::from)?;
+
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .refresh_itinerary(draft_id.to_string(), &itinerary)
+        .await
+        .map_err(|e| {
+            service_error!("unable to refresh itinerary in redis: {e}");
+            ServiceError::from(e)
+        })?;
+
+    let ttl_seconds = draft_ttl(draft_id).await?;
+    service_info!("refreshed draft {draft_id} for {ttl_seconds} seconds.");
+
+    Ok(DraftItineraryDetails {
+        id: draft_id.to_string(),
+        itinerary,
+        ttl_seconds,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::Parcel;
+    use lib_common::time::Duration;
+    use lib_common::uuid::Uuid;
+    use svc_storage_client_grpc::prelude::flight_plan;
+
+    fn mock_flight_plan() -> FlightPlan {
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(Uuid::new_v4().to_string());
+        data.target_vertiport_id = Some(Uuid::new_v4().to_string());
+        FlightPlan::try_from(data).unwrap()
+    }
+
+    #[test]
+    fn test_draft_query() {
+        let draft = Itinerary {
+            flight_plans: vec![mock_flight_plan()],
+            cargo_weight_g: 500,
+            parcels: vec![Parcel {
+                weight_g: 500,
+                ..Default::default()
+            }],
+            user_id: Uuid::new_v4().to_string(),
+            acquisition_vertiport_id: Uuid::new_v4().to_string(),
+            delivery_vertiport_id: Uuid::new_v4().to_string(),
+            ..Default::default()
+        };
+
+        let query = draft_query(&draft).unwrap();
+        assert_eq!(query.origin_vertiport_id, draft.acquisition_vertiport_id);
+        assert_eq!(query.target_vertiport_id, draft.delivery_vertiport_id);
+        assert_eq!(query.cargo_weight_g(), 500);
+        assert_eq!(query.parcels, draft.parcels);
+        assert_eq!(query.user_id, draft.user_id);
+        assert_eq!(
+            query.time_depart_window.timestamp_min,
+            draft.flight_plans[0].origin_timeslot_start
+        );
+
+        let error = draft_query(&Itinerary::default()).unwrap_err();
+        assert_eq!(error, ServiceError::Internal);
+    }
+
+    #[test]
+    fn test_same_flights() {
+        let a = vec![mock_flight_plan(), mock_flight_plan()];
+        assert!(same_flights(&a, &a.clone()));
+        assert!(!same_flights(&a, &a[..1]));
+
+        let mut b = a.clone();
+        b[1].origin_timeslot_start += Duration::try_minutes(5).unwrap();
+        assert!(!same_flights(&a, &b));
+
+        let mut b = a.clone();
+        b[0].vehicle_id = Uuid::new_v4().to_string();
+        assert!(!same_flights(&a, &b));
+    }
+
+    #[tokio::test]
+    async fn test_get_draft() {
+        let user_id = Uuid::new_v4().to_string();
+        let draft_id = Uuid::new_v4().to_string();
+        let itinerary = Itinerary {
+            flight_plans: vec![mock_flight_plan()],
+            cargo_weight_g: 500,
+            user_id: user_id.clone(),
+            ..Default::default()
+        };
+
+        get_pool()
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .store_itinerary(draft_id.clone(), &itinerary)
+            .await
+            .unwrap();
+
+        let details = get_draft(&draft_id, &user_id).await.unwrap();
+        assert_eq!(details.id, draft_id);
+        assert_eq!(details.itinerary.cargo_weight_g, 500);
+        assert!(details.ttl_seconds > 0);
+
+        // drafts of other users
+        let error = get_draft(&draft_id, &Uuid::new_v4().to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+
+        // expired or unknown drafts
+        let error = get_draft(&Uuid::new_v4().to_string(), &user_id)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+
+        // bad ids
+        let error = get_draft("invalid", &user_id).await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+        let error = get_draft(&draft_id, "invalid").await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+}
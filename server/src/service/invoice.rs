@@ -0,0 +1,160 @@
+//! Invoices of booked itineraries
+//!
+//! svc-storage does not keep the pricing of an itinerary, so the priced
+//!  draft is kept in the cache under the booked itinerary ID until the
+//!  itinerary departs. Cancellations are refunded from it.
+
+use super::ServiceError;
+use crate::cache::pool::{get_pool, ItineraryPool};
+use crate::rest::api::rest_types::Itinerary;
+use lib_common::time::{DateTime, Utc};
+
+/// The amount charged for an itinerary
+pub(super) fn invoice_total(itinerary: &Itinerary) -> f32 {
+    itinerary.invoice.iter().map(|item| item.cost).sum()
+}
+
+/// Seconds until the itinerary departs, if it departs in the future
+fn seconds_to_departure(itinerary: &Itinerary, now: DateTime<Utc>) -> Option<usize> {
+    let departure = itinerary.flight_plans.first()?.origin_timeslot_start;
+    let seconds = (departure - now).num_seconds();
+    (seconds > 0).then_some(seconds as usize)
+}
+
+/// Keep the priced itinerary of a booking until it departs
+/// Bookings fail if their invoice can't be kept, cancellations could not
+///  be refunded without it.
+pub(super) async fn store_invoice(
+    itinerary_id: &str,
+    itinerary: &Itinerary,
+) -> Result<(), ServiceError> {
+    let Some(ttl_seconds) = seconds_to_departure(itinerary, Utc::now()) else {
+        service_warn!("itinerary {itinerary_id} already departed, invoice not kept.");
+        return Ok(());
+    };
+
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .store_booked_itinerary(itinerary_id.to_string(), itinerary, ttl_seconds)
+        .await
+        .map_err(|e| {
+            service_error!("could not keep invoice of itinerary {itinerary_id}: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get the priced itinerary of a booking
+pub(super) async fn load_invoice(itinerary_id: &str) -> Result<Itinerary, ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .get_booked_itinerary(itinerary_id.to_string())
+        .await
+        .map_err(|e| {
+            service_warn!("unable to get invoice of itinerary {itinerary_id}: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Drop the priced itinerary of a booking that was replaced
+pub(super) async fn remove_invoice(itinerary_id: &str) -> Result<(), ServiceError> {
+    get_pool()
+        .await
+        .map_err(|e| {
+            service_error!("unable to get redis pool: {e}");
+            ServiceError::from(e)
+        })?
+        .lock()
+        .await
+        .remove_booked_itinerary(itinerary_id)
+        .await
+        .map_err(|e| {
+            service_error!("could not remove invoice of itinerary {itinerary_id}: {e}");
+            ServiceError::from(e)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::{FlightPlan, InvoiceItem};
+    use lib_common::time::Duration;
+    use lib_common::uuid::Uuid;
+    use svc_storage_client_grpc::prelude::flight_plan;
+
+    fn priced_itinerary(departure: DateTime<Utc>) -> Itinerary {
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(Uuid::new_v4().to_string());
+        data.target_vertiport_id = Some(Uuid::new_v4().to_string());
+        let mut flight_plan = FlightPlan::try_from(data).unwrap();
+        flight_plan.origin_timeslot_start = departure;
+
+        Itinerary {
+            flight_plans: vec![flight_plan],
+            invoice: vec![
+                InvoiceItem {
+                    item: "flight".to_string(),
+                    cost: 10.0,
+                },
+                InvoiceItem {
+                    item: "fee".to_string(),
+                    cost: 2.5,
+                },
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_invoice_total() {
+        let itinerary = priced_itinerary(Utc::now());
+        assert_eq!(invoice_total(&itinerary), 12.5);
+        assert_eq!(invoice_total(&Itinerary::default()), 0.0);
+    }
+
+    #[test]
+    fn test_seconds_to_departure() {
+        let now = Utc::now();
+        let itinerary = priced_itinerary(now + Duration::try_minutes(2).unwrap());
+        assert_eq!(seconds_to_departure(&itinerary, now), Some(120));
+
+        let itinerary = priced_itinerary(now - Duration::try_minutes(2).unwrap());
+        assert!(seconds_to_departure(&itinerary, now).is_none());
+        assert!(seconds_to_departure(&Itinerary::default(), now).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_store_load_invoice() {
+        let departure = Utc::now() + Duration::try_hours(1).unwrap();
+        let itinerary_id = Uuid::new_v4().to_string();
+        store_invoice(&itinerary_id, &priced_itinerary(departure))
+            .await
+            .unwrap();
+        let itinerary = load_invoice(&itinerary_id).await.unwrap();
+        assert_eq!(invoice_total(&itinerary), 12.5);
+
+        remove_invoice(&itinerary_id).await.unwrap();
+        let error = load_invoice(&itinerary_id).await.unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+
+        // departed itineraries are not kept
+        let departure = Utc::now() - Duration::try_hours(1).unwrap();
+        let itinerary_id = Uuid::new_v4().to_string();
+        store_invoice(&itinerary_id, &priced_itinerary(departure))
+            .await
+            .unwrap();
+        let error = load_invoice(&itinerary_id).await.unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+}
@@ -0,0 +1,892 @@
+//! Booked itineraries
+
+use super::invoice::load_invoice;
+use super::parcel::load_parcel;
+use super::track::get_latest_scan;
+use super::ServiceError;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::request::PRICING_CURRENCY;
+use crate::rest::api::rest_types::{
+    BookedItinerary, FlightPlan, Itinerary, ItineraryDetails, ItineraryStatus, ParcelDetails,
+    ParcelInfo, ParcelStatus, QueryItinerariesRequest, QueryItinerariesResponse, Vertiport,
+    DEFAULT_ITINERARIES_PER_PAGE, MAX_ITINERARIES_PER_PAGE,
+};
+use crate::rest::api::utils::get_vertiport_id_from_vertipad_id;
+use lib_common::uuid::to_uuid;
+use svc_storage_client_grpc::link_service::Client as LinkClient;
+use svc_storage_client_grpc::prelude::flight_plan_parcel::RowData as FlightPlanParcel;
+use svc_storage_client_grpc::prelude::{
+    flight_plan, AdvancedSearchFilter, Id, SortOption, SortOrder,
+};
+use svc_storage_client_grpc::resources::itinerary::{
+    self, ItineraryStatus as StorageItineraryStatus,
+};
+use svc_storage_client_grpc::resources::parcel::ParcelStatus as StorageParcelStatus;
+use svc_storage_client_grpc::simple_service::Client as SimpleClient;
+use svc_storage_client_grpc::simple_service_linked::Client as SimpleLinkedClient;
+use tonic::Code;
+
+impl From<StorageItineraryStatus> for ItineraryStatus {
+    fn from(status: StorageItineraryStatus) -> Self {
+        match status {
+            StorageItineraryStatus::Active => ItineraryStatus::Active,
+            StorageItineraryStatus::Cancelled => ItineraryStatus::Cancelled,
+        }
+    }
+}
+
+impl From<ItineraryStatus> for StorageItineraryStatus {
+    fn from(status: ItineraryStatus) -> Self {
+        match status {
+            ItineraryStatus::Active => StorageItineraryStatus::Active,
+            ItineraryStatus::Cancelled => StorageItineraryStatus::Cancelled,
+        }
+    }
+}
+
+/// svc-storage has no status for parcels loaded on board, they are kept as
+///  picked up (from the vertiport) until they are in flight.
+/// TODO(R5): add a loaded status to svc-storage
+impl From<StorageParcelStatus> for ParcelStatus {
+    fn from(status: StorageParcelStatus) -> Self {
+        match status {
+            StorageParcelStatus::Notdroppedoff => ParcelStatus::NotDroppedOff,
+            StorageParcelStatus::Droppedoff => ParcelStatus::DroppedOff,
+            StorageParcelStatus::Pickedup => ParcelStatus::Loaded,
+            StorageParcelStatus::Enroute => ParcelStatus::InFlight,
+            StorageParcelStatus::Arrived => ParcelStatus::Arrived,
+            StorageParcelStatus::Complete => ParcelStatus::Delivered,
+        }
+    }
+}
+
+impl From<ParcelStatus> for StorageParcelStatus {
+    fn from(status: ParcelStatus) -> Self {
+        match status {
+            ParcelStatus::NotDroppedOff => StorageParcelStatus::Notdroppedoff,
+            ParcelStatus::DroppedOff => StorageParcelStatus::Droppedoff,
+            ParcelStatus::Loaded => StorageParcelStatus::Pickedup,
+            ParcelStatus::InFlight => StorageParcelStatus::Enroute,
+            ParcelStatus::Arrived => StorageParcelStatus::Arrived,
+            ParcelStatus::Delivered => StorageParcelStatus::Complete,
+        }
+    }
+}
+
+/// Validated paging of a [`QueryItinerariesRequest`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Paging {
+    page: u32,
+    per_page: u32,
+}
+
+fn validate_request(request: &QueryItinerariesRequest) -> Result<Paging, ServiceError> {
+    let page = request.page.unwrap_or(1);
+    if page == 0 {
+        service_error!("pages start at 1.");
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    let per_page = request.per_page.unwrap_or(DEFAULT_ITINERARIES_PER_PAGE);
+    if per_page == 0 || per_page > MAX_ITINERARIES_PER_PAGE {
+        service_error!("itineraries per page must be between 1 and {MAX_ITINERARIES_PER_PAGE}.");
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    if let (Some(after), Some(before)) = (request.booked_after, request.booked_before) {
+        if after >= before {
+            service_error!("invalid booking time range.");
+            return Err(ServiceError::InvalidArgument);
+        }
+    }
+
+    Ok(Paging { page, per_page })
+}
+
+/// Storage search for the itineraries of a user matching the request
+/// Itineraries are filtered by status and booking time, latest booking
+///  first. Paging is added by the caller.
+fn search_filter(user_id: &str, request: &QueryItinerariesRequest) -> AdvancedSearchFilter {
+    let mut filter =
+        AdvancedSearchFilter::search_equals("user_id".to_string(), user_id.to_string());
+
+    if let Some(status) = request.status {
+        filter = filter.and_equals(
+            "status".to_string(),
+            (StorageItineraryStatus::from(status) as i32).to_string(),
+        );
+    }
+
+    if let Some(after) = request.booked_after {
+        filter = filter.and_greater_or_equal("created_at".to_string(), after.to_string());
+    }
+
+    if let Some(before) = request.booked_before {
+        filter = filter.and_less("created_at".to_string(), before.to_string());
+    }
+
+    filter.order_by = vec![SortOption {
+        sort_field: "created_at".to_string(),
+        sort_order: SortOrder::Desc as i32,
+    }];
+
+    filter
+}
+
+/// Get the flight plans of an itinerary with their IDs, earliest departure first
+pub(super) async fn get_flight_plans(
+    itinerary_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<(String, FlightPlan)>, ServiceError> {
+    let objects = grpc_clients
+        .storage
+        .itinerary_flight_plan_link
+        .get_linked(Id {
+            id: itinerary_id.to_string(),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::Internal
+        })?
+        .into_inner()
+        .list;
+
+    let mut flight_plans = vec![];
+    for flight_plan::Object { id, data } in objects {
+        let mut data = data.ok_or_else(|| {
+            service_error!("flight plan {id} data is None.");
+            ServiceError::Internal
+        })?;
+
+        if data.origin_vertiport_id.is_none() {
+            data.origin_vertiport_id = Some(
+                get_vertiport_id_from_vertipad_id(grpc_clients, &data.origin_vertipad_id).await?,
+            );
+        }
+
+        if data.target_vertiport_id.is_none() {
+            data.target_vertiport_id = Some(
+                get_vertiport_id_from_vertipad_id(grpc_clients, &data.target_vertipad_id).await?,
+            );
+        }
+
+        let flight_plan = FlightPlan::try_from(data).map_err(|e| {
+            service_error!("invalid flight plan {id}: {e}");
+            ServiceError::Internal
+        })?;
+
+        flight_plans.push((id, flight_plan));
+    }
+
+    flight_plans.sort_by_key(|(_, fp)| fp.origin_timeslot_start);
+    Ok(flight_plans)
+}
+
+/// Get the parcels acquired or delivered on a flight plan
+pub(super) async fn get_parcel_links(
+    flight_plan_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<FlightPlanParcel>, ServiceError> {
+    let filter = AdvancedSearchFilter::search_equals(
+        "flight_plan_id".to_string(),
+        flight_plan_id.to_string(),
+    );
+
+    grpc_clients
+        .storage
+        .flight_plan_parcel
+        .search(filter)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::Internal
+        })
+        .map(|response| response.into_inner().list)
+}
+
+/// Get a parcel record
+pub(super) async fn get_parcel(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<ParcelInfo, ServiceError> {
+    let data = grpc_clients
+        .storage
+        .parcel
+        .get_by_id(Id {
+            id: parcel_id.to_string(),
+        })
+        .await
+        .map_err(|e| match e.code() {
+            Code::NotFound => {
+                service_warn!("parcel {parcel_id} not found: {e}");
+                ServiceError::NotFound
+            }
+            _ => {
+                service_error!("svc-storage error {:?}", e);
+                ServiceError::Internal
+            }
+        })?
+        .into_inner()
+        .data
+        .ok_or_else(|| {
+            service_error!("parcel {parcel_id} data is None.");
+            ServiceError::Internal
+        })?;
+
+    let status = StorageParcelStatus::try_from(data.status).map_err(|_| {
+        service_error!("unrecognized parcel status: {}", data.status);
+        ServiceError::Internal
+    })?;
+
+    Ok(ParcelInfo {
+        id: parcel_id.to_string(),
+        weight_g: data.weight_grams,
+        status: status.into(),
+    })
+}
+
+/// Get the booked itinerary a parcel is carried on
+/// svc-storage only links itineraries to their flight plans, so the
+///  itinerary is recorded with the parcel's description and checked against
+///  the flight plans the parcel is linked to. Parcels of cancelled
+///  itineraries are reported as not found.
+pub(super) async fn get_parcel_itinerary(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<itinerary::Object, ServiceError> {
+    let filter =
+        AdvancedSearchFilter::search_equals("parcel_id".to_string(), parcel_id.to_string());
+    let flight_plan_ids: Vec<String> = grpc_clients
+        .storage
+        .flight_plan_parcel
+        .search(filter)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::Internal
+        })?
+        .into_inner()
+        .list
+        .into_iter()
+        .map(|link| link.flight_plan_id)
+        .collect();
+
+    if flight_plan_ids.is_empty() {
+        service_warn!("parcel {parcel_id} is not linked to a flight plan.");
+        return Err(ServiceError::NotFound);
+    }
+
+    let Some(itinerary_id) = load_parcel(parcel_id).await.itinerary_id else {
+        service_warn!("no itinerary recorded for parcel {parcel_id}.");
+        return Err(ServiceError::NotFound);
+    };
+
+    let object = grpc_clients
+        .storage
+        .itinerary
+        .get_by_id(Id {
+            id: itinerary_id.clone(),
+        })
+        .await
+        .map_err(|e| match e.code() {
+            Code::NotFound => {
+                service_warn!("itinerary {itinerary_id} of parcel {parcel_id} not found: {e}");
+                ServiceError::NotFound
+            }
+            _ => {
+                service_error!("svc-storage error {:?}", e);
+                ServiceError::Internal
+            }
+        })?
+        .into_inner();
+
+    let status = object.data.as_ref().map(|data| data.status);
+    if status != Some(StorageItineraryStatus::Active as i32) {
+        service_warn!("itinerary {itinerary_id} of parcel {parcel_id} is not active.");
+        return Err(ServiceError::NotFound);
+    }
+
+    // The recorded itinerary must still carry the parcel
+    let linked = grpc_clients
+        .storage
+        .itinerary_flight_plan_link
+        .get_linked(Id {
+            id: itinerary_id.clone(),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::Internal
+        })?
+        .into_inner()
+        .list;
+
+    if !linked
+        .iter()
+        .any(|flight_plan| flight_plan_ids.contains(&flight_plan.id))
+    {
+        service_warn!("itinerary {itinerary_id} does not carry parcel {parcel_id}.");
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(object)
+}
+
+/// Get the booked itinerary a parcel of a user is carried on
+/// Parcels of other users are reported as not found.
+pub(super) async fn get_user_parcel_itinerary(
+    parcel_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<itinerary::Object, ServiceError> {
+    to_uuid(parcel_id).ok_or_else(|| {
+        service_error!("parcel ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let object = get_parcel_itinerary(parcel_id, grpc_clients).await?;
+    if object.data.as_ref().map(|data| data.user_id.as_str()) != Some(user_id) {
+        service_warn!("parcel {parcel_id} does not belong to user {user_id}.");
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(object)
+}
+
+/// Join the flight plans and parcels of an itinerary record
+pub(crate) async fn get_booked_itinerary(
+    object: itinerary::Object,
+    grpc_clients: &GrpcClients,
+) -> Result<BookedItinerary, ServiceError> {
+    let itinerary_id = object.id;
+    let data = object.data.ok_or_else(|| {
+        service_error!("itinerary {itinerary_id} data is None.");
+        ServiceError::Internal
+    })?;
+
+    let status = StorageItineraryStatus::try_from(data.status).map_err(|_| {
+        service_error!("unrecognized itinerary status: {}", data.status);
+        ServiceError::Internal
+    })?;
+
+    let flight_plans = get_flight_plans(&itinerary_id, grpc_clients).await?;
+
+    //
+    // Parcels are linked to the flight plans they are acquired and delivered on
+    let mut parcel_ids: Vec<String> = vec![];
+    let mut acquisition_vertiport_id = None;
+    let mut delivery_vertiport_id = None;
+    let links = futures::future::try_join_all(
+        flight_plans
+            .iter()
+            .map(|(flight_plan_id, _)| get_parcel_links(flight_plan_id, grpc_clients)),
+    )
+    .await?;
+
+    for ((_, flight_plan), links) in flight_plans.iter().zip(links) {
+        for link in links {
+            if link.acquire {
+                acquisition_vertiport_id.get_or_insert(flight_plan.origin_vertiport_id.clone());
+            }
+
+            if link.deliver {
+                delivery_vertiport_id = Some(flight_plan.target_vertiport_id.clone());
+            }
+
+            if !parcel_ids.contains(&link.parcel_id) {
+                parcel_ids.push(link.parcel_id);
+            }
+        }
+    }
+
+    let parcels = futures::future::try_join_all(
+        parcel_ids
+            .iter()
+            .map(|parcel_id| get_parcel(parcel_id, grpc_clients)),
+    )
+    .await?;
+
+    let flight_plans: Vec<FlightPlan> = flight_plans.into_iter().map(|(_, fp)| fp).collect();
+
+    // The invoice is kept in the cache; without it, the itinerary is shown
+    //  in the currency it was priced in, without its invoice lines
+    let (currency_unit, invoice) = match load_invoice(&itinerary_id).await {
+        Ok(priced) => (priced.currency_unit, priced.invoice),
+        Err(_) => (PRICING_CURRENCY, vec![]),
+    };
+
+    // Without parcels, fall back to the ends of the itinerary
+    let acquisition_vertiport_id = acquisition_vertiport_id
+        .or_else(|| {
+            flight_plans
+                .first()
+                .map(|fp| fp.origin_vertiport_id.clone())
+        })
+        .unwrap_or_default();
+    let delivery_vertiport_id = delivery_vertiport_id
+        .or_else(|| flight_plans.last().map(|fp| fp.target_vertiport_id.clone()))
+        .unwrap_or_default();
+
+    Ok(BookedItinerary {
+        id: itinerary_id,
+        status: status.into(),
+        itinerary: Itinerary {
+            flight_plans,
+            currency_unit,
+            invoice,
+            cargo_weight_g: parcels.iter().map(|parcel| parcel.weight_g).sum(),
+            user_id: data.user_id,
+            acquisition_vertiport_id,
+            delivery_vertiport_id,
+        },
+        parcels,
+    })
+}
+
+/// List the booked itineraries of a user
+pub async fn list_itineraries(
+    user_id: &str,
+    request: &QueryItinerariesRequest,
+    grpc_clients: &GrpcClients,
+) -> Result<QueryItinerariesResponse, ServiceError> {
+    service_debug!("entry.");
+
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let paging = validate_request(request)?;
+    let filter = search_filter(user_id, request);
+
+    // svc-storage lists carry no count, so the matching records are counted
+    //  alongside the search for the page. Only the page is joined.
+    let mut page_filter = filter.clone();
+    page_filter.page_number = paging.page as i32;
+    page_filter.results_per_page = paging.per_page as i32;
+    let (matching, objects) = futures::future::try_join(
+        grpc_clients.storage.itinerary.search(filter),
+        grpc_clients.storage.itinerary.search(page_filter),
+    )
+    .await
+    .map_err(|e| {
+        service_error!("svc-storage error {:?}", e);
+        ServiceError::Internal
+    })?;
+
+    let total = matching.into_inner().list.len() as u32;
+    let itineraries = futures::future::join_all(
+        objects
+            .into_inner()
+            .list
+            .into_iter()
+            .map(|object| get_booked_itinerary(object, grpc_clients)),
+    )
+    .await
+    .into_iter()
+    .filter_map(|booked| {
+        booked
+            .map_err(|e| service_warn!("skipping itinerary: {e}"))
+            .ok()
+    })
+    .collect::<Vec<_>>();
+
+    service_debug!("returning {} of {total} itineraries.", itineraries.len());
+
+    Ok(QueryItinerariesResponse {
+        itineraries,
+        page: paging.page,
+        per_page: paging.per_page,
+        total,
+    })
+}
+
+/// Get a vertiport by ID, if it exists
+pub(super) async fn get_vertiport(
+    vertiport_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Option<Vertiport> {
+    let object = grpc_clients
+        .storage
+        .vertiport
+        .get_by_id(Id {
+            id: vertiport_id.to_string(),
+        })
+        .await
+        .map_err(|e| service_warn!("could not get vertiport {vertiport_id}: {e}"))
+        .ok()?
+        .into_inner();
+
+    Vertiport::try_from(object).ok()
+}
+
+/// Get the itinerary record of a user.
+/// Itineraries of other users are reported as not found.
+pub(super) async fn get_user_itinerary(
+    itinerary_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<itinerary::Object, ServiceError> {
+    to_uuid(itinerary_id).ok_or_else(|| {
+        service_error!("itinerary ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let object = grpc_clients
+        .storage
+        .itinerary
+        .get_by_id(Id {
+            id: itinerary_id.to_string(),
+        })
+        .await
+        .map_err(|e| {
+            service_warn!("could not get itinerary {itinerary_id}: {e}");
+            ServiceError::NotFound
+        })?
+        .into_inner();
+
+    if object.data.as_ref().map(|data| data.user_id.as_str()) != Some(user_id) {
+        service_warn!("itinerary {itinerary_id} does not belong to user {user_id}.");
+        return Err(ServiceError::NotFound);
+    }
+
+    Ok(object)
+}
+
+/// Get a booked itinerary of a user with its vertiports and the latest
+///  scan of each parcel.
+/// Itineraries of other users are reported as not found.
+pub async fn get_itinerary(
+    itinerary_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<ItineraryDetails, ServiceError> {
+    service_debug!("entry.");
+
+    let object = get_user_itinerary(itinerary_id, user_id, grpc_clients).await?;
+    let booked = get_booked_itinerary(object, grpc_clients).await?;
+    let acquisition_vertiport =
+        get_vertiport(&booked.itinerary.acquisition_vertiport_id, grpc_clients).await;
+    let delivery_vertiport =
+        get_vertiport(&booked.itinerary.delivery_vertiport_id, grpc_clients).await;
+
+    let mut parcels = vec![];
+    for parcel in booked.parcels {
+        // The parcel is still shown if its scans can't be read
+        let latest_scan = get_latest_scan(&parcel.id, grpc_clients)
+            .await
+            .unwrap_or_else(|e| {
+                service_warn!("could not get latest scan of parcel {}: {e}", parcel.id);
+                None
+            });
+
+        parcels.push(ParcelDetails {
+            parcel,
+            latest_scan,
+        });
+    }
+
+    Ok(ItineraryDetails {
+        id: booked.id,
+        status: booked.status,
+        itinerary: booked.itinerary,
+        acquisition_vertiport,
+        delivery_vertiport,
+        parcels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::pool::{get_pool, ItineraryPool};
+    use crate::rest::api::rest_types::{CargoInfo, Parcel};
+    use crate::test_util::test_storage::register_parcel;
+    use lib_common::time::{Duration, Utc};
+    use lib_common::uuid::Uuid;
+    use svc_storage_client_grpc::prelude::FieldMask;
+
+    #[test]
+    fn test_validate_request() {
+        let mut request = QueryItinerariesRequest::default();
+        assert_eq!(
+            validate_request(&request).unwrap(),
+            Paging {
+                page: 1,
+                per_page: DEFAULT_ITINERARIES_PER_PAGE
+            }
+        );
+
+        request.page = Some(0);
+        assert_eq!(
+            validate_request(&request).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+        request.page = Some(2);
+
+        request.per_page = Some(MAX_ITINERARIES_PER_PAGE + 1);
+        assert_eq!(
+            validate_request(&request).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+        request.per_page = Some(0);
+        assert_eq!(
+            validate_request(&request).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+        request.per_page = Some(MAX_ITINERARIES_PER_PAGE);
+
+        let now = Utc::now();
+        request.booked_after = Some(now);
+        request.booked_before = Some(now);
+        assert_eq!(
+            validate_request(&request).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+
+        request.booked_before = Some(now + Duration::try_hours(1).unwrap());
+        assert_eq!(
+            validate_request(&request).unwrap(),
+            Paging {
+                page: 2,
+                per_page: MAX_ITINERARIES_PER_PAGE
+            }
+        );
+    }
+
+    #[test]
+    fn test_search_filter() {
+        let user_id = Uuid::new_v4().to_string();
+        let mut request = QueryItinerariesRequest::default();
+
+        // latest booking first
+        let filter = search_filter(&user_id, &request);
+        assert_eq!(filter.filters.len(), 1);
+        assert_eq!(filter.filters[0].search_field, "user_id");
+        assert_eq!(filter.order_by.len(), 1);
+        assert_eq!(filter.order_by[0].sort_field, "created_at");
+        assert_eq!(filter.order_by[0].sort_order, SortOrder::Desc as i32);
+
+        // status and booking time range
+        let now = Utc::now();
+        request.status = Some(ItineraryStatus::Cancelled);
+        request.booked_after = Some(now);
+        request.booked_before = Some(now + Duration::try_hours(1).unwrap());
+        let filter = search_filter(&user_id, &request);
+        let fields: Vec<&str> = filter
+            .filters
+            .iter()
+            .map(|option| option.search_field.as_str())
+            .collect();
+        assert_eq!(fields, ["user_id", "status", "created_at", "created_at"]);
+        assert_eq!(
+            filter.filters[1].search_value,
+            [(StorageItineraryStatus::Cancelled as i32).to_string()]
+        );
+    }
+
+    #[test]
+    fn test_status_from_storage() {
+        assert_eq!(
+            ItineraryStatus::from(StorageItineraryStatus::Active),
+            ItineraryStatus::Active
+        );
+        assert_eq!(
+            ItineraryStatus::from(StorageItineraryStatus::Cancelled),
+            ItineraryStatus::Cancelled
+        );
+        assert_eq!(
+            StorageItineraryStatus::from(ItineraryStatus::Active),
+            StorageItineraryStatus::Active
+        );
+        assert_eq!(
+            ParcelStatus::from(StorageParcelStatus::Notdroppedoff),
+            ParcelStatus::NotDroppedOff
+        );
+        assert_eq!(
+            ParcelStatus::from(StorageParcelStatus::Pickedup),
+            ParcelStatus::Loaded
+        );
+        assert_eq!(
+            ParcelStatus::from(StorageParcelStatus::Complete),
+            ParcelStatus::Delivered
+        );
+
+        for status in [
+            StorageParcelStatus::Notdroppedoff,
+            StorageParcelStatus::Droppedoff,
+            StorageParcelStatus::Enroute,
+            StorageParcelStatus::Arrived,
+            StorageParcelStatus::Pickedup,
+            StorageParcelStatus::Complete,
+        ] {
+            assert_eq!(
+                StorageParcelStatus::from(ParcelStatus::from(status)),
+                status
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_itinerary_not_found() {
+        use svc_storage_client_grpc::resources::itinerary::Data as ItineraryData;
+
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let user_id = Uuid::new_v4().to_string();
+
+        assert_eq!(
+            get_itinerary("invalid", &user_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+
+        // not in storage
+        let itinerary_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            get_itinerary(&itinerary_id, &user_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::NotFound
+        );
+
+        // belongs to another user
+        let itinerary_id = grpc_clients
+            .storage
+            .itinerary
+            .insert(ItineraryData {
+                user_id: Uuid::new_v4().to_string(),
+                status: StorageItineraryStatus::Active as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        assert_eq!(
+            get_itinerary(&itinerary_id, &user_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_parcel_itinerary() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        // not in storage
+        let parcel_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            get_parcel_itinerary(&parcel_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::NotFound
+        );
+
+        let parcel = register_parcel(&grpc_clients).await;
+        assert_eq!(
+            get_parcel_itinerary(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap()
+                .id,
+            parcel.itinerary_id
+        );
+
+        // on a flight plan of no active itinerary of the user
+        grpc_clients
+            .storage
+            .itinerary
+            .update(itinerary::UpdateObject {
+                id: parcel.itinerary_id.clone(),
+                data: Some(itinerary::Data {
+                    user_id: parcel.user_id.clone(),
+                    status: StorageItineraryStatus::Cancelled as i32,
+                }),
+                mask: Some(FieldMask {
+                    paths: vec!["status".to_string()],
+                }),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_parcel_itinerary(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::NotFound
+        );
+
+        // recorded on an active itinerary that does not carry it
+        let object = grpc_clients
+            .storage
+            .itinerary
+            .insert(itinerary::Data {
+                user_id: parcel.user_id.clone(),
+                status: StorageItineraryStatus::Active as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        let mut info = CargoInfo::new(parcel.parcel_id.clone(), &Parcel::default());
+        info.itinerary_id = Some(object.id);
+        get_pool()
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .store_parcel(&info)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            get_parcel_itinerary(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_itineraries_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let request = QueryItinerariesRequest::default();
+        assert_eq!(
+            list_itineraries("invalid", &request, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+
+        let request = QueryItinerariesRequest {
+            page: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            list_itineraries(&Uuid::new_v4().to_string(), &request, &grpc_clients)
+                .await
+                .unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+    }
+}
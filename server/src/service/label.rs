@@ -0,0 +1,294 @@
+//! Shipping labels
+//!
+//! Each parcel gets a label to print and stick on the parcel. The label code
+//!  is a signed token of the parcel ID, its itinerary and its vertiports, so
+//!  scanners can identify the parcel and tell a forged or altered label
+//!  from a genuine one.
+
+use super::itinerary::get_user_parcel_itinerary;
+use super::scan::get_parcel_route;
+use super::ServiceError;
+use crate::config::Config;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::ParcelLabel;
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// The fields signed in a label code
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LabelClaims {
+    /// The parcel ID
+    pub pid: String,
+
+    /// The itinerary ID
+    pub iti: String,
+
+    /// The name of the origin vertiport
+    pub org: String,
+
+    /// The name of the target vertiport
+    pub dst: String,
+}
+
+impl From<&ParcelLabel> for LabelClaims {
+    fn from(label: &ParcelLabel) -> Self {
+        LabelClaims {
+            pid: label.parcel_id.clone(),
+            iti: label.itinerary_id.clone(),
+            org: label.origin_vertiport.clone(),
+            dst: label.target_vertiport.clone(),
+        }
+    }
+}
+
+/// Errors signing or verifying label codes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LabelError {
+    /// No signing key is configured
+    NoKey,
+
+    /// The code could not be signed
+    Sign,
+
+    /// The code is malformed, altered or signed with another key
+    Code,
+}
+
+impl Display for LabelError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            LabelError::NoKey => write!(f, "no label signing key configured"),
+            LabelError::Sign => write!(f, "could not sign label code"),
+            LabelError::Code => write!(f, "label code invalid or altered"),
+        }
+    }
+}
+
+impl From<LabelError> for ServiceError {
+    fn from(error: LabelError) -> Self {
+        match error {
+            LabelError::NoKey => ServiceError::Unavailable,
+            LabelError::Sign => ServiceError::Internal,
+            LabelError::Code => ServiceError::InvalidArgument,
+        }
+    }
+}
+
+/// Signs and verifies label codes
+#[derive(Clone, Default)]
+pub struct LabelSigner {
+    /// The secret key, labels are not issued or verified without one
+    key: Option<Vec<u8>>,
+}
+
+impl Debug for LabelSigner {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        // Never log the key
+        f.debug_struct("LabelSigner")
+            .field("key", &self.key.as_ref().map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+impl From<&Config> for LabelSigner {
+    fn from(config: &Config) -> Self {
+        LabelSigner::new(config.label_signing_key.as_deref())
+    }
+}
+
+impl LabelSigner {
+    /// A signer with the given secret key
+    /// Empty keys are ignored.
+    pub fn new(key: Option<&str>) -> Self {
+        LabelSigner {
+            key: key
+                .filter(|key| !key.is_empty())
+                .map(|key| key.as_bytes().to_vec()),
+        }
+    }
+
+    /// Sign the fields of a label into its code
+    pub fn sign(&self, claims: &LabelClaims) -> Result<String, LabelError> {
+        let key = self.key.as_ref().ok_or(LabelError::NoKey)?;
+        encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(key),
+        )
+        .map_err(|e| {
+            service_error!("could not sign label code: {e}");
+            LabelError::Sign
+        })
+    }
+
+    /// Verify a label code and return its fields
+    pub fn verify(&self, code: &str) -> Result<LabelClaims, LabelError> {
+        let key = self.key.as_ref().ok_or_else(|| {
+            service_error!("no label signing key configured.");
+            LabelError::NoKey
+        })?;
+
+        // Labels stay valid for the life of the parcel
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.required_spec_claims.clear();
+        validation.validate_exp = false;
+
+        decode::<LabelClaims>(code.trim(), &DecodingKey::from_secret(key), &validation)
+            .map(|data| data.claims)
+            .map_err(|e| {
+                service_error!("label code rejected: {e}");
+                LabelError::Code
+            })
+    }
+}
+
+/// Render a label for Zebra printers, with the code as a QR code
+pub fn render_zpl(label: &ParcelLabel) -> String {
+    // ZPL reserves ^ and ~ as command prefixes
+    let text = |value: &str| value.replace(['^', '~'], " ");
+
+    [
+        "^XA".to_string(),
+        "^CF0,30".to_string(),
+        format!("^FO40,40^FDFrom: {}^FS", text(&label.origin_vertiport)),
+        format!("^FO40,80^FDTo: {}^FS", text(&label.target_vertiport)),
+        "^CF0,20".to_string(),
+        format!("^FO40,130^FDParcel: {}^FS", label.parcel_id),
+        format!("^FO40,160^FDItinerary: {}^FS", label.itinerary_id),
+        format!("^FO40,200^BQN,2,5^FDQA,{}^FS", label.code),
+        "^XZ".to_string(),
+    ]
+    .join("\n")
+}
+
+/// Get the shipping label of a parcel of a user
+/// Parcels of other users are reported as not found.
+pub async fn get_label(
+    parcel_id: &str,
+    user_id: &str,
+    signer: &LabelSigner,
+    grpc_clients: &GrpcClients,
+) -> Result<ParcelLabel, ServiceError> {
+    service_debug!("entry.");
+
+    let itinerary = get_user_parcel_itinerary(parcel_id, user_id, grpc_clients).await?;
+    let route = get_parcel_route(parcel_id, grpc_clients).await?;
+
+    let mut label = ParcelLabel {
+        parcel_id: parcel_id.to_string(),
+        itinerary_id: itinerary.id,
+        origin_vertiport: route.acquisition.label,
+        target_vertiport: route.delivery.label,
+        code: String::new(),
+    };
+
+    label.code = signer.sign(&LabelClaims::from(&label))?;
+    Ok(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lib_common::uuid::Uuid;
+
+    fn label() -> ParcelLabel {
+        ParcelLabel {
+            parcel_id: Uuid::new_v4().to_string(),
+            itinerary_id: Uuid::new_v4().to_string(),
+            origin_vertiport: "Amsterdam Centraal".to_string(),
+            target_vertiport: "Utrecht^FS".to_string(),
+            code: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        let signer = LabelSigner::new(Some("label secret"));
+        let claims = LabelClaims::from(&label());
+
+        let code = signer.sign(&claims).unwrap();
+        assert_eq!(signer.verify(&code).unwrap(), claims);
+
+        // signed with another key
+        let other = LabelSigner::new(Some("other secret"));
+        assert_eq!(other.verify(&code).unwrap_err(), LabelError::Code);
+
+        // altered payload
+        let forged = other
+            .sign(&LabelClaims {
+                pid: Uuid::new_v4().to_string(),
+                ..claims
+            })
+            .unwrap();
+        let mut parts: Vec<&str> = code.split('.').collect();
+        parts[1] = forged.split('.').nth(1).unwrap();
+        assert_eq!(
+            signer.verify(&parts.join(".")).unwrap_err(),
+            LabelError::Code
+        );
+
+        assert_eq!(signer.verify("not a code").unwrap_err(), LabelError::Code);
+    }
+
+    #[test]
+    fn test_no_key() {
+        let claims = LabelClaims::from(&label());
+        for signer in [LabelSigner::default(), LabelSigner::new(Some(""))] {
+            assert_eq!(signer.sign(&claims).unwrap_err(), LabelError::NoKey);
+            assert_eq!(signer.verify("code").unwrap_err(), LabelError::NoKey);
+        }
+    }
+
+    #[test]
+    fn test_signer_debug_hides_key() {
+        let signer = LabelSigner::new(Some("label secret"));
+        assert!(!format!("{signer:?}").contains("label secret"));
+    }
+
+    #[test]
+    fn test_render_zpl() {
+        let mut label = label();
+        label.code = "header.payload.signature".to_string();
+
+        let zpl = render_zpl(&label);
+        assert!(zpl.starts_with("^XA"));
+        assert!(zpl.ends_with("^XZ"));
+        assert!(zpl.contains("^FDQA,header.payload.signature^FS"));
+        assert!(zpl.contains(&label.parcel_id));
+        assert!(zpl.contains("^FDTo: Utrecht FS^FS"));
+    }
+
+    #[test]
+    fn test_service_error_from_label_error() {
+        assert_eq!(
+            ServiceError::from(LabelError::NoKey),
+            ServiceError::Unavailable
+        );
+        assert_eq!(ServiceError::from(LabelError::Sign), ServiceError::Internal);
+        assert_eq!(
+            ServiceError::from(LabelError::Code),
+            ServiceError::InvalidArgument
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_label_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let signer = LabelSigner::new(Some("label secret"));
+        let user_id = Uuid::new_v4().to_string();
+
+        let error = get_label("invalid", &user_id, &signer, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // not booked
+        let parcel_id = Uuid::new_v4().to_string();
+        let error = get_label(&parcel_id, &user_id, &signer, &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+}
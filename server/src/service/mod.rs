@@ -0,0 +1,167 @@
+//! Service
+//! Transport-neutral booking, booking job, cancellation, delivery, draft, itinerary, label,
+//!  modification, notification, parcel, scan and tracking logic shared by the REST and gRPC servers
+
+#[macro_use]
+pub mod macros;
+pub mod booking;
+pub mod cancel;
+pub mod compensation;
+pub mod create;
+pub mod delivery;
+pub mod draft;
+mod invoice;
+pub mod itinerary;
+pub mod label;
+pub mod modify;
+pub mod notify;
+pub mod parcel;
+pub mod scan;
+pub mod track;
+
+use crate::cache::pool::CacheError;
+use hyper::StatusCode;
+use std::fmt::{self, Display, Formatter};
+
+/// Errors returned by the service layer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServiceError {
+    /// The request contains invalid data
+    InvalidArgument,
+
+    /// The requested record does not exist or has expired
+    NotFound,
+
+    /// The request was rejected by svc-scheduler
+    Rejected,
+
+    /// The operation did not complete in time
+    Timeout,
+
+    /// A dependency could not be reached
+    Unavailable,
+
+    /// A dependency returned an error
+    Internal,
+}
+
+impl Display for ServiceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ServiceError::InvalidArgument => write!(f, "invalid argument"),
+            ServiceError::NotFound => write!(f, "not found"),
+            ServiceError::Rejected => write!(f, "rejected"),
+            ServiceError::Timeout => write!(f, "timed out"),
+            ServiceError::Unavailable => write!(f, "dependency unavailable"),
+            ServiceError::Internal => write!(f, "internal error"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+/// Lowercase hex SHA-256 digest
+pub fn sha256_hex(data: &[u8]) -> String {
+    openssl::sha::sha256(data)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// The shared helpers in [`crate::rest::api::utils`] report errors as HTTP
+///  status codes
+impl From<StatusCode> for ServiceError {
+    fn from(code: StatusCode) -> Self {
+        match code {
+            StatusCode::BAD_REQUEST => ServiceError::InvalidArgument,
+            StatusCode::NOT_FOUND => ServiceError::NotFound,
+            StatusCode::REQUEST_TIMEOUT => ServiceError::Timeout,
+            StatusCode::SERVICE_UNAVAILABLE => ServiceError::Unavailable,
+            _ => ServiceError::Internal,
+        }
+    }
+}
+
+/// Drafts are cached with a short lifetime
+impl From<CacheError> for ServiceError {
+    fn from(error: CacheError) -> Self {
+        match error {
+            CacheError::NotFound => ServiceError::NotFound,
+            CacheError::PoolUnavailable | CacheError::CouldNotConnect => ServiceError::Unavailable,
+            _ => ServiceError::Internal,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_error_display() {
+        assert_eq!(
+            ServiceError::InvalidArgument.to_string(),
+            "invalid argument"
+        );
+        assert_eq!(ServiceError::NotFound.to_string(), "not found");
+        assert_eq!(ServiceError::Rejected.to_string(), "rejected");
+        assert_eq!(ServiceError::Timeout.to_string(), "timed out");
+        assert_eq!(
+            ServiceError::Unavailable.to_string(),
+            "dependency unavailable"
+        );
+        assert_eq!(ServiceError::Internal.to_string(), "internal error");
+    }
+
+    #[test]
+    fn test_service_error_from_status_code() {
+        assert_eq!(
+            ServiceError::from(StatusCode::BAD_REQUEST),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(StatusCode::NOT_FOUND),
+            ServiceError::NotFound
+        );
+        assert_eq!(
+            ServiceError::from(StatusCode::REQUEST_TIMEOUT),
+            ServiceError::Timeout
+        );
+        assert_eq!(
+            ServiceError::from(StatusCode::SERVICE_UNAVAILABLE),
+            ServiceError::Unavailable
+        );
+        assert_eq!(
+            ServiceError::from(StatusCode::INTERNAL_SERVER_ERROR),
+            ServiceError::Internal
+        );
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b""),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_service_error_from_cache_error() {
+        assert_eq!(
+            ServiceError::from(CacheError::NotFound),
+            ServiceError::NotFound
+        );
+        assert_eq!(
+            ServiceError::from(CacheError::PoolUnavailable),
+            ServiceError::Unavailable
+        );
+        assert_eq!(
+            ServiceError::from(CacheError::CouldNotConnect),
+            ServiceError::Unavailable
+        );
+        assert_eq!(
+            ServiceError::from(CacheError::InvalidValue),
+            ServiceError::Internal
+        );
+    }
+}
@@ -0,0 +1,568 @@
+//! Itinerary modification
+//!
+//! A booked itinerary is modified in two steps, like a new booking: the
+//!  replacement itineraries are queried and priced as drafts, then the
+//!  chosen draft is booked and takes over the parcels of the original
+//!  itinerary. The original booking is only cancelled once the replacement
+//!  holds the parcels and is paid for, then it is refunded from its
+//!  invoice. Every earlier step is undone on failure with the compensation
+//!  of failed bookings, and the hold placed on the customer's payment
+//!  option is always released.
+
+use super::cancel::{payment_refund, scheduler_cancel};
+use super::compensation::{compensate, Compensation};
+use super::create::{
+    link_parcel, payment_confirm, payment_release, scheduler_poll, scheduler_request,
+    unlink_parcel, SCHEDULER_TASK_TIMEOUT_SECONDS,
+};
+use super::draft::load_draft;
+use super::invoice::{self, load_invoice, remove_invoice, store_invoice};
+use super::itinerary::{
+    get_booked_itinerary, get_flight_plans, get_parcel_links, get_user_itinerary,
+};
+use super::parcel::{load_parcel, move_parcel};
+use super::ServiceError;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{
+    BookedItinerary, DraftItinerary, Itinerary, ItineraryModifyRequest, ItineraryStatus, Parcel,
+    ParcelInfo, ParcelStatus, QueryItineraryRequest, TimeWindow,
+};
+use lib_common::time::{Duration, Utc};
+use lib_common::uuid::to_uuid;
+use svc_storage_client_grpc::prelude::FieldMask;
+use svc_storage_client_grpc::resources::parcel::{Data as ParcelData, UpdateObject};
+use svc_storage_client_grpc::simple_service::Client as SimpleClient;
+
+/// Itineraries can be changed until their parcels are loaded
+fn check_modifiable(booked: &BookedItinerary) -> Result<(), ServiceError> {
+    if booked.status != ItineraryStatus::Active {
+        service_error!("itinerary {} is not active.", booked.id);
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    if booked.parcels.iter().any(|parcel| {
+        !matches!(
+            parcel.status,
+            ParcelStatus::NotDroppedOff | ParcelStatus::DroppedOff
+        )
+    }) {
+        service_error!("itinerary {} has parcels in transit.", booked.id);
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+/// The weight of a single parcel can be changed, the weight of several
+///  parcels can't be split
+fn check_weight(booked: &BookedItinerary, cargo_weight_g: u32) -> Result<(), ServiceError> {
+    if cargo_weight_g != booked.itinerary.cargo_weight_g && booked.parcels.len() != 1 {
+        service_error!(
+            "can't change the weight of itinerary {} with {} parcels.",
+            booked.id,
+            booked.parcels.len()
+        );
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    Ok(())
+}
+
+/// A registered parcel, with its weight from svc-storage and the rest of
+///  its description from the cache
+/// Parcels without a kept description have unknown dimensions, they
+///  can't be moved without knowing their handling.
+async fn stored_parcel(parcel: &ParcelInfo) -> Result<Parcel, ServiceError> {
+    let description = load_parcel(&parcel.id).await;
+    let Some(handling) = description.handling else {
+        service_error!("unknown handling of parcel {}.", parcel.id);
+        return Err(ServiceError::Internal);
+    };
+
+    Ok(Parcel {
+        weight_g: parcel.weight_g,
+        length_mm: description.length_mm,
+        width_mm: description.width_mm,
+        height_mm: description.height_mm,
+        reference: description.reference,
+        handling,
+        recipient: None,
+        delivery_pin: false,
+    })
+}
+
+/// The flight query for the replacements of a booked itinerary
+/// Values not given in the request are taken from the booked itinerary,
+///  `parcels` are the parcels it was priced for.
+fn replacement_query(
+    booked: &BookedItinerary,
+    parcels: &[Parcel],
+    request: &ItineraryModifyRequest,
+) -> Result<QueryItineraryRequest, ServiceError> {
+    if request.time_depart_window.is_none() && request.cargo_weight_g.is_none() {
+        service_error!("nothing to modify.");
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    let cargo_weight_g = request
+        .cargo_weight_g
+        .unwrap_or(booked.itinerary.cargo_weight_g);
+    check_weight(booked, cargo_weight_g)?;
+
+    let mut parcels = parcels.to_vec();
+    if let [parcel] = parcels.as_mut_slice() {
+        parcel.weight_g = cargo_weight_g;
+    }
+
+    let time_depart_window = match request.time_depart_window {
+        Some(window) => window,
+        None => {
+            let departure = booked.itinerary.flight_plans.first().ok_or_else(|| {
+                service_error!("itinerary {} has no flight plans.", booked.id);
+                ServiceError::Internal
+            })?;
+
+            TimeWindow {
+                timestamp_min: departure.origin_timeslot_start,
+                timestamp_max: departure.origin_timeslot_end,
+            }
+        }
+    };
+
+    Ok(QueryItineraryRequest {
+        origin_vertiport_id: booked.itinerary.acquisition_vertiport_id.clone(),
+        target_vertiport_id: booked.itinerary.delivery_vertiport_id.clone(),
+        time_depart_window,
+        parcels,
+        cargo_weight_g: None,
+        user_id: booked.itinerary.user_id.clone(),
+    })
+}
+
+/// A draft can only replace an itinerary of the same user and route
+fn check_draft(booked: &BookedItinerary, draft: &Itinerary) -> Result<(), ServiceError> {
+    if draft.user_id != booked.itinerary.user_id {
+        service_error!("draft belongs to another user.");
+        return Err(ServiceError::NotFound);
+    }
+
+    if draft.acquisition_vertiport_id != booked.itinerary.acquisition_vertiport_id
+        || draft.delivery_vertiport_id != booked.itinerary.delivery_vertiport_id
+    {
+        service_error!("draft does not serve the route of itinerary {}.", booked.id);
+        return Err(ServiceError::InvalidArgument);
+    }
+
+    check_weight(booked, draft.cargo_weight_g)
+}
+
+/// Set the weight of a parcel
+pub(super) async fn update_parcel_weight(
+    parcel_id: &str,
+    weight_g: u32,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    grpc_clients
+        .storage
+        .parcel
+        .update(UpdateObject {
+            id: parcel_id.to_string(),
+            data: Some(ParcelData {
+                weight_grams: weight_g,
+                ..Default::default()
+            }),
+            mask: Some(FieldMask {
+                paths: vec!["weight_grams".to_string()],
+            }),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("could not update weight of parcel {parcel_id}: {:?}", e);
+            ServiceError::Internal
+        })?;
+
+    Ok(())
+}
+
+/// Move the parcels of a booked itinerary to its replacement, charge for
+///  the replacement, then cancel and refund the booked itinerary
+/// The changes made to the replacement are recorded in `compensation`, to
+///  be undone if the swap fails before the booked itinerary is cancelled.
+async fn swap(
+    booked: &BookedItinerary,
+    draft: &Itinerary,
+    compensation: &mut Compensation,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    //
+    // Keep the links of the original flight plans to remove them once the
+    //  replacement holds the parcels
+    let mut old_links = vec![];
+    for (flight_plan_id, _) in get_flight_plans(&booked.id, grpc_clients).await? {
+        old_links.extend(get_parcel_links(&flight_plan_id, grpc_clients).await?);
+    }
+
+    // The original booking is refunded from its invoice
+    let original = load_invoice(&booked.id).await.map_err(|e| {
+        service_error!("no invoice to refund itinerary {}: {e}", booked.id);
+        ServiceError::Internal
+    })?;
+
+    for parcel in &booked.parcels {
+        let links = link_parcel(
+            &compensation.itinerary_id,
+            &parcel.id,
+            &draft.acquisition_vertiport_id,
+            &draft.delivery_vertiport_id,
+            grpc_clients,
+        )
+        .await?;
+
+        compensation.links.extend(links.into_iter().map(Into::into));
+    }
+
+    // The weight of several parcels is never changed, see check_weight
+    if let [parcel] = booked.parcels.as_slice() {
+        if parcel.weight_g != draft.cargo_weight_g {
+            update_parcel_weight(&parcel.id, draft.cargo_weight_g, grpc_clients).await?;
+            compensation.weight = Some((parcel.id.clone(), parcel.weight_g));
+        }
+    }
+
+    payment_confirm(compensation.total, compensation.currency_unit, false).await?;
+    compensation.charged = true;
+
+    payment_release(compensation.total, compensation.currency_unit).await?;
+    compensation.released = true;
+
+    //
+    // Point of no return, the original booking is released
+    scheduler_cancel(&booked.id, &booked.itinerary.user_id, grpc_clients).await?;
+
+    // The parcels are looked up through the itinerary kept with them
+    for parcel in &booked.parcels {
+        move_parcel(&parcel.id, &compensation.itinerary_id).await;
+    }
+
+    let refund_amount = invoice::invoice_total(&original);
+    if let Err(e) = payment_refund(refund_amount, original.currency_unit).await {
+        // The refund is owed, it is retried by the payment service
+        service_error!("could not refund itinerary {}: {e}", booked.id);
+    }
+
+    // The invoice of the replacement was kept before the swap
+    if let Err(e) = remove_invoice(&booked.id).await {
+        service_warn!(
+            "invoice of itinerary {} kept until departure: {e}",
+            booked.id
+        );
+    }
+
+    // The original flight plans are cancelled, stale links only clutter them
+    if let Err(e) = unlink_parcel(&old_links, grpc_clients).await {
+        service_warn!("links of itinerary {} not removed: {e}", booked.id);
+    }
+
+    Ok(())
+}
+
+/// Book the replacement of an itinerary with the scheduler
+/// Returns the ID of the replacement itinerary.
+async fn book_replacement(
+    draft: &Itinerary,
+    grpc_clients: &GrpcClients,
+) -> Result<String, ServiceError> {
+    let delta = Duration::try_seconds(SCHEDULER_TASK_TIMEOUT_SECONDS).ok_or_else(|| {
+        service_error!("failed to create duration.");
+        ServiceError::Internal
+    })?;
+
+    let expiry = Utc::now() + delta;
+    let task_id = scheduler_request(draft, expiry, grpc_clients)
+        .await?
+        .task_id;
+
+    let itinerary_id = scheduler_poll(task_id, expiry, grpc_clients.clone()).await?;
+    to_uuid(&itinerary_id).ok_or_else(|| {
+        service_error!("invalid itinerary UUID.");
+        ServiceError::Internal
+    })?;
+
+    Ok(itinerary_id)
+}
+
+/// Get priced replacements for a booked itinerary of a user
+/// The replacements are stored as drafts that can be confirmed with
+///  [`modify_itinerary`].
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) need backends to test (integration)
+pub async fn query_modifications(
+    itinerary_id: &str,
+    user_id: &str,
+    request: &ItineraryModifyRequest,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<DraftItinerary>, ServiceError> {
+    service_debug!("entry.");
+
+    let object = get_user_itinerary(itinerary_id, user_id, grpc_clients).await?;
+    let booked = get_booked_itinerary(object, grpc_clients).await?;
+    check_modifiable(&booked)?;
+
+    let mut parcels = vec![];
+    for parcel in &booked.parcels {
+        parcels.push(stored_parcel(parcel).await?);
+    }
+
+    let payload = replacement_query(&booked, &parcels, request)?;
+    crate::rest::api::request::query_itineraries(&payload, &mut grpc_clients.clone())
+        .await
+        .map_err(ServiceError::from)
+}
+
+/// Replace a booked itinerary of a user with a draft from
+///  [`query_modifications`]
+/// Returns the ID of the replacement itinerary. If any step fails, the
+///  original itinerary is kept as it was.
+pub async fn modify_itinerary(
+    itinerary_id: &str,
+    draft_id: &str,
+    user_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<String, ServiceError> {
+    service_debug!("entry.");
+
+    to_uuid(draft_id).ok_or_else(|| {
+        service_error!("draft ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let object = get_user_itinerary(itinerary_id, user_id, grpc_clients).await?;
+    let booked = get_booked_itinerary(object, grpc_clients).await?;
+    check_modifiable(&booked)?;
+
+    let draft = load_draft(draft_id).await?;
+    check_draft(&booked, &draft)?;
+
+    let invoice_total = invoice::invoice_total(&draft);
+    payment_confirm(invoice_total, draft.currency_unit, true).await?;
+
+    //
+    // Book the replacement with the scheduler
+    let new_itinerary_id = match book_replacement(&draft, grpc_clients).await {
+        Ok(new_itinerary_id) => new_itinerary_id,
+        Err(e) => {
+            service_error!("could not book replacement of itinerary {itinerary_id}: {e}");
+            if let Err(e) = payment_release(invoice_total, draft.currency_unit).await {
+                service_error!("could not release payment hold for itinerary {itinerary_id}: {e}");
+            }
+
+            return Err(e);
+        }
+    };
+
+    //
+    // From here on, every change is undone if the modification fails
+    let mut compensation = Compensation::new(&new_itinerary_id, &draft, invoice_total);
+
+    // Cancellations of the replacement are refunded from its invoice
+    if let Err(e) = store_invoice(&new_itinerary_id, &draft).await {
+        compensate(compensation, grpc_clients).await;
+        return Err(e);
+    }
+
+    if let Err(e) = swap(&booked, &draft, &mut compensation, grpc_clients).await {
+        service_error!("could not replace itinerary {itinerary_id}: {e}");
+        compensate(compensation, grpc_clients).await;
+        return Err(e);
+    }
+
+    service_info!("itinerary {itinerary_id} replaced by {new_itinerary_id}.");
+    Ok(new_itinerary_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::api::rest_types::FlightPlan;
+    use lib_common::uuid::Uuid;
+    use svc_storage_client_grpc::prelude::flight_plan;
+
+    fn booked_itinerary(parcels: usize) -> BookedItinerary {
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(Uuid::new_v4().to_string());
+        data.target_vertiport_id = Some(Uuid::new_v4().to_string());
+        let flight_plan = FlightPlan::try_from(data).unwrap();
+
+        BookedItinerary {
+            id: Uuid::new_v4().to_string(),
+            status: ItineraryStatus::Active,
+            itinerary: Itinerary {
+                acquisition_vertiport_id: flight_plan.origin_vertiport_id.clone(),
+                delivery_vertiport_id: flight_plan.target_vertiport_id.clone(),
+                flight_plans: vec![flight_plan],
+                cargo_weight_g: 100 * parcels as u32,
+                user_id: Uuid::new_v4().to_string(),
+                ..Default::default()
+            },
+            parcels: (0..parcels)
+                .map(|_| ParcelInfo {
+                    id: Uuid::new_v4().to_string(),
+                    weight_g: 100,
+                    status: ParcelStatus::NotDroppedOff,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_check_modifiable() {
+        let mut booked = booked_itinerary(2);
+        check_modifiable(&booked).unwrap();
+
+        booked.parcels[0].status = ParcelStatus::DroppedOff;
+        check_modifiable(&booked).unwrap();
+
+        booked.parcels[1].status = ParcelStatus::Loaded;
+        assert_eq!(
+            check_modifiable(&booked).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+
+        booked.parcels[1].status = ParcelStatus::NotDroppedOff;
+        booked.status = ItineraryStatus::Cancelled;
+        assert_eq!(
+            check_modifiable(&booked).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+    }
+
+    fn priced_parcels(booked: &BookedItinerary) -> Vec<Parcel> {
+        booked
+            .parcels
+            .iter()
+            .map(|parcel| Parcel {
+                weight_g: parcel.weight_g,
+                length_mm: 300,
+                width_mm: 200,
+                height_mm: 100,
+                reference: None,
+                handling: vec![],
+                recipient: None,
+                delivery_pin: false,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_replacement_query() {
+        let booked = booked_itinerary(1);
+        let parcels = priced_parcels(&booked);
+
+        // nothing to modify
+        let error =
+            replacement_query(&booked, &parcels, &ItineraryModifyRequest::default()).unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // same departure slot, new weight
+        let request = ItineraryModifyRequest {
+            cargo_weight_g: Some(500),
+            ..Default::default()
+        };
+        let query = replacement_query(&booked, &parcels, &request).unwrap();
+        let departure = &booked.itinerary.flight_plans[0];
+        assert_eq!(query.cargo_weight_g(), 500);
+        assert_eq!(query.parcels[0].length_mm, parcels[0].length_mm);
+        assert_eq!(
+            query.time_depart_window.timestamp_min,
+            departure.origin_timeslot_start
+        );
+        assert_eq!(
+            query.time_depart_window.timestamp_max,
+            departure.origin_timeslot_end
+        );
+        assert_eq!(
+            query.origin_vertiport_id,
+            booked.itinerary.acquisition_vertiport_id
+        );
+        assert_eq!(
+            query.target_vertiport_id,
+            booked.itinerary.delivery_vertiport_id
+        );
+        assert_eq!(query.user_id, booked.itinerary.user_id);
+
+        // new departure, same weight
+        let window = TimeWindow {
+            timestamp_min: Utc::now() + Duration::try_hours(1).unwrap(),
+            timestamp_max: Utc::now() + Duration::try_hours(2).unwrap(),
+        };
+        let request = ItineraryModifyRequest {
+            time_depart_window: Some(window),
+            ..Default::default()
+        };
+        let query = replacement_query(&booked, &parcels, &request).unwrap();
+        assert_eq!(query.parcels, parcels);
+        assert_eq!(query.cargo_weight_g(), booked.itinerary.cargo_weight_g);
+        assert_eq!(query.time_depart_window.timestamp_min, window.timestamp_min);
+
+        // weight of several parcels
+        let booked = booked_itinerary(2);
+        let parcels = priced_parcels(&booked);
+        let request = ItineraryModifyRequest {
+            cargo_weight_g: Some(500),
+            ..Default::default()
+        };
+        let error = replacement_query(&booked, &parcels, &request).unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+
+    #[test]
+    fn test_check_draft() {
+        let booked = booked_itinerary(1);
+        let mut draft = booked.itinerary.clone();
+        check_draft(&booked, &draft).unwrap();
+
+        draft.cargo_weight_g = 500;
+        check_draft(&booked, &draft).unwrap();
+
+        draft.delivery_vertiport_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            check_draft(&booked, &draft).unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+
+        draft.user_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            check_draft(&booked, &draft).unwrap_err(),
+            ServiceError::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_modify_itinerary_invalid() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let user_id = Uuid::new_v4().to_string();
+
+        // bad draft id
+        let error = modify_itinerary(
+            &Uuid::new_v4().to_string(),
+            "invalid",
+            &user_id,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        // bad itinerary id
+        let error = modify_itinerary(
+            "invalid",
+            &Uuid::new_v4().to_string(),
+            &user_id,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+}
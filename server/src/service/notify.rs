@@ -0,0 +1,652 @@
+//! Parcel notifications
+//!
+//! The sender and the recipient of a parcel are notified when it is dropped
+//!  off, departs, arrives, is delivered or is delayed. Users can opt out of
+//!  notifications for themselves and for the recipients of their parcels.
+//!  Delays are checked when the arrival window of a booked parcel ends, and
+//!  on each scan after it. The checks of booked parcels are scheduled in
+//!  the cache and polled by [`watch_delays`].
+//!  TODO(R5): keep contacts and settings in svc-storage
+
+use super::scan::{get_parcel_route, ScanError};
+use super::ServiceError;
+use crate::cache::pool::{get_pool, CacheError, ItineraryPool};
+use crate::grpc::client::{get_clients, GrpcClients};
+use crate::grpc::contact::{CargoEvent, CargoNotificationRequest, CargoRecipient};
+use crate::rest::api::rest_types::{NotificationSettings, ParcelStatus, Recipient};
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::to_uuid;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display, Formatter};
+
+/// Delay after the end of an arrival window before it is checked
+const DELAY_CHECK_MARGIN_SECONDS: i64 = 60;
+
+/// Interval between polls of the scheduled delay checks
+const DELAY_CHECK_INTERVAL_SECONDS: u64 = 30;
+
+/// Parcel lifecycle events notified to the sender and the recipient
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParcelEvent {
+    /// The parcel was dropped off at the acquisition vertiport
+    DroppedOff,
+
+    /// The parcel was loaded on board for departure
+    Departed,
+
+    /// The parcel arrived at the delivery vertiport
+    Arrived,
+
+    /// The parcel was handed over to the recipient
+    Delivered,
+
+    /// The parcel has not arrived by the end of its arrival window
+    Delayed,
+}
+
+impl Display for ParcelEvent {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ParcelEvent::DroppedOff => write!(f, "dropped off"),
+            ParcelEvent::Departed => write!(f, "departed"),
+            ParcelEvent::Arrived => write!(f, "arrived"),
+            ParcelEvent::Delivered => write!(f, "delivered"),
+            ParcelEvent::Delayed => write!(f, "delayed"),
+        }
+    }
+}
+
+impl ParcelEvent {
+    /// The event of a parcel status change, if it is notified
+    pub fn from_transition(previous: ParcelStatus, status: ParcelStatus) -> Option<ParcelEvent> {
+        if previous == status {
+            return None;
+        }
+
+        match status {
+            ParcelStatus::DroppedOff => Some(ParcelEvent::DroppedOff),
+            ParcelStatus::Loaded => Some(ParcelEvent::Departed),
+            ParcelStatus::Arrived => Some(ParcelEvent::Arrived),
+            ParcelStatus::Delivered => Some(ParcelEvent::Delivered),
+            _ => None,
+        }
+    }
+}
+
+/// Who to notify of the events of a parcel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParcelContacts {
+    /// The user who booked the parcel
+    pub user_id: String,
+
+    /// The person receiving the parcel, if given at booking
+    pub recipient: Option<Recipient>,
+
+    /// A delay was already notified
+    #[serde(default)]
+    pub delay_notified: bool,
+}
+
+/// Who a notification is sent to
+#[derive(Debug, Clone, PartialEq)]
+pub enum Addressee {
+    /// The user who booked the parcel
+    Sender(String),
+
+    /// The person receiving the parcel
+    Recipient(Recipient),
+}
+
+impl Display for Addressee {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Addressee::Sender(user_id) => write!(f, "user {user_id}"),
+            Addressee::Recipient(recipient) => write!(f, "recipient {}", recipient.name),
+        }
+    }
+}
+
+/// A parcel event to notify
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    /// The parcel ID
+    pub parcel_id: String,
+
+    /// What happened to the parcel
+    pub event: ParcelEvent,
+
+    /// Who to notify
+    pub addressee: Addressee,
+}
+
+/// The notifications of a parcel event, as allowed by the sender's settings
+pub fn notifications(
+    parcel_id: &str,
+    event: ParcelEvent,
+    contacts: &ParcelContacts,
+    settings: &NotificationSettings,
+) -> Vec<Notification> {
+    let mut addressees = vec![];
+    if settings.sender {
+        addressees.push(Addressee::Sender(contacts.user_id.clone()));
+    }
+
+    if let Some(recipient) = contacts.recipient.clone().filter(|_| settings.recipient) {
+        addressees.push(Addressee::Recipient(recipient));
+    }
+
+    addressees
+        .into_iter()
+        .map(|addressee| Notification {
+            parcel_id: parcel_id.to_string(),
+            event,
+            addressee,
+        })
+        .collect()
+}
+
+/// Keep the contacts to notify of a parcel's events
+/// A parcel booked without its contacts would never be notified, so the
+///  booking fails if they can't be kept.
+pub(super) async fn store_contacts(
+    parcel_id: &str,
+    contacts: &ParcelContacts,
+) -> Result<(), ServiceError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .store_parcel_contacts(parcel_id, contacts)
+        .await
+        .map_err(|e| {
+            service_error!("could not keep contacts of parcel {parcel_id}: {e}");
+            ServiceError::from(e)
+        })
+}
+
+/// Get the contacts to notify of a parcel's events
+async fn load_contacts(parcel_id: &str) -> Result<ParcelContacts, CacheError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .get_parcel_contacts(parcel_id.to_string())
+        .await
+}
+
+/// Get the notification settings of a user
+/// Users are notified until they opt out.
+pub async fn get_settings(user_id: &str) -> Result<NotificationSettings, ServiceError> {
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let result = get_pool()
+        .await?
+        .lock()
+        .await
+        .get_notification_settings(user_id.to_string())
+        .await;
+
+    match result {
+        Ok(settings) => Ok(settings),
+        Err(CacheError::NotFound) => Ok(NotificationSettings::default()),
+        Err(e) => {
+            service_error!("could not get notification settings of user {user_id}: {e}");
+            Err(ServiceError::from(e))
+        }
+    }
+}
+
+/// Replace the notification settings of a user
+pub async fn update_settings(
+    user_id: &str,
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, ServiceError> {
+    to_uuid(user_id).ok_or_else(|| {
+        service_error!("user ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .store_notification_settings(user_id, &settings)
+        .await
+        .map_err(|e| {
+            service_error!("could not keep notification settings of user {user_id}: {e}");
+            ServiceError::from(e)
+        })?;
+
+    service_info!("user {user_id} notification settings: {settings:?}.");
+    Ok(settings)
+}
+
+impl From<ParcelEvent> for CargoEvent {
+    fn from(event: ParcelEvent) -> Self {
+        match event {
+            ParcelEvent::DroppedOff => CargoEvent::DroppedOff,
+            ParcelEvent::Departed => CargoEvent::Departed,
+            ParcelEvent::Arrived => CargoEvent::Arrived,
+            ParcelEvent::Delivered => CargoEvent::Delivered,
+            ParcelEvent::Delayed => CargoEvent::Delayed,
+        }
+    }
+}
+
+impl From<&Notification> for CargoNotificationRequest {
+    fn from(notification: &Notification) -> Self {
+        let mut request = CargoNotificationRequest {
+            parcel_id: notification.parcel_id.clone(),
+            event: CargoEvent::from(notification.event) as i32,
+            user_id: None,
+            recipient: None,
+            delivery_pin: None,
+        };
+
+        match &notification.addressee {
+            Addressee::Sender(user_id) => request.user_id = Some(user_id.clone()),
+            Addressee::Recipient(recipient) => {
+                request.recipient = Some(CargoRecipient {
+                    name: recipient.name.clone(),
+                    email: recipient.email.clone(),
+                    phone: recipient.phone.clone(),
+                })
+            }
+        }
+
+        request
+    }
+}
+
+/// Send each notification of a parcel event through svc-contact
+/// Every notification is attempted, sending fails if any of them does.
+async fn send(
+    notifications: &[Notification],
+    grpc_clients: &GrpcClients,
+) -> Result<(), ServiceError> {
+    let mut result = Ok(());
+    for notification in notifications {
+        service_info!(
+            "notifying {} that parcel {} {}.",
+            notification.addressee,
+            notification.parcel_id,
+            notification.event
+        );
+
+        if let Err(e) = grpc_clients
+            .notifications
+            .cargo_notification(CargoNotificationRequest::from(notification))
+            .await
+        {
+            service_error!("svc-contact error {:?}", e);
+            result = Err(ServiceError::Unavailable);
+        }
+    }
+
+    result
+}
+
+/// Notify the sender and the recipient of a parcel event
+/// Failures are logged, notifications never fail a scan.
+pub(super) async fn notify(parcel_id: &str, event: ParcelEvent, grpc_clients: &GrpcClients) {
+    let mut contacts = match load_contacts(parcel_id).await {
+        Ok(contacts) => contacts,
+        Err(e) => {
+            service_warn!("no contacts to notify for parcel {parcel_id}: {e}");
+            return;
+        }
+    };
+
+    // Delays are only notified once
+    if event == ParcelEvent::Delayed {
+        if contacts.delay_notified {
+            return;
+        }
+
+        // Better to miss a delay than to notify it on every scan
+        contacts.delay_notified = true;
+        if store_contacts(parcel_id, &contacts).await.is_err() {
+            return;
+        }
+    }
+
+    let settings = get_settings(&contacts.user_id).await.unwrap_or_default();
+    let notifications = notifications(parcel_id, event, &contacts, &settings);
+    if let Err(e) = send(&notifications, grpc_clients).await {
+        service_error!("could not notify that parcel {parcel_id} {event}: {e}");
+    }
+}
+
+/// When the delay of a parcel arriving by `arrive_by` is checked
+fn delay_check_due(arrive_by: DateTime<Utc>) -> DateTime<Utc> {
+    arrive_by + Duration::try_seconds(DELAY_CHECK_MARGIN_SECONDS).unwrap_or_else(Duration::zero)
+}
+
+/// Schedule the delay check of a parcel at `due`
+async fn store_delay_check(parcel_id: &str, due: DateTime<Utc>) -> Result<(), CacheError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .schedule_delay_check(parcel_id, due.timestamp())
+        .await
+}
+
+/// Check a newly booked parcel for a delay when its arrival window ends
+/// The check is kept in the cache until it is done, so it survives
+///  restarts. Its arrival window is read at the first poll.
+pub(super) async fn schedule_delay_check(parcel_id: &str) -> Result<(), ServiceError> {
+    store_delay_check(parcel_id, Utc::now()).await.map_err(|e| {
+        service_error!("could not schedule delay check of parcel {parcel_id}: {e}");
+        ServiceError::from(e)
+    })
+}
+
+/// Notify a delay if the parcel has not arrived by the end of its arrival
+///  window
+/// The window is read again at each check, so parcels moved to a later
+///  flight are checked against their new window. Returns when to check
+///  again, none once the parcel was checked or is no longer booked.
+async fn check_delay(
+    parcel_id: &str,
+    now: DateTime<Utc>,
+    grpc_clients: &GrpcClients,
+) -> Option<DateTime<Utc>> {
+    let route = match get_parcel_route(parcel_id, grpc_clients).await {
+        Ok(route) => route,
+        Err(ScanError::Storage) => return Some(delay_check_due(now)),
+        Err(e) => {
+            service_warn!("stopped checking parcel {parcel_id} for delays: {e}");
+            return None;
+        }
+    };
+
+    let Some(arrive_by) = route.arrive_by else {
+        service_warn!("no arrival window for parcel {parcel_id}.");
+        return None;
+    };
+
+    let due = delay_check_due(arrive_by);
+    if now < due {
+        return Some(due);
+    }
+
+    if route.is_delayed(route.status, now) {
+        notify(parcel_id, ParcelEvent::Delayed, grpc_clients).await;
+    }
+
+    None
+}
+
+/// Run the delay checks that are due
+/// Checks are removed once done, and rescheduled otherwise.
+pub async fn run_delay_checks(grpc_clients: &GrpcClients) -> Result<(), ServiceError> {
+    let now = Utc::now();
+    let parcel_ids = get_pool()
+        .await?
+        .lock()
+        .await
+        .get_due_delay_checks(now.timestamp())
+        .await
+        .map_err(|e| {
+            service_error!("could not get due delay checks: {e}");
+            ServiceError::from(e)
+        })?;
+
+    for parcel_id in parcel_ids {
+        let result = match check_delay(&parcel_id, now, grpc_clients).await {
+            Some(due) => store_delay_check(&parcel_id, due).await,
+            None => {
+                get_pool()
+                    .await?
+                    .lock()
+                    .await
+                    .remove_delay_check(&parcel_id)
+                    .await
+            }
+        };
+
+        // Left as it is, the check is run again at the next poll
+        if let Err(e) = result {
+            service_error!("could not update delay check of parcel {parcel_id}: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Poll the scheduled delay checks every [`DELAY_CHECK_INTERVAL_SECONDS`]
+#[cfg(not(tarpaulin_include))]
+// no_coverage: (R5) runs for the lifetime of the server
+pub async fn watch_delays() {
+    let grpc_clients = get_clients().await;
+    loop {
+        if let Err(e) = run_delay_checks(grpc_clients).await {
+            service_error!("could not run delay checks: {e}");
+        }
+
+        tokio::time::sleep(std::time::Duration::from_secs(DELAY_CHECK_INTERVAL_SECONDS)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::test_storage::register_parcel;
+    use lib_common::uuid::Uuid;
+
+    fn contacts() -> ParcelContacts {
+        ParcelContacts {
+            user_id: Uuid::new_v4().to_string(),
+            recipient: Some(Recipient {
+                name: "J. Doe".to_string(),
+                email: Some("j.doe@example.com".to_string()),
+                phone: None,
+            }),
+            delay_notified: false,
+        }
+    }
+
+    #[test]
+    fn test_event_from_transition() {
+        for (previous, status, expected) in [
+            (
+                ParcelStatus::NotDroppedOff,
+                ParcelStatus::DroppedOff,
+                Some(ParcelEvent::DroppedOff),
+            ),
+            (
+                ParcelStatus::DroppedOff,
+                ParcelStatus::Loaded,
+                Some(ParcelEvent::Departed),
+            ),
+            (ParcelStatus::Loaded, ParcelStatus::Loaded, None),
+            (ParcelStatus::Loaded, ParcelStatus::InFlight, None),
+            (ParcelStatus::InFlight, ParcelStatus::InFlight, None),
+            (
+                ParcelStatus::InFlight,
+                ParcelStatus::Arrived,
+                Some(ParcelEvent::Arrived),
+            ),
+            (
+                ParcelStatus::Arrived,
+                ParcelStatus::Delivered,
+                Some(ParcelEvent::Delivered),
+            ),
+        ] {
+            assert_eq!(ParcelEvent::from_transition(previous, status), expected);
+        }
+    }
+
+    #[test]
+    fn test_notifications() {
+        let contacts = contacts();
+        let parcel_id = Uuid::new_v4().to_string();
+
+        let result = notifications(
+            &parcel_id,
+            ParcelEvent::Arrived,
+            &contacts,
+            &NotificationSettings::default(),
+        );
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].parcel_id, parcel_id);
+        assert_eq!(result[0].event, ParcelEvent::Arrived);
+        assert_eq!(
+            result[0].addressee,
+            Addressee::Sender(contacts.user_id.clone())
+        );
+        assert_eq!(
+            result[1].addressee,
+            Addressee::Recipient(contacts.recipient.clone().unwrap())
+        );
+
+        // opted out for themselves
+        let settings = NotificationSettings {
+            sender: false,
+            recipient: true,
+        };
+        let result = notifications(&parcel_id, ParcelEvent::Delayed, &contacts, &settings);
+        assert_eq!(result.len(), 1);
+        assert!(matches!(result[0].addressee, Addressee::Recipient(_)));
+
+        // opted out for their recipients
+        let settings = NotificationSettings {
+            sender: true,
+            recipient: false,
+        };
+        let result = notifications(&parcel_id, ParcelEvent::Delayed, &contacts, &settings);
+        assert_eq!(result.len(), 1);
+        assert!(matches!(result[0].addressee, Addressee::Sender(_)));
+
+        // no recipient given at booking
+        let contacts = ParcelContacts {
+            recipient: None,
+            ..contacts
+        };
+        let result = notifications(
+            &parcel_id,
+            ParcelEvent::Delivered,
+            &contacts,
+            &NotificationSettings::default(),
+        );
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn test_delay_check_due() {
+        let arrive_by = Utc::now();
+        assert_eq!(
+            (delay_check_due(arrive_by) - arrive_by).num_seconds(),
+            DELAY_CHECK_MARGIN_SECONDS
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_delay_checks() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+        let unregistered = Uuid::new_v4().to_string();
+
+        schedule_delay_check(&parcel.parcel_id).await.unwrap();
+        schedule_delay_check(&unregistered).await.unwrap();
+        run_delay_checks(&grpc_clients).await.unwrap();
+
+        // rescheduled at the end of the arrival window, within the hour
+        let mut pool = get_pool().await.unwrap().lock().await;
+        let now = Utc::now();
+        let due = pool.get_due_delay_checks(now.timestamp()).await.unwrap();
+        assert!(!due.contains(&parcel.parcel_id));
+        let due = pool
+            .get_due_delay_checks((now + Duration::try_hours(2).unwrap()).timestamp())
+            .await
+            .unwrap();
+        assert!(due.contains(&parcel.parcel_id));
+
+        // not booked, no longer checked
+        assert!(!due.contains(&unregistered));
+        pool.remove_delay_check(&parcel.parcel_id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_store_contacts() {
+        let parcel_id = Uuid::new_v4().to_string();
+        let contacts = contacts();
+        store_contacts(&parcel_id, &contacts).await.unwrap();
+        assert_eq!(load_contacts(&parcel_id).await.unwrap(), contacts);
+    }
+
+    #[test]
+    fn test_notification_request() {
+        let contacts = contacts();
+        let result = notifications(
+            "parcel",
+            ParcelEvent::Delayed,
+            &contacts,
+            &NotificationSettings::default(),
+        );
+
+        let request = CargoNotificationRequest::from(&result[0]);
+        assert_eq!(request.parcel_id, "parcel");
+        assert_eq!(request.event, CargoEvent::Delayed as i32);
+        assert_eq!(request.user_id, Some(contacts.user_id.clone()));
+        assert!(request.recipient.is_none());
+
+        let request = CargoNotificationRequest::from(&result[1]);
+        assert!(request.user_id.is_none());
+        let recipient = request.recipient.unwrap();
+        assert_eq!(recipient.name, "J. Doe");
+        assert_eq!(recipient.email, Some("j.doe@example.com".to_string()));
+        assert!(recipient.phone.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_notify_delay_once() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel_id = Uuid::new_v4().to_string();
+        store_contacts(&parcel_id, &contacts()).await.unwrap();
+
+        // a delay is only notified once, even if it could not be sent
+        notify(&parcel_id, ParcelEvent::Delayed, &grpc_clients).await;
+        assert!(load_contacts(&parcel_id).await.unwrap().delay_notified);
+    }
+
+    #[test]
+    fn test_parcel_event_display() {
+        assert_eq!(ParcelEvent::DroppedOff.to_string(), "dropped off");
+        assert_eq!(ParcelEvent::Departed.to_string(), "departed");
+        assert_eq!(ParcelEvent::Arrived.to_string(), "arrived");
+        assert_eq!(ParcelEvent::Delivered.to_string(), "delivered");
+        assert_eq!(ParcelEvent::Delayed.to_string(), "delayed");
+    }
+
+    #[tokio::test]
+    async fn test_settings() {
+        let user_id = Uuid::new_v4().to_string();
+        assert_eq!(
+            get_settings(&user_id).await.unwrap(),
+            NotificationSettings::default()
+        );
+
+        let settings = NotificationSettings {
+            sender: false,
+            recipient: false,
+        };
+        assert_eq!(update_settings(&user_id, settings).await.unwrap(), settings);
+        assert_eq!(get_settings(&user_id).await.unwrap(), settings);
+
+        assert_eq!(
+            get_settings("invalid").await.unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            update_settings("invalid", settings).await.unwrap_err(),
+            ServiceError::InvalidArgument
+        );
+    }
+}
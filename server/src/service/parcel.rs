@@ -0,0 +1,90 @@
+//! Registered parcels
+//!
+//! svc-storage only records the weight and status of a parcel. The
+//!  dimensions and handling requirements given at booking are kept in the
+//!  cache without expiry, so ground crews can see them in vertiport
+//!  occupations and modifications can price the parcel again for as long
+//!  as it exists. A booking fails if they can't be kept. The itinerary
+//!  carrying the parcel is kept with them, svc-storage can't find it from
+//!  the parcel's flight plans.
+
+use super::itinerary::get_parcel_links;
+use super::ServiceError;
+use crate::cache::pool::{get_pool, ItineraryPool};
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{CargoInfo, Parcel};
+
+/// Keep the description of a registered parcel
+pub(super) async fn store_parcel(parcel: &CargoInfo) -> Result<(), ServiceError> {
+    let result = match get_pool().await {
+        Ok(pool) => pool.lock().await.store_parcel(parcel).await,
+        Err(e) => Err(e),
+    };
+
+    result.map_err(|e| {
+        service_error!("could not keep parcel {}: {e}", parcel.parcel_id);
+        ServiceError::Internal
+    })
+}
+
+/// Get the description of a registered parcel
+/// Parcels without a kept description are returned with their ID only,
+///  of unknown dimensions and handling.
+pub(super) async fn load_parcel(parcel_id: &str) -> CargoInfo {
+    let result = match get_pool().await {
+        Ok(pool) => pool.lock().await.get_parcel(parcel_id.to_string()).await,
+        Err(e) => Err(e),
+    };
+
+    result.unwrap_or_else(|e| {
+        service_warn!("no description of parcel {parcel_id}: {e}");
+        CargoInfo {
+            handling: None,
+            ..CargoInfo::new(parcel_id.to_string(), &Parcel::default())
+        }
+    })
+}
+
+/// Record the itinerary now carrying a registered parcel
+/// Parcels without a kept description are left as they are, their
+///  itinerary can't be looked up either way.
+pub(super) async fn move_parcel(parcel_id: &str, itinerary_id: &str) {
+    let result = match get_pool().await {
+        Ok(pool) => {
+            let mut pool = pool.lock().await;
+            match pool.get_parcel(parcel_id.to_string()).await {
+                Ok(mut parcel) => {
+                    parcel.itinerary_id = Some(itinerary_id.to_string());
+                    pool.store_parcel(&parcel).await
+                }
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        service_warn!("itinerary {itinerary_id} not recorded for parcel {parcel_id}: {e}");
+    }
+}
+
+/// The parcels acquired and delivered on a flight plan
+pub async fn get_flight_plan_cargo(
+    flight_plan_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<(Vec<CargoInfo>, Vec<CargoInfo>), ServiceError> {
+    let mut acquire = vec![];
+    let mut deliver = vec![];
+    for link in get_parcel_links(flight_plan_id, grpc_clients).await? {
+        let parcel = load_parcel(&link.parcel_id).await;
+        if link.acquire {
+            acquire.push(parcel.clone());
+        }
+
+        if link.deliver {
+            deliver.push(parcel);
+        }
+    }
+
+    Ok((acquire, deliver))
+}
@@ -0,0 +1,1633 @@
+//! Parcel scans
+//!
+//! Each accepted scan advances the parcel status. Parcels are dropped off
+//!  and loaded at the acquisition vertiport, scanned in flight or at
+//!  transfers, then arrive and are delivered at the delivery vertiport. The
+//!  delivery itself is confirmed with a proof of delivery.
+//! The status is changed before the scan is recorded, and only if no other
+//!  scan changed it meanwhile. It is changed back if the scan can't be
+//!  recorded.
+//! Scans away from the vertiports must be along the flights of the itinerary
+//!  carrying the parcel.
+//! Scans keep the scanner's timestamp as the time they were taken, which
+//!  can be long before they are received from a scanner that was offline.
+//!  The time each scan was received is kept apart. Scans received after a
+//!  later scan of the same parcel are recorded without changing its
+//!  status.
+
+use super::itinerary::{get_parcel_itinerary, get_vertiport};
+use super::label::{LabelClaims, LabelError, LabelSigner};
+use super::notify::{notify, ParcelEvent};
+use super::track::get_latest_scan;
+use super::ServiceError;
+use crate::cache::pool::{get_pool, CacheError, ItineraryPool};
+use crate::config::Config;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{CargoScan, ParcelStatus, Vertiport};
+use crate::rest::api::utils::{get_distance_meters, get_vertiport_id_from_vertipad_id};
+use geo::{Closest, Contains, HaversineClosestPoint, HaversineDistance, LineString, Polygon};
+use lib_common::time::{DateTime, Duration, Utc};
+use lib_common::uuid::to_uuid;
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use svc_scheduler_client_grpc::prelude::scheduler_storage::GeoPointZ as PathPoint;
+use svc_storage_client_grpc::link_service::Client as LinkClient;
+use svc_storage_client_grpc::prelude::*;
+use svc_storage_client_grpc::resources::parcel::{
+    Data as ParcelData, ParcelStatus as StorageParcelStatus, UpdateObject,
+};
+use svc_storage_client_grpc::resources::parcel_scan::Data as CargoScanData;
+use svc_storage_client_grpc::simple_service_linked::Client as SimpleLinkedClient;
+use tokio::sync::{broadcast, OnceCell};
+use tonic::Code;
+
+/// Number of accepted scans kept for slow watchers before they lag
+pub const SCAN_CHANNEL_CAPACITY: usize = 1024;
+
+/// How long a scan holds the status of a parcel while changing it
+const PARCEL_LOCK_SECONDS: usize = 10;
+
+/// How many times a scan tries to hold the status of a parcel held by
+///  another scan
+const PARCEL_LOCK_ATTEMPTS: u32 = 20;
+
+/// Wait between attempts to hold the status of a parcel
+const PARCEL_LOCK_RETRY_MILLISECONDS: u64 = 50;
+
+/// Accepted scans, fanned out to parcel watchers
+static SCAN_EVENTS: OnceCell<broadcast::Sender<CargoScan>> = OnceCell::const_new();
+
+/// Returns the sender for accepted scans.
+/// Watchers call [`broadcast::Sender::subscribe`] to receive every scan
+///  accepted after they subscribed.
+pub async fn scan_events() -> &'static broadcast::Sender<CargoScan> {
+    SCAN_EVENTS
+        .get_or_init(|| async move { broadcast::channel(SCAN_CHANNEL_CAPACITY).0 })
+        .await
+}
+
+/// Where and when scans are accepted, relative to the itinerary of a parcel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScanPolicy {
+    /// Scans inside a vertiport's area, or within this distance of it, are
+    ///  taken at the vertiport
+    pub vertiport_tolerance_meters: f64,
+
+    /// Scans away from the vertiports may detour a flight of the parcel by
+    ///  at most this distance
+    pub route_tolerance_meters: f64,
+
+    /// Scans are received at most this long after they were taken
+    pub max_delay: Duration,
+
+    /// Scanner clocks may run at most this far ahead of the server clock
+    pub max_lead: Duration,
+}
+
+/// Convert a number of seconds from the configuration to a [`Duration`]
+fn seconds(seconds: u32) -> Duration {
+    // u32 seconds are always within range
+    Duration::try_seconds(i64::from(seconds)).unwrap_or_else(Duration::zero)
+}
+
+impl From<&Config> for ScanPolicy {
+    fn from(config: &Config) -> Self {
+        ScanPolicy {
+            vertiport_tolerance_meters: f64::from(config.scan_vertiport_tolerance_meters),
+            route_tolerance_meters: f64::from(config.scan_route_tolerance_meters),
+            max_delay: seconds(config.scan_max_delay_seconds),
+            max_lead: seconds(config.scan_max_lead_seconds),
+        }
+    }
+}
+
+impl Default for ScanPolicy {
+    fn default() -> Self {
+        ScanPolicy::from(&Config::new())
+    }
+}
+
+/// Reasons a scan can be rejected
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanError {
+    /// The parcel ID is not a UUID
+    ParcelId,
+
+    /// The label code could not be verified, or is for another parcel
+    Label(LabelError),
+
+    /// The scanner ID is not a UUID
+    ScannerId,
+
+    /// The scan location is not a valid latitude and longitude
+    Coordinates,
+
+    /// The scan was taken too long before it was received
+    Stale,
+
+    /// The scan timestamp is ahead of the server clock
+    Future,
+
+    /// The scan location is this many meters off the flights of the parcel
+    Geofence(u32),
+
+    /// The parcel is not registered for an itinerary
+    Parcel,
+
+    /// The labelled parcel is no longer booked on the itinerary of its label
+    NotBooked,
+
+    /// The scan does not follow from the parcel status
+    Transition(ParcelStatus, ScanPlace),
+
+    /// The parcel can only be delivered with a delivery confirmation
+    Delivery,
+
+    /// Another scan changed the parcel status meanwhile
+    Moved(ParcelStatus),
+
+    /// Another scan held the parcel status for too long
+    Busy,
+
+    /// svc-storage could not record the scan
+    Storage,
+}
+
+/// Where a parcel was scanned, relative to its itinerary
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScanPlace {
+    /// At the vertiport where the parcel is dropped off
+    Acquisition,
+
+    /// At the vertiport where the parcel is delivered
+    Delivery,
+
+    /// Away from both vertiports, in flight or at a transfer
+    Elsewhere,
+}
+
+impl Display for ScanPlace {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ScanPlace::Acquisition => write!(f, "at the acquisition vertiport"),
+            ScanPlace::Delivery => write!(f, "at the delivery vertiport"),
+            ScanPlace::Elsewhere => write!(f, "away from its vertiports"),
+        }
+    }
+}
+
+impl Display for ScanError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ScanError::ParcelId => write!(f, "parcel ID not in UUID format"),
+            ScanError::Label(e) => write!(f, "{e}"),
+            ScanError::ScannerId => write!(f, "scanner ID not in UUID format"),
+            ScanError::Coordinates => write!(f, "coordinates out of range"),
+            ScanError::Stale => write!(f, "scan timestamp too old"),
+            ScanError::Future => write!(f, "scan timestamp ahead of the server clock"),
+            ScanError::Geofence(meters) => {
+                write!(f, "scan location {meters} m off the parcel route")
+            }
+            ScanError::Parcel => write!(f, "parcel not registered"),
+            ScanError::NotBooked => write!(f, "parcel no longer booked on its label itinerary"),
+            ScanError::Transition(status, place) => {
+                write!(f, "parcel {status:?} can't be scanned {place}")
+            }
+            ScanError::Delivery => write!(f, "delivery needs a proof of delivery"),
+            ScanError::Moved(status) => {
+                write!(f, "parcel status changed to {status:?} by another scan")
+            }
+            ScanError::Busy => write!(f, "parcel status held by another scan"),
+            ScanError::Storage => write!(f, "could not record scan"),
+        }
+    }
+}
+
+impl From<ScanError> for ServiceError {
+    fn from(error: ScanError) -> Self {
+        match error {
+            ScanError::ParcelId
+            | ScanError::ScannerId
+            | ScanError::Coordinates
+            | ScanError::Stale
+            | ScanError::Future
+            | ScanError::Geofence(_)
+            | ScanError::Transition(..)
+            | ScanError::Delivery
+            | ScanError::Moved(_) => ServiceError::InvalidArgument,
+            ScanError::Label(e) => ServiceError::from(e),
+            ScanError::Parcel | ScanError::NotBooked => ServiceError::NotFound,
+            ScanError::Busy => ServiceError::Unavailable,
+            ScanError::Storage => ServiceError::Internal,
+        }
+    }
+}
+
+/// Identify the parcel of a scan by its label code, if one is given
+/// A parcel ID given along with the code must be the one signed in it.
+///  Returns the fields signed in the code, to check with [`check_booking`].
+pub fn resolve_code(
+    scan: &mut CargoScan,
+    signer: &LabelSigner,
+) -> Result<Option<LabelClaims>, ScanError> {
+    let Some(code) = scan.code.take() else {
+        return Ok(None);
+    };
+
+    let claims = signer.verify(&code).map_err(ScanError::Label)?;
+    if !scan.parcel_id.is_empty() && scan.parcel_id != claims.pid {
+        service_error!(
+            "label code is for parcel {}, not {}.",
+            claims.pid,
+            scan.parcel_id
+        );
+        return Err(ScanError::Label(LabelError::Code));
+    }
+
+    scan.parcel_id = claims.pid.clone();
+    Ok(Some(claims))
+}
+
+/// Checks a labelled parcel is still booked on the itinerary of its label
+/// Labels of cancelled itineraries, or of parcels moved to another
+///  itinerary, are rejected.
+pub(super) async fn check_booking(
+    claims: &LabelClaims,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ScanError> {
+    let itinerary = get_parcel_itinerary(&claims.pid, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            ServiceError::NotFound => ScanError::NotBooked,
+            _ => ScanError::Storage,
+        })?;
+
+    if itinerary.id != claims.iti {
+        service_error!(
+            "parcel {} is booked on itinerary {}, not {}.",
+            claims.pid,
+            itinerary.id,
+            claims.iti
+        );
+        return Err(ScanError::NotBooked);
+    }
+
+    Ok(())
+}
+
+/// Checks the scan identifiers and location
+pub fn validate_scan(scan: &CargoScan) -> Result<(), ScanError> {
+    to_uuid(&scan.parcel_id).ok_or_else(|| {
+        service_error!("parcel ID not in UUID format.");
+        ScanError::ParcelId
+    })?;
+
+    to_uuid(&scan.scanner_id).ok_or_else(|| {
+        service_error!("scanner ID not in UUID format.");
+        ScanError::ScannerId
+    })?;
+
+    if scan.latitude < -90.0
+        || scan.latitude > 90.0
+        || scan.longitude < -180.0
+        || scan.longitude > 180.0
+    {
+        service_error!(
+            "coordinates out of range: (lat: {}, lon: {})",
+            scan.latitude,
+            scan.longitude
+        );
+
+        return Err(ScanError::Coordinates);
+    }
+
+    Ok(())
+}
+
+/// Checks that a scan was taken within the accepted window before `now`
+pub fn check_timestamp(
+    scan: &CargoScan,
+    now: DateTime<Utc>,
+    policy: &ScanPolicy,
+) -> Result<(), ScanError> {
+    if scan.timestamp > now + policy.max_lead {
+        service_error!(
+            "scan of parcel {} taken at {}, ahead of the server clock ({now}).",
+            scan.parcel_id,
+            scan.timestamp
+        );
+        return Err(ScanError::Future);
+    }
+
+    if scan.timestamp < now - policy.max_delay {
+        service_error!(
+            "scan of parcel {} taken at {}, received too late ({now}).",
+            scan.parcel_id,
+            scan.timestamp
+        );
+        return Err(ScanError::Stale);
+    }
+
+    Ok(())
+}
+
+/// The status of a parcel after a scan
+/// Loaded parcels may be scanned again before departure, and parcels in
+///  flight at every transfer. A delivered parcel can't be scanned again.
+pub fn next_status(status: ParcelStatus, place: ScanPlace) -> Result<ParcelStatus, ScanError> {
+    match (status, place) {
+        (ParcelStatus::NotDroppedOff, ScanPlace::Acquisition) => Ok(ParcelStatus::DroppedOff),
+        (ParcelStatus::DroppedOff | ParcelStatus::Loaded, ScanPlace::Acquisition) => {
+            Ok(ParcelStatus::Loaded)
+        }
+        (ParcelStatus::Loaded | ParcelStatus::InFlight, ScanPlace::Elsewhere) => {
+            Ok(ParcelStatus::InFlight)
+        }
+        (ParcelStatus::Loaded | ParcelStatus::InFlight, ScanPlace::Delivery) => {
+            Ok(ParcelStatus::Arrived)
+        }
+        (ParcelStatus::Arrived, ScanPlace::Delivery) => Ok(ParcelStatus::Delivered),
+        (status, place) => {
+            service_error!("parcel {status:?} can't be scanned {place}.");
+            Err(ScanError::Transition(status, place))
+        }
+    }
+}
+
+/// The position of a scan
+fn scan_point(scan: &CargoScan) -> PathPoint {
+    PathPoint {
+        x: scan.longitude,
+        y: scan.latitude,
+        z: 0.0,
+    }
+}
+
+/// The centroid of a vertiport
+fn vertiport_point(vertiport: &Vertiport) -> PathPoint {
+    PathPoint {
+        x: vertiport.longitude as f64,
+        y: vertiport.latitude as f64,
+        z: 0.0,
+    }
+}
+
+/// The distance of a position to a vertiport's area in meters, zero inside
+/// Vertiports without an area are taken at their centroid.
+fn vertiport_distance_meters(position: &PathPoint, vertiport: &Vertiport) -> Option<f64> {
+    if vertiport.boundary.len() < 3 {
+        return get_distance_meters(&[position.clone(), vertiport_point(vertiport)]);
+    }
+
+    let point = geo::point!(x: position.x, y: position.y);
+    let exterior: LineString = vertiport
+        .boundary
+        .iter()
+        .map(|point| (point.x, point.y))
+        .collect();
+
+    let area = Polygon::new(exterior, vec![]);
+    if area.contains(&point) {
+        return Some(0.0);
+    }
+
+    match area.exterior().haversine_closest_point(&point) {
+        Closest::Intersection(closest) | Closest::SinglePoint(closest) => {
+            Some(point.haversine_distance(&closest))
+        }
+        Closest::Indeterminate => None,
+    }
+}
+
+/// Where a scan was taken, inside or by the area of the parcel's vertiports
+pub fn scan_place(
+    scan: &CargoScan,
+    acquisition: &Vertiport,
+    delivery: &Vertiport,
+    policy: &ScanPolicy,
+) -> ScanPlace {
+    let position = scan_point(scan);
+    let near = |vertiport: &Vertiport| {
+        vertiport_distance_meters(&position, vertiport).map_or(false, |distance| {
+            distance <= policy.vertiport_tolerance_meters
+        })
+    };
+
+    if near(acquisition) {
+        ScanPlace::Acquisition
+    } else if near(delivery) {
+        ScanPlace::Delivery
+    } else {
+        ScanPlace::Elsewhere
+    }
+}
+
+/// How far a flight of the parcel detours to pass through a scan location,
+///  in meters
+/// Scans anywhere between the vertiports of a flight detour it little;
+///  scans behind a vertiport detour it twice their distance to it. The
+///  closest flight is taken, None without flights.
+pub fn route_detour_meters(scan: &CargoScan, legs: &[(Vertiport, Vertiport)]) -> Option<f64> {
+    let position = scan_point(scan);
+    legs.iter()
+        .filter_map(|(origin, target)| {
+            let (origin, target) = (vertiport_point(origin), vertiport_point(target));
+            let direct = get_distance_meters(&[origin, target])?;
+            let through = get_distance_meters(&[origin, position, target])?;
+            Some(through - direct)
+        })
+        .min_by(f64::total_cmp)
+}
+
+/// Checks that a scan away from the parcel's vertiports is along the flights
+///  of its itinerary
+pub(super) fn check_geofence(
+    scan: &CargoScan,
+    route: &ParcelRoute,
+    policy: &ScanPolicy,
+) -> Result<(), ScanError> {
+    let Some(detour) = route_detour_meters(scan, &route.legs) else {
+        return Ok(());
+    };
+
+    if detour > policy.route_tolerance_meters {
+        service_error!(
+            "scan of parcel {} is {detour:.0} m off its route (lat: {}, lon: {}).",
+            scan.parcel_id,
+            scan.latitude,
+            scan.longitude
+        );
+        return Err(ScanError::Geofence(detour.round() as u32));
+    }
+
+    Ok(())
+}
+
+/// The status of a parcel and the vertiports it travels between
+#[derive(Debug, Clone)]
+pub(super) struct ParcelRoute {
+    pub status: ParcelStatus,
+    pub acquisition: Vertiport,
+    pub delivery: Vertiport,
+
+    /// The origin and target vertiports of the flights of the itinerary
+    ///  carrying the parcel, including flights without it
+    pub legs: Vec<(Vertiport, Vertiport)>,
+
+    /// The end of the arrival window at the delivery vertiport
+    pub arrive_by: Option<DateTime<Utc>>,
+}
+
+impl ParcelRoute {
+    /// The parcel has not arrived by the end of its arrival window
+    pub fn is_delayed(&self, status: ParcelStatus, now: DateTime<Utc>) -> bool {
+        let arriving = matches!(
+            status,
+            ParcelStatus::NotDroppedOff
+                | ParcelStatus::DroppedOff
+                | ParcelStatus::Loaded
+                | ParcelStatus::InFlight
+        );
+
+        arriving && self.arrive_by.map_or(false, |arrive_by| now > arrive_by)
+    }
+}
+
+/// Get a vertiport of a flight plan
+/// Flight plans may only give the vertipad.
+async fn get_flight_plan_vertiport(
+    vertiport_id: Option<String>,
+    vertipad_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Vertiport, ScanError> {
+    let vertiport_id = match vertiport_id {
+        Some(id) => id,
+        None => get_vertiport_id_from_vertipad_id(grpc_clients, vertipad_id)
+            .await
+            .map_err(|_| ScanError::Storage)?,
+    };
+
+    get_vertiport(&vertiport_id, grpc_clients)
+        .await
+        .ok_or(ScanError::Storage)
+}
+
+/// Get the status of a parcel
+async fn get_parcel_status(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<ParcelStatus, ScanError> {
+    let data = grpc_clients
+        .storage
+        .parcel
+        .get_by_id(Id {
+            id: parcel_id.to_string(),
+        })
+        .await
+        .map_err(|e| match e.code() {
+            Code::NotFound => {
+                service_warn!("parcel {parcel_id} not found: {e}");
+                ScanError::Parcel
+            }
+            _ => {
+                service_error!("svc-storage error {:?}", e);
+                ScanError::Storage
+            }
+        })?
+        .into_inner()
+        .data
+        .ok_or_else(|| {
+            service_error!("parcel {parcel_id} data is None.");
+            ScanError::Storage
+        })?;
+
+    StorageParcelStatus::try_from(data.status)
+        .map(ParcelStatus::from)
+        .map_err(|_| {
+            service_error!("unrecognized parcel status: {}", data.status);
+            ScanError::Storage
+        })
+}
+
+/// Get the status of a parcel and its acquisition and delivery vertiports
+/// Parcels no longer booked on an itinerary are reported as not found.
+pub(super) async fn get_parcel_route(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<ParcelRoute, ScanError> {
+    let status = get_parcel_status(parcel_id, grpc_clients).await?;
+
+    let itinerary = get_parcel_itinerary(parcel_id, grpc_clients)
+        .await
+        .map_err(|e| match e {
+            ServiceError::NotFound => ScanError::Parcel,
+            _ => ScanError::Storage,
+        })?;
+
+    let flight_plans = grpc_clients
+        .storage
+        .itinerary_flight_plan_link
+        .get_linked(Id {
+            id: itinerary.id.clone(),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ScanError::Storage
+        })?
+        .into_inner()
+        .list;
+
+    let filter =
+        AdvancedSearchFilter::search_equals("parcel_id".to_string(), parcel_id.to_string());
+    let links = grpc_clients
+        .storage
+        .flight_plan_parcel
+        .search(filter)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ScanError::Storage
+        })?
+        .into_inner()
+        .list;
+
+    let mut acquisition = None;
+    let mut delivery = None;
+    let mut arrive_by = None;
+    let mut legs = vec![];
+    for flight_plan::Object { id, data } in flight_plans {
+        let data = data.ok_or_else(|| {
+            service_error!("flight plan {id} data is None.");
+            ScanError::Storage
+        })?;
+
+        let origin = get_flight_plan_vertiport(
+            data.origin_vertiport_id.clone(),
+            &data.origin_vertipad_id,
+            grpc_clients,
+        )
+        .await?;
+        let target = get_flight_plan_vertiport(
+            data.target_vertiport_id.clone(),
+            &data.target_vertipad_id,
+            grpc_clients,
+        )
+        .await?;
+
+        for link in links.iter().filter(|link| link.flight_plan_id == id) {
+            if link.acquire {
+                acquisition = Some(origin.clone());
+            }
+
+            if link.deliver {
+                arrive_by = data.target_timeslot_end.clone().map(Into::into);
+                delivery = Some(target.clone());
+            }
+        }
+
+        legs.push((origin, target));
+    }
+
+    let (Some(acquisition), Some(delivery)) = (acquisition, delivery) else {
+        service_warn!("parcel {parcel_id} is not linked to an itinerary.");
+        return Err(ScanError::Parcel);
+    };
+
+    Ok(ParcelRoute {
+        status,
+        acquisition,
+        delivery,
+        legs,
+        arrive_by,
+    })
+}
+
+/// Hold the status of a parcel, waiting for other scans to release it
+async fn lock_parcel(parcel_id: &str) -> Result<(), ScanError> {
+    let pool = get_pool().await.map_err(|e| {
+        service_error!("could not hold status of parcel {parcel_id}: {e}");
+        ScanError::Storage
+    })?;
+
+    for _ in 0..PARCEL_LOCK_ATTEMPTS {
+        let claimed = pool
+            .lock()
+            .await
+            .claim_parcel_lock(parcel_id, PARCEL_LOCK_SECONDS)
+            .await
+            .map_err(|e| {
+                service_error!("could not hold status of parcel {parcel_id}: {e}");
+                ScanError::Storage
+            })?;
+
+        if claimed {
+            return Ok(());
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(
+            PARCEL_LOCK_RETRY_MILLISECONDS,
+        ))
+        .await;
+    }
+
+    service_error!("status of parcel {parcel_id} held by another scan.");
+    Err(ScanError::Busy)
+}
+
+/// Release the status of a parcel
+/// Failures are logged, the status is then released when the hold expires.
+async fn unlock_parcel(parcel_id: &str) {
+    let result = match get_pool().await {
+        Ok(pool) => pool.lock().await.release_parcel_lock(parcel_id).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        service_warn!("could not release status of parcel {parcel_id}: {e}");
+    }
+}
+
+/// Move a parcel from the `previous` status to `status`
+/// The status is read again while it is held, the parcel is left as it is
+///  if another scan changed its status meanwhile.
+pub(super) async fn update_parcel_status(
+    parcel_id: &str,
+    previous: ParcelStatus,
+    status: ParcelStatus,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ScanError> {
+    lock_parcel(parcel_id).await?;
+    let result = set_parcel_status(parcel_id, previous, status, grpc_clients).await;
+    unlock_parcel(parcel_id).await;
+    result
+}
+
+/// Set the status of a held parcel, if it is still `previous`
+async fn set_parcel_status(
+    parcel_id: &str,
+    previous: ParcelStatus,
+    status: ParcelStatus,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ScanError> {
+    let current = get_parcel_status(parcel_id, grpc_clients).await?;
+    if current != previous {
+        service_error!("parcel {parcel_id} was moved to {current:?} by another scan.");
+        return Err(ScanError::Moved(current));
+    }
+
+    grpc_clients
+        .storage
+        .parcel
+        .update(UpdateObject {
+            id: parcel_id.to_string(),
+            data: Some(ParcelData {
+                status: StorageParcelStatus::from(status) as i32,
+                ..Default::default()
+            }),
+            mask: Some(FieldMask {
+                paths: vec!["status".to_string()],
+            }),
+        })
+        .await
+        .map_err(|e| {
+            service_error!("could not update status of parcel {parcel_id}: {:?}", e);
+            ScanError::Storage
+        })?;
+
+    Ok(())
+}
+
+/// When the recorded scans of a parcel were received, by scan ID
+/// svc-storage keeps only the time a scan was taken.
+/// TODO(R5): keep the receipt time in svc-storage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScanReceipts {
+    /// The receipt time of each scan
+    pub received: HashMap<String, DateTime<Utc>>,
+}
+
+/// Keep the time a recorded scan was received
+async fn store_receipt(
+    parcel_id: &str,
+    scan_id: &str,
+    received_at: DateTime<Utc>,
+) -> Result<(), CacheError> {
+    get_pool()
+        .await?
+        .lock()
+        .await
+        .store_scan_receipt(parcel_id, scan_id, received_at)
+        .await
+}
+
+/// Get when the recorded scans of a parcel were received
+/// Scans without a kept receipt time are left out.
+pub(super) async fn load_receipts(parcel_id: &str) -> ScanReceipts {
+    let result = match get_pool().await {
+        Ok(pool) => {
+            pool.lock()
+                .await
+                .get_scan_receipts(parcel_id.to_string())
+                .await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(receipts) => receipts,
+        Err(CacheError::NotFound) => ScanReceipts::default(),
+        Err(e) => {
+            service_warn!("could not get scan receipts of parcel {parcel_id}: {e}");
+            ScanReceipts::default()
+        }
+    }
+}
+
+/// Record a scan with svc-storage
+/// The scan is stored as taken at its timestamp, and its receipt time is
+///  kept apart, now if not set yet. Recorded scans are published to
+///  [`scan_events`].
+pub(super) async fn record_scan(
+    mut scan: CargoScan,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ScanError> {
+    let received_at = *scan.received_at.get_or_insert_with(Utc::now);
+
+    // Make request, process response
+    let data = CargoScanData {
+        scanner_id: scan.scanner_id.clone(),
+        parcel_id: scan.parcel_id.clone(),
+        geo_location: Some(GeoPointZ {
+            y: scan.latitude,
+            x: scan.longitude,
+            z: scan.altitude,
+        }),
+        created_at: Some(scan.timestamp.into()),
+    };
+
+    let response = grpc_clients
+        .storage
+        .parcel_scan
+        .insert(data)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error: {:?}", e);
+            ScanError::Storage
+        })?
+        .into_inner();
+
+    response
+        .validation_result
+        .ok_or_else(|| {
+            service_error!("svc-storage response missing validation result.");
+            ScanError::Storage
+        })?
+        .success
+        .then(|| {
+            service_info!("svc-storage success.");
+        })
+        .ok_or_else(|| {
+            service_error!("svc-storage failure.");
+            ScanError::Storage
+        })?;
+
+    // The scan is recorded, it is then shown without its receipt time
+    match response.object {
+        Some(object) => {
+            if let Err(e) = store_receipt(&scan.parcel_id, &object.id, received_at).await {
+                service_warn!(
+                    "could not keep receipt time of scan of parcel {}: {e}",
+                    scan.parcel_id
+                );
+            }
+        }
+        None => service_warn!("svc-storage response missing scan object."),
+    }
+
+    // No receivers is not an error, nobody is watching this parcel
+    let watchers = scan_events().await.send(scan).unwrap_or(0);
+    service_debug!("scan sent to {watchers} watchers.");
+
+    Ok(())
+}
+
+/// Record a scan that moved its parcel from `previous` to `status`
+/// If the scan can't be recorded the parcel is moved back, so that a retry
+///  of the scan starts from the same status.
+pub(super) async fn record_transition(
+    scan: CargoScan,
+    previous: ParcelStatus,
+    status: ParcelStatus,
+    grpc_clients: &GrpcClients,
+) -> Result<(), ScanError> {
+    let parcel_id = scan.parcel_id.clone();
+    let Err(e) = record_scan(scan, grpc_clients).await else {
+        return Ok(());
+    };
+
+    if status != previous {
+        match update_parcel_status(&parcel_id, status, previous, grpc_clients).await {
+            Ok(()) => service_warn!("parcel {parcel_id} moved back to {previous:?}."),
+            Err(restore) => {
+                service_error!("could not move parcel {parcel_id} back to {previous:?}: {restore}")
+            }
+        }
+    }
+
+    Err(e)
+}
+
+/// Checks whether a scan was taken before the latest recorded scan of its
+///  parcel, the status already reflecting that later scan
+async fn is_late(scan: &CargoScan, grpc_clients: &GrpcClients) -> Result<bool, ScanError> {
+    let latest = get_latest_scan(&scan.parcel_id, grpc_clients)
+        .await
+        .map_err(|e| {
+            service_error!(
+                "could not get latest scan of parcel {}: {e}",
+                scan.parcel_id
+            );
+            ScanError::Storage
+        })?;
+
+    Ok(latest.is_some_and(|latest| scan.timestamp < latest.timestamp))
+}
+
+/// Validate a scan, advance the parcel status and record the scan with
+///  svc-storage
+/// The parcel is identified by its ID or by the signed code of its label.
+/// Scans must be taken within the window of [`ScanPolicy`]. Scans taken
+///  before the latest recorded scan of the parcel are recorded without
+///  changing its status.
+/// Returns the new status of the parcel. Parcels are delivered with
+///  [`super::delivery::confirm_delivery`] instead.
+pub async fn scan_parcel(
+    mut scan: CargoScan,
+    policy: &ScanPolicy,
+    signer: &LabelSigner,
+    grpc_clients: &GrpcClients,
+) -> Result<ParcelStatus, ScanError> {
+    service_debug!("entry.");
+
+    let claims = resolve_code(&mut scan, signer)?;
+    validate_scan(&scan)?;
+    let received_at = Utc::now();
+    scan.received_at = Some(received_at);
+    check_timestamp(&scan, received_at, policy)?;
+    if let Some(claims) = claims {
+        check_booking(&claims, grpc_clients).await?;
+    }
+
+    let route = get_parcel_route(&scan.parcel_id, grpc_clients).await?;
+    let place = scan_place(&scan, &route.acquisition, &route.delivery, policy);
+    if place == ScanPlace::Elsewhere {
+        check_geofence(&scan, &route, policy)?;
+    }
+
+    // Transitions are taken in the order scans were taken, a late scan
+    //  is only kept in the history
+    if is_late(&scan, grpc_clients).await? {
+        service_warn!(
+            "scan of parcel {} taken before its latest scan, status kept.",
+            scan.parcel_id
+        );
+        record_scan(scan, grpc_clients).await?;
+        return Ok(route.status);
+    }
+
+    let status = next_status(route.status, place)?;
+    if status == ParcelStatus::Delivered {
+        service_error!(
+            "parcel {} can't be delivered without proof.",
+            scan.parcel_id
+        );
+        return Err(ScanError::Delivery);
+    }
+
+    let parcel_id = scan.parcel_id.clone();
+    if status != route.status {
+        update_parcel_status(&parcel_id, route.status, status, grpc_clients).await?;
+        service_info!("parcel {parcel_id} is now {status:?}.");
+    }
+
+    record_transition(scan, route.status, status, grpc_clients).await?;
+
+    if let Some(event) = ParcelEvent::from_transition(route.status, status) {
+        notify(&parcel_id, event, grpc_clients).await;
+    }
+
+    if route.is_delayed(status, Utc::now()) {
+        notify(&parcel_id, ParcelEvent::Delayed, grpc_clients).await;
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::label::LabelClaims;
+    use crate::test_util::test_storage::{register_parcel, RegisteredParcel};
+
+    fn scan() -> CargoScan {
+        CargoScan {
+            parcel_id: "00000000-0000-0000-0000-000000000000".to_string(),
+            code: None,
+            scanner_id: "00000000-0000-0000-0000-000000000001".to_string(),
+            latitude: 0.0,
+            longitude: 0.0,
+            altitude: 0.0,
+            timestamp: Utc::now(),
+            received_at: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_code() {
+        let signer = LabelSigner::new(Some("label secret"));
+        let parcel_id = lib_common::uuid::Uuid::new_v4().to_string();
+        let code = signer
+            .sign(&LabelClaims {
+                pid: parcel_id.clone(),
+                iti: lib_common::uuid::Uuid::new_v4().to_string(),
+                org: "Amsterdam".to_string(),
+                dst: "Utrecht".to_string(),
+            })
+            .unwrap();
+
+        // no code, the parcel ID is kept
+        let mut data = scan();
+        resolve_code(&mut data, &signer).unwrap();
+        assert_eq!(data.parcel_id, scan().parcel_id);
+
+        let mut data = scan();
+        data.parcel_id = String::new();
+        data.code = Some(code.clone());
+        let claims = resolve_code(&mut data, &signer).unwrap().unwrap();
+        assert_eq!(claims.pid, parcel_id);
+        assert_eq!(data.parcel_id, parcel_id);
+        assert_eq!(data.code, None);
+
+        // the code is for another parcel
+        let mut data = scan();
+        data.code = Some(code.clone());
+        assert_eq!(
+            resolve_code(&mut data, &signer).unwrap_err(),
+            ScanError::Label(LabelError::Code)
+        );
+
+        let mut data = scan();
+        data.code = Some(code);
+        assert_eq!(
+            resolve_code(&mut data, &LabelSigner::new(Some("other secret"))).unwrap_err(),
+            ScanError::Label(LabelError::Code)
+        );
+    }
+
+    #[test]
+    fn test_validate_scan() {
+        validate_scan(&scan()).unwrap();
+
+        let mut data = scan();
+        data.parcel_id = "invalid".to_string();
+        assert_eq!(validate_scan(&data).unwrap_err(), ScanError::ParcelId);
+
+        let mut data = scan();
+        data.scanner_id = "invalid".to_string();
+        assert_eq!(validate_scan(&data).unwrap_err(), ScanError::ScannerId);
+
+        for (latitude, longitude) in [(-90.01, 0.0), (90.01, 0.0), (0.0, -180.01), (0.0, 180.01)] {
+            let mut data = scan();
+            data.latitude = latitude;
+            data.longitude = longitude;
+            assert_eq!(validate_scan(&data).unwrap_err(), ScanError::Coordinates);
+        }
+    }
+
+    #[test]
+    fn test_check_timestamp() {
+        let now = Utc::now();
+        let policy = ScanPolicy::default();
+        check_timestamp(&scan(), now, &policy).unwrap();
+
+        // kept by a scanner while offline
+        let mut data = scan();
+        data.timestamp = now - policy.max_delay;
+        check_timestamp(&data, now, &policy).unwrap();
+
+        data.timestamp = now - policy.max_delay - Duration::try_seconds(1).unwrap();
+        assert_eq!(
+            check_timestamp(&data, now, &policy).unwrap_err(),
+            ScanError::Stale
+        );
+
+        // scanner clock slightly ahead
+        data.timestamp = now + policy.max_lead;
+        check_timestamp(&data, now, &policy).unwrap();
+
+        data.timestamp = now + policy.max_lead + Duration::try_seconds(1).unwrap();
+        assert_eq!(
+            check_timestamp(&data, now, &policy).unwrap_err(),
+            ScanError::Future
+        );
+    }
+
+    fn vertiport(latitude: f32, longitude: f32) -> Vertiport {
+        Vertiport {
+            id: lib_common::uuid::Uuid::new_v4().to_string(),
+            label: "vertiport".to_string(),
+            latitude,
+            longitude,
+            boundary: vec![],
+        }
+    }
+
+    #[test]
+    fn test_scan_place() {
+        let acquisition = vertiport(52.37, 4.89);
+        let delivery = vertiport(52.09, 5.12);
+        let mut policy = ScanPolicy::default();
+
+        let mut data = scan();
+        data.latitude = 52.371;
+        data.longitude = 4.891;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Acquisition
+        );
+
+        data.latitude = 52.09;
+        data.longitude = 5.12;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Delivery
+        );
+
+        // about 1.1km north of the delivery vertiport
+        data.latitude = 52.10;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Elsewhere
+        );
+
+        policy.vertiport_tolerance_meters = 1500.0;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Delivery
+        );
+    }
+
+    #[test]
+    fn test_scan_place_area() {
+        let acquisition = vertiport(52.37, 4.89);
+        let mut delivery = vertiport(52.09, 5.12);
+        let policy = ScanPolicy::default();
+
+        // about 2.2km north to south and 2.7km east to west
+        delivery.boundary = [(5.10, 52.08), (5.14, 52.08), (5.14, 52.10), (5.10, 52.10)]
+            .into_iter()
+            .map(|(x, y)| PathPoint { x, y, z: 0.0 })
+            .collect();
+
+        // inside the area, about 900m from its centroid
+        let mut data = scan();
+        data.latitude = 52.098;
+        data.longitude = 5.12;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Delivery
+        );
+
+        // about 330m north of the area
+        data.latitude = 52.103;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Delivery
+        );
+
+        // about 1.1km north of the area
+        data.latitude = 52.11;
+        assert_eq!(
+            scan_place(&data, &acquisition, &delivery, &policy),
+            ScanPlace::Elsewhere
+        );
+    }
+
+    fn route() -> ParcelRoute {
+        let acquisition = vertiport(52.37, 4.89);
+        let delivery = vertiport(52.09, 5.12);
+        ParcelRoute {
+            status: ParcelStatus::InFlight,
+            legs: vec![(acquisition.clone(), delivery.clone())],
+            acquisition,
+            delivery,
+            arrive_by: None,
+        }
+    }
+
+    #[test]
+    fn test_route_detour_meters() {
+        let route = route();
+
+        // halfway between the vertiports
+        let mut data = scan();
+        data.latitude = 52.23;
+        data.longitude = 5.005;
+        assert!(route_detour_meters(&data, &route.legs).unwrap() < 100.0);
+
+        // behind the acquisition vertiport, about 1.1km each way
+        data.latitude = 52.38;
+        data.longitude = 4.89;
+        let detour = route_detour_meters(&data, &route.legs).unwrap();
+        assert!((2000.0..2400.0).contains(&detour));
+
+        // the closest of several flights
+        let mut legs = route.legs.clone();
+        legs.insert(0, (vertiport(48.85, 2.35), vertiport(50.85, 4.35)));
+        assert_eq!(route_detour_meters(&data, &legs).unwrap(), detour);
+
+        assert_eq!(route_detour_meters(&data, &[]), None);
+    }
+
+    #[test]
+    fn test_check_geofence() {
+        let route = route();
+        let policy = ScanPolicy::default();
+
+        // in flight along the route
+        let mut data = scan();
+        data.latitude = 52.25;
+        data.longitude = 5.0;
+        check_geofence(&data, &route, &policy).unwrap();
+
+        // another city
+        data.latitude = 51.92;
+        data.longitude = 4.48;
+        let error = check_geofence(&data, &route, &policy).unwrap_err();
+        assert!(matches!(error, ScanError::Geofence(meters) if meters > 5000));
+
+        // flights unknown
+        let route = ParcelRoute {
+            legs: vec![],
+            ..route
+        };
+        check_geofence(&data, &route, &policy).unwrap();
+    }
+
+    #[test]
+    fn test_is_delayed() {
+        let now = Utc::now();
+        let mut route = ParcelRoute {
+            status: ParcelStatus::InFlight,
+            acquisition: vertiport(52.37, 4.89),
+            delivery: vertiport(52.09, 5.12),
+            legs: vec![],
+            arrive_by: None,
+        };
+        assert!(!route.is_delayed(ParcelStatus::InFlight, now));
+
+        route.arrive_by = Some(now - lib_common::time::Duration::try_minutes(1).unwrap());
+        assert!(route.is_delayed(ParcelStatus::DroppedOff, now));
+        assert!(route.is_delayed(ParcelStatus::Loaded, now));
+        assert!(route.is_delayed(ParcelStatus::InFlight, now));
+        assert!(!route.is_delayed(ParcelStatus::Arrived, now));
+        assert!(!route.is_delayed(ParcelStatus::Delivered, now));
+
+        route.arrive_by = Some(now + lib_common::time::Duration::try_minutes(1).unwrap());
+        assert!(!route.is_delayed(ParcelStatus::InFlight, now));
+    }
+
+    #[test]
+    fn test_next_status() {
+        // nominal
+        let mut status = ParcelStatus::NotDroppedOff;
+        for (place, expected) in [
+            (ScanPlace::Acquisition, ParcelStatus::DroppedOff),
+            (ScanPlace::Acquisition, ParcelStatus::Loaded),
+            (ScanPlace::Elsewhere, ParcelStatus::InFlight),
+            (ScanPlace::Elsewhere, ParcelStatus::InFlight),
+            (ScanPlace::Delivery, ParcelStatus::Arrived),
+            (ScanPlace::Delivery, ParcelStatus::Delivered),
+        ] {
+            status = next_status(status, place).unwrap();
+            assert_eq!(status, expected);
+        }
+
+        // scanned again on board before departure
+        assert_eq!(
+            next_status(ParcelStatus::Loaded, ScanPlace::Acquisition).unwrap(),
+            ParcelStatus::Loaded
+        );
+
+        // not scanned in flight
+        assert_eq!(
+            next_status(ParcelStatus::Loaded, ScanPlace::Delivery).unwrap(),
+            ParcelStatus::Arrived
+        );
+
+        // illegal
+        for (status, place) in [
+            (ParcelStatus::NotDroppedOff, ScanPlace::Elsewhere),
+            (ParcelStatus::NotDroppedOff, ScanPlace::Delivery),
+            (ParcelStatus::DroppedOff, ScanPlace::Elsewhere),
+            (ParcelStatus::DroppedOff, ScanPlace::Delivery),
+            (ParcelStatus::InFlight, ScanPlace::Acquisition),
+            (ParcelStatus::Arrived, ScanPlace::Acquisition),
+            (ParcelStatus::Arrived, ScanPlace::Elsewhere),
+            (ParcelStatus::Delivered, ScanPlace::Delivery),
+            (ParcelStatus::Delivered, ScanPlace::Acquisition),
+        ] {
+            assert_eq!(
+                next_status(status, place).unwrap_err(),
+                ScanError::Transition(status, place)
+            );
+        }
+    }
+
+    #[test]
+    fn test_scan_error_display() {
+        assert_eq!(
+            ScanError::ParcelId.to_string(),
+            "parcel ID not in UUID format"
+        );
+        assert_eq!(
+            ScanError::ScannerId.to_string(),
+            "scanner ID not in UUID format"
+        );
+        assert_eq!(
+            ScanError::Coordinates.to_string(),
+            "coordinates out of range"
+        );
+        assert_eq!(ScanError::Stale.to_string(), "scan timestamp too old");
+        assert_eq!(
+            ScanError::Future.to_string(),
+            "scan timestamp ahead of the server clock"
+        );
+        assert_eq!(
+            ScanError::Geofence(12345).to_string(),
+            "scan location 12345 m off the parcel route"
+        );
+        assert_eq!(ScanError::Parcel.to_string(), "parcel not registered");
+        assert_eq!(
+            ScanError::NotBooked.to_string(),
+            "parcel no longer booked on its label itinerary"
+        );
+        assert_eq!(
+            ScanError::Transition(ParcelStatus::Delivered, ScanPlace::Elsewhere).to_string(),
+            "parcel Delivered can't be scanned away from its vertiports"
+        );
+        assert_eq!(
+            ScanError::Moved(ParcelStatus::Loaded).to_string(),
+            "parcel status changed to Loaded by another scan"
+        );
+        assert_eq!(
+            ScanError::Busy.to_string(),
+            "parcel status held by another scan"
+        );
+        assert_eq!(
+            ScanError::Delivery.to_string(),
+            "delivery needs a proof of delivery"
+        );
+        assert_eq!(
+            ScanError::Label(LabelError::Code).to_string(),
+            "label code invalid or altered"
+        );
+        assert_eq!(ScanError::Storage.to_string(), "could not record scan");
+    }
+
+    #[test]
+    fn test_service_error_from_scan_error() {
+        assert_eq!(
+            ServiceError::from(ScanError::ParcelId),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Coordinates),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Stale),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Future),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Geofence(12345)),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Transition(
+                ParcelStatus::NotDroppedOff,
+                ScanPlace::Delivery
+            )),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Delivery),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Moved(ParcelStatus::Loaded)),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Busy),
+            ServiceError::Unavailable
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::NotBooked),
+            ServiceError::NotFound
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Label(LabelError::Code)),
+            ServiceError::InvalidArgument
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Label(LabelError::NoKey)),
+            ServiceError::Unavailable
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Parcel),
+            ServiceError::NotFound
+        );
+        assert_eq!(
+            ServiceError::from(ScanError::Storage),
+            ServiceError::Internal
+        );
+    }
+
+    /// A scan of the parcel at a location
+    fn scan_at(parcel: &RegisteredParcel, latitude: f64, longitude: f64) -> CargoScan {
+        CargoScan {
+            parcel_id: parcel.parcel_id.clone(),
+            latitude,
+            longitude,
+            ..scan()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_parcel_nominal() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+        let policy = ScanPolicy::default();
+        let signer = LabelSigner::default();
+
+        let acquisition = (
+            f64::from(parcel.acquisition.latitude),
+            f64::from(parcel.acquisition.longitude),
+        );
+        let delivery = (
+            f64::from(parcel.delivery.latitude),
+            f64::from(parcel.delivery.longitude),
+        );
+
+        for ((latitude, longitude), expected) in [
+            (acquisition, ParcelStatus::DroppedOff),
+            (acquisition, ParcelStatus::Loaded),
+            // halfway between the vertiports
+            ((52.23, 5.005), ParcelStatus::InFlight),
+            (delivery, ParcelStatus::Arrived),
+        ] {
+            let status = scan_parcel(
+                scan_at(&parcel, latitude, longitude),
+                &policy,
+                &signer,
+                &grpc_clients,
+            )
+            .await
+            .unwrap();
+            assert_eq!(status, expected);
+            assert_eq!(
+                get_parcel_status(&parcel.parcel_id, &grpc_clients)
+                    .await
+                    .unwrap(),
+                expected
+            );
+        }
+
+        // taken before the scans received so far, kept without a transition
+        let mut late = scan_at(&parcel, acquisition.0, acquisition.1);
+        late.timestamp = Utc::now() - Duration::try_minutes(10).unwrap();
+        let status = scan_parcel(late, &policy, &signer, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(status, ParcelStatus::Arrived);
+        assert_eq!(
+            get_parcel_status(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap(),
+            ParcelStatus::Arrived
+        );
+
+        // delivered with a proof of delivery only
+        let error = scan_parcel(
+            scan_at(&parcel, delivery.0, delivery.1),
+            &policy,
+            &signer,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ScanError::Delivery);
+        assert_eq!(
+            get_parcel_status(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap(),
+            ParcelStatus::Arrived
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_parcel_status() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+
+        update_parcel_status(
+            &parcel.parcel_id,
+            ParcelStatus::NotDroppedOff,
+            ParcelStatus::DroppedOff,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+
+        // moved by another scan meanwhile
+        let error = update_parcel_status(
+            &parcel.parcel_id,
+            ParcelStatus::NotDroppedOff,
+            ParcelStatus::DroppedOff,
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ScanError::Moved(ParcelStatus::DroppedOff));
+
+        // the status is released after each change
+        update_parcel_status(
+            &parcel.parcel_id,
+            ParcelStatus::DroppedOff,
+            ParcelStatus::Loaded,
+            &grpc_clients,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            get_parcel_status(&parcel.parcel_id, &grpc_clients)
+                .await
+                .unwrap(),
+            ParcelStatus::Loaded
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_parcel_route() {
+        use crate::test_util::test_storage::register_vertiport;
+        use svc_storage_client_grpc::resources::itinerary::ItineraryFlightPlans;
+
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel = register_parcel(&grpc_clients).await;
+
+        // a flight of the itinerary without the parcel, on to Rotterdam
+        let rotterdam = register_vertiport(51.92, 4.48, &grpc_clients).await;
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(parcel.delivery.id.clone());
+        data.target_vertiport_id = Some(rotterdam.id.clone());
+        let flight_plan_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        grpc_clients
+            .storage
+            .itinerary_flight_plan_link
+            .link(ItineraryFlightPlans {
+                id: parcel.itinerary_id.clone(),
+                other_id_list: Some(IdList {
+                    ids: vec![flight_plan_id],
+                }),
+            })
+            .await
+            .unwrap();
+
+        let route = get_parcel_route(&parcel.parcel_id, &grpc_clients)
+            .await
+            .unwrap();
+        assert_eq!(route.status, ParcelStatus::NotDroppedOff);
+        assert_eq!(route.acquisition.id, parcel.acquisition.id);
+        assert_eq!(route.delivery.id, parcel.delivery.id);
+        assert_eq!(route.legs.len(), 2);
+        assert!(route
+            .legs
+            .iter()
+            .any(|(origin, target)| origin.id == parcel.delivery.id && target.id == rotterdam.id));
+    }
+
+    #[tokio::test]
+    async fn test_scan_parcel_unregistered() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let mut data = scan();
+        data.parcel_id = lib_common::uuid::Uuid::new_v4().to_string();
+        let error = scan_parcel(
+            data,
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, ScanError::Parcel);
+    }
+
+    #[tokio::test]
+    async fn test_record_scan_publishes_scan() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let mut receiver = scan_events().await.subscribe();
+
+        let mut data = scan();
+        data.parcel_id = lib_common::uuid::Uuid::new_v4().to_string();
+        data.timestamp -= Duration::try_hours(1).unwrap();
+        record_scan(data.clone(), &grpc_clients).await.unwrap();
+
+        // other tests may publish scans concurrently
+        loop {
+            let event = receiver.recv().await.unwrap();
+            if event.parcel_id == data.parcel_id {
+                assert_eq!(event.scanner_id, data.scanner_id);
+                assert_eq!(event.timestamp, data.timestamp);
+                assert!(event.received_at.unwrap() > data.timestamp);
+                break;
+            }
+        }
+
+        let receipts = load_receipts(&data.parcel_id).await;
+        assert_eq!(receipts.received.len(), 1);
+
+        // rejected scans are not published
+        let mut data = scan();
+        data.parcel_id = "invalid".to_string();
+        scan_parcel(
+            data,
+            &ScanPolicy::default(),
+            &LabelSigner::default(),
+            &grpc_clients,
+        )
+        .await
+        .unwrap_err();
+    }
+}
@@ -0,0 +1,231 @@
+//! Parcel tracking
+
+use super::delivery::get_delivery_proof;
+use super::itinerary::get_parcel;
+use super::scan::{load_receipts, scan_events};
+use super::ServiceError;
+use crate::grpc::client::GrpcClients;
+use crate::rest::api::rest_types::{CargoScan, QueryParcelResponse};
+use futures::stream::{self, BoxStream, StreamExt};
+use lib_common::uuid::to_uuid;
+use svc_storage_client_grpc::prelude::{AdvancedSearchFilter, SortOption, SortOrder};
+use svc_storage_client_grpc::simple_service::Client;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Get the recorded scans of a parcel, in the order they were taken
+/// Scans keep the time they were received, if known.
+pub async fn get_scans(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Vec<CargoScan>, ServiceError> {
+    to_uuid(parcel_id).ok_or_else(|| {
+        service_error!("parcel ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let mut filter =
+        AdvancedSearchFilter::search_equals("parcel_id".to_string(), parcel_id.to_string());
+
+    filter.order_by = vec![SortOption {
+        sort_field: "created_at".to_string(),
+        sort_order: SortOrder::Asc as i32,
+    }];
+
+    let objects = grpc_clients
+        .storage
+        .parcel_scan
+        .search(filter)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::NotFound
+        })?
+        .into_inner()
+        .list;
+
+    let receipts = load_receipts(parcel_id).await;
+    let mut scans = objects
+        .into_iter()
+        .filter_map(|object| {
+            let received_at = receipts.received.get(&object.id).copied();
+            let mut scan = CargoScan::try_from(object).ok()?;
+            scan.received_at = received_at;
+            Some(scan)
+        })
+        .collect::<Vec<CargoScan>>();
+
+    // Scans uploaded late are still listed when they were taken
+    scans.sort_by_key(|scan| scan.timestamp);
+    Ok(scans)
+}
+
+/// Get the status, recorded scans and proof of delivery of a parcel
+pub async fn track_parcel(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<QueryParcelResponse, ServiceError> {
+    let scans = get_scans(parcel_id, grpc_clients).await?;
+    let parcel = get_parcel(parcel_id, grpc_clients).await?;
+
+    // The status and scans are still shown if the proof can't be read
+    let delivery = get_delivery_proof(parcel_id).await.unwrap_or_else(|e| {
+        service_warn!("could not get proof of delivery of parcel {parcel_id}: {e}");
+        None
+    });
+
+    Ok(QueryParcelResponse {
+        status: parcel.status,
+        scans,
+        delivery,
+    })
+}
+
+/// Get the most recent scan of a parcel, if it was scanned
+pub async fn get_latest_scan(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<Option<CargoScan>, ServiceError> {
+    to_uuid(parcel_id).ok_or_else(|| {
+        service_error!("parcel ID not in UUID format.");
+        ServiceError::InvalidArgument
+    })?;
+
+    let mut filter =
+        AdvancedSearchFilter::search_equals("parcel_id".to_string(), parcel_id.to_string());
+
+    filter.results_per_page = 1;
+    filter.order_by = vec![SortOption {
+        sort_field: "created_at".to_string(),
+        sort_order: SortOrder::Desc as i32,
+    }];
+
+    let scan = grpc_clients
+        .storage
+        .parcel_scan
+        .search(filter)
+        .await
+        .map_err(|e| {
+            service_error!("svc-storage error {:?}", e);
+            ServiceError::Internal
+        })?
+        .into_inner()
+        .list
+        .into_iter()
+        .find_map(|scan| CargoScan::try_from(scan).ok());
+
+    Ok(scan)
+}
+
+/// Watch the scans of a parcel
+/// The stream starts with the recorded scan history, followed by each new
+///  scan of the parcel accepted by [`super::scan::scan_parcel`]. It ends
+///  when the watcher is dropped.
+pub async fn watch_parcel(
+    parcel_id: &str,
+    grpc_clients: &GrpcClients,
+) -> Result<BoxStream<'static, CargoScan>, ServiceError> {
+    service_debug!("entry.");
+
+    // Subscribe before reading the history so no scan falls in between
+    let receiver = scan_events().await.subscribe();
+    let history = get_scans(parcel_id, grpc_clients).await?;
+    service_debug!("sending {} recorded scans.", history.len());
+
+    // Scans accepted while the history was read can appear in both. Scans
+    //  uploaded late are taken before the history, so they are told apart
+    //  by the time they were received.
+    let latest = history.iter().filter_map(|scan| scan.received_at).max();
+    let parcel_id = parcel_id.to_string();
+    let live = stream::unfold(receiver, |mut receiver| async move {
+        loop {
+            match receiver.recv().await {
+                Ok(scan) => return Some((scan, receiver)),
+                Err(RecvError::Lagged(skipped)) => {
+                    service_warn!("watcher lagged, skipped {skipped} scans.");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    })
+    .filter(move |scan| {
+        let matches = scan.parcel_id == parcel_id
+            && latest.map_or(true, |latest| scan.received_at > Some(latest));
+        futures::future::ready(matches)
+    });
+
+    Ok(stream::iter(history).chain(live).boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::scan::record_scan;
+    use lib_common::time::Utc;
+    use lib_common::uuid::Uuid;
+
+    const SCANNER_ID: &str = "9d1c2a34-5f6e-4b7a-8c9d-0e1f2a3b4c5d";
+
+    #[tokio::test]
+    async fn test_get_scans_invalid_id() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = get_scans("invalid", &grpc_clients).await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        let error = get_latest_scan("invalid", &grpc_clients).await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        let error = track_parcel("invalid", &grpc_clients).await.unwrap_err();
+        assert_eq!(error, ServiceError::InvalidArgument);
+
+        let error = watch_parcel("invalid", &grpc_clients).await.err().unwrap();
+        assert_eq!(error, ServiceError::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn test_track_parcel_not_found() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+
+        let error = track_parcel(&Uuid::new_v4().to_string(), &grpc_clients)
+            .await
+            .unwrap_err();
+        assert_eq!(error, ServiceError::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_watch_parcel_live_scans() {
+        let config = crate::config::Config::default();
+        let grpc_clients = GrpcClients::default(config);
+        let parcel_id = Uuid::new_v4().to_string();
+
+        // skip the recorded history returned by the storage stub
+        let mut watcher = watch_parcel(&parcel_id, &grpc_clients)
+            .await
+            .unwrap()
+            .skip_while(|scan| futures::future::ready(scan.scanner_id != SCANNER_ID));
+
+        // the first scan is for another parcel and must not be sent. The
+        //  second was kept by a scanner while offline.
+        let taken_at = Utc::now() - lib_common::time::Duration::try_hours(1).unwrap();
+        for id in [Uuid::new_v4().to_string(), parcel_id.clone()] {
+            let scan = CargoScan {
+                parcel_id: id,
+                code: None,
+                scanner_id: SCANNER_ID.to_string(),
+                latitude: 0.0,
+                longitude: 0.0,
+                altitude: 0.0,
+                timestamp: taken_at,
+                received_at: None,
+            };
+            record_scan(scan, &grpc_clients).await.unwrap();
+        }
+
+        let scan = watcher.next().await.unwrap();
+        assert_eq!(scan.parcel_id, parcel_id);
+        assert_eq!(scan.timestamp, taken_at);
+        assert!(scan.received_at.unwrap() > taken_at);
+    }
+}
@@ -9,9 +9,13 @@ pub mod test_pool {
     use std::ops::{Deref, DerefMut};
     use std::sync::{Arc, Mutex};
 
+    /// The fields of each hash key
+    type Store = HashMap<String, HashMap<String, String>>;
+
     #[derive(Debug, Clone)]
     pub struct Connection {
-        store: Arc<Mutex<HashMap<String, String>>>,
+        store: Arc<Mutex<Store>>,
+        expiries: Arc<Mutex<HashMap<String, usize>>>,
     }
 
     #[derive(Debug, Clone)]
@@ -26,6 +30,7 @@ pub mod test_pool {
                 fail: false,
                 connection: Connection {
                     store: Arc::new(Mutex::new(HashMap::new())),
+                    expiries: Arc::new(Mutex::new(HashMap::new())),
                 },
             }
         }
@@ -41,8 +46,18 @@ pub mod test_pool {
         }
     }
 
+    /// The value as stored by redis
+    fn to_string(value: impl ToRedisArgs) -> String {
+        value
+            .to_redis_args()
+            .into_iter()
+            .map(|v| String::from_utf8_lossy(&v).to_string())
+            .collect::<Vec<String>>()
+            .join("")
+    }
+
     impl Connection {
-        pub async fn hget(&self, key: &str, _field: &str) -> Result<Value, ()> {
+        pub async fn hget(&self, key: &str, field: &str) -> Result<Value, ()> {
             // if no key provided, return error
             if key.ends_with(":") {
                 return Err(());
@@ -53,9 +68,36 @@ pub mod test_pool {
                 .map_err(|_| ())?
                 .deref()
                 .get(key)
+                .and_then(|fields| fields.get(field))
                 .map_or(Ok(Value::Nil), |v| Ok(Value::Data(v.as_bytes().to_vec())))
         }
 
+        pub async fn hgetall(&self, key: &str) -> Result<Value, ()> {
+            // if no key provided, return error
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            let store = self.store.try_lock().map_err(|_| ())?;
+            let mut fields: Vec<(&String, &String)> = store
+                .get(key)
+                .map(|f| f.iter().collect())
+                .unwrap_or_default();
+            fields.sort();
+
+            Ok(Value::Bulk(
+                fields
+                    .into_iter()
+                    .flat_map(|(field, value)| {
+                        [
+                            Value::Data(field.as_bytes().to_vec()),
+                            Value::Data(value.as_bytes().to_vec()),
+                        ]
+                    })
+                    .collect(),
+            ))
+        }
+
         pub async fn hset_nx(
             &mut self,
             key: &str,
@@ -72,44 +114,183 @@ pub mod test_pool {
                 return Ok(Value::Nil);
             }
 
-            let value = value
-                .to_redis_args()
-                .into_iter()
-                .map(|v| String::from_utf8_lossy(&v).to_string())
-                .collect::<Vec<String>>()
-                .join("");
+            let value = to_string(value);
+            let mut store = self.store.try_lock().map_err(|_| ())?;
+            let fields = store.deref_mut().entry(key.to_string()).or_default();
+            if fields.contains_key(field) {
+                return Ok(Value::Int(0));
+            }
+
+            fields.insert(field.to_string(), value);
+            Ok(Value::Int(1))
+        }
+
+        pub async fn expire(&mut self, key: &str, seconds: usize) -> Result<Value, ()> {
+            // allow ways to exercise other branches
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            // allow ways to exercise other branches
+            if seconds == 0 {
+                return Ok(Value::Nil);
+            }
+
+            match self.store
+                .try_lock()
+                .map_err(|_| ())?
+                .deref()
+                .contains_key(key) // hashmap
+            {
+                true => {
+                    self.expiries
+                        .try_lock()
+                        .map_err(|_| ())?
+                        .insert(key.to_string(), seconds);
+                    Ok(Value::Int(1))
+                }
+                false => Ok(Value::Int(0)),
+            }
+        }
+
+        pub async fn hset(
+            &mut self,
+            key: &str,
+            field: &str,
+            value: impl ToRedisArgs,
+        ) -> Result<Value, ()> {
+            // allow ways to exercise other branches
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            let value = to_string(value);
 
+            // number of new fields
             match self
                 .store
                 .try_lock()
                 .map_err(|_| ())?
                 .deref_mut()
-                .insert(key.to_string(), value.to_string())
+                .entry(key.to_string())
+                .or_default()
+                .insert(field.to_string(), value)
             {
                 None => Ok(Value::Int(1)),
                 Some(_) => Ok(Value::Int(0)),
             }
         }
 
-        pub async fn expire(&mut self, key: &str, seconds: usize) -> Result<Value, ()> {
+        pub async fn hincr(&mut self, key: &str, field: &str, delta: i64) -> Result<Value, ()> {
             // allow ways to exercise other branches
             if key.ends_with(":") {
                 return Err(());
             }
 
+            let mut store = self.store.try_lock().map_err(|_| ())?;
+            let value = store
+                .deref_mut()
+                .entry(key.to_string())
+                .or_default()
+                .entry(field.to_string())
+                .or_insert_with(|| "0".to_string());
+
+            // redis rejects fields that are not integers
+            let count = value.parse::<i64>().map_err(|_| ())? + delta;
+            *value = count.to_string();
+            Ok(Value::Int(count))
+        }
+
+        pub async fn ttl(&self, key: &str) -> Result<Value, ()> {
             // allow ways to exercise other branches
-            if seconds == 0 {
-                return Ok(Value::Nil);
+            if key.ends_with(":") {
+                return Err(());
             }
 
-            match self.store
+            if !self.store.try_lock().map_err(|_| ())?.contains_key(key) {
+                return Ok(Value::Int(-2));
+            }
+
+            match self.expiries.try_lock().map_err(|_| ())?.get(key) {
+                Some(seconds) => Ok(Value::Int(*seconds as i64)),
+                None => Ok(Value::Int(-1)),
+            }
+        }
+
+        pub async fn zadd(&mut self, key: &str, member: &str, score: i64) -> Result<Value, ()> {
+            // allow ways to exercise other branches
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            // members are kept as fields, with their score as value
+            match self
+                .store
                 .try_lock()
                 .map_err(|_| ())?
-                .deref()
-                .contains_key(key) // hashmap
+                .deref_mut()
+                .entry(key.to_string())
+                .or_default()
+                .insert(member.to_string(), score.to_string())
             {
-                true => Ok(Value::Int(1)),
-                false => Ok(Value::Int(0)),
+                None => Ok(Value::Int(1)),
+                Some(_) => Ok(Value::Int(0)),
+            }
+        }
+
+        pub async fn zrangebyscore(&self, key: &str, min: &str, max: i64) -> Result<Value, ()> {
+            // allow ways to exercise other branches
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            let min = match min {
+                "-inf" => i64::MIN,
+                min => min.parse::<i64>().map_err(|_| ())?,
+            };
+
+            let store = self.store.try_lock().map_err(|_| ())?;
+            let mut members: Vec<(i64, &String)> = store
+                .get(key)
+                .map(|members| {
+                    members
+                        .iter()
+                        .filter_map(|(member, score)| Some((score.parse().ok()?, member)))
+                        .filter(|(score, _)| (min..=max).contains(score))
+                        .collect()
+                })
+                .unwrap_or_default();
+            members.sort();
+
+            Ok(Value::Bulk(
+                members
+                    .into_iter()
+                    .map(|(_, member)| Value::Data(member.as_bytes().to_vec()))
+                    .collect(),
+            ))
+        }
+
+        pub async fn zrem(&mut self, key: &str, member: &str) -> Result<Value, ()> {
+            // allow ways to exercise other branches
+            if key.ends_with(":") {
+                return Err(());
+            }
+
+            let mut store = self.store.try_lock().map_err(|_| ())?;
+            match store
+                .get_mut(key)
+                .and_then(|members| members.remove(member))
+            {
+                Some(_) => Ok(Value::Int(1)),
+                None => Ok(Value::Int(0)),
+            }
+        }
+
+        pub async fn del(&mut self, key: &str) -> Result<Value, ()> {
+            self.expiries.try_lock().map_err(|_| ())?.remove(key);
+            match self.store.try_lock().map_err(|_| ())?.remove(key) {
+                Some(_) => Ok(Value::Int(1)),
+                None => Ok(Value::Int(0)),
             }
         }
     }
@@ -219,5 +400,272 @@ pub mod test_pool {
             // will fail if the store is locked
             connection.expire("key", 1).await.unwrap_err();
         }
+
+        #[tokio::test]
+        async fn test_connection_hset_ttl_del() {
+            let pool = Pool::default();
+            let mut connection = pool.get().await.unwrap();
+
+            // keys ending in ":" should return error for this test util
+            connection.hset("key:", "field", "value").await.unwrap_err();
+            connection.ttl("key:").await.unwrap_err();
+
+            // missing key
+            assert_eq!(connection.ttl("key").await.unwrap(), Value::Int(-2));
+
+            // new field, then overwritten field
+            let value = connection.hset("key", "field", "value").await.unwrap();
+            assert_eq!(value, Value::Int(1));
+            let value = connection.hset("key", "field", "other").await.unwrap();
+            assert_eq!(value, Value::Int(0));
+            let value = connection.hget("key", "field").await.unwrap();
+            assert_eq!(value, Value::Data(b"other".to_vec()));
+
+            // no expiry, then expiry
+            assert_eq!(connection.ttl("key").await.unwrap(), Value::Int(-1));
+            connection.expire("key", 10).await.unwrap();
+            assert_eq!(connection.ttl("key").await.unwrap(), Value::Int(10));
+
+            assert_eq!(connection.del("key").await.unwrap(), Value::Int(1));
+            assert_eq!(connection.del("key").await.unwrap(), Value::Int(0));
+            assert_eq!(connection.ttl("key").await.unwrap(), Value::Int(-2));
+        }
+
+        #[tokio::test]
+        async fn test_connection_sorted_set() {
+            let pool = Pool::default();
+            let mut connection = pool.get().await.unwrap();
+
+            // keys ending in ":" return Err in this test util
+            connection.zadd("key:", "a", 1).await.unwrap_err();
+            connection
+                .zrangebyscore("key:", "-inf", 1)
+                .await
+                .unwrap_err();
+            connection.zrem("key:", "a").await.unwrap_err();
+
+            assert_eq!(connection.zadd("key", "b", 2).await.unwrap(), Value::Int(1));
+            assert_eq!(connection.zadd("key", "a", 3).await.unwrap(), Value::Int(1));
+            assert_eq!(connection.zadd("key", "a", 1).await.unwrap(), Value::Int(0));
+            assert_eq!(connection.zadd("key", "c", 5).await.unwrap(), Value::Int(1));
+
+            // ordered by score
+            assert_eq!(
+                connection.zrangebyscore("key", "-inf", 2).await.unwrap(),
+                Value::Bulk(vec![Value::Data(b"a".to_vec()), Value::Data(b"b".to_vec())])
+            );
+            assert_eq!(
+                connection.zrangebyscore("key", "2", 5).await.unwrap(),
+                Value::Bulk(vec![Value::Data(b"b".to_vec()), Value::Data(b"c".to_vec())])
+            );
+
+            assert_eq!(connection.zrem("key", "a").await.unwrap(), Value::Int(1));
+            assert_eq!(connection.zrem("key", "a").await.unwrap(), Value::Int(0));
+            assert_eq!(
+                connection.zrangebyscore("key", "-inf", 5).await.unwrap(),
+                Value::Bulk(vec![Value::Data(b"b".to_vec()), Value::Data(b"c".to_vec())])
+            );
+        }
+
+        #[tokio::test]
+        async fn test_connection_hincr_hgetall() {
+            let pool = Pool::default();
+            let mut connection = pool.get().await.unwrap();
+
+            // keys ending in ":" should return error for this test util
+            connection.hincr("key:", "count", 1).await.unwrap_err();
+            connection.hgetall("key:").await.unwrap_err();
+
+            // missing key
+            assert_eq!(
+                connection.hgetall("key").await.unwrap(),
+                Value::Bulk(vec![])
+            );
+
+            // missing fields count from 0
+            assert_eq!(
+                connection.hincr("key", "count", 1).await.unwrap(),
+                Value::Int(1)
+            );
+            assert_eq!(
+                connection.hincr("key", "count", 2).await.unwrap(),
+                Value::Int(3)
+            );
+            connection.hset("key", "data", "value").await.unwrap();
+            connection.hincr("key", "data", 1).await.unwrap_err();
+
+            // fields are kept apart
+            let value = connection.hget("key", "count").await.unwrap();
+            assert_eq!(value, Value::Data(b"3".to_vec()));
+            assert_eq!(connection.hget("key", "other").await.unwrap(), Value::Nil);
+            assert_eq!(
+                connection.hgetall("key").await.unwrap(),
+                Value::Bulk(vec![
+                    Value::Data(b"count".to_vec()),
+                    Value::Data(b"3".to_vec()),
+                    Value::Data(b"data".to_vec()),
+                    Value::Data(b"value".to_vec()),
+                ])
+            );
+        }
+    }
+}
+
+/// Records registered with the svc-storage stub
+#[cfg(test)]
+pub mod test_storage {
+    use crate::cache::pool::{get_pool, ItineraryPool};
+    use crate::grpc::client::GrpcClients;
+    use crate::rest::api::rest_types::{CargoInfo, Parcel, Vertiport};
+    use lib_common::time::{Duration, Utc};
+    use svc_storage_client_grpc::link_service::Client as LinkClient;
+    use svc_storage_client_grpc::prelude::flight_plan_parcel::RowData as FlightPlanParcel;
+    use svc_storage_client_grpc::prelude::{
+        flight_plan, vertiport, GeoLineStringZ, GeoPointZ, GeoPolygonZ, IdList,
+    };
+    use svc_storage_client_grpc::resources::itinerary::{
+        Data as ItineraryData, ItineraryFlightPlans, ItineraryStatus,
+    };
+    use svc_storage_client_grpc::resources::parcel::{Data as ParcelData, ParcelStatus};
+    use svc_storage_client_grpc::simple_service::Client as SimpleClient;
+    use svc_storage_client_grpc::simple_service_linked::Client as SimpleLinkedClient;
+
+    /// A parcel booked on a single flight, not yet dropped off
+    #[derive(Debug, Clone)]
+    pub struct RegisteredParcel {
+        pub parcel_id: String,
+        pub user_id: String,
+        pub itinerary_id: String,
+        pub flight_plan_id: String,
+        pub acquisition: Vertiport,
+        pub delivery: Vertiport,
+    }
+
+    /// Register a vertiport around a single point
+    pub async fn register_vertiport(
+        latitude: f64,
+        longitude: f64,
+        grpc_clients: &GrpcClients,
+    ) -> Vertiport {
+        let data = vertiport::Data {
+            geo_location: Some(GeoPolygonZ {
+                rings: vec![GeoLineStringZ {
+                    points: vec![GeoPointZ {
+                        x: longitude,
+                        y: latitude,
+                        z: 0.0,
+                    }],
+                }],
+            }),
+            ..vertiport::mock::get_data_obj()
+        };
+
+        let object = grpc_clients
+            .storage
+            .vertiport
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap();
+
+        Vertiport::try_from(object).unwrap()
+    }
+
+    /// Register a parcel flying from Amsterdam to Utrecht, arriving within
+    ///  the hour, on an active itinerary of its user
+    pub async fn register_parcel(grpc_clients: &GrpcClients) -> RegisteredParcel {
+        let acquisition = register_vertiport(52.37, 4.89, grpc_clients).await;
+        let delivery = register_vertiport(52.09, 5.12, grpc_clients).await;
+
+        let mut data = flight_plan::mock::get_data_obj();
+        data.origin_vertiport_id = Some(acquisition.id.clone());
+        data.target_vertiport_id = Some(delivery.id.clone());
+        data.target_timeslot_end = Some((Utc::now() + Duration::try_hours(1).unwrap()).into());
+        let flight_plan_id = grpc_clients
+            .storage
+            .flight_plan
+            .insert(data)
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        let user_id = lib_common::uuid::Uuid::new_v4().to_string();
+        let itinerary_id = grpc_clients
+            .storage
+            .itinerary
+            .insert(ItineraryData {
+                user_id: user_id.clone(),
+                status: ItineraryStatus::Active as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        grpc_clients
+            .storage
+            .itinerary_flight_plan_link
+            .link(ItineraryFlightPlans {
+                id: itinerary_id.clone(),
+                other_id_list: Some(IdList {
+                    ids: vec![flight_plan_id.clone()],
+                }),
+            })
+            .await
+            .unwrap();
+
+        let parcel_id = grpc_clients
+            .storage
+            .parcel
+            .insert(ParcelData {
+                user_id: user_id.clone(),
+                weight_grams: 100,
+                status: ParcelStatus::Notdroppedoff as i32,
+            })
+            .await
+            .unwrap()
+            .into_inner()
+            .object
+            .unwrap()
+            .id;
+
+        grpc_clients
+            .storage
+            .flight_plan_parcel
+            .insert(FlightPlanParcel {
+                flight_plan_id: flight_plan_id.clone(),
+                parcel_id: parcel_id.clone(),
+                acquire: true,
+                deliver: true,
+            })
+            .await
+            .unwrap();
+
+        let mut info = CargoInfo::new(parcel_id.clone(), &Parcel::default());
+        info.itinerary_id = Some(itinerary_id.clone());
+        get_pool()
+            .await
+            .unwrap()
+            .lock()
+            .await
+            .store_parcel(&info)
+            .await
+            .unwrap();
+
+        RegisteredParcel {
+            parcel_id,
+            user_id,
+            itinerary_id,
+            flight_plan_id,
+            acquisition,
+            delivery,
+        }
     }
 }